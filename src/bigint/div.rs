@@ -0,0 +1,368 @@
+use crate::bigint::BigIntImpl;
+use crate::treepp::*;
+
+impl<const N_BITS: u32, const LIMB_SIZE: u32> BigIntImpl<N_BITS, LIMB_SIZE> {
+    /// Input: a b
+    /// Output: a / b, a % b
+    ///
+    /// Binary long division over the bits of `a`, most significant bit first. Fails if `b` is
+    /// zero.
+    pub fn div_rem() -> Script {
+        script! {
+            { Self::is_zero_keep_element(0) }
+            OP_NOT OP_VERIFY
+
+            { Self::roll(1) }
+            { Self::convert_to_le_bits_toaltstack() }
+
+            // stack invariant for this loop: q, b, r | bits
+            { Self::push_zero() }
+            { Self::roll(1) }
+            { Self::push_zero() }
+
+            for _ in 0..N_BITS {
+                { Self::shift_in_bit() }
+                { Self::reduce_once(true) }
+
+                // q = 2 * q + (r >= b)
+                { Self::roll(2) }
+                { Self::double(0) }
+                OP_FROMALTSTACK OP_ADD
+                { Self::roll(2) }
+                { Self::roll(2) }
+            }
+
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: a b
+    /// Output: a / b
+    pub fn div() -> Script {
+        script! {
+            { Self::div_rem() }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: a m
+    /// Output: a % m
+    ///
+    /// Same as [`Self::div_rem`] without keeping track of the quotient. Fails if `m` is zero.
+    pub fn rem() -> Script {
+        script! {
+            { Self::is_zero_keep_element(0) }
+            OP_NOT OP_VERIFY
+
+            { Self::roll(1) }
+            { Self::convert_to_le_bits_toaltstack() }
+
+            // stack invariant for this loop: m, r | bits
+            { Self::push_zero() }
+
+            for _ in 0..N_BITS {
+                { Self::shift_in_bit() }
+                { Self::reduce_once(false) }
+            }
+
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: a b m
+    /// Output: (a + b) % m
+    ///
+    /// Both `a` and `b` have to be smaller than `m`.
+    pub fn add_mod() -> Script {
+        script! {
+            { Self::roll(2) }
+            { Self::roll(2) }
+            // m, a, b
+            { Self::copy(1) }
+            { Self::add(1, 0) }
+            { Self::add_overflow_toaltstack() }
+            { Self::roll(1) }
+            { Self::drop() }
+            { Self::reduce_once(false) }
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: a b m
+    /// Output: (a * b) % m
+    ///
+    /// `a` has to be smaller than `m`, `b` may be any value. Double-and-add over the bits of `b`
+    /// with a reduction after every step, so the result never leaves `N_BITS`.
+    pub fn mul_mod() -> Script {
+        script! {
+            { Self::roll(1) }
+            { Self::convert_to_le_bits_toaltstack() }
+
+            // stack invariant for this loop: a, m, r | bits
+            { Self::push_zero() }
+
+            for _ in 0..N_BITS {
+                { Self::double_mod() }
+
+                OP_FROMALTSTACK
+                OP_IF
+                    { Self::copy(2) }
+                    { Self::roll(1) }
+                    // a, m, a, r
+                    { Self::copy(1) }
+                    { Self::add(1, 0) }
+                    { Self::add_overflow_toaltstack() }
+                    { Self::roll(1) }
+                    { Self::drop() }
+                    { Self::reduce_once(false) }
+                OP_ENDIF
+            }
+
+            { Self::roll(2) }
+            { Self::drop() }
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: a e m
+    /// Output: a^e % m
+    ///
+    /// `a` has to be smaller than `m` and `m` has to be larger than one. Square-and-multiply
+    /// over the bits of `e`, most significant bit first.
+    pub fn pow_mod() -> Script {
+        script! {
+            { Self::roll(1) }
+            { Self::convert_to_le_bits_toaltstack() }
+
+            // stack invariant for this loop: a, m, r | bits
+            { Self::push_one() }
+
+            for _ in 0..N_BITS {
+                { Self::copy(0) }
+                { Self::copy(2) }
+                { Self::mul_mod() }
+
+                OP_FROMALTSTACK
+                OP_IF
+                    { Self::copy(2) }
+                    { Self::copy(2) }
+                    { Self::mul_mod() }
+                OP_ENDIF
+            }
+
+            { Self::roll(2) }
+            { Self::drop() }
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input: m r | bit
+    /// Output: m 2r+bit | overflow
+    ///
+    /// `overflow` is set if the top bit of `r` was shifted out.
+    fn shift_in_bit() -> Script {
+        script! {
+            { Self::is_negative(0) }
+            OP_FROMALTSTACK
+            OP_SWAP OP_TOALTSTACK
+            OP_TOALTSTACK
+            { Self::double(0) }
+            // the lowest limb of 2r is even, so adding the bit never carries
+            OP_FROMALTSTACK OP_ADD
+        }
+    }
+
+    /// Input: m r
+    /// Output: m 2r % m
+    ///
+    /// `r` has to be smaller than `m`.
+    fn double_mod() -> Script {
+        script! {
+            { Self::is_negative(0) }
+            OP_TOALTSTACK
+            { Self::double(0) }
+            { Self::reduce_once(false) }
+        }
+    }
+
+    /// Input: a s
+    /// Output: a s | overflow
+    ///
+    /// `s` is the wrapped sum of `a` and another number, which overflowed iff `s < a`.
+    fn add_overflow_toaltstack() -> Script {
+        script! {
+            { Self::copy(0) }
+            { Self::copy(2) }
+            { Self::lessthan(1, 0) }
+            OP_TOALTSTACK
+        }
+    }
+
+    /// Input: m x | overflow
+    /// Output: m x' | (x' != x), if `keep_flag` is set
+    ///
+    /// `x'` is `x - m` if the computation of `x` overflowed or `x >= m`, and `x` otherwise.
+    /// Requires the true value of `x` to be smaller than `2m`.
+    fn reduce_once(keep_flag: bool) -> Script {
+        script! {
+            { Self::copy(0) }
+            { Self::copy(2) }
+            { Self::greaterthanorequal(1, 0) }
+            OP_FROMALTSTACK OP_BOOLOR
+            if keep_flag {
+                OP_DUP OP_TOALTSTACK
+            }
+            OP_IF
+                { Self::copy(1) }
+                { Self::sub(1, 0) }
+            OP_ENDIF
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bigint::{U254, U64};
+    use crate::treepp::*;
+    use num_bigint::{BigUint, RandomBits};
+    use num_traits::{One, Zero};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_div_rem() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        for _ in 0..10 {
+            let a: BigUint = prng.sample(RandomBits::new(254));
+            let n_bits = prng.gen_range(1..=254);
+            let b: BigUint = prng.sample::<BigUint, _>(RandomBits::new(n_bits)) | BigUint::one();
+
+            let script = script! {
+                { U254::push_u32_le(&a.to_u32_digits()) }
+                { U254::push_u32_le(&b.to_u32_digits()) }
+                { U254::div_rem() }
+                { U254::push_u32_le(&(&a % &b).to_u32_digits()) }
+                { U254::equalverify(1, 0) }
+                { U254::push_u32_le(&(&a / &b).to_u32_digits()) }
+                { U254::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+
+        // the largest divisors overflow the remainder when it is doubled
+        let a = (BigUint::one() << 64) - BigUint::one();
+        let b = (BigUint::one() << 63) + BigUint::one();
+        let script = script! {
+            { U64::push_u32_le(&a.to_u32_digits()) }
+            { U64::push_u32_le(&b.to_u32_digits()) }
+            { U64::div_rem() }
+            { U64::push_u32_le(&(&a % &b).to_u32_digits()) }
+            { U64::equalverify(1, 0) }
+            { U64::push_u32_le(&(&a / &b).to_u32_digits()) }
+            { U64::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_div_by_zero() {
+        let script = script! {
+            { U64::push_u32_le(&[5]) }
+            { U64::push_zero() }
+            { U64::div() }
+            { U64::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+
+    #[test]
+    fn test_rem() {
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+
+        for _ in 0..10 {
+            let a: BigUint = prng.sample(RandomBits::new(254));
+            let m: BigUint = prng.sample::<BigUint, _>(RandomBits::new(254)) | BigUint::one();
+
+            let script = script! {
+                { U254::push_u32_le(&a.to_u32_digits()) }
+                { U254::push_u32_le(&m.to_u32_digits()) }
+                { U254::rem() }
+                { U254::push_u32_le(&(&a % &m).to_u32_digits()) }
+                { U254::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_add_mod_and_mul_mod() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+
+        for _ in 0..10 {
+            // moduli close to 2^64 make the intermediate sums overflow
+            let m: BigUint = prng.sample::<BigUint, _>(RandomBits::new(64)) | (BigUint::one() << 63);
+            let a: BigUint = prng.sample::<BigUint, _>(RandomBits::new(64)) % &m;
+            let b: BigUint = prng.sample::<BigUint, _>(RandomBits::new(64)) % &m;
+
+            let script = script! {
+                { U64::push_u32_le(&a.to_u32_digits()) }
+                { U64::push_u32_le(&b.to_u32_digits()) }
+                { U64::push_u32_le(&m.to_u32_digits()) }
+                { U64::add_mod() }
+                { U64::push_u32_le(&((&a + &b) % &m).to_u32_digits()) }
+                { U64::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+
+            let script = script! {
+                { U64::push_u32_le(&a.to_u32_digits()) }
+                { U64::push_u32_le(&b.to_u32_digits()) }
+                { U64::push_u32_le(&m.to_u32_digits()) }
+                { U64::mul_mod() }
+                { U64::push_u32_le(&((&a * &b) % &m).to_u32_digits()) }
+                { U64::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+
+        for e in [BigUint::zero(), BigUint::one(), prng.sample(RandomBits::new(64))] {
+            let m: BigUint = prng.sample::<BigUint, _>(RandomBits::new(64)) | BigUint::one();
+            let a: BigUint = prng.sample::<BigUint, _>(RandomBits::new(64)) % &m;
+
+            let script = script! {
+                { U64::push_u32_le(&a.to_u32_digits()) }
+                { U64::push_u32_le(&e.to_u32_digits()) }
+                { U64::push_u32_le(&m.to_u32_digits()) }
+                { U64::pow_mod() }
+                { U64::push_u32_le(&a.modpow(&e, &m).to_u32_digits()) }
+                { U64::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+}
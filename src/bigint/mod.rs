@@ -1,20 +1,86 @@
 pub mod add;
 pub mod bits;
 pub mod cmp;
+pub mod div;
 pub mod inv;
 pub mod mul;
 pub mod std;
 pub mod sub;
 pub mod u29x9;
 
+use crate::treepp::Script;
+
 pub struct BigIntImpl<const N_BITS: u32, const LIMB_SIZE: u32> {}
 
 impl<const N_BITS: u32, const LIMB_SIZE: u32> BigIntImpl<N_BITS, LIMB_SIZE> {
     pub const N_BITS: u32 = N_BITS;
+    pub const LIMB_SIZE: u32 = LIMB_SIZE;
     pub const N_LIMBS: u32 = (N_BITS + LIMB_SIZE - 1) / LIMB_SIZE;
     pub const HEAD: u32 = N_BITS - (Self::N_LIMBS - 1) * LIMB_SIZE;
     pub const HEAD_OFFSET: u32 = 1u32 << Self::HEAD;
 }
 
+/// The limb operations of [`BigIntImpl`] for code that is generic over the integer width, such
+/// as the prime fields of [`crate::bn254::fp254impl::Fp254Impl`].
+pub trait BigIntOps {
+    const N_BITS: u32;
+    const N_LIMBS: u32;
+
+    fn copy(a: u32) -> Script;
+    fn roll(a: u32) -> Script;
+    fn drop() -> Script;
+    fn zip(a: u32, b: u32) -> Script;
+    fn push_u32_le(v: &[u32]) -> Script;
+    fn push_hex(hex_string: &str) -> Script;
+    fn push_zero() -> Script;
+    fn push_one() -> Script;
+    fn equal(a: u32, b: u32) -> Script;
+    fn equalverify(a: u32, b: u32) -> Script;
+    fn lessthan(a: u32, b: u32) -> Script;
+    fn is_zero(a: u32) -> Script;
+    fn is_zero_keep_element(a: u32) -> Script;
+    fn convert_to_be_bits() -> Script;
+    fn convert_to_be_bits_toaltstack() -> Script;
+    fn convert_to_le_bits() -> Script;
+    fn convert_to_le_bits_toaltstack() -> Script;
+    fn div2rem() -> Script;
+    fn div3rem() -> Script;
+    fn inv_stage1() -> Script;
+    fn inv_stage2(modulus_hex: &str) -> Script;
+    fn toaltstack() -> Script;
+    fn fromaltstack() -> Script;
+}
+
+impl<const N_BITS: u32, const LIMB_SIZE: u32> BigIntOps for BigIntImpl<N_BITS, LIMB_SIZE> {
+    const N_BITS: u32 = N_BITS;
+    const N_LIMBS: u32 = (N_BITS + LIMB_SIZE - 1) / LIMB_SIZE;
+
+    fn copy(a: u32) -> Script { Self::copy(a) }
+    fn roll(a: u32) -> Script { Self::roll(a) }
+    fn drop() -> Script { Self::drop() }
+    fn zip(a: u32, b: u32) -> Script { Self::zip(a, b) }
+    fn push_u32_le(v: &[u32]) -> Script { Self::push_u32_le(v) }
+    fn push_hex(hex_string: &str) -> Script { Self::push_hex(hex_string) }
+    fn push_zero() -> Script { Self::push_zero() }
+    fn push_one() -> Script { Self::push_one() }
+    fn equal(a: u32, b: u32) -> Script { Self::equal(a, b) }
+    fn equalverify(a: u32, b: u32) -> Script { Self::equalverify(a, b) }
+    fn lessthan(a: u32, b: u32) -> Script { Self::lessthan(a, b) }
+    fn is_zero(a: u32) -> Script { Self::is_zero(a) }
+    fn is_zero_keep_element(a: u32) -> Script { Self::is_zero_keep_element(a) }
+    fn convert_to_be_bits() -> Script { Self::convert_to_be_bits() }
+    fn convert_to_be_bits_toaltstack() -> Script { Self::convert_to_be_bits_toaltstack() }
+    fn convert_to_le_bits() -> Script { Self::convert_to_le_bits() }
+    fn convert_to_le_bits_toaltstack() -> Script { Self::convert_to_le_bits_toaltstack() }
+    fn div2rem() -> Script { Self::div2rem() }
+    fn div3rem() -> Script { Self::div3rem() }
+    fn inv_stage1() -> Script { Self::inv_stage1() }
+    fn inv_stage2(modulus_hex: &str) -> Script { Self::inv_stage2(modulus_hex) }
+    fn toaltstack() -> Script { Self::toaltstack() }
+    fn fromaltstack() -> Script { Self::fromaltstack() }
+}
+
 pub type U254 = BigIntImpl<254, 29>;
+pub type U256 = BigIntImpl<256, 29>;
+pub type U381 = BigIntImpl<381, 29>;
 pub type U64 = BigIntImpl<64, 16>;
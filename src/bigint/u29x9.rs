@@ -65,7 +65,7 @@ fn u29_bits_to_altstack() -> Script {
 
 // (A₂₈…₀ ⋅ B₂₈…₀)₂₈…₀ (A₂₈…₀ ⋅ B₂₈…₀)₅₇…₂₉
 // A₂₈…₀ B₂₈…₀
-fn u29_mul_carry_29() -> Script {
+pub fn u29_mul_carry_29() -> Script {
     script! {
         // A₂₈…₀ B₂₈…₀
         { assert_nn_le(0x1FFFFFFF) } // 0≤A₂₈…₀<2²⁹
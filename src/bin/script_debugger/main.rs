@@ -0,0 +1,119 @@
+use bitcoin::ScriptBuf;
+use bitvm::debugger::{Debugger, StopReason};
+use clap::{arg, command};
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "Commands:
+  s, step [n]        execute the next n opcodes (default 1)
+  c, continue        run until the next breakpoint or the end of the script
+  b, break <index>   set a breakpoint at the given opcode index
+  d, delete <index>  remove the breakpoint at the given opcode index
+  m, marker <name>   run until the named DEBUG marker
+  markers            list all DEBUG markers
+  where              show the next opcode
+  stack              print the stack
+  altstack           print the altstack
+  q, quit            run to the end and print the result";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let matches = command!() // requires `cargo` feature
+        .about("Step through a compiled script")
+        .arg(arg!(<SCRIPT> "Path to a file containing the hex encoded compiled script"))
+        .arg(
+            arg!(-b --breakpoint <INDEX> "Opcode index to break at (can be repeated)")
+                .required(false)
+                .action(clap::ArgAction::Append)
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .get_matches();
+
+    let path = matches.get_one::<String>("SCRIPT").unwrap();
+    let hex = std::fs::read_to_string(path)?;
+    let script = ScriptBuf::from_hex(hex.trim())?;
+
+    let mut debugger = Debugger::from_script_buf(script)?;
+    if let Some(indices) = matches.get_many::<usize>("breakpoint") {
+        for index in indices {
+            debugger.set_breakpoint(*index);
+        }
+    }
+    println!(
+        "Loaded {} opcodes and {} DEBUG markers. Type `help` for a list of commands.",
+        debugger.opcode_count(),
+        debugger.markers().len()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("({}) > ", debugger.position());
+        io::stdout().flush()?;
+        let Some(line) = lines.next() else { break };
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else { continue };
+        let argument = words.next();
+
+        match command {
+            "s" | "step" => {
+                let n = argument.and_then(|n| n.parse().ok()).unwrap_or(1);
+                for _ in 0..n {
+                    match debugger.step() {
+                        Some(snapshot) => println!(
+                            "{}\t@{}\t{}",
+                            snapshot.opcode_index, snapshot.byte_offset, snapshot.instruction
+                        ),
+                        None => {
+                            println!("Script finished.");
+                            break;
+                        }
+                    }
+                }
+            }
+            "c" | "continue" => print_stop(debugger.resume()),
+            "b" | "break" | "d" | "delete" => match argument.and_then(|i| i.parse().ok()) {
+                Some(index) if command.starts_with('b') => debugger.set_breakpoint(index),
+                Some(index) => debugger.clear_breakpoint(index),
+                None => println!("Expected an opcode index."),
+            },
+            "m" | "marker" => match argument {
+                Some(name) => match debugger.run_to_marker(name) {
+                    Some(reason) => print_stop(reason),
+                    None => println!("No marker named `{}` ahead.", name),
+                },
+                None => println!("Expected a marker name."),
+            },
+            "markers" => {
+                for marker in debugger.markers() {
+                    println!(
+                        "{}\t{}",
+                        marker.opcode_index,
+                        marker.name.as_deref().unwrap_or("<unnamed>")
+                    );
+                }
+            }
+            "where" => match debugger.current_instruction() {
+                Some((offset, instruction)) => {
+                    println!("{}\t@{}\t{}", debugger.position(), offset, instruction)
+                }
+                None => println!("No opcodes left."),
+            },
+            "stack" => println!("{:4}", debugger.stack()),
+            "altstack" => println!("{:4}", debugger.altstack()),
+            "q" | "quit" => break,
+            _ => println!("{}", HELP),
+        }
+    }
+
+    println!("{}", debugger.finish());
+    Ok(())
+}
+
+fn print_stop(reason: StopReason) {
+    match reason {
+        StopReason::Breakpoint(index) => println!("Breakpoint at opcode {}.", index),
+        StopReason::Marker(name) => println!("Reached marker `{}`.", name),
+        StopReason::Finished => println!("Script finished."),
+    }
+}
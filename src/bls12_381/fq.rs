@@ -0,0 +1,258 @@
+use crate::bigint::U381;
+use crate::bn254::fp254impl::Fp254Impl;
+
+/// The base field of BLS12-381.
+pub struct Fq;
+
+impl Fp254Impl for Fq {
+    const MODULUS: &'static str =
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab";
+
+    // 2⁴⁰⁶ mod p
+    const MONTGOMERY_ONE: &'static str =
+        "13317c30f3a0d636d56a23c34fdd80b891ecbde7c2b7d6e16b0f4b0b7e6d26cb6147acde629c4a23c57400d203a9fb84";
+
+    const MODULUS_LIMBS: &'static [u32] = &[
+        0x1fffaaab, 0xff7ffff, 0x14ffffee, 0x17fffd62, 0xf6241ea, 0x9507b58, 0xafd9cc3,
+        0x109e70a2, 0x1764774b, 0x121a5d66, 0x12c6e9ed, 0x12ffcd34, 0x111ea3, 0xd
+    ];
+
+    // inv₄₀₆ p  <=>  0x1d85f314f9ef90155036b974ce9011d9730a7e61335f1714d24b3e910d10f371cf4b7d795246d262eec17760c000300030003
+    const MODULUS_INV: &'static [u32] = &[
+        0x30003, 0x10600018, 0xbbb05dd, 0xa48da4c, 0xf4b7d79, 0x8879b8e, 0x12cfa443,
+        0x1e2e29a4, 0x7e61335, 0x8ecb985, 0x5d33a40, 0x2aa06d7, 0x14f9ef90, 0xec2f98
+    ];
+
+    const P_PLUS_ONE_DIV2: &'static str =
+        "d0088f51cbff34d258dd3db21a5d66bb23ba5c279c2895fb39869507b587b120f55ffff58a9ffffdcff7fffffffd556";
+
+    const TWO_P_PLUS_ONE_DIV3: &'static str =
+        "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71d";
+
+    const P_PLUS_TWO_DIV3: &'static str =
+        "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38f";
+
+    type BigInt = U381;
+    type ConstantType = ark_bls12_381::Fq;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bigint::U381;
+    use crate::bls12_381::fq::Fq;
+    use crate::bn254::fp254impl::{montgomery_mul, Fp254Impl};
+    use crate::treepp::*;
+    use ark_ff::{AdditiveGroup, Field, PrimeField};
+    use ark_std::UniformRand;
+    use core::ops::{Mul, Rem, Shl};
+    use num_bigint::BigUint;
+    use num_traits::{Num, One};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_constants() {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        assert_eq!(m, BigUint::from(ark_bls12_381::Fq::MODULUS));
+        let r = BigUint::one().shl(406u32);
+        assert_eq!(
+            r.clone().rem(&m),
+            BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap()
+        );
+
+        let limbs = |v: &BigUint| -> Vec<u32> {
+            (0..Fq::N_LIMBS)
+                .map(|i| {
+                    *(v >> (29 * i))
+                        .rem(BigUint::one().shl(29u32))
+                        .to_u32_digits()
+                        .first()
+                        .unwrap_or(&0)
+                })
+                .collect()
+        };
+        assert_eq!(limbs(&m), Fq::MODULUS_LIMBS.to_vec());
+        let inv = m.modpow(&(r.clone() / 2u32 - BigUint::one()), &r);
+        assert_eq!(m.clone().mul(&inv).rem(&r), BigUint::one());
+        assert_eq!(limbs(&inv), Fq::MODULUS_INV.to_vec());
+
+        assert_eq!(
+            (m.clone() + BigUint::one()) / 2u32,
+            BigUint::from_str_radix(Fq::P_PLUS_ONE_DIV2, 16).unwrap()
+        );
+        assert_eq!(
+            (m.clone() * 2u32 + BigUint::one()) / 3u32,
+            BigUint::from_str_radix(Fq::TWO_P_PLUS_ONE_DIV3, 16).unwrap()
+        );
+        assert_eq!(
+            (m + 2u32) / 3u32,
+            BigUint::from_str_radix(Fq::P_PLUS_TWO_DIV3, 16).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_montgomery() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bls12_381::Fq::rand(&mut prng);
+
+        let script = script! {
+            { Fq::push_constant(&a) }
+            { Fq::decode_montgomery() }
+            { U381::push_u32_le(&BigUint::from(a).to_u32_digits()) }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_add_sub_double_neg() {
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+
+        // the largest elements overflow 381 bits when added
+        let max = -ark_bls12_381::Fq::ONE;
+        let mut cases = vec![(max, max), (ark_bls12_381::Fq::ZERO, max)];
+        for _ in 0..20 {
+            cases.push((ark_bls12_381::Fq::rand(&mut prng), ark_bls12_381::Fq::rand(&mut prng)));
+        }
+
+        for (a, b) in cases {
+            let script = script! {
+                { Fq::push_constant(&a) }
+                { Fq::push_constant(&b) }
+                { Fq::add(1, 0) }
+                { Fq::push_constant(&(a + b)) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&a) }
+                { Fq::push_constant(&b) }
+                { Fq::sub(1, 0) }
+                { Fq::push_constant(&(a - b)) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&b) }
+                { Fq::push_constant(&a) }
+                { Fq::sub(0, 1) }
+                { Fq::push_constant(&(a - b)) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&a) }
+                { Fq::double(0) }
+                { Fq::push_constant(&a.double()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&a) }
+                { Fq::neg(0) }
+                { Fq::push_constant(&-a) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_mul_square() {
+        println!("Fq.mul: {} bytes", Fq::mul().len());
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+
+        for _ in 0..5 {
+            let a = ark_bls12_381::Fq::rand(&mut prng);
+            let b = ark_bls12_381::Fq::rand(&mut prng);
+
+            let script = script! {
+                { Fq::push_constant(&a) }
+                { Fq::push_constant(&b) }
+                { Fq::mul() }
+                { Fq::push_constant(&(a * b)) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&a) }
+                { Fq::mul_by_constant(&b) }
+                { Fq::push_constant(&(a * b)) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_constant(&a) }
+                { Fq::square() }
+                { Fq::push_constant(&a.square()) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    // All 14 limbs of `v`, unlike `U381::push_u32_le` which cuts it to 381 bits
+    fn push_limbs(v: &BigUint) -> Script {
+        let limb = |i: u32| -> u32 {
+            *(v >> (29 * i))
+                .rem(BigUint::one().shl(29u32))
+                .to_u32_digits()
+                .first()
+                .unwrap_or(&0)
+        };
+        script! {
+            for i in (0..Fq::N_LIMBS).rev() {
+                { limb(i) }
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_mul_edge_values() {
+        let p = BigUint::from(ark_bls12_381::Fq::MODULUS);
+        let r = BigUint::one().shl(406u32);
+        // -p⁻¹ mod R
+        let m_factor = &r - p.modpow(&(r.clone() / 2u32 - BigUint::one()), &r);
+        let montgomery_one = BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap();
+
+        let values = [
+            ark_bls12_381::Fq::ZERO,
+            ark_bls12_381::Fq::ONE,
+            -ark_bls12_381::Fq::ONE,
+            ark_bls12_381::Fq::from(montgomery_one),
+        ];
+        for a in values {
+            for b in values {
+                let x = BigUint::from(a).mul(&r).rem(&p);
+                let y = BigUint::from(b).mul(&r).rem(&p);
+                // The limbs of m add up to the only m < R with x⋅y + m⋅p ≡ 0 mod R
+                let m = (&x * &y).mul(&m_factor).rem(&r);
+                let t = (&x * &y + m * &p) / &r;
+                assert!(t < &p * 2u32);
+                assert_eq!(t.clone().rem(&p), BigUint::from(a * b).mul(&r).rem(&p));
+
+                let script = script! {
+                    { push_limbs(&x) }
+                    { push_limbs(&y) }
+                    { montgomery_mul(Fq::MODULUS_LIMBS, Fq::MODULUS_INV[0]) }
+                    { push_limbs(&t) }
+                    { U381::equalverify(1, 0) }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(exec_result.success, "{} * {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_field() {
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+        let a = ark_bls12_381::Fq::rand(&mut prng);
+
+        let script = script! {
+            { Fq::push_constant(&a) }
+            { Fq::is_field() }
+            OP_VERIFY
+            { Fq::push_modulus() }
+            { Fq::is_field() }
+            OP_NOT
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::bls12_381::fq::Fq;
+use crate::bls12_381::fq2::Fq2;
+use crate::bls12_381::fq6::Fq6;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::treepp::{script, Script};
+
+pub struct Fq12;
+
+impl Fq12 {
+    pub fn add(mut a: u32, mut b: u32) -> Script {
+        if a < b {
+            (a, b) = (b, a);
+        }
+        script! {
+            { Fq6::add(a + 6, b + 6) }
+            { Fq6::add(a, b + 6) }
+        }
+    }
+
+    pub fn sub(a: u32, b: u32) -> Script {
+        if a > b {
+            script! {
+                { Fq6::sub(a + 6, b + 6) }
+                { Fq6::sub(a, b + 6) }
+            }
+        } else {
+            script! {
+                { Fq6::sub(a + 6, b + 6) }
+                { Fq6::sub(a + 6, b) }
+            }
+        }
+    }
+
+    pub fn equalverify() -> Script {
+        script! {
+            for i in 0..12 {
+                { Fq::equalverify(23 - i * 2, 11 - i) }
+            }
+        }
+    }
+
+    pub fn push_one() -> Script {
+        script! {
+            { Fq6::push_one() }
+            { Fq6::push_zero() }
+        }
+    }
+
+    pub fn push_zero() -> Script {
+        script! {
+            { Fq6::push_zero() }
+            { Fq6::push_zero() }
+        }
+    }
+
+    pub fn mul(a: u32, b: u32) -> Script {
+        assert_ne!(a, b);
+        let on_top = (a, b) == (12, 0) || (a, b) == (0, 12);
+
+        // The degree-12 extension on BLS12-381 Fq6 is under the polynomial z^2 - y
+        script! {
+            if !on_top {
+                { Fq12::roll(a) }
+                { Fq12::roll(if b < a { b + 12 } else { b }) }
+            }
+
+            // stack: a0 a1 b0 b1
+            // t0 = a0 b0, t1 = a1 b1
+            { Fq6::copy(18) }
+            { Fq6::copy(12) }
+            { Fq6::mul(6, 0) }
+            { Fq6::copy(18) }
+            { Fq6::copy(12) }
+            { Fq6::mul(6, 0) }
+
+            // c1 = (a0 + a1) (b0 + b1) - t0 - t1
+            { Fq6::copy(30) }
+            { Fq6::copy(30) }
+            { Fq6::add(6, 0) }
+            { Fq6::copy(24) }
+            { Fq6::copy(24) }
+            { Fq6::add(6, 0) }
+            { Fq6::mul(6, 0) }
+            { Fq6::copy(12) }
+            { Fq6::sub(6, 0) }
+            { Fq6::copy(6) }
+            { Fq6::sub(6, 0) }
+
+            // c0 = t0 + v t1
+            { Fq6::roll(6) }
+            { Fq6::mul_by_nonresidue() }
+            { Fq6::roll(12) }
+            { Fq6::add(6, 0) }
+            { Fq6::roll(6) }
+
+            { Fq12::toaltstack() }
+            { Fq12::drop() }
+            { Fq12::drop() }
+            { Fq12::fromaltstack() }
+        }
+    }
+
+    pub fn square() -> Script {
+        script! {
+            { Fq12::copy(0) }
+            { Fq12::mul(12, 0) }
+        }
+    }
+
+    /// Inverse of an element of the cyclotomic subgroup, e.g. the output of the Miller loop
+    /// after the final exponentiation.
+    pub fn conjugate() -> Script {
+        script! {
+            { Fq6::neg(0) }
+        }
+    }
+
+    /// Input: f (12 elements), c0 c1 d1 (2 elements each)
+    /// Output: f ⋅ (c0 + c1 v + d1 v w), the sparse product used to evaluate lines
+    pub fn mul_by_014() -> Script {
+        script! {
+            // aa = f0 ⋅ (c0 + c1 v)
+            { Fq6::copy(12) }
+            { Fq2::copy(10) }
+            { Fq2::copy(10) }
+            { Fq6::mul_by_01() }
+
+            // bb = f1 ⋅ d1 v
+            { Fq6::copy(12) }
+            { Fq2::copy(12) }
+            { Fq6::mul_by_1() }
+
+            // o = d1 + c1
+            { Fq2::copy(12) }
+            { Fq2::copy(16) }
+            { Fq2::add(2, 0) }
+
+            // c1 = (f0 + f1) ⋅ (c0 + o v) - aa - bb
+            { Fq6::roll(20) }
+            { Fq6::roll(26) }
+            { Fq6::add(6, 0) }
+            { Fq2::roll(24) }
+            { Fq2::roll(8) }
+            { Fq6::mul_by_01() }
+            { Fq2::roll(18) }
+            { Fq2::drop() }
+            { Fq2::roll(18) }
+            { Fq2::drop() }
+            { Fq6::copy(12) }
+            { Fq6::sub(6, 0) }
+            { Fq6::copy(6) }
+            { Fq6::sub(6, 0) }
+
+            // c0 = aa + v bb
+            { Fq6::roll(6) }
+            { Fq6::mul_by_nonresidue() }
+            { Fq6::roll(12) }
+            { Fq6::add(6, 0) }
+            { Fq6::roll(6) }
+        }
+    }
+
+    pub fn copy(a: u32) -> Script {
+        script! {
+            { Fq6::copy(a + 6) }
+            { Fq6::copy(a + 6) }
+        }
+    }
+
+    pub fn roll(a: u32) -> Script {
+        script! {
+            { Fq6::roll(a + 6) }
+            { Fq6::roll(a + 6) }
+        }
+    }
+
+    pub fn toaltstack() -> Script {
+        script! {
+            { Fq6::toaltstack() }
+            { Fq6::toaltstack() }
+        }
+    }
+
+    pub fn fromaltstack() -> Script {
+        script! {
+            { Fq6::fromaltstack() }
+            { Fq6::fromaltstack() }
+        }
+    }
+
+    pub fn drop() -> Script {
+        script! {
+            { Fq6::drop() }
+            { Fq6::drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls12_381::fq12::Fq12;
+    use crate::bls12_381::utils::{fq12_push, fq2_push};
+    use crate::treepp::*;
+    use ark_ff::Field;
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_bls12_381_fq12_add_sub() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bls12_381::Fq12::rand(&mut prng);
+        let b = ark_bls12_381::Fq12::rand(&mut prng);
+
+        let script = script! {
+            { fq12_push(a) }
+            { fq12_push(b) }
+            { Fq12::add(12, 0) }
+            { fq12_push(a + b) }
+            { Fq12::equalverify() }
+
+            { fq12_push(a) }
+            { fq12_push(b) }
+            { Fq12::sub(12, 0) }
+            { fq12_push(a - b) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq12_mul() {
+        println!("Fq12.mul: {} bytes", Fq12::mul(12, 0).len());
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let a = ark_bls12_381::Fq12::rand(&mut prng);
+        let b = ark_bls12_381::Fq12::rand(&mut prng);
+
+        let script = script! {
+            { fq12_push(a) }
+            { fq12_push(b) }
+            { Fq12::mul(12, 0) }
+            { fq12_push(a * b) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq12_square() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let a = ark_bls12_381::Fq12::rand(&mut prng);
+
+        let script = script! {
+            { fq12_push(a) }
+            { Fq12::square() }
+            { fq12_push(a.square()) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq12_mul_by_014() {
+        println!("Fq12.mul_by_014: {} bytes", Fq12::mul_by_014().len());
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+        let a = ark_bls12_381::Fq12::rand(&mut prng);
+        let c0 = ark_bls12_381::Fq2::rand(&mut prng);
+        let c1 = ark_bls12_381::Fq2::rand(&mut prng);
+        let d1 = ark_bls12_381::Fq2::rand(&mut prng);
+        let mut b = a;
+        b.mul_by_014(&c0, &c1, &d1);
+
+        let script = script! {
+            { fq12_push(a) }
+            { fq2_push(c0) }
+            { fq2_push(c1) }
+            { fq2_push(d1) }
+            { Fq12::mul_by_014() }
+            { fq12_push(b) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq12_conjugate() {
+        let mut prng = ChaCha20Rng::seed_from_u64(4);
+        let a = ark_bls12_381::Fq12::rand(&mut prng);
+        let mut b = a;
+        b.conjugate_in_place();
+
+        let script = script! {
+            { fq12_push(a) }
+            { Fq12::conjugate() }
+            { fq12_push(b) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::bls12_381::fq::Fq;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::treepp::{script, Script};
+use std::ops::Add;
+
+pub struct Fq2;
+
+impl Fq2 {
+    pub fn add(mut a: u32, mut b: u32) -> Script {
+        if a < b {
+            (a, b) = (b, a);
+        }
+
+        script! {
+            { Fq::add(a + 1, b + 1) }
+            { Fq::add(a, b + 1) }
+        }
+    }
+
+    pub fn sub(a: u32, b: u32) -> Script {
+        if a > b {
+            script! {
+                { Fq::sub(a + 1, b + 1) }
+                { Fq::sub(a, b + 1) }
+            }
+        } else {
+            script! {
+                { Fq::sub(a + 1, b + 1) }
+                { Fq::sub(a + 1, b) }
+            }
+        }
+    }
+
+    pub fn double(a: u32) -> Script {
+        script! {
+            { Fq::double(a + 1) }
+            { Fq::double(a + 1) }
+        }
+    }
+
+    /// Square the top Fq2 element
+    pub fn square() -> Script {
+        script! {
+            { Fq::copy(1) }
+            { Fq::copy(1) }
+            { Fq::copy(1) }
+            { Fq::copy(1) }
+            { Fq::mul() }
+            { Fq::double(0) }
+            { Fq::sub(2, 1) }
+            { Fq::add(3, 2) }
+            { Fq::mul() }
+            { Fq::roll(1) }
+        }
+    }
+
+    pub fn copy(a: u32) -> Script {
+        script! {
+            { Fq::copy(a + 1) }
+            { Fq::copy(a + 1) }
+        }
+    }
+
+    pub fn equalverify() -> Script {
+        script! {
+            { Fq::equalverify(3, 1) }
+            { Fq::equalverify(1, 0) }
+        }
+    }
+
+    pub fn roll(a: u32) -> Script {
+        script! {
+            { Fq::roll(a + 1) }
+            { Fq::roll(a + 1) }
+        }
+    }
+
+    pub fn mul(mut a: u32, mut b: u32) -> Script {
+        if a < b {
+            (a, b) = (b, a);
+        }
+
+        // The degree-2 extension on BLS12-381 Fq is under the polynomial x^2 + 1
+        script! {
+            { Fq::copy(a + 1) }
+            { Fq::copy(b + 1 + 1) }
+            { Fq::mul() }
+            { Fq::copy(a + 1) }
+            { Fq::copy(b + 1 + 1) }
+            { Fq::mul() }
+            { Fq::add(a + 2, a + 3) }
+            { Fq::add(b + 3, b + 4) }
+            { Fq::mul() }
+            { Fq::copy(2) }
+            { Fq::copy(2) }
+            { Fq::sub(1, 0) }
+            { Fq::add(3, 2) }
+            { Fq::sub(2, 0) }
+        }
+    }
+
+    pub fn mul_by_fq(mut a: u32, b: u32) -> Script {
+        if a < b {
+            a += 1;
+        }
+
+        script! {
+            { Fq::copy(b) }
+            { Fq::roll(a + 2) }
+
+            { Fq::mul() }
+            { Fq::roll(b + 1) }
+            { Fq::roll(a + 1) }
+
+            { Fq::mul() }
+        }
+    }
+
+    /// Multiplies the top Fq2 element by the nonresidue ξ = 1 + u of the sextic extension.
+    pub fn mul_by_nonresidue() -> Script {
+        script! {
+            { Fq::copy(1) }
+            { Fq::copy(1) }
+            { Fq::sub(1, 0) }
+            { Fq::add(2, 1) }
+        }
+    }
+
+    pub fn mul_by_constant(constant: &ark_bls12_381::Fq2) -> Script {
+        script! {
+            { Fq::copy(1) }
+            { Fq::mul_by_constant(&constant.c0) }
+            { Fq::copy(1) }
+            { Fq::mul_by_constant(&constant.c1) }
+            { Fq::add(3, 2) }
+            { Fq::mul_by_constant(&constant.c0.add(constant.c1)) }
+            { Fq::copy(2) }
+            { Fq::copy(2) }
+            { Fq::add(1, 0) }
+            { Fq::sub(1, 0) }
+            { Fq::sub(2, 1) }
+            { Fq::roll(1) }
+        }
+    }
+
+    pub fn push_one() -> Script {
+        script! {
+            { Fq::push_one() }
+            { Fq::push_zero() }
+        }
+    }
+
+    pub fn push_zero() -> Script {
+        script! {
+            { Fq::push_zero() }
+            { Fq::push_zero() }
+        }
+    }
+
+    pub fn neg(a: u32) -> Script {
+        script! {
+            { Fq::neg(a + 1) }
+            { Fq::neg(a + 1) }
+        }
+    }
+
+    pub fn toaltstack() -> Script {
+        script! {
+            { Fq::toaltstack() }
+            { Fq::toaltstack() }
+        }
+    }
+
+    pub fn fromaltstack() -> Script {
+        script! {
+            { Fq::fromaltstack() }
+            { Fq::fromaltstack() }
+        }
+    }
+
+    pub fn drop() -> Script {
+        script! {
+            { Fq::drop() }
+            { Fq::drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls12_381::fq2::Fq2;
+    use crate::bls12_381::utils::{fq2_push, fq_push};
+    use crate::treepp::*;
+    use ark_ff::{AdditiveGroup, Field};
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_bls12_381_fq2_add_sub_double_neg() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        for _ in 0..10 {
+            let a = ark_bls12_381::Fq2::rand(&mut prng);
+            let b = ark_bls12_381::Fq2::rand(&mut prng);
+
+            let script = script! {
+                { fq2_push(a) }
+                { fq2_push(b) }
+                { Fq2::add(0, 2) }
+                { fq2_push(a + b) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { fq2_push(b) }
+                { Fq2::sub(2, 0) }
+                { fq2_push(a - b) }
+                { Fq2::equalverify() }
+
+                { fq2_push(b) }
+                { fq2_push(a) }
+                { Fq2::sub(0, 2) }
+                { fq2_push(a - b) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { Fq2::double(0) }
+                { fq2_push(a.double()) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { Fq2::neg(0) }
+                { fq2_push(-a) }
+                { Fq2::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_fq2_mul_square() {
+        println!("Fq2.mul: {} bytes", Fq2::mul(2, 0).len());
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+
+        for _ in 0..3 {
+            let a = ark_bls12_381::Fq2::rand(&mut prng);
+            let b = ark_bls12_381::Fq2::rand(&mut prng);
+            let c = ark_bls12_381::Fq::rand(&mut prng);
+
+            let script = script! {
+                { fq2_push(a) }
+                { fq2_push(b) }
+                { Fq2::mul(2, 0) }
+                { fq2_push(a * b) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { Fq2::square() }
+                { fq2_push(a.square()) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { Fq2::mul_by_constant(&b) }
+                { fq2_push(a * b) }
+                { Fq2::equalverify() }
+
+                { fq2_push(a) }
+                { fq_push(c) }
+                { Fq2::mul_by_fq(1, 0) }
+                { fq2_push(a * ark_bls12_381::Fq2::new(c, ark_bls12_381::Fq::ZERO)) }
+                { Fq2::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_fq2_mul_by_nonresidue() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let a = ark_bls12_381::Fq2::rand(&mut prng);
+        let nonresidue = ark_bls12_381::Fq2::new(ark_bls12_381::Fq::ONE, ark_bls12_381::Fq::ONE);
+
+        let script = script! {
+            { fq2_push(a) }
+            { Fq2::mul_by_nonresidue() }
+            { fq2_push(a * nonresidue) }
+            { Fq2::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::bls12_381::fq::Fq;
+use crate::bls12_381::fq2::Fq2;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::treepp::{script, Script};
+
+pub struct Fq6;
+
+/// Multiplies the Fq2 elements at depth `a` and `b` and pushes the product, keeping both.
+fn fq2_copy_mul(a: u32, b: u32) -> Script {
+    script! {
+        { Fq2::copy(a) }
+        { Fq2::copy(b + 2) }
+        { Fq2::mul(2, 0) }
+    }
+}
+
+impl Fq6 {
+    pub fn add(mut a: u32, mut b: u32) -> Script {
+        if a < b {
+            (a, b) = (b, a);
+        }
+        script! {
+            { Fq2::add(a + 4, b + 4) }
+            { Fq2::add(a + 2, b + 4) }
+            { Fq2::add(a, b + 4) }
+        }
+    }
+
+    pub fn sub(a: u32, b: u32) -> Script {
+        if a > b {
+            script! {
+                { Fq2::sub(a + 4, b + 4) }
+                { Fq2::sub(a + 2, b + 4) }
+                { Fq2::sub(a, b + 4) }
+            }
+        } else {
+            script! {
+                { Fq2::sub(a + 4, b + 4) }
+                { Fq2::sub(a + 4, b + 2) }
+                { Fq2::sub(a + 4, b) }
+            }
+        }
+    }
+
+    pub fn double(a: u32) -> Script {
+        script! {
+            { Fq2::double(a + 4) }
+            { Fq2::double(a + 4) }
+            { Fq2::double(a + 4) }
+        }
+    }
+
+    pub fn equalverify() -> Script {
+        script! {
+            for i in 0..6 {
+                { Fq::equalverify(11 - i * 2, 5 - i) }
+            }
+        }
+    }
+
+    pub fn push_one() -> Script {
+        script! {
+            { Fq2::push_one() }
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+        }
+    }
+
+    pub fn push_zero() -> Script {
+        script! {
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+        }
+    }
+
+    /// Multiplies the top Fq6 element by v, where v³ = ξ.
+    pub fn mul_by_nonresidue() -> Script {
+        script! {
+            { Fq2::mul_by_nonresidue() }
+            { Fq2::roll(4) }
+            { Fq2::roll(4) }
+        }
+    }
+
+    pub fn mul(a: u32, b: u32) -> Script {
+        assert_ne!(a, b);
+        let on_top = (a, b) == (6, 0) || (a, b) == (0, 6);
+
+        // The degree-6 extension on BLS12-381 Fq2 is under the polynomial y^3 - x - 1
+        script! {
+            if !on_top {
+                { Fq6::roll(a) }
+                { Fq6::roll(if b < a { b + 6 } else { b }) }
+            }
+
+            // stack: a0 a1 a2 b0 b1 b2
+            // c0 = a0 b0 + ξ (a1 b2 + a2 b1)
+            { fq2_copy_mul(8, 0) }
+            { fq2_copy_mul(8, 4) }
+            { Fq2::add(2, 0) }
+            { Fq2::mul_by_nonresidue() }
+            { fq2_copy_mul(12, 6) }
+            { Fq2::add(2, 0) }
+
+            // c1 = a0 b1 + a1 b0 + ξ a2 b2
+            { fq2_copy_mul(8, 2) }
+            { Fq2::mul_by_nonresidue() }
+            { fq2_copy_mul(14, 6) }
+            { Fq2::add(2, 0) }
+            { fq2_copy_mul(12, 8) }
+            { Fq2::add(2, 0) }
+
+            // c2 = a0 b2 + a1 b1 + a2 b0
+            { fq2_copy_mul(14, 4) }
+            { fq2_copy_mul(14, 8) }
+            { Fq2::add(2, 0) }
+            { fq2_copy_mul(12, 10) }
+            { Fq2::add(2, 0) }
+
+            { Fq6::toaltstack() }
+            { Fq6::drop() }
+            { Fq6::drop() }
+            { Fq6::fromaltstack() }
+        }
+    }
+
+    pub fn square() -> Script {
+        script! {
+            { Fq6::copy(0) }
+            { Fq6::mul(6, 0) }
+        }
+    }
+
+    /// Input: a (6 elements), d0 d1 (2 elements each)
+    /// Output: a ⋅ (d0 + d1 v)
+    pub fn mul_by_01() -> Script {
+        script! {
+            // c0 = a0 d0 + ξ a2 d1
+            { fq2_copy_mul(4, 0) }
+            { Fq2::mul_by_nonresidue() }
+            { fq2_copy_mul(10, 4) }
+            { Fq2::add(2, 0) }
+
+            // c1 = a0 d1 + a1 d0
+            { fq2_copy_mul(10, 2) }
+            { fq2_copy_mul(10, 6) }
+            { Fq2::add(2, 0) }
+
+            // c2 = a1 d1 + a2 d0
+            { fq2_copy_mul(10, 4) }
+            { fq2_copy_mul(10, 8) }
+            { Fq2::add(2, 0) }
+
+            { Fq6::toaltstack() }
+            { Fq2::drop() }
+            { Fq2::drop() }
+            { Fq6::drop() }
+            { Fq6::fromaltstack() }
+        }
+    }
+
+    /// Input: a (6 elements), d1 (2 elements)
+    /// Output: a ⋅ d1 v
+    pub fn mul_by_1() -> Script {
+        script! {
+            // c0 = ξ a2 d1
+            { fq2_copy_mul(2, 0) }
+            { Fq2::mul_by_nonresidue() }
+            // c1 = a0 d1
+            { fq2_copy_mul(8, 2) }
+            // c2 = a1 d1
+            { fq2_copy_mul(8, 4) }
+
+            { Fq6::toaltstack() }
+            { Fq2::drop() }
+            { Fq6::drop() }
+            { Fq6::fromaltstack() }
+        }
+    }
+
+    pub fn copy(a: u32) -> Script {
+        script! {
+            { Fq2::copy(a + 4) }
+            { Fq2::copy(a + 4) }
+            { Fq2::copy(a + 4) }
+        }
+    }
+
+    pub fn roll(a: u32) -> Script {
+        script! {
+            { Fq2::roll(a + 4) }
+            { Fq2::roll(a + 4) }
+            { Fq2::roll(a + 4) }
+        }
+    }
+
+    pub fn neg(a: u32) -> Script {
+        script! {
+            { Fq2::neg(a + 4) }
+            { Fq2::neg(a + 4) }
+            { Fq2::neg(a + 4) }
+        }
+    }
+
+    pub fn toaltstack() -> Script {
+        script! {
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+        }
+    }
+
+    pub fn fromaltstack() -> Script {
+        script! {
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+        }
+    }
+
+    pub fn drop() -> Script {
+        script! {
+            { Fq2::drop() }
+            { Fq2::drop() }
+            { Fq2::drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls12_381::fq6::Fq6;
+    use crate::bls12_381::utils::{fq2_push, fq6_push};
+    use crate::treepp::*;
+    use ark_ff::{AdditiveGroup, Field};
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_bls12_381_fq6_add_sub_neg() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bls12_381::Fq6::rand(&mut prng);
+        let b = ark_bls12_381::Fq6::rand(&mut prng);
+
+        let script = script! {
+            { fq6_push(a) }
+            { fq6_push(b) }
+            { Fq6::add(6, 0) }
+            { fq6_push(a + b) }
+            { Fq6::equalverify() }
+
+            { fq6_push(a) }
+            { fq6_push(b) }
+            { Fq6::sub(6, 0) }
+            { fq6_push(a - b) }
+            { Fq6::equalverify() }
+
+            { fq6_push(b) }
+            { fq6_push(a) }
+            { Fq6::sub(0, 6) }
+            { fq6_push(a - b) }
+            { Fq6::equalverify() }
+
+            { fq6_push(a) }
+            { Fq6::neg(0) }
+            { fq6_push(-a) }
+            { Fq6::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq6_mul() {
+        println!("Fq6.mul: {} bytes", Fq6::mul(6, 0).len());
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let a = ark_bls12_381::Fq6::rand(&mut prng);
+        let b = ark_bls12_381::Fq6::rand(&mut prng);
+        let c = ark_bls12_381::Fq6::rand(&mut prng);
+
+        let script = script! {
+            { fq6_push(a) }
+            { fq6_push(b) }
+            { Fq6::mul(6, 0) }
+            { fq6_push(a * b) }
+            { Fq6::equalverify() }
+
+            // operands below the top of the stack
+            { fq6_push(a) }
+            { fq6_push(b) }
+            { fq6_push(c) }
+            { Fq6::mul(0, 12) }
+            { fq6_push(a * c) }
+            { Fq6::equalverify() }
+            { fq6_push(b) }
+            { Fq6::equalverify() }
+
+            { fq6_push(a) }
+            { Fq6::square() }
+            { fq6_push(a.square()) }
+            { Fq6::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq6_mul_by_01_and_1() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let a = ark_bls12_381::Fq6::rand(&mut prng);
+        let d0 = ark_bls12_381::Fq2::rand(&mut prng);
+        let d1 = ark_bls12_381::Fq2::rand(&mut prng);
+
+        let mut b = a;
+        b.mul_by_01(&d0, &d1);
+        let mut c = a;
+        c.mul_by_1(&d1);
+
+        let script = script! {
+            { fq6_push(a) }
+            { fq2_push(d0) }
+            { fq2_push(d1) }
+            { Fq6::mul_by_01() }
+            { fq6_push(b) }
+            { Fq6::equalverify() }
+
+            { fq6_push(a) }
+            { fq2_push(d1) }
+            { Fq6::mul_by_1() }
+            { fq6_push(c) }
+            { Fq6::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_bls12_381_fq6_mul_by_nonresidue() {
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+        let a = ark_bls12_381::Fq6::rand(&mut prng);
+        let v = ark_bls12_381::Fq6::new(
+            ark_bls12_381::Fq2::ZERO,
+            ark_bls12_381::Fq2::ONE,
+            ark_bls12_381::Fq2::ZERO,
+        );
+
+        let script = script! {
+            { fq6_push(a) }
+            { Fq6::mul_by_nonresidue() }
+            { fq6_push(a * v) }
+            { Fq6::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod fq;
+pub mod fq12;
+pub mod fq2;
+pub mod fq6;
+
+pub mod pairing;
+
+pub mod utils;
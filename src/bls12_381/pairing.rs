@@ -0,0 +1,232 @@
+use crate::bls12_381::fq12::Fq12;
+use crate::bls12_381::fq2::Fq2;
+use crate::bls12_381::utils;
+use crate::treepp::*;
+use ark_ec::bls12::{Bls12Config, G2Prepared};
+use ark_ff::BitIteratorBE;
+
+pub struct Pairing;
+
+impl Pairing {
+    // input:
+    //   p_1.x
+    //   p_1.y
+    //   ...
+    //   p_n.x
+    //   p_n.y
+    //
+    // output:
+    //   ∏ f_{|x|, q_i}(p_i), conjugated as x is negative
+    pub fn multi_miller_loop(constants: &[G2Prepared<ark_bls12_381::Config>]) -> Script {
+        Self::multi_miller_loop_steps(constants, Self::multi_miller_loop_len())
+    }
+
+    // number of bits of |x| after the leading one, i.e. iterations of the Miller loop
+    fn multi_miller_loop_len() -> usize {
+        BitIteratorBE::without_leading_zeros(ark_bls12_381::Config::X).count() - 1
+    }
+
+    // [`Pairing::multi_miller_loop`] over only the first `n_steps` bits of |x| after the leading
+    // one, so that the loop body can be tested without running all of it
+    pub(crate) fn multi_miller_loop_steps(
+        constants: &[G2Prepared<ark_bls12_381::Config>], n_steps: usize,
+    ) -> Script {
+        let n = constants.len() as u32;
+        let mut constant_iters = constants
+            .iter()
+            .map(|constant| constant.ell_coeffs.iter())
+            .collect::<Vec<_>>();
+        let bits = BitIteratorBE::without_leading_zeros(ark_bls12_381::Config::X)
+            .skip(1)
+            .take(n_steps)
+            .collect::<Vec<_>>();
+
+        let script = script! {
+            { Fq12::push_one() }
+
+            for (i, bit) in bits.iter().enumerate() {
+                // squaring one can be skipped
+                if i != 0 {
+                    { Fq12::square() }
+                }
+
+                for j in 0..n {
+                    { Fq2::copy(12 + 2 * (n - 1 - j)) }
+                    { utils::ell_by_constant(constant_iters[j as usize].next().unwrap()) }
+                }
+
+                if *bit {
+                    for j in 0..n {
+                        { Fq2::copy(12 + 2 * (n - 1 - j)) }
+                        { utils::ell_by_constant(constant_iters[j as usize].next().unwrap()) }
+                    }
+                }
+            }
+
+            if ark_bls12_381::Config::X_IS_NEGATIVE {
+                { Fq12::conjugate() }
+            }
+
+            { Fq12::toaltstack() }
+            for _ in 0..n {
+                { Fq2::drop() }
+            }
+            { Fq12::fromaltstack() }
+        };
+
+        // the full loop consumes all coefficients
+        if bits.len() == Self::multi_miller_loop_len() {
+            for mut constant_iter in constant_iters {
+                assert_eq!(constant_iter.next(), None);
+            }
+        }
+        script
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls12_381::fq12::Fq12;
+    use crate::bls12_381::pairing::Pairing;
+    use crate::bls12_381::utils::{self, fq12_push, fq_push};
+    use crate::treepp::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::bls12::{Bls12Config, EllCoeff, G2Prepared};
+    use ark_ec::pairing::Pairing as _;
+    use ark_ff::{BitIteratorBE, Field};
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // f ⋅ ℓ(p) for a line with the coefficients of a prepared G2 point
+    fn ell(
+        f: &mut ark_bls12_381::Fq12, coeffs: &EllCoeff<ark_bls12_381::Config>,
+        p: &ark_bls12_381::G1Affine,
+    ) {
+        let (c0, mut c1, mut c2) = *coeffs;
+        c1.c0 *= p.x;
+        c1.c1 *= p.x;
+        c2.c0 *= p.y;
+        c2.c1 *= p.y;
+        f.mul_by_014(&c0, &c1, &c2);
+    }
+
+    #[test]
+    fn test_ell_by_constant() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let f = ark_bls12_381::Fq12::rand(&mut prng);
+        let p = ark_bls12_381::G1Affine::rand(&mut prng);
+        let q = ark_bls12_381::G2Affine::rand(&mut prng);
+
+        let q_prepared = G2Prepared::<ark_bls12_381::Config>::from(q);
+        let mut hint = f;
+        ell(&mut hint, &q_prepared.ell_coeffs[0], &p);
+
+        let ell_by_constant = utils::ell_by_constant(&q_prepared.ell_coeffs[0]);
+        println!("utils.ell_by_constant: {} bytes", ell_by_constant.len());
+
+        let script = script! {
+            { fq12_push(f) }
+            { fq_push(p.x) }
+            { fq_push(p.y) }
+            { ell_by_constant }
+            { fq12_push(hint) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    // The first `n_steps` iterations of the Miller loop of arkworks, i.e. the whole loop of
+    // `Bls12_381::multi_miller_loop` for `n_steps` = `Pairing::multi_miller_loop_len()`
+    fn multi_miller_loop_prefix(
+        p: &[ark_bls12_381::G1Affine], q: &[G2Prepared<ark_bls12_381::Config>], n_steps: usize,
+    ) -> ark_bls12_381::Fq12 {
+        let mut coeffs = q.iter().map(|q| q.ell_coeffs.iter()).collect::<Vec<_>>();
+        let mut f = ark_bls12_381::Fq12::ONE;
+        let bits = BitIteratorBE::without_leading_zeros(ark_bls12_381::Config::X).skip(1);
+        for bit in bits.take(n_steps) {
+            f.square_in_place();
+            for (p, coeffs) in p.iter().zip(coeffs.iter_mut()) {
+                ell(&mut f, coeffs.next().unwrap(), p);
+            }
+            if bit {
+                for (p, coeffs) in p.iter().zip(coeffs.iter_mut()) {
+                    ell(&mut f, coeffs.next().unwrap(), p);
+                }
+            }
+        }
+        if ark_bls12_381::Config::X_IS_NEGATIVE {
+            f.conjugate_in_place();
+        }
+        f
+    }
+
+    #[test]
+    fn test_multi_miller_loop_prefix() {
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+        let p = [ark_bls12_381::G1Affine::rand(&mut prng), ark_bls12_381::G1Affine::rand(&mut prng)];
+        let q = [ark_bls12_381::G2Affine::rand(&mut prng), ark_bls12_381::G2Affine::rand(&mut prng)];
+        let q_prepared = q.map(G2Prepared::<ark_bls12_381::Config>::from);
+
+        assert_eq!(
+            multi_miller_loop_prefix(&p, &q_prepared, Pairing::multi_miller_loop_len()),
+            Bls12_381::multi_miller_loop(p, q).0
+        );
+    }
+
+    #[test]
+    fn test_multi_miller_loop_steps() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let p = [ark_bls12_381::G1Affine::rand(&mut prng), ark_bls12_381::G1Affine::rand(&mut prng)];
+        let q = [ark_bls12_381::G2Affine::rand(&mut prng), ark_bls12_381::G2Affine::rand(&mut prng)];
+        let q_prepared = q.map(G2Prepared::<ark_bls12_381::Config>::from);
+
+        // |x| = 0b1101001…, the first three iterations add in the first and the third one
+        let n_steps = 3;
+        let bits = BitIteratorBE::without_leading_zeros(ark_bls12_381::Config::X)
+            .skip(1)
+            .take(n_steps)
+            .collect::<Vec<_>>();
+        assert_eq!(bits, [true, false, true]);
+        let hint = multi_miller_loop_prefix(&p, &q_prepared, n_steps);
+
+        let script = script! {
+            for p_i in p {
+                { fq_push(p_i.x) }
+                { fq_push(p_i.y) }
+            }
+            { Pairing::multi_miller_loop_steps(&q_prepared, n_steps) }
+            { fq12_push(hint) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    #[ignore = "the full loop is several gigabytes of script"]
+    fn test_multi_miller_loop() {
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let p = [ark_bls12_381::G1Affine::rand(&mut prng), ark_bls12_381::G1Affine::rand(&mut prng)];
+        let q = [ark_bls12_381::G2Affine::rand(&mut prng), ark_bls12_381::G2Affine::rand(&mut prng)];
+
+        let q_prepared = q.map(G2Prepared::<ark_bls12_381::Config>::from);
+        let hint = Bls12_381::multi_miller_loop(p, q).0;
+
+        let script = script! {
+            for p_i in p {
+                { fq_push(p_i.x) }
+                { fq_push(p_i.y) }
+            }
+            { Pairing::multi_miller_loop(&q_prepared) }
+            { fq12_push(hint) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
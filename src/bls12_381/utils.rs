@@ -0,0 +1,74 @@
+use crate::bls12_381::fq::Fq;
+use crate::bls12_381::fq12::Fq12;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::treepp::{script, Script};
+use ark_ff::Field;
+
+pub type EllCoeff = (ark_bls12_381::Fq2, ark_bls12_381::Fq2, ark_bls12_381::Fq2);
+
+pub fn fq_push(element: ark_bls12_381::Fq) -> Script {
+    script! {
+        { Fq::push_constant(&element) }
+    }
+}
+
+pub fn fq2_push(element: ark_bls12_381::Fq2) -> Script {
+    script! {
+        { Fq::push_constant(&element.c0) }
+        { Fq::push_constant(&element.c1) }
+    }
+}
+
+pub fn fq6_push(element: ark_bls12_381::Fq6) -> Script {
+    script! {
+        for elem in element.to_base_prime_field_elements() {
+            { Fq::push_constant(&elem) }
+       }
+    }
+}
+
+pub fn fq12_push(element: ark_bls12_381::Fq12) -> Script {
+    script! {
+        for elem in element.to_base_prime_field_elements() {
+            { Fq::push_constant(&elem) }
+       }
+    }
+}
+
+// stack input:
+//  f            12 elements
+//  p.x          1 element
+//  p.y          1 element
+// func params:
+//  (c0, c1, c2) of a line of the M-type twist
+//
+// output:
+//  new f        12 elements
+pub fn ell_by_constant(constant: &EllCoeff) -> Script {
+    script! {
+        // [f, px, py, c0]
+        { fq2_push(constant.0) }
+
+        // compute the new c1
+        // [f, px, py, c0, px * c1.c0]
+        { Fq::copy(3) }
+        { Fq::mul_by_constant(&constant.1.c0) }
+        // [f, px, py, c0, px * c1.c0, px * c1.c1]
+        { Fq::copy(4) }
+        { Fq::mul_by_constant(&constant.1.c1) }
+
+        // compute the new c2
+        // [f, px, py, c0, px * c1, py * c2.c0]
+        { Fq::copy(4) }
+        { Fq::mul_by_constant(&constant.2.c0) }
+        // [f, px, c0, px * c1, py * c2.c0, py * c2.c1]
+        { Fq::roll(5) }
+        { Fq::mul_by_constant(&constant.2.c1) }
+        // [f, c0, px * c1, py * c2]
+        { Fq::roll(6) }
+        { Fq::drop() }
+
+        // compute the new f
+        { Fq12::mul_by_014() }
+    }
+}
@@ -1,8 +1,12 @@
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
 use num_bigint::BigUint;
 
 use crate::bigint::U254;
 use crate::bn254::fp254impl::Fp254Impl;
 use crate::bn254::fq::Fq;
+use crate::bn254::fq2::Fq2;
 use crate::bn254::fr::Fr;
 use crate::treepp::{script, Script};
 use std::cmp::min;
@@ -10,189 +14,242 @@ use std::sync::OnceLock;
 
 static G1_DOUBLE_PROJECTIVE: OnceLock<Script> = OnceLock::new();
 static G1_NONZERO_ADD_PROJECTIVE: OnceLock<Script> = OnceLock::new();
-
-pub struct G1Projective;
-
-impl G1Projective {
-    pub fn push_generator() -> Script {
-        script! {
-            { Fq::push_one() }
-            { Fq::push_hex("2") }
-            { Fq::push_one() }
-        }
+static G2_DOUBLE_PROJECTIVE: OnceLock<Script> = OnceLock::new();
+static G2_NONZERO_ADD_PROJECTIVE: OnceLock<Script> = OnceLock::new();
+
+/// Doubles a point that is not zero on a curve with a = 0 over the field `F`.
+///
+/// Input Stack: [x, y, z]
+/// Output Stack: [x', y', z']
+pub fn projective_nonzero_double<F: Fp254Impl>() -> Script {
+    script! {
+        { F::copy(2) }
+        { F::square() }
+        { F::copy(2) }
+        { F::square() }
+        { F::copy(0) }
+        { F::square() }
+        { F::add(5, 1) }
+        { F::square() }
+        { F::copy(1) }
+        { F::sub(1, 0) }
+        { F::copy(2) }
+        { F::sub(1, 0) }
+        { F::double(0) }
+        { F::copy(2) }
+        { F::double(0) }
+        { F::add(3, 0) }
+        { F::copy(0) }
+        { F::square() }
+        { F::copy(2) }
+        { F::double(0) }
+        { F::sub(1, 0) }
+        { F::copy(0) }
+        { F::sub(3, 0) }
+        { F::roll(2) }
+        { F::mul() }
+        { F::double(2) }
+        { F::double(0) }
+        { F::double(0) }
+        { F::sub(1, 0) }
+        { F::roll(2) }
+        { F::roll(3) }
+        { F::mul() }
+        { F::double(0) }
     }
+}
 
-    pub fn push_zero() -> Script {
-        script! {
-            { Fq::push_zero() }
-            { Fq::push_zero() }
-            { Fq::push_zero() }
-        }
+/// Adds two distinct points that are both not zero over the field `F`. Adding a point to
+/// itself gives zero, double it instead.
+///
+/// Input Stack: [x1, y1, z1, x2, y2, z2]
+/// Output Stack: [x3, y3, z3]
+pub fn projective_nonzero_add<F: Fp254Impl>() -> Script {
+    script! {
+        { F::copy(3) }
+        { F::square() }
+        { F::copy(1) }
+        { F::square() }
+        { F::roll(7) }
+        { F::copy(1) }
+        { F::mul() }
+        { F::roll(5) }
+        { F::copy(3) }
+        { F::mul() }
+        { F::copy(2) }
+        { F::roll(8) }
+        { F::mul() }
+        { F::copy(5) }
+        { F::mul() }
+        { F::copy(4) }
+        { F::roll(7) }
+        { F::mul() }
+        { F::copy(7) }
+        { F::mul() }
+        { F::add(7, 6)}
+        { F::copy(4) }
+        { F::sub(4, 0)}
+        { F::copy(0) }
+        { F::double(0) }
+        { F::square() }
+        { F::copy(1) }
+        { F::copy(1) }
+        { F::mul() }
+        { F::copy(5) }
+        { F::sub(5, 0) }
+        { F::double(0) }
+        { F::roll(6) }
+        { F::roll(3) }
+        { F::mul() }
+        { F::copy(1) }
+        { F::square() }
+        { F::copy(3) }
+        { F::sub(1, 0) }
+        { F::copy(1) }
+        { F::double(0) }
+        { F::sub(1, 0) }
+        { F::copy(0) }
+        { F::sub(2, 0) }
+        { F::roll(2) }
+        { F::mul() }
+        { F::roll(5) }
+        { F::roll(3) }
+        { F::mul() }
+        { F::double(0) }
+        { F::sub(1, 0) }
+        { F::roll(3) }
+        { F::square() }
+        { F::sub(0, 5) }
+        { F::sub(0, 4) }
+        { F::roll(3) }
+        { F::mul() }
     }
+}
 
-    pub fn push(element: ark_bn254::G1Projective) -> Script {
+/// Points [x, y, z] in Jacobian coordinates on a short Weierstrass curve y² = x³ + b over the
+/// prime field `Fq`, with x/z² and y/z³ the affine coordinates. The point at infinity has z = 0.
+///
+/// Implemented by [`G1Projective`] and [`crate::secp256k1::curves::ProjectivePoint`], which only
+/// differ in the field and cache the non-zero formulas.
+pub trait ProjectiveCurve {
+    type Fq: Fp254Impl;
+
+    /// See [`projective_nonzero_double`].
+    fn nonzero_double() -> Script;
+
+    /// See [`projective_nonzero_add`].
+    fn nonzero_add() -> Script;
+
+    fn push_zero() -> Script {
         script! {
-            { Fq::push_u32_le(&BigUint::from(element.x).to_u32_digits()) }
-            { Fq::push_u32_le(&BigUint::from(element.y).to_u32_digits()) }
-            { Fq::push_u32_le(&BigUint::from(element.z).to_u32_digits()) }
+            { Self::Fq::push_zero() }
+            { Self::Fq::push_zero() }
+            { Self::Fq::push_zero() }
         }
     }
 
-    pub fn is_zero_keep_element(a: u32) -> Script {
+    fn is_zero_keep_element(a: u32) -> Script {
         script! {
             // Check if the third coordinate(z) is zero
-            { Fq::is_zero_keep_element(a * 3) }
+            { Self::Fq::is_zero_keep_element(a * 3) }
         }
     }
 
-    pub fn nonzero_double() -> Script {
-        G1_DOUBLE_PROJECTIVE
-            .get_or_init(|| {
-                script! {
-                    { Fq::copy(2) }
-                    { Fq::square() }
-                    { Fq::copy(2) }
-                    { Fq::square() }
-                    { Fq::copy(0) }
-                    { Fq::square() }
-                    { Fq::add(5, 1) }
-                    { Fq::square() }
-                    { Fq::copy(1) }
-                    { Fq::sub(1, 0) }
-                    { Fq::copy(2) }
-                    { Fq::sub(1, 0) }
-                    { Fq::double(0) }
-                    { Fq::copy(2) }
-                    { Fq::double(0) }
-                    { Fq::add(3, 0) }
-                    { Fq::copy(0) }
-                    { Fq::square() }
-                    { Fq::copy(2) }
-                    { Fq::double(0) }
-                    { Fq::sub(1, 0) }
-                    { Fq::copy(0) }
-                    { Fq::sub(3, 0) }
-                    { Fq::roll(2) }
-                    { Fq::mul() }
-                    { Fq::double(2) }
-                    { Fq::double(0) }
-                    { Fq::double(0) }
-                    { Fq::sub(1, 0) }
-                    { Fq::roll(2) }
-                    { Fq::roll(3) }
-                    { Fq::mul() }
-                    { Fq::double(0) }
-                }
-            })
-            .clone()
-    }
-
-    pub fn double() -> Script {
+    fn double() -> Script {
         script! {
-            { G1Projective::copy(0) }
-            { G1Projective::toaltstack() }
+            { Self::copy(0) }
+            { Self::toaltstack() }
             // Check if the first point is zero
-            { G1Projective::is_zero_keep_element(0) }
+            { Self::is_zero_keep_element(0) }
             OP_TOALTSTACK
             // Perform a regular addition
-            { G1Projective::nonzero_double() }
+            { Self::nonzero_double() }
 
             // Select result
             OP_FROMALTSTACK
             OP_IF
                 // Return original point
-                { G1Projective::drop() }
-                { G1Projective::fromaltstack() }
+                { Self::drop() }
+                { Self::fromaltstack() }
             OP_ELSE
                 // Return regular addition result
-                { G1Projective::fromaltstack() }
-                { G1Projective::drop() }
+                { Self::fromaltstack() }
+                { Self::drop() }
             OP_ENDIF
         }
     }
 
-    pub fn nonzero_add() -> Script {
-        G1_NONZERO_ADD_PROJECTIVE
-            .get_or_init(|| {
-                script! {
-                    { Fq::copy(3) }
-                    { Fq::square() }
-                    { Fq::copy(1) }
-                    { Fq::square() }
-                    { Fq::roll(7) }
-                    { Fq::copy(1) }
-                    { Fq::mul() }
-                    { Fq::roll(5) }
-                    { Fq::copy(3) }
-                    { Fq::mul() }
-                    { Fq::copy(2) }
-                    { Fq::roll(8) }
-                    { Fq::mul() }
-                    { Fq::copy(5) }
-                    { Fq::mul() }
-                    { Fq::copy(4) }
-                    { Fq::roll(7) }
-                    { Fq::mul() }
-                    { Fq::copy(7) }
-                    { Fq::mul() }
-                    { Fq::add(7, 6)}
-                    { Fq::copy(4) }
-                    { Fq::sub(4, 0)}
-                    { Fq::copy(0) }
-                    { Fq::double(0) }
-                    { Fq::square() }
-                    { Fq::copy(1) }
-                    { Fq::copy(1) }
-                    { Fq::mul() }
-                    { Fq::copy(5) }
-                    { Fq::sub(5, 0) }
-                    { Fq::double(0) }
-                    { Fq::roll(6) }
-                    { Fq::roll(3) }
-                    { Fq::mul() }
-                    { Fq::copy(1) }
-                    { Fq::square() }
-                    { Fq::copy(3) }
-                    { Fq::sub(1, 0) }
-                    { Fq::copy(1) }
-                    { Fq::double(0) }
-                    { Fq::sub(1, 0) }
-                    { Fq::copy(0) }
-                    { Fq::sub(2, 0) }
-                    { Fq::roll(2) }
-                    { Fq::mul() }
-                    { Fq::roll(5) }
-                    { Fq::roll(3) }
-                    { Fq::mul() }
-                    { Fq::double(0) }
-                    { Fq::sub(1, 0) }
-                    { Fq::roll(3) }
-                    { Fq::square() }
-                    { Fq::sub(0, 5) }
-                    { Fq::sub(0, 4) }
-                    { Fq::roll(3) }
-                    { Fq::mul() }
-                }
-            })
-            .clone()
+    fn add() -> Script {
+        script! {
+            { Self::copy(0) }
+            { Self::toaltstack() }
+            { Self::copy(1) }
+            { Self::toaltstack() }
+
+            // Check if the first point is zero
+            { Self::is_zero_keep_element(0) }
+            OP_TOALTSTACK
+            // Check if the second point is zero
+            { Self::is_zero_keep_element(1) }
+            OP_TOALTSTACK
+
+            // Perform a regular addition
+            { Self::nonzero_add() }
+
+            // Select result
+            OP_FROMALTSTACK
+            OP_FROMALTSTACK
+            OP_IF
+                // First point is zero
+                OP_DROP
+                { Self::drop() }
+                { Self::fromaltstack() }
+                { Self::fromaltstack() }
+                { Self::drop() }
+            OP_ELSE
+                OP_IF
+                    // Second point is zero
+                    { Self::drop() }
+                    { Self::fromaltstack() }
+                    { Self::drop() }
+                    { Self::fromaltstack() }
+
+                OP_ELSE
+                    // Both summands are non-zero
+                    { Self::fromaltstack() }
+                    { Self::fromaltstack() }
+                    { Self::drop() }
+                    { Self::drop() }
+                OP_ENDIF
+            OP_ENDIF
+        }
     }
 
-    pub fn add() -> Script {
+    /// Like [`Self::add`], but doubles instead if both summands are the same point.
+    fn complete_add() -> Script {
         script! {
-            { G1Projective::copy(0) }
-            { G1Projective::toaltstack() }
-            { G1Projective::copy(1) }
-            { G1Projective::toaltstack() }
+            { Self::copy(0) }
+            { Self::toaltstack() }
+            { Self::copy(1) }
+            { Self::toaltstack() }
 
             // Check if the first point is zero
-            { G1Projective::is_zero_keep_element(0) }
+            { Self::is_zero_keep_element(0) }
             OP_TOALTSTACK
             // Check if the second point is zero
-            { G1Projective::is_zero_keep_element(1) }
+            { Self::is_zero_keep_element(1) }
             OP_TOALTSTACK
 
-            // Perform a regular addition
-            { G1Projective::nonzero_add() }
+            // The addition formula is undefined for equal summands, so double instead
+            { Self::copy(1) }
+            { Self::copy(1) }
+            { Self::equal() }
+            OP_IF
+                { Self::drop() }
+                { Self::nonzero_double() }
+            OP_ELSE
+                { Self::nonzero_add() }
+            OP_ENDIF
 
             // Select result
             OP_FROMALTSTACK
@@ -200,107 +257,162 @@ impl G1Projective {
             OP_IF
                 // First point is zero
                 OP_DROP
-                { G1Projective::drop() }
-                { G1Projective::fromaltstack() }
-                { G1Projective::fromaltstack() }
-                { G1Projective::drop() }
+                { Self::drop() }
+                { Self::fromaltstack() }
+                { Self::fromaltstack() }
+                { Self::drop() }
             OP_ELSE
                 OP_IF
                     // Second point is zero
-                    { G1Projective::drop() }
-                    { G1Projective::fromaltstack() }
-                    { G1Projective::drop() }
-                    { G1Projective::fromaltstack() }
+                    { Self::drop() }
+                    { Self::fromaltstack() }
+                    { Self::drop() }
+                    { Self::fromaltstack() }
 
                 OP_ELSE
                     // Both summands are non-zero
-                    { G1Projective::fromaltstack() }
-                    { G1Projective::fromaltstack() }
-                    { G1Projective::drop() }
-                    { G1Projective::drop() }
+                    { Self::fromaltstack() }
+                    { Self::fromaltstack() }
+                    { Self::drop() }
+                    { Self::drop() }
                 OP_ENDIF
             OP_ENDIF
         }
     }
 
-    pub fn neg() -> Script {
+    fn neg() -> Script {
         script! {
-            { Fq::neg(1) }
-            { Fq::roll(1) }
+            { Self::Fq::neg(1) }
+            { Self::Fq::roll(1) }
         }
     }
 
-    pub fn copy(mut a: u32) -> Script {
+    fn copy(mut a: u32) -> Script {
         a *= 3;
         script! {
-            { Fq::copy(a + 2) }
-            { Fq::copy(a + 2) }
-            { Fq::copy(a + 2) }
+            { Self::Fq::copy(a + 2) }
+            { Self::Fq::copy(a + 2) }
+            { Self::Fq::copy(a + 2) }
         }
     }
 
-    pub fn roll(mut a: u32) -> Script {
+    fn roll(mut a: u32) -> Script {
         a *= 3;
         script! {
-            { Fq::roll(a + 2) }
-            { Fq::roll(a + 2) }
-            { Fq::roll(a + 2) }
+            { Self::Fq::roll(a + 2) }
+            { Self::Fq::roll(a + 2) }
+            { Self::Fq::roll(a + 2) }
         }
     }
 
-    pub fn equalverify() -> Script {
+    fn equalverify() -> Script {
         script! {
-            { Fq::copy(3) }
-            { Fq::square() }
-            { Fq::roll(4) }
-            { Fq::copy(1) }
-            { Fq::mul() }
+            { Self::Fq::copy(3) }
+            { Self::Fq::square() }
+            { Self::Fq::roll(4) }
+            { Self::Fq::copy(1) }
+            { Self::Fq::mul() }
+
+            { Self::Fq::copy(2) }
+            { Self::Fq::square() }
+            { Self::Fq::roll(3) }
+            { Self::Fq::copy(1) }
+            { Self::Fq::mul() }
+
+            { Self::Fq::roll(7) }
+            { Self::Fq::roll(2) }
+            { Self::Fq::mul() }
+            { Self::Fq::roll(5) }
+            { Self::Fq::roll(4) }
+            { Self::Fq::mul() }
+            { Self::Fq::equalverify(1, 0) }
+
+            { Self::Fq::roll(3) }
+            { Self::Fq::roll(1) }
+            { Self::Fq::mul() }
+            { Self::Fq::roll(2) }
+            { Self::Fq::roll(2) }
+            { Self::Fq::mul() }
+            { Self::Fq::equalverify(1, 0) }
+        }
+    }
 
-            { Fq::copy(2) }
-            { Fq::square() }
-            { Fq::roll(3) }
-            { Fq::copy(1) }
-            { Fq::mul() }
+    fn equal() -> Script {
+        script! {
+            { Self::Fq::copy(3) }
+            { Self::Fq::square() }
+            { Self::Fq::roll(4) }
+            { Self::Fq::copy(1) }
+            { Self::Fq::mul() }
+
+            { Self::Fq::copy(2) }
+            { Self::Fq::square() }
+            { Self::Fq::roll(3) }
+            { Self::Fq::copy(1) }
+            { Self::Fq::mul() }
+
+            { Self::Fq::roll(7) }
+            { Self::Fq::roll(2) }
+            { Self::Fq::mul() }
+            { Self::Fq::roll(5) }
+            { Self::Fq::roll(4) }
+            { Self::Fq::mul() }
+            { Self::Fq::equal(1, 0) }
+            OP_TOALTSTACK
 
-            { Fq::roll(7) }
-            { Fq::roll(2) }
-            { Fq::mul() }
-            { Fq::roll(5) }
-            { Fq::roll(4) }
-            { Fq::mul() }
-            { Fq::equalverify(1, 0) }
+            { Self::Fq::roll(3) }
+            { Self::Fq::roll(1) }
+            { Self::Fq::mul() }
+            { Self::Fq::roll(2) }
+            { Self::Fq::roll(2) }
+            { Self::Fq::mul() }
+            { Self::Fq::equal(1, 0) }
+            OP_FROMALTSTACK
+            OP_BOOLAND
+        }
+    }
 
-            { Fq::roll(3) }
-            { Fq::roll(1) }
-            { Fq::mul() }
-            { Fq::roll(2) }
-            { Fq::roll(2) }
-            { Fq::mul() }
-            { Fq::equalverify(1, 0) }
+    fn drop() -> Script {
+        script! {
+            { Self::Fq::drop() }
+            { Self::Fq::drop() }
+            { Self::Fq::drop() }
         }
     }
 
-    pub fn drop() -> Script {
+    fn toaltstack() -> Script {
         script! {
-            { Fq::drop() }
-            { Fq::drop() }
-            { Fq::drop() }
+            { Self::Fq::toaltstack() }
+            { Self::Fq::toaltstack() }
+            { Self::Fq::toaltstack() }
         }
     }
 
-    pub fn toaltstack() -> Script {
+    fn fromaltstack() -> Script {
         script! {
-            { Fq::toaltstack() }
-            { Fq::toaltstack() }
-            { Fq::toaltstack() }
+            { Self::Fq::fromaltstack() }
+            { Self::Fq::fromaltstack() }
+            { Self::Fq::fromaltstack() }
         }
     }
+}
 
-    pub fn fromaltstack() -> Script {
+pub struct G1Projective;
+
+impl G1Projective {
+    pub fn push_generator() -> Script {
         script! {
-            { Fq::fromaltstack() }
-            { Fq::fromaltstack() }
-            { Fq::fromaltstack() }
+            { Fq::push_one() }
+            { Fq::push_hex("2") }
+            { Fq::push_one() }
+        }
+    }
+
+    pub fn push(element: ark_bn254::G1Projective) -> Script {
+        script! {
+            { Fq::push_u32_le(&BigUint::from(element.x).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.y).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.z).to_u32_digits()) }
         }
     }
 
@@ -380,6 +492,51 @@ impl G1Projective {
         )
     }
 
+    /// Input Stack: [x, y, z, z⁻¹]
+    /// Output Stack: [x / z², y / z³]
+    ///
+    /// Like [`G1Projective::into_affine`], but z⁻¹ is supplied as a hint, see
+    /// [`crate::bn254::hints::into_affine_hint`]. The hint is ignored for the point at infinity,
+    /// but it has to be reduced either way.
+    pub fn hinted_into_affine() -> Script {
+        script! {
+            { Fq::is_field() }
+            OP_VERIFY
+            { Fq::roll(1) }
+            { Fq::is_zero_keep_element(0) }
+            OP_IF
+                // Z is zero so return the affine::identity
+                for _ in 0..4 {
+                    { Fq::drop() }
+                }
+                { G1Affine::identity() }
+            OP_ELSE
+                // check z * z⁻¹ = 1
+                { Fq::copy(1) }
+                { Fq::mul() }
+                { Fq::is_one(0) }
+                OP_VERIFY
+
+                // compute z⁻² and z⁻³
+                { Fq::copy(0) }
+                { Fq::square() }
+                { Fq::copy(0) }
+                { Fq::roll(2) }
+                { Fq::mul() }
+
+                // compute y / z³
+                { Fq::roll(2) }
+                { Fq::mul() }
+
+                // compute x / z²
+                { Fq::roll(2) }
+                { Fq::roll(2) }
+                { Fq::mul() }
+                { Fq::roll(1) }
+            OP_ENDIF
+        }
+    }
+
     /// Convert a number to digits
     fn to_digits_helper<const DIGIT_COUNT: usize>(mut number: u32) -> [u8; DIGIT_COUNT] {
         let mut digits: [u8; DIGIT_COUNT] = [0; DIGIT_COUNT];
@@ -658,6 +815,22 @@ impl G1Projective {
 
 }
 
+impl ProjectiveCurve for G1Projective {
+    type Fq = Fq;
+
+    fn nonzero_double() -> Script {
+        G1_DOUBLE_PROJECTIVE
+            .get_or_init(projective_nonzero_double::<Fq>)
+            .clone()
+    }
+
+    fn nonzero_add() -> Script {
+        G1_NONZERO_ADD_PROJECTIVE
+            .get_or_init(projective_nonzero_add::<Fq>)
+            .clone()
+    }
+}
+
 pub struct G1Affine;
 
 impl G1Affine {
@@ -714,11 +887,503 @@ impl G1Affine {
     pub fn into_projective() -> Script { script!({ Fq::push_one() }) }
 }
 
+pub struct G2Projective;
+
+impl G2Projective {
+    pub fn push_generator() -> Script {
+        G2Projective::push(ark_bn254::G2Affine::generator().into())
+    }
+
+    pub fn push_zero() -> Script {
+        script! {
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+        }
+    }
+
+    pub fn push(element: ark_bn254::G2Projective) -> Script {
+        script! {
+            { Fq::push_u32_le(&BigUint::from(element.x.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.x.c1).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.y.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.y.c1).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.z.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.z.c1).to_u32_digits()) }
+        }
+    }
+
+    pub fn is_zero_keep_element(a: u32) -> Script {
+        script! {
+            // Check if both components of the third coordinate(z) are zero
+            { Fq::is_zero_keep_element(a * 6) }
+            OP_TOALTSTACK
+            { Fq::is_zero_keep_element(a * 6 + 1) }
+            OP_FROMALTSTACK
+            OP_BOOLAND
+        }
+    }
+
+    // Input Stack: [x, y, z]
+    // Output Stack: [x', y', z'] = 2 * [x, y, z] for z != 0
+    pub fn nonzero_double() -> Script {
+        G2_DOUBLE_PROJECTIVE
+            .get_or_init(|| {
+                script! {
+                    { Fq2::copy(4) }
+                    { Fq2::square() }
+                    { Fq2::copy(4) }
+                    { Fq2::square() }
+                    { Fq2::copy(0) }
+                    { Fq2::square() }
+                    { Fq2::roll(10) }
+                    { Fq2::add(0, 4) }
+                    { Fq2::square() }
+                    { Fq2::copy(4) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::copy(2) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::double(0) }
+                    { Fq2::roll(4) }
+                    { Fq2::copy(0) }
+                    { Fq2::double(0) }
+                    { Fq2::add(2, 0) }
+                    { Fq2::copy(0) }
+                    { Fq2::square() }
+                    { Fq2::copy(4) }
+                    { Fq2::double(0) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(4) }
+                    { Fq2::copy(2) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(4) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(4) }
+                    { Fq2::double(0) }
+                    { Fq2::double(0) }
+                    { Fq2::double(0) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(6) }
+                    { Fq2::roll(6) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::double(0) }
+                }
+            })
+            .clone()
+    }
+
+    pub fn double() -> Script {
+        script! {
+            { G2Projective::copy(0) }
+            { G2Projective::toaltstack() }
+            // Check if the first point is zero
+            { G2Projective::is_zero_keep_element(0) }
+            OP_TOALTSTACK
+            // Perform a regular doubling
+            { G2Projective::nonzero_double() }
+
+            // Select result
+            OP_FROMALTSTACK
+            OP_IF
+                // Return original point
+                { G2Projective::drop() }
+                { G2Projective::fromaltstack() }
+            OP_ELSE
+                // Return regular doubling result
+                { G2Projective::fromaltstack() }
+                { G2Projective::drop() }
+            OP_ENDIF
+        }
+    }
+
+    // Input Stack: [x1, y1, z1, x2, y2, z2]
+    // Output Stack: [x3, y3, z3] for z1 != 0, z2 != 0 and distinct points
+    pub fn nonzero_add() -> Script {
+        G2_NONZERO_ADD_PROJECTIVE
+            .get_or_init(|| {
+                script! {
+                    { Fq2::copy(6) }
+                    { Fq2::square() }
+                    { Fq2::copy(2) }
+                    { Fq2::square() }
+                    { Fq2::roll(14) }
+                    { Fq2::copy(2) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(10) }
+                    { Fq2::copy(6) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(14) }
+                    { Fq2::copy(10) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::copy(6) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(12) }
+                    { Fq2::copy(14) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::copy(10) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(14) }
+                    { Fq2::roll(14) }
+                    { Fq2::add(2, 0) }
+                    { Fq2::square() }
+                    { Fq2::roll(12) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(10) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(6) }
+                    { Fq2::copy(8) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::copy(0) }
+                    { Fq2::roll(4) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::toaltstack() }
+                    { Fq2::copy(0) }
+                    { Fq2::double(0) }
+                    { Fq2::square() }
+                    { Fq2::roll(2) }
+                    { Fq2::copy(2) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(4) }
+                    { Fq2::copy(6) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::double(0) }
+                    { Fq2::roll(8) }
+                    { Fq2::roll(6) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::copy(2) }
+                    { Fq2::square() }
+                    { Fq2::copy(6) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::copy(2) }
+                    { Fq2::double(0) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(2) }
+                    { Fq2::copy(2) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::roll(4) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::roll(6) }
+                    { Fq2::roll(6) }
+                    { Fq2::mul(2, 0) }
+                    { Fq2::double(0) }
+                    { Fq2::sub(2, 0) }
+                    { Fq2::fromaltstack() }
+                }
+            })
+            .clone()
+    }
+
+    pub fn add() -> Script {
+        script! {
+            { G2Projective::copy(0) }
+            { G2Projective::toaltstack() }
+            { G2Projective::copy(1) }
+            { G2Projective::toaltstack() }
+
+            // Check if the first point is zero
+            { G2Projective::is_zero_keep_element(0) }
+            OP_TOALTSTACK
+            // Check if the second point is zero
+            { G2Projective::is_zero_keep_element(1) }
+            OP_TOALTSTACK
+
+            // The addition formula is undefined for equal summands, so double instead
+            { G2Projective::copy(1) }
+            { G2Projective::copy(1) }
+            { G2Projective::equal() }
+            OP_IF
+                { G2Projective::drop() }
+                { G2Projective::nonzero_double() }
+            OP_ELSE
+                { G2Projective::nonzero_add() }
+            OP_ENDIF
+
+            // Select result
+            OP_FROMALTSTACK
+            OP_FROMALTSTACK
+            OP_IF
+                // First point is zero
+                OP_DROP
+                { G2Projective::drop() }
+                { G2Projective::fromaltstack() }
+                { G2Projective::fromaltstack() }
+                { G2Projective::drop() }
+            OP_ELSE
+                OP_IF
+                    // Second point is zero
+                    { G2Projective::drop() }
+                    { G2Projective::fromaltstack() }
+                    { G2Projective::drop() }
+                    { G2Projective::fromaltstack() }
+
+                OP_ELSE
+                    // Both summands are non-zero
+                    { G2Projective::fromaltstack() }
+                    { G2Projective::fromaltstack() }
+                    { G2Projective::drop() }
+                    { G2Projective::drop() }
+                OP_ENDIF
+            OP_ENDIF
+        }
+    }
+
+    pub fn neg() -> Script {
+        script! {
+            { Fq2::neg(2) }
+            { Fq2::roll(2) }
+        }
+    }
+
+    pub fn copy(mut a: u32) -> Script {
+        a *= 6;
+        script! {
+            { Fq2::copy(a + 4) }
+            { Fq2::copy(a + 4) }
+            { Fq2::copy(a + 4) }
+        }
+    }
+
+    pub fn roll(mut a: u32) -> Script {
+        a *= 6;
+        script! {
+            { Fq2::roll(a + 4) }
+            { Fq2::roll(a + 4) }
+            { Fq2::roll(a + 4) }
+        }
+    }
+
+    // Compares x1 * z2^2 with x2 * z1^2 and y1 * z2^3 with y2 * z1^3
+    pub fn equalverify() -> Script {
+        script! {
+            { Fq2::copy(6) }
+            { Fq2::square() }
+            { Fq2::copy(2) }
+            { Fq2::square() }
+            { Fq2::roll(14) }
+            { Fq2::copy(2) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(10) }
+            { Fq2::copy(6) }
+            { Fq2::mul(2, 0) }
+            { Fq2::equalverify() }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(8) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(6) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::equalverify() }
+        }
+    }
+
+    pub fn equal() -> Script {
+        script! {
+            { Fq2::copy(6) }
+            { Fq2::square() }
+            { Fq2::copy(2) }
+            { Fq2::square() }
+            { Fq2::roll(14) }
+            { Fq2::copy(2) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(10) }
+            { Fq2::copy(6) }
+            { Fq2::mul(2, 0) }
+            { Fq2::equal() }
+            OP_TOALTSTACK
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(8) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(6) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::equal() }
+            OP_FROMALTSTACK
+            OP_BOOLAND
+        }
+    }
+
+    pub fn drop() -> Script {
+        script! {
+            { Fq2::drop() }
+            { Fq2::drop() }
+            { Fq2::drop() }
+        }
+    }
+
+    pub fn toaltstack() -> Script {
+        script! {
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+        }
+    }
+
+    pub fn fromaltstack() -> Script {
+        script! {
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+        }
+    }
+
+    // Input Stack: [x, y, z]
+    // Output Stack: [x/z^2, y/z^3]
+    pub fn into_affine() -> Script {
+        script! {
+            { G2Projective::is_zero_keep_element(0) }
+            OP_TOALTSTACK
+
+            // Run normal calculation anyway
+            // compute z^-1, z^-2 and z^-3
+            { Fq2::inv() }
+            { Fq2::copy(0) }
+            { Fq2::square() }
+            { Fq2::copy(0) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+
+            // compute y/z^3
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+
+            // compute x/z^2
+            { Fq2::roll(4) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(2) }
+
+            // Z is zero so drop the calculated affine point and return the affine::identity
+            OP_FROMALTSTACK
+            OP_IF
+                { Fq2::drop() }
+                { Fq2::drop() }
+                { G2Affine::identity() }
+            OP_ENDIF
+        }
+    }
+
+    // [g2projective, scalar]
+    pub fn scalar_mul() -> Script {
+        script! {
+            { Fr::decode_montgomery() }
+            { Fr::convert_to_le_bits_toaltstack() }
+
+            { G2Projective::push_zero() }
+            for _ in 0..Fr::N_BITS {
+                { G2Projective::double() }
+                OP_FROMALTSTACK
+                OP_IF
+                    { G2Projective::copy(1) }
+                    { G2Projective::add() }
+                OP_ENDIF
+            }
+
+            { G2Projective::roll(1) }
+            { G2Projective::drop() }
+        }
+    }
+
+    // [g2projective]
+    pub fn scalar_mul_by_constant_scalar(scalar: &BigUint) -> Script {
+        let n_bits = scalar.bits();
+        script! {
+            if n_bits == 0 {
+                { G2Projective::drop() }
+                { G2Projective::push_zero() }
+            } else {
+                // the leading bit of the scalar is always set
+                { G2Projective::copy(0) }
+                for i in (0..n_bits - 1).rev() {
+                    { G2Projective::double() }
+                    if scalar.bit(i) {
+                        { G2Projective::copy(1) }
+                        { G2Projective::add() }
+                    }
+                }
+                { G2Projective::roll(1) }
+                { G2Projective::drop() }
+            }
+        }
+    }
+
+    /// Input Stack: [g2projective]
+    /// Output Stack: [r * g2projective == 0]
+    ///
+    /// Checks membership of the order-r subgroup. The point has to be on the curve, see
+    /// [`G2Affine::is_on_curve`].
+    pub fn is_in_subgroup() -> Script {
+        script! {
+            { G2Projective::scalar_mul_by_constant_scalar(&BigUint::from(ark_bn254::Fr::MODULUS)) }
+            { G2Projective::is_zero_keep_element(0) }
+            OP_TOALTSTACK
+            { G2Projective::drop() }
+            OP_FROMALTSTACK
+        }
+    }
+}
+
+pub struct G2Affine;
+
+impl G2Affine {
+    pub fn identity() -> Script {
+        script! {
+            { Fq2::push_zero() }
+            { Fq2::push_zero() }
+        }
+    }
+
+    pub fn push(element: ark_bn254::G2Affine) -> Script {
+        script! {
+            { Fq::push_u32_le(&BigUint::from(element.x.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.x.c1).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.y.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(element.y.c1).to_u32_digits()) }
+        }
+    }
+
+    // y^2 == x^3 + 3 / (9 + u)
+    pub fn is_on_curve() -> Script {
+        let b = ark_bn254::g2::Config::COEFF_B;
+        script! {
+            { Fq2::copy(2) }
+            { Fq2::square() }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq::push_u32_le(&BigUint::from(b.c0).to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from(b.c1).to_u32_digits()) }
+            { Fq2::add(2, 0) }
+            { Fq2::roll(2) }
+            { Fq2::square() }
+            { Fq2::equal() }
+        }
+    }
+
+    // Init stack: [x1, y1, x2, y2]
+    pub fn equalverify() -> Script {
+        script! {
+            { Fq2::roll(4) }
+            { Fq2::equalverify() }
+            { Fq2::equalverify() }
+        }
+    }
+
+    // Input Stack: [x, y]
+    // Output Stack: [x, y, z] (z=1)
+    pub fn into_projective() -> Script { script!({ Fq2::push_one() }) }
+}
+
 #[cfg(test)]
 mod test {
 
-    use crate::bn254::curves::{G1Affine, G1Projective};
+    use crate::bn254::curves::{G1Affine, G1Projective, G2Affine, G2Projective, ProjectiveCurve};
     use crate::bn254::fq::Fq;
+    use crate::bn254::fq2::Fq2;
     use crate::treepp::{script, Script};
     use crate::{execute_script, execute_script_as_chunks, run};
 
@@ -921,6 +1586,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_complete_add_curves() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bn254::G1Projective::rand(&mut prng);
+        let b = ark_bn254::G1Projective::rand(&mut prng);
+
+        let script = script! {
+            // Test random a + b = c
+            { G1Projective::push(a) }
+            { G1Projective::push(b) }
+            { G1Projective::complete_add() }
+            { G1Projective::push(a.add(&b)) }
+            { G1Projective::equalverify() }
+
+            // Test a + a = 2a with a in different coordinates
+            { G1Projective::push(a) }
+            { G1Projective::push(a.add(&b).add(&b.neg())) }
+            { G1Projective::complete_add() }
+            { G1Projective::push(a.add(&a)) }
+            { G1Projective::equalverify() }
+
+            // Test 0 + a = a
+            { G1Projective::push_zero() }
+            { G1Projective::push(a) }
+            { G1Projective::complete_add() }
+            { G1Projective::push(a) }
+            { G1Projective::equal() }
+        };
+        println!("curves::test_complete_add = {} bytes", script.len());
+        run(script);
+    }
+
     #[test]
     fn test_scalar_mul() {
         let scalar_mul = G1Projective::scalar_mul();
@@ -1350,4 +2047,173 @@ mod test {
             assert!(exec_result.success);
         }
     }
+
+    #[test]
+    fn test_g2_double_and_add_projective() {
+        println!("G2.double: {} bytes", G2Projective::double().len());
+        println!("G2.add: {} bytes", G2Projective::add().len());
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        let a = ark_bn254::G2Projective::rand(&mut prng);
+        let b = ark_bn254::G2Projective::rand(&mut prng);
+
+        let script = script! {
+            // Test random a + a = 2a
+            { G2Projective::push(a) }
+            { G2Projective::double() }
+            { G2Projective::push(a + a) }
+            { G2Projective::equalverify() }
+
+            // Test random a + b = c
+            { G2Projective::push(a) }
+            { G2Projective::push(b) }
+            { G2Projective::add() }
+            { G2Projective::push(a + b) }
+            { G2Projective::equalverify() }
+
+            // Test a + a with different z coordinates
+            { G2Projective::push(a) }
+            { G2Projective::push(a.into_affine().into()) }
+            { G2Projective::add() }
+            { G2Projective::push(a + a) }
+            { G2Projective::equalverify() }
+
+            // Test random a + 0 = a and 0 + a = a
+            { G2Projective::push(a) }
+            { G2Projective::push_zero() }
+            { G2Projective::add() }
+            { G2Projective::push(a) }
+            { G2Projective::equalverify() }
+            { G2Projective::push_zero() }
+            { G2Projective::push(a) }
+            { G2Projective::add() }
+            { G2Projective::push(a) }
+            { G2Projective::equalverify() }
+
+            // Test a + (-a) = 0
+            { G2Projective::push(a) }
+            { G2Projective::push(a) }
+            { G2Projective::neg() }
+            { G2Projective::add() }
+            { G2Projective::is_zero_keep_element(0) }
+            OP_VERIFY
+            { G2Projective::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_equal() {
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let a = ark_bn254::G2Projective::rand(&mut prng);
+        let b = ark_bn254::G2Projective::rand(&mut prng);
+
+        let script = script! {
+            { G2Projective::push(a) }
+            { G2Projective::push(a.into_affine().into()) }
+            { G2Projective::equal() }
+            OP_VERIFY
+            { G2Projective::push(a) }
+            { G2Projective::push(b) }
+            { G2Projective::equal() }
+            OP_NOT
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_into_affine() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let a = ark_bn254::G2Projective::rand(&mut prng);
+
+        let script = script! {
+            { G2Projective::push(a) }
+            { G2Projective::into_affine() }
+            { G2Affine::push(a.into_affine()) }
+            { G2Affine::equalverify() }
+
+            { G2Projective::push_zero() }
+            { G2Projective::into_affine() }
+            { G2Affine::identity() }
+            { G2Affine::equalverify() }
+
+            { G2Affine::push(a.into_affine()) }
+            { G2Affine::into_projective() }
+            { G2Projective::push(a) }
+            { G2Projective::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_affine_is_on_curve() {
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+        let p = ark_bn254::G2Affine::rand(&mut prng);
+
+        let script = script! {
+            { G2Affine::push(p) }
+            { G2Affine::is_on_curve() }
+            OP_VERIFY
+            { G2Affine::push(p) }
+            { Fq2::double(0) }
+            { G2Affine::is_on_curve() }
+            OP_NOT
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_scalar_mul() {
+        let scalar_mul = G2Projective::scalar_mul();
+        println!("G2.scalar_mul: {} bytes", scalar_mul.len());
+        let mut prng = ChaCha20Rng::seed_from_u64(4);
+
+        let scalar = Fr::rand(&mut prng);
+        let p = ark_bn254::G2Projective::rand(&mut prng);
+
+        let script = script! {
+            { G2Projective::push(p) }
+            { fr_push(scalar) }
+            { scalar_mul }
+            { G2Projective::push(p.mul(scalar)) }
+            { G2Projective::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_is_in_subgroup() {
+        let is_in_subgroup = G2Projective::is_in_subgroup();
+        println!("G2.is_in_subgroup: {} bytes", is_in_subgroup.len());
+        let mut prng = ChaCha20Rng::seed_from_u64(5);
+
+        let p = ark_bn254::G2Affine::rand(&mut prng);
+        // points on the twist are almost never in the order-r subgroup
+        let q = loop {
+            let x = ark_bn254::Fq2::rand(&mut prng);
+            if let Some(q) = ark_bn254::G2Affine::get_point_from_x_unchecked(x, false) {
+                break q;
+            }
+        };
+        assert!(!q.is_in_correct_subgroup_assuming_on_curve());
+
+        let script = script! {
+            { G2Projective::push(p.into()) }
+            { is_in_subgroup.clone() }
+            OP_VERIFY
+            { G2Projective::push(q.into()) }
+            { is_in_subgroup }
+            OP_NOT
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
 }
@@ -0,0 +1,229 @@
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fr::Fr;
+use crate::treepp::*;
+use ark_ff::{Field, One, PrimeField, Zero};
+use num_bigint::BigUint;
+
+/// 2²⁸ is the largest power of two dividing r - 1, so radix-2 domains have at most 2²⁸ elements.
+pub const TWO_ADICITY: u32 = 28;
+
+/// Returns the primitive root of unity 5⁽ʳ⁻¹⁾ᐟⁿ of order n, 5 generates the multiplicative group of Fr.
+///
+/// These are the roots snarkjs derives its domains and the ω₈, ω₄ of fflonk from. Every other
+/// primitive root of the same order is a power of it.
+pub fn root_of_unity(order: u64) -> ark_bn254::Fr {
+    let r_minus_one = BigUint::from(ark_bn254::Fr::MODULUS) - 1u32;
+    assert!(
+        order > 0 && (&r_minus_one % order).is_zero(),
+        "there is no root of unity of order {order}"
+    );
+    ark_bn254::Fr::from(5u64).pow((r_minus_one / order).to_u64_digits())
+}
+
+/// The subgroup {1, ω, ω², ..., ωⁿ⁻¹} of n = 2ᵏ roots of unity that PLONK-family proof systems
+/// interpolate their polynomials over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Domain {
+    pub log_size: u32,
+}
+
+impl Domain {
+    pub fn new(size: u64) -> Self {
+        assert!(
+            size.is_power_of_two() && size.trailing_zeros() <= TWO_ADICITY,
+            "domain size {size} is not a power of two up to 2^{TWO_ADICITY}"
+        );
+        Domain { log_size: size.trailing_zeros() }
+    }
+
+    pub fn size(&self) -> u64 { 1 << self.log_size }
+
+    /// ω
+    pub fn generator(&self) -> ark_bn254::Fr { root_of_unity(self.size()) }
+
+    /// ωⁱ
+    pub fn element(&self, i: u64) -> ark_bn254::Fr { self.generator().pow([i]) }
+
+    /// Z_H(z) = zⁿ - 1
+    pub fn evaluate_vanishing_polynomial(&self, z: ark_bn254::Fr) -> ark_bn254::Fr {
+        z.pow([self.size()]) - ark_bn254::Fr::one()
+    }
+
+    /// Lᵢ(z) = ωⁱ ⋅ (zⁿ - 1) / (n ⋅ (z - ωⁱ)), the Lagrange basis polynomial that is one at ωⁱ
+    /// and zero on the rest of the domain.
+    pub fn evaluate_lagrange(&self, i: u64, z: ark_bn254::Fr) -> ark_bn254::Fr {
+        let w = self.element(i);
+        if z == w {
+            return ark_bn254::Fr::one();
+        }
+        w * self.evaluate_vanishing_polynomial(z)
+            / (ark_bn254::Fr::from(self.size()) * (z - w))
+    }
+
+    /// Input Stack: [z]
+    /// Output Stack: [zⁿ - 1]
+    pub fn vanishing_polynomial(&self) -> Script {
+        script! {
+            for _ in 0..self.log_size {
+                { Fr::square() }
+            }
+            { Fr::push_one() }
+            { Fr::sub(1, 0) }
+        }
+    }
+
+    /// Input Stack: [z]
+    /// Output Stack: [zⁿ - 1, Lᵢ₀(z), Lᵢ₁(z), ..., Lᵢₘ₋₁(z)] for `indices` = [i₀, i₁, ..., iₘ₋₁]
+    ///
+    /// The denominators z - ωⁱ share a single inversion. The script fails if z is in the domain,
+    /// which PLONK challenges are only with negligible probability.
+    pub fn lagranges(&self, indices: &[u64]) -> Script { self.lagranges_with(indices, false) }
+
+    /// Input Stack: [z, ((z - ωⁱ⁰) ⋯ (z - ωⁱᵐ⁻¹))⁻¹]
+    /// Output Stack: [zⁿ - 1, Lᵢ₀(z), Lᵢ₁(z), ..., Lᵢₘ₋₁(z)]
+    ///
+    /// [`Domain::lagranges`] with the inversion supplied as a hint, see
+    /// [`crate::bn254::hints::lagranges_hint`].
+    pub fn hinted_lagranges(&self, indices: &[u64]) -> Script { self.lagranges_with(indices, true) }
+
+    fn lagranges_with(&self, indices: &[u64], hinted: bool) -> Script {
+        assert!(!indices.is_empty());
+        let m = indices.len() as u32;
+        let n_inv = ark_bn254::Fr::from(self.size()).inverse().unwrap();
+        script! {
+            if hinted {
+                { Fr::toaltstack() }
+            }
+            { Fr::copy(0) }
+            { self.vanishing_polynomial() }
+            // [z, zⁿ - 1]
+
+            for (j, i) in indices.iter().enumerate() {
+                { Fr::copy(j as u32 + 1) }
+                { Fr::push_u32_le(&BigUint::from(self.element(*i)).to_u32_digits()) }
+                { Fr::sub(1, 0) }
+            }
+            // [z, zⁿ - 1, z - ωⁱ⁰, ..., z - ωⁱᵐ⁻¹]
+
+            if hinted {
+                { Fr::fromaltstack() }
+                { Fr::hinted_batch_inv(m) }
+            } else {
+                { Fr::batch_inv(m) }
+            }
+            for _ in 0..m {
+                { Fr::toaltstack() }
+            }
+
+            // Lᵢ(z) = (z - ωⁱ)⁻¹ ⋅ (zⁿ - 1) ⋅ ωⁱ / n
+            for (j, i) in indices.iter().enumerate() {
+                { Fr::fromaltstack() }
+                { Fr::copy(j as u32 + 1) }
+                { Fr::mul() }
+                { Fr::mul_by_constant(&(self.element(*i) * n_inv)) }
+            }
+
+            { Fr::roll(m + 1) }
+            { Fr::drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bn254::hints::lagranges_hint;
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use std::str::FromStr;
+
+    fn fr_push(a: ark_bn254::Fr) -> Script {
+        script! {
+            { Fr::push_u32_le(&BigUint::from(a).to_u32_digits()) }
+        }
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        for log_n in 0..=TWO_ADICITY {
+            let w = Domain::new(1 << log_n).generator();
+            assert!(w.pow([1u64 << log_n]).is_one());
+            if log_n > 0 {
+                assert!(!w.pow([1u64 << (log_n - 1)]).is_one());
+            }
+        }
+        assert_eq!(
+            Domain::new(1 << TWO_ADICITY).generator(),
+            ark_bn254::Fr::from_str(
+                "19103219067921713944291392827692070036145651957329286315305642004821462161904"
+            )
+            .unwrap()
+        );
+
+        // ω₈ of the fflonk reference verifier
+        assert_eq!(
+            root_of_unity(8),
+            ark_bn254::Fr::from_str(
+                "19540430494807482326159819597004422086093766032135589407132600596362845576832"
+            )
+            .unwrap()
+        );
+        let w3 = root_of_unity(3);
+        assert!(!w3.is_one() && w3.pow([3u64]).is_one());
+    }
+
+    #[test]
+    fn test_vanishing_polynomial() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let domain = Domain::new(1 << 10);
+        println!("Domain(2^10).vanishing_polynomial: {} bytes", domain.vanishing_polynomial().len());
+
+        let z = ark_bn254::Fr::rand(&mut prng);
+        assert!(domain.evaluate_vanishing_polynomial(domain.element(3)).is_zero());
+
+        let script = script! {
+            { fr_push(z) }
+            { domain.vanishing_polynomial() }
+            { fr_push(domain.evaluate_vanishing_polynomial(z)) }
+            { Fr::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_lagranges() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let domain = Domain::new(1 << 6);
+        let indices = [0, 1, 5, 63];
+
+        let z = ark_bn254::Fr::rand(&mut prng);
+        for i in 0..domain.size() {
+            assert!(domain.evaluate_lagrange(i, domain.element(1)) == ark_bn254::Fr::from(i == 1));
+        }
+
+        for hinted in [false, true] {
+            let lagranges = if hinted { domain.hinted_lagranges(&indices) } else { domain.lagranges(&indices) };
+            println!("Domain.lagranges(hinted: {}, 4 indices): {} bytes", hinted, lagranges.len());
+
+            let script = script! {
+                { fr_push(z) }
+                if hinted {
+                    { fr_push(lagranges_hint(&domain, z, &indices)) }
+                }
+                { lagranges }
+                for i in indices.iter().rev() {
+                    { fr_push(domain.evaluate_lagrange(*i, z)) }
+                    { Fr::equalverify(1, 0) }
+                }
+                { fr_push(domain.evaluate_vanishing_polynomial(z)) }
+                { Fr::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+}
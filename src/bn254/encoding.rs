@@ -0,0 +1,698 @@
+//
+// Canonical byte encodings of field elements and curve points
+//
+// Every field element is encoded as 32 big-endian bytes of its canonical integer in [0, p).
+// Extension field elements concatenate their coefficients from the highest to the lowest one,
+// an Fq2 element is c1 ‖ c0 and an Fq12 element starts with c1.c2.c1 and ends with c0.c0.c0.
+//
+// Points are the encodings of x ‖ y, or only of x in compressed form. As p < 2²⁵⁴, the two most
+// significant bits of the first byte are free and hold the flags:
+//
+//   0x40  the point at infinity, all other bits are zero
+//   0x80  compressed only, y is the larger one of y and -y, i.e. y > (p - 1) / 2
+//
+// In the script the bytes are single stack elements with the first byte on top, the order in
+// which `Fp254Impl::convert_to_be_bytes` and `G1Affine::convert_to_compressed` leave them. The
+// decoders reject bytes out of range, values not smaller than p, flags that do not match the
+// point and points that are not on the curve. Compressed points come with y as a hint instead of
+// computing a square root. G2 points are not checked for membership of the prime order subgroup,
+// see `G2Projective::is_in_subgroup`.
+//
+
+use crate::bigint::bits::limb_to_be_bits;
+use crate::bigint::U254;
+use crate::bn254::curves::{G1Affine, G2Affine};
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fq::Fq;
+use crate::bn254::fq2::Fq2;
+use crate::bn254::utils::fq2_push;
+use crate::treepp::*;
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use core::fmt;
+use num_bigint::BigUint;
+use num_traits::Num;
+
+pub const FQ_BYTES: usize = 32;
+
+const FLAG_MASK: u8 = 0xc0;
+const FLAG_INFINITY: u8 = 0x40;
+const FLAG_Y_LARGEST: u8 = 0x80;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    InvalidLength { expected: usize, actual: usize },
+    /// The value is not smaller than the modulus.
+    NotInField,
+    InvalidFlags(u8),
+    /// The point at infinity with a nonzero coordinate.
+    NonCanonicalInfinity,
+    NotOnCurve,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::InvalidLength { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+            EncodingError::NotInField => write!(f, "value is not smaller than the modulus"),
+            EncodingError::InvalidFlags(flags) => write!(f, "invalid flags {:#04x}", flags),
+            EncodingError::NonCanonicalInfinity => {
+                write!(f, "point at infinity with nonzero coordinates")
+            }
+            EncodingError::NotOnCurve => write!(f, "point is not on the curve"),
+        }
+    }
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), EncodingError> {
+    if bytes.len() != expected {
+        return Err(EncodingError::InvalidLength { expected, actual: bytes.len() });
+    }
+    Ok(())
+}
+
+// y > (p - 1) / 2
+fn is_largest(y: ark_bn254::Fq) -> bool {
+    y.into_bigint() > ark_bn254::Fq::MODULUS_MINUS_ONE_DIV_TWO
+}
+
+// Compares c1 first, c0 only if c1 is zero
+fn is_largest_fq2(y: ark_bn254::Fq2) -> bool {
+    if y.c1.is_zero() {
+        is_largest(y.c0)
+    } else {
+        is_largest(y.c1)
+    }
+}
+
+// Returns the element and the flags of the first byte
+fn read_fq(bytes: &[u8]) -> Result<(ark_bn254::Fq, u8), EncodingError> {
+    check_length(bytes, FQ_BYTES)?;
+    let flags = bytes[0] & FLAG_MASK;
+    let mut value = bytes.to_vec();
+    value[0] &= !FLAG_MASK;
+    let value = BigUint::from_bytes_be(&value);
+    if value >= BigUint::from(ark_bn254::Fq::MODULUS) {
+        return Err(EncodingError::NotInField);
+    }
+    Ok((ark_bn254::Fq::from(value), flags))
+}
+
+fn read_fq2(bytes: &[u8]) -> Result<(ark_bn254::Fq2, u8), EncodingError> {
+    check_length(bytes, 2 * FQ_BYTES)?;
+    let (c1, flags) = read_fq(&bytes[..FQ_BYTES])?;
+    let c0 = fq_from_bytes(&bytes[FQ_BYTES..])?;
+    Ok((ark_bn254::Fq2::new(c0, c1), flags))
+}
+
+fn check_no_flags<T>((value, flags): (T, u8)) -> Result<T, EncodingError> {
+    if flags != 0 {
+        return Err(EncodingError::InvalidFlags(flags));
+    }
+    Ok(value)
+}
+
+// Sets the flags of an encoded point
+fn write_flags(bytes: &mut [u8], infinity: bool, y_largest: bool) {
+    if infinity {
+        bytes.fill(0);
+        bytes[0] = FLAG_INFINITY;
+    } else if y_largest {
+        bytes[0] |= FLAG_Y_LARGEST;
+    }
+}
+
+pub fn fq_to_bytes(a: ark_bn254::Fq) -> Vec<u8> { a.into_bigint().to_bytes_be() }
+
+pub fn fq_from_bytes(bytes: &[u8]) -> Result<ark_bn254::Fq, EncodingError> {
+    check_no_flags(read_fq(bytes)?)
+}
+
+pub fn fq2_to_bytes(a: ark_bn254::Fq2) -> Vec<u8> {
+    [fq_to_bytes(a.c1), fq_to_bytes(a.c0)].concat()
+}
+
+pub fn fq2_from_bytes(bytes: &[u8]) -> Result<ark_bn254::Fq2, EncodingError> {
+    check_no_flags(read_fq2(bytes)?)
+}
+
+pub fn fq12_to_bytes(a: ark_bn254::Fq12) -> Vec<u8> {
+    let coefficients: Vec<_> = a.to_base_prime_field_elements().collect();
+    coefficients.into_iter().rev().flat_map(fq_to_bytes).collect()
+}
+
+pub fn fq12_from_bytes(bytes: &[u8]) -> Result<ark_bn254::Fq12, EncodingError> {
+    check_length(bytes, 12 * FQ_BYTES)?;
+    let mut c = bytes
+        .chunks(FQ_BYTES)
+        .rev()
+        .map(fq_from_bytes)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+    let mut fq2 = || ark_bn254::Fq2::new(c.next().unwrap(), c.next().unwrap());
+    let c0 = ark_bn254::Fq6::new(fq2(), fq2(), fq2());
+    let c1 = ark_bn254::Fq6::new(fq2(), fq2(), fq2());
+    Ok(ark_bn254::Fq12::new(c0, c1))
+}
+
+pub fn g1_to_bytes(p: ark_bn254::G1Affine, compressed: bool) -> Vec<u8> {
+    let mut bytes = fq_to_bytes(p.x);
+    if !compressed {
+        bytes.extend(fq_to_bytes(p.y));
+    }
+    write_flags(&mut bytes, p.infinity, compressed && is_largest(p.y));
+    bytes
+}
+
+pub fn g1_from_bytes(bytes: &[u8], compressed: bool) -> Result<ark_bn254::G1Affine, EncodingError> {
+    check_length(bytes, if compressed { FQ_BYTES } else { 2 * FQ_BYTES })?;
+    let (x, flags) = read_fq(&bytes[..FQ_BYTES])?;
+    if flags == FLAG_INFINITY {
+        if !x.is_zero() || bytes[FQ_BYTES..].iter().any(|b| *b != 0) {
+            return Err(EncodingError::NonCanonicalInfinity);
+        }
+        return Ok(ark_bn254::G1Affine::zero());
+    }
+
+    let y = if compressed {
+        if flags != 0 && flags != FLAG_Y_LARGEST {
+            return Err(EncodingError::InvalidFlags(flags));
+        }
+        let y = (x.square() * x + ark_bn254::g1::Config::COEFF_B)
+            .sqrt()
+            .ok_or(EncodingError::NotOnCurve)?;
+        let y = if is_largest(y) == (flags == FLAG_Y_LARGEST) { y } else { -y };
+        // y = 0 has no larger root
+        if is_largest(y) != (flags == FLAG_Y_LARGEST) {
+            return Err(EncodingError::InvalidFlags(flags));
+        }
+        y
+    } else {
+        check_no_flags((x, flags))?;
+        fq_from_bytes(&bytes[FQ_BYTES..])?
+    };
+
+    let p = ark_bn254::G1Affine::new_unchecked(x, y);
+    if !p.is_on_curve() {
+        return Err(EncodingError::NotOnCurve);
+    }
+    Ok(p)
+}
+
+pub fn g2_to_bytes(p: ark_bn254::G2Affine, compressed: bool) -> Vec<u8> {
+    let mut bytes = fq2_to_bytes(p.x);
+    if !compressed {
+        bytes.extend(fq2_to_bytes(p.y));
+    }
+    write_flags(&mut bytes, p.infinity, compressed && is_largest_fq2(p.y));
+    bytes
+}
+
+pub fn g2_from_bytes(bytes: &[u8], compressed: bool) -> Result<ark_bn254::G2Affine, EncodingError> {
+    check_length(bytes, if compressed { 2 * FQ_BYTES } else { 4 * FQ_BYTES })?;
+    let (x, flags) = read_fq2(&bytes[..2 * FQ_BYTES])?;
+    if flags == FLAG_INFINITY {
+        if !x.is_zero() || bytes[2 * FQ_BYTES..].iter().any(|b| *b != 0) {
+            return Err(EncodingError::NonCanonicalInfinity);
+        }
+        return Ok(ark_bn254::G2Affine::zero());
+    }
+
+    let y = if compressed {
+        if flags != 0 && flags != FLAG_Y_LARGEST {
+            return Err(EncodingError::InvalidFlags(flags));
+        }
+        let y = (x.square() * x + ark_bn254::g2::Config::COEFF_B)
+            .sqrt()
+            .ok_or(EncodingError::NotOnCurve)?;
+        let y = if is_largest_fq2(y) == (flags == FLAG_Y_LARGEST) { y } else { -y };
+        if is_largest_fq2(y) != (flags == FLAG_Y_LARGEST) {
+            return Err(EncodingError::InvalidFlags(flags));
+        }
+        y
+    } else {
+        check_no_flags((x, flags))?;
+        fq2_from_bytes(&bytes[2 * FQ_BYTES..])?
+    };
+
+    let p = ark_bn254::G2Affine::new_unchecked(x, y);
+    if !p.is_on_curve() {
+        return Err(EncodingError::NotOnCurve);
+    }
+    Ok(p)
+}
+
+/// Pushes the bytes with the first one on top, the input of the decoders below.
+pub fn push_bytes(bytes: &[u8]) -> Script {
+    script! {
+        for byte in bytes.iter().rev() {
+            { *byte as u32 }
+        }
+    }
+}
+
+/// Pushes the witness of [`decode_g1`], y is the hint of the compressed encoding.
+pub fn push_g1(p: ark_bn254::G1Affine, compressed: bool) -> Script {
+    let y = if p.infinity { ark_bn254::Fq::zero() } else { p.y };
+    script! {
+        if compressed {
+            { Fq::push_u32_le(&BigUint::from(y).to_u32_digits()) }
+        }
+        { push_bytes(&g1_to_bytes(p, compressed)) }
+    }
+}
+
+/// Pushes the witness of [`decode_g2`], y is the hint of the compressed encoding.
+pub fn push_g2(p: ark_bn254::G2Affine, compressed: bool) -> Script {
+    let y = if p.infinity { ark_bn254::Fq2::zero() } else { p.y };
+    script! {
+        if compressed {
+            { fq2_push(y) }
+        }
+        { push_bytes(&g2_to_bytes(p, compressed)) }
+    }
+}
+
+/// Input Stack: [b₃₁, ..., b₁, b₀]
+/// Output Stack: [U254, flags]
+///
+/// Reads 32 big-endian bytes into the two flag bits and the 254-bit integer below them.
+fn decode_u256() -> Script {
+    // bit positions, counted from the most significant one, at which the flags and the limbs of
+    // a `U254` end
+    let mut ends = vec![2, 2 + U254::N_BITS - 29 * (U254::N_LIMBS - 1)];
+    for _ in 1..U254::N_LIMBS {
+        ends.push(ends.last().unwrap() + 29);
+    }
+    assert_eq!(*ends.last().unwrap(), 256);
+
+    script! {
+        0 OP_TOALTSTACK
+        for i in 0..32 {
+            OP_DUP 0 256 OP_WITHIN OP_VERIFY
+            { limb_to_be_bits(8) }
+            OP_FROMALTSTACK
+            for j in 1..=8 {
+                // acc ← 2⋅acc + bit
+                OP_DUP OP_ADD OP_ADD
+                if 8 * i + j < 256 && ends.contains(&(8 * i + j)) {
+                    OP_TOALTSTACK 0
+                }
+            }
+            OP_TOALTSTACK
+        }
+        // the altstack holds the flags and the limbs, the least significant limb on top
+        for _ in 0..=U254::N_LIMBS {
+            OP_FROMALTSTACK
+        }
+        OP_TOALTSTACK
+        for i in 1..U254::N_LIMBS {
+            { i } OP_ROLL
+        }
+        OP_FROMALTSTACK
+    }
+}
+
+/// Input Stack: [b₃₁, ..., b₀]
+/// Output Stack: [fq, flags]
+fn decode_fq_with_flags() -> Script {
+    let r = BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap();
+    script! {
+        { decode_u256() }
+        OP_TOALTSTACK
+        { Fq::copy(0) }
+        { Fq::is_field() }
+        OP_VERIFY
+        // a ⋅ 2²⁶¹
+        { Fq::mul_by_constant(&ark_bn254::Fq::from(r)) }
+        OP_FROMALTSTACK
+    }
+}
+
+// [fq] → [y > (p - 1) / 2]
+fn is_largest_script() -> Script {
+    script! {
+        { Fq::decode_montgomery() }
+        { U254::push_hex(Fq::P_PLUS_ONE_DIV2) }
+        { U254::greaterthanorequal(1, 0) }
+    }
+}
+
+/// Input Stack: [b₃₁, ..., b₀]
+/// Output Stack: [fq]
+pub fn decode_fq() -> Script {
+    script! {
+        { decode_fq_with_flags() }
+        0 OP_EQUALVERIFY
+    }
+}
+
+/// Input Stack: [c1, c0] with the bytes of c1 on top
+/// Output Stack: [fq2]
+pub fn decode_fq2() -> Script {
+    script! {
+        { decode_fq() }
+        { Fq::toaltstack() }
+        { decode_fq() }
+        { Fq::fromaltstack() }
+    }
+}
+
+/// Input Stack: [c0.c0.c0, ..., c1.c2.c1] with the bytes of c1.c2.c1 on top
+/// Output Stack: [fq12]
+pub fn decode_fq12() -> Script {
+    script! {
+        for _ in 0..12 {
+            { decode_fq() }
+            { Fq::toaltstack() }
+        }
+        for _ in 0..12 {
+            { Fq::fromaltstack() }
+        }
+    }
+}
+
+/// Input Stack: [x, y, y > (p - 1) / 2, flags]
+/// Output Stack: [x, y]
+fn check_g1_flags() -> Script {
+    script! {
+        OP_DUP { (FLAG_INFINITY >> 6) as u32 } OP_EQUAL
+        OP_IF
+            OP_2DROP
+            { Fq::is_zero_keep_element(0) }
+            OP_VERIFY
+            { Fq::is_zero_keep_element(1) }
+            OP_VERIFY
+        OP_ELSE
+            // the flags are 0x80 for the larger y and zero otherwise
+            OP_SWAP OP_DUP OP_ADD OP_EQUALVERIFY
+            { Fq::copy(1) }
+            { Fq::copy(1) }
+            { G1Affine::is_on_curve() }
+            OP_VERIFY
+        OP_ENDIF
+    }
+}
+
+/// Input Stack: [x, y, y > (p - 1) / 2, flags]
+/// Output Stack: [x, y]
+fn check_g2_flags() -> Script {
+    script! {
+        OP_DUP { (FLAG_INFINITY >> 6) as u32 } OP_EQUAL
+        OP_IF
+            OP_2DROP
+            for i in 0..4 {
+                { Fq::is_zero_keep_element(i) }
+                OP_VERIFY
+            }
+        OP_ELSE
+            OP_SWAP OP_DUP OP_ADD OP_EQUALVERIFY
+            { Fq2::copy(2) }
+            { Fq2::copy(2) }
+            { G2Affine::is_on_curve() }
+            OP_VERIFY
+        OP_ENDIF
+    }
+}
+
+/// Input Stack: [x ‖ y] or, compressed, [y, x] with y as a hint
+/// Output Stack: [x, y], the point at infinity is (0, 0) like [`G1Affine::identity`]
+pub fn decode_g1(compressed: bool) -> Script {
+    script! {
+        { decode_fq_with_flags() }
+        OP_TOALTSTACK
+        if compressed {
+            { Fq::roll(1) }
+            { Fq::copy(0) }
+            { Fq::is_field() }
+            OP_VERIFY
+            { Fq::copy(0) }
+            { is_largest_script() }
+        } else {
+            { Fq::toaltstack() }
+            { decode_fq() }
+            { Fq::fromaltstack() }
+            { Fq::roll(1) }
+            // without y in the encoding only zero flags are valid for finite points
+            0
+        }
+        OP_FROMALTSTACK
+        { check_g1_flags() }
+    }
+}
+
+/// Input Stack: [x ‖ y] or, compressed, [y, x] with y as a hint
+/// Output Stack: [x, y], the point at infinity is (0, 0) like [`G2Affine::identity`]
+pub fn decode_g2(compressed: bool) -> Script {
+    script! {
+        // x.c1 and the flags
+        { decode_fq_with_flags() }
+        OP_TOALTSTACK
+        { Fq::toaltstack() }
+        { decode_fq() }
+        if compressed {
+            { Fq::fromaltstack() }
+            { Fq2::roll(2) }
+            { Fq::copy(0) }
+            { Fq::is_field() }
+            OP_VERIFY
+            { Fq::copy(1) }
+            { Fq::is_field() }
+            OP_VERIFY
+
+            // y.c1 decides unless it is zero
+            { Fq::copy(0) }
+            { Fq::decode_montgomery() }
+            { U254::is_zero_keep_element(0) }
+            OP_IF
+                { U254::drop() }
+                { Fq::copy(1) }
+                { Fq::decode_montgomery() }
+            OP_ENDIF
+            { U254::push_hex(Fq::P_PLUS_ONE_DIV2) }
+            { U254::greaterthanorequal(1, 0) }
+        } else {
+            { Fq::toaltstack() }
+            { decode_fq2() }
+            { Fq::fromaltstack() }
+            { Fq::fromaltstack() }
+            { Fq2::roll(2) }
+            0
+        }
+        OP_FROMALTSTACK
+        { check_g2_flags() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bn254::msm::g1_affine_push;
+    use crate::bn254::utils::fq12_push;
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn fq_push(a: ark_bn254::Fq) -> Script {
+        script! {
+            { Fq::push_u32_le(&BigUint::from(a).to_u32_digits()) }
+        }
+    }
+
+    #[test]
+    fn test_fq_encoding() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        println!("encoding.decode_fq: {} bytes", decode_fq().len());
+
+        for _ in 0..3 {
+            let a = ark_bn254::Fq::rand(&mut prng);
+            let bytes = fq_to_bytes(a);
+            assert_eq!(bytes.len(), FQ_BYTES);
+            assert_eq!(fq_from_bytes(&bytes), Ok(a));
+
+            let script = script! {
+                { push_bytes(&bytes) }
+                { decode_fq() }
+                { fq_push(a) }
+                { Fq::equalverify(1, 0) }
+
+                // the same bytes as `convert_to_be_bytes`
+                { fq_push(a) }
+                { Fq::convert_to_be_bytes() }
+                { decode_fq() }
+                { fq_push(a) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+
+        let modulus = BigUint::from(ark_bn254::Fq::MODULUS).to_bytes_be();
+        let mut flagged = fq_to_bytes(ark_bn254::Fq::from(1u32));
+        flagged[0] |= FLAG_INFINITY;
+        assert_eq!(fq_from_bytes(&modulus), Err(EncodingError::NotInField));
+        assert_eq!(fq_from_bytes(&flagged), Err(EncodingError::InvalidFlags(FLAG_INFINITY)));
+        assert!(fq_from_bytes(&modulus[1..]).is_err());
+
+        for bytes in [modulus, flagged] {
+            let script = script! {
+                { push_bytes(&bytes) }
+                { decode_fq() }
+                { Fq::drop() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(!exec_result.success);
+        }
+
+        // a byte out of range
+        let script = script! {
+            { push_bytes(&[0; 31]) }
+            256
+            { decode_fq() }
+            { Fq::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+
+    #[test]
+    fn test_fq2_fq12_encoding() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bn254::Fq2::rand(&mut prng);
+        let b = ark_bn254::Fq12::rand(&mut prng);
+        assert_eq!(fq2_from_bytes(&fq2_to_bytes(a)), Ok(a));
+        assert_eq!(fq12_from_bytes(&fq12_to_bytes(b)), Ok(b));
+        assert_eq!(&fq2_to_bytes(a)[FQ_BYTES..], &fq_to_bytes(a.c0)[..]);
+
+        let script = script! {
+            { push_bytes(&fq2_to_bytes(a)) }
+            { decode_fq2() }
+            { fq2_push(a) }
+            { Fq2::equalverify() }
+            { push_bytes(&fq12_to_bytes(b)) }
+            { decode_fq12() }
+            { fq12_push(b) }
+            { crate::bn254::fq12::Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_g1_encoding() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        println!("encoding.decode_g1: {} bytes", decode_g1(false).len());
+        println!("encoding.decode_g1(compressed): {} bytes", decode_g1(true).len());
+
+        let mut points = vec![ark_bn254::G1Affine::zero()];
+        for _ in 0..2 {
+            let p = ark_bn254::G1Affine::rand(&mut prng);
+            points.push(p);
+            points.push(-p);
+        }
+
+        for p in points {
+            for compressed in [false, true] {
+                assert_eq!(g1_from_bytes(&g1_to_bytes(p, compressed), compressed), Ok(p));
+
+                let script = script! {
+                    { push_g1(p, compressed) }
+                    { decode_g1(compressed) }
+                    if p.infinity {
+                        { G1Affine::identity() }
+                    } else {
+                        { g1_affine_push(p) }
+                    }
+                    { G1Affine::equalverify() }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(exec_result.success);
+            }
+
+            if !p.infinity {
+                // `convert_to_compressed` uses the same format
+                let script = script! {
+                    { g1_affine_push(p) }
+                    { G1Affine::convert_to_compressed() }
+                    for byte in g1_to_bytes(p, true) {
+                        { byte as u32 } OP_EQUALVERIFY
+                    }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(exec_result.success);
+
+                // the hint has to match the flag
+                let script = script! {
+                    { fq_push(-p.y) }
+                    { push_bytes(&g1_to_bytes(p, true)) }
+                    { decode_g1(true) }
+                    { Fq::drop() }
+                    { Fq::drop() }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(!exec_result.success);
+            }
+        }
+
+        let mut off_curve = g1_to_bytes(ark_bn254::G1Affine::rand(&mut prng), false);
+        off_curve[63] ^= 1;
+        assert_eq!(g1_from_bytes(&off_curve, false), Err(EncodingError::NotOnCurve));
+        let script = script! {
+            { push_bytes(&off_curve) }
+            { decode_g1(false) }
+            { Fq::drop() }
+            { Fq::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+
+    #[test]
+    fn test_g2_encoding() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        println!("encoding.decode_g2: {} bytes", decode_g2(false).len());
+        println!("encoding.decode_g2(compressed): {} bytes", decode_g2(true).len());
+
+        let mut points = vec![ark_bn254::G2Affine::zero()];
+        for _ in 0..2 {
+            let p = ark_bn254::G2Affine::rand(&mut prng);
+            points.push(p);
+            points.push(-p);
+        }
+
+        for p in points {
+            for compressed in [false, true] {
+                assert_eq!(g2_from_bytes(&g2_to_bytes(p, compressed), compressed), Ok(p));
+
+                let script = script! {
+                    { push_g2(p, compressed) }
+                    { decode_g2(compressed) }
+                    if p.infinity {
+                        { G2Affine::identity() }
+                    } else {
+                        { G2Affine::push(p) }
+                    }
+                    { G2Affine::equalverify() }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(exec_result.success);
+            }
+        }
+
+        let mut flipped = g2_to_bytes(ark_bn254::G2Affine::rand(&mut prng), true);
+        flipped[0] |= FLAG_MASK;
+        assert_eq!(g2_from_bytes(&flipped, true), Err(EncodingError::InvalidFlags(FLAG_MASK)));
+    }
+}
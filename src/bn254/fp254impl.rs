@@ -1,9 +1,9 @@
 use crate::bigint::add::limb_add_carry;
 use crate::bigint::bits::limb_to_be_bits;
 use crate::bigint::sub::limb_sub_borrow;
-use crate::bigint::U254;
-use crate::bigint::u29x9::{u29x9_mul_karazuba, u29x9_mul_karazuba_imm, u29x9_mulhi_karazuba_imm, u29x9_mullo_karazuba_imm, u29x9_square};
-use crate::pseudo::OP_256MUL;
+use crate::bigint::{BigIntOps, U254};
+use crate::bigint::u29x9::{u29_mul_carry_29, u29x9_mul_karazuba, u29x9_mul_karazuba_imm, u29x9_mulhi_karazuba_imm, u29x9_mullo_karazuba_imm, u29x9_square};
+use crate::pseudo::{push_to_stack, OP_256MUL};
 use crate::treepp::*;
 use ark_ff::{BigInteger, PrimeField};
 use bitcoin_script::script;
@@ -12,15 +12,24 @@ use num_traits::{Num, One};
 use std::ops::{Add, Div, Mul, Rem, Shl};
 use std::sync::OnceLock;
 
+/// Prime fields in Montgomery form with R = 2²⁹ⁿ for elements of n 29-bit limbs.
+///
+/// `BigInt` sets the width of the elements. Nine limbs, i.e. R = 2²⁶¹, use the Karatsuba
+/// gadgets of [`crate::bigint::u29x9`] and fit any modulus of up to 256 bits. Wider fields such
+/// as the base field of BLS12-381 multiply limb by limb, see [`montgomery_mul`].
+///
+/// The name is historical: the trait started out with the 254-bit fields of BN254, but it
+/// also implements the 256-bit fields of secp256k1 and the 381-bit base field of BLS12-381.
 pub trait Fp254Impl {
     const MODULUS: &'static str;
     const MONTGOMERY_ONE: &'static str;
-    const N_LIMBS: u32 = U254::N_LIMBS;
-    const N_BITS: u32 = U254::N_BITS;
+    const N_LIMBS: u32 = <Self::BigInt as BigIntOps>::N_LIMBS;
+    const N_BITS: u32 = <Self::BigInt as BigIntOps>::N_BITS;
 
-    // Modulus as 30-bit limbs
-    const MODULUS_LIMBS: [u32; U254::N_LIMBS as usize];
-    const MODULUS_INV_261: [u32; U254::N_LIMBS as usize];
+    // Modulus as 29-bit limbs
+    const MODULUS_LIMBS: &'static [u32];
+    // p⁻¹ mod R as 29-bit limbs
+    const MODULUS_INV: &'static [u32];
 
     const P_PLUS_ONE_DIV2: &'static str;
     const TWO_P_PLUS_ONE_DIV3: &'static str;
@@ -30,34 +39,35 @@ pub trait Fp254Impl {
     const SUB_ONCELOCK: OnceLock<Script> = OnceLock::new();
     const MUL_ONCELOCK: OnceLock<Script> = OnceLock::new();
 
+    type BigInt: BigIntOps;
     type ConstantType: PrimeField;
 
     #[inline]
-    fn copy(a: u32) -> Script { U254::copy(a) }
+    fn copy(a: u32) -> Script { Self::BigInt::copy(a) }
 
     #[inline]
-    fn roll(a: u32) -> Script { U254::roll(a) }
+    fn roll(a: u32) -> Script { Self::BigInt::roll(a) }
 
     #[inline]
-    fn drop() -> Script { U254::drop() }
+    fn drop() -> Script { Self::BigInt::drop() }
 
     #[inline]
-    fn zip(a: u32, b: u32) -> Script { U254::zip(a, b) }
+    fn zip(a: u32, b: u32) -> Script { Self::BigInt::zip(a, b) }
 
     #[inline]
     fn push_u32_le(v: &[u32]) -> Script {
         let r = BigUint::from_str_radix(Self::MONTGOMERY_ONE, 16).unwrap();
         let p = BigUint::from_str_radix(Self::MODULUS, 16).unwrap();
         script! {
-            { U254::push_u32_le(&BigUint::from_slice(v).mul(r).rem(p).to_u32_digits()) }
+            { Self::BigInt::push_u32_le(&BigUint::from_slice(v).mul(r).rem(p).to_u32_digits()) }
         }
     }
 
     #[inline]
-    fn equal(a: u32, b: u32) -> Script { U254::equal(a, b) }
+    fn equal(a: u32, b: u32) -> Script { Self::BigInt::equal(a, b) }
 
     #[inline]
-    fn equalverify(a: u32, b: u32) -> Script { U254::equalverify(a, b) }
+    fn equalverify(a: u32, b: u32) -> Script { Self::BigInt::equalverify(a, b) }
 
     #[inline]
     fn push_dec(dec_string: &str) -> Script {
@@ -65,7 +75,7 @@ pub trait Fp254Impl {
         let r = BigUint::from_str_radix(Self::MONTGOMERY_ONE, 16).unwrap();
         let p = BigUint::from_str_radix(Self::MODULUS, 16).unwrap();
         script! {
-            { U254::push_u32_le(&v.mul(r).rem(p).to_u32_digits()) }
+            { Self::BigInt::push_u32_le(&v.mul(r).rem(p).to_u32_digits()) }
         }
     }
 
@@ -75,39 +85,51 @@ pub trait Fp254Impl {
         let r = BigUint::from_str_radix(Self::MONTGOMERY_ONE, 16).unwrap();
         let p = BigUint::from_str_radix(Self::MODULUS, 16).unwrap();
         script! {
-            { U254::push_u32_le(&v.mul(r).rem(p).to_u32_digits()) }
+            { Self::BigInt::push_u32_le(&v.mul(r).rem(p).to_u32_digits()) }
         }
     }
 
     #[inline]
-    fn convert_to_be_bits() -> Script { U254::convert_to_be_bits() }
+    fn convert_to_be_bits() -> Script { Self::BigInt::convert_to_be_bits() }
 
     #[inline]
-    fn convert_to_be_bits_toaltstack() -> Script { U254::convert_to_be_bits_toaltstack() }
+    fn convert_to_be_bits_toaltstack() -> Script { Self::BigInt::convert_to_be_bits_toaltstack() }
 
     #[inline]
-    fn convert_to_le_bits() -> Script { U254::convert_to_le_bits() }
+    fn convert_to_le_bits() -> Script { Self::BigInt::convert_to_le_bits() }
 
     #[inline]
-    fn convert_to_le_bits_toaltstack() -> Script { U254::convert_to_le_bits_toaltstack() }
+    fn convert_to_le_bits_toaltstack() -> Script { Self::BigInt::convert_to_le_bits_toaltstack() }
 
     #[inline]
-    fn push_modulus() -> Script { U254::push_hex(Self::MODULUS) }
+    fn push_constant(constant: &Self::ConstantType) -> Script {
+        Self::push_u32_le(&BigUint::from(constant.into_bigint()).to_u32_digits())
+    }
+
+    #[inline]
+    fn push_modulus() -> Script { Self::BigInt::push_hex(Self::MODULUS) }
 
     #[inline]
-    fn push_zero() -> Script { U254::push_zero() }
+    fn push_zero() -> Script { Self::BigInt::push_zero() }
 
     #[inline]
-    fn push_one() -> Script { U254::push_hex(Self::MONTGOMERY_ONE) }
+    fn push_one() -> Script { Self::BigInt::push_hex(Self::MONTGOMERY_ONE) }
 
     fn decode_montgomery() -> Script {
+        if Self::N_LIMBS != U254::N_LIMBS {
+            // a ⋅ 1 ⋅ R⁻¹
+            return script! {
+                { Self::BigInt::push_one() }
+                { Self::mul() }
+            };
+        }
         script! {
             // a ⋅ p⁻¹
-            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV_261) }
+            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV.try_into().unwrap()) }
             // ⋯ ❨A₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀
             
             // ❨a ⋅ p⁻¹❩ ⋅ p
-            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS) }
+            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS.try_into().unwrap()) }
             // ⋯ ❨❨A₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀⋅P₂₆₀…₀❩₅₂₁…₂₆₁
 
             // - ❨a ⋅ p⁻¹❩ ⋅ p
@@ -233,47 +255,24 @@ pub trait Fp254Impl {
             { Self::MODULUS_LIMBS[0] } OP_SWAP { 0x20000000 }
             limb_sub_borrow OP_TOALTSTACK
             // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ A₁ 2²⁹ C₀⁻ | M₀-A₀ ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ 2²⁹ C₀⁻+A₁
-            { Self::MODULUS_LIMBS[1] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ 2²⁹ C₁⁻ | M₁-(C₀⁻+A₁) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ 2²⁹ C₁⁻+A₂
-            { Self::MODULUS_LIMBS[2] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ 2²⁹ C₂⁻ | M₂-(C₁⁻+A₂) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ A₆ A₅ A₄ 2²⁹ C₂⁻+A₃
-            { Self::MODULUS_LIMBS[3] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ A₆ A₅ A₄ 2²⁹ C₃⁻ | M₃-(C₂⁻+A₃) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ A₆ A₅ 2²⁹ C₃⁻+A₄
-            { Self::MODULUS_LIMBS[4] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ A₆ A₅ 2²⁹ C₄⁻ | M₄-(C₃⁻+A₄) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ A₆ 2²⁹ C₄⁻+A₅
-            { Self::MODULUS_LIMBS[5] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ A₆ 2²⁹ C₅⁻ | M₅-(C₄⁻+A₅) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ A₇ 2²⁹ C₅⁻+A₆
-            { Self::MODULUS_LIMBS[6] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ A₇ 2²⁹ C₆⁻ | M₆-(C₅⁻+A₆) ⋯
-            OP_ROT OP_ADD
-            // ⋯ A₈ 2²⁹ C₆⁻+A₇
-            { Self::MODULUS_LIMBS[7] } OP_SWAP OP_ROT
-            limb_sub_borrow OP_TOALTSTACK
-            // ⋯ A₈ 2²⁹ C₇⁻ | M₇-(C₆⁻+A₇) ⋯
+
+            // from     M₁      - (C₀⁻ + A₁)
+            //   to     M{N-2}  - (C{N-3}⁻ + A{N-2})
+            for i in 1..Self::N_LIMBS - 1 {
+                OP_ROT OP_ADD
+                // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ 2²⁹ C₀⁻+A₁
+                { Self::MODULUS_LIMBS[i as usize] } OP_SWAP OP_ROT
+                limb_sub_borrow OP_TOALTSTACK
+                // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ 2²⁹ C₁⁻ | M₁-(C₀⁻+A₁) ⋯
+            }
+            // ⋯ A₈ 2²⁹ C₇⁻
             OP_NIP OP_ADD
             // ⋯ C₇⁻+A₈
-            { Self::MODULUS_LIMBS[8] } OP_SWAP OP_SUB
+            { *Self::MODULUS_LIMBS.last().unwrap() } OP_SWAP OP_SUB
             // ⋯ M₈-(C₇⁻+A₈)
-            OP_FROMALTSTACK OP_FROMALTSTACK OP_FROMALTSTACK OP_FROMALTSTACK
-            OP_FROMALTSTACK OP_FROMALTSTACK OP_FROMALTSTACK OP_FROMALTSTACK
+            for _ in 0..Self::N_LIMBS - 1 {
+                OP_FROMALTSTACK
+            }
         }
     }
 
@@ -485,6 +484,14 @@ pub trait Fp254Impl {
     }
 
     fn mul() -> Script {
+        if Self::N_LIMBS != U254::N_LIMBS {
+            return script! {
+                { montgomery_mul(Self::MODULUS_LIMBS, Self::MODULUS_INV[0]) }
+                // the sum with zero subtracts the modulus once if needed
+                { Self::push_zero() }
+                { Self::add(1, 0) }
+            };
+        }
         Self::MUL_ONCELOCK.get_or_init(|| {
             script! {
                 // a ⋅ b  →  ❨a ⋅ b❩ᵐᵒᵈ2²⁶¹ ⌊2⁻²⁶¹⋅❨a ⋅ b❩⌋
@@ -494,11 +501,11 @@ pub trait Fp254Impl {
 
                 // lo ⋅ p⁻¹
                 // lo  <=>  ❨a ⋅ b❩ᵐᵒᵈ2²⁶¹
-                { u29x9_mullo_karazuba_imm(Self::MODULUS_INV_261) }
+                { u29x9_mullo_karazuba_imm(Self::MODULUS_INV.try_into().unwrap()) }
                 // ⋯ ❨A₂₆₀…₀⋅B₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨A₂₆₀…₀⋅B₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀
                 
                 // ❨lo ⋅ p⁻¹❩ ⋅ p
-                { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS) }
+                { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS.try_into().unwrap()) }
                 // ⋯ ❨A₂₆₀…₀⋅B₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨❨A₂₆₀…₀⋅B₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀⋅P₂₆₀…₀❩₅₂₁…₂₆₁
                 
                 // hi - ❨lo ⋅ p⁻¹❩ ⋅ p
@@ -510,9 +517,9 @@ pub trait Fp254Impl {
         .clone()
     }
 
-    fn is_zero(a: u32) -> Script { U254::is_zero(a) }
+    fn is_zero(a: u32) -> Script { Self::BigInt::is_zero(a) }
 
-    fn is_zero_keep_element(a: u32) -> Script { U254::is_zero_keep_element(a) }
+    fn is_zero_keep_element(a: u32) -> Script { Self::BigInt::is_zero_keep_element(a) }
 
     fn is_one_keep_element(a: u32) -> Script {
         script! {
@@ -522,28 +529,29 @@ pub trait Fp254Impl {
     }
 
     fn is_one(a: u32) -> Script {
-        let mut u29x9_one = [0u32; 9];
         let montgomery_one = BigUint::from_str_radix(Self::MONTGOMERY_ONE, 16).unwrap();
-        for i in 0..9 {
-            u29x9_one[i] = *montgomery_one.clone()
-                .div(BigUint::one().shl(29 * i) as BigUint)
-                .rem(BigUint::one().shl(29) as BigUint)
-                .to_u32_digits().first().unwrap_or(&0);
-        }
+        let one_limbs = (0..Self::N_LIMBS as usize)
+            .map(|i| {
+                *montgomery_one.clone()
+                    .div(BigUint::one().shl(29 * i) as BigUint)
+                    .rem(BigUint::one().shl(29) as BigUint)
+                    .to_u32_digits().first().unwrap_or(&0)
+            })
+            .collect::<Vec<_>>();
         script! {
             { Self::roll(a) }
             // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ A₁ A₀
-            { *u29x9_one.first().unwrap() } OP_EQUAL OP_SWAP
+            { *one_limbs.first().unwrap() } OP_EQUAL OP_SWAP
             // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ A₁ A₀=1₀ A₁
             for i in 1..Self::N_LIMBS as usize - 1 {
                 // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ A₀=1₀ A₁
-                { u29x9_one[i] } OP_EQUAL
+                { one_limbs[i] } OP_EQUAL
                 // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ A₂ A₀=1₀ A₁=1₁
                 OP_BOOLAND OP_SWAP
                 // ⋯ A₈ A₇ A₆ A₅ A₄ A₃ (A₀=1₀)∧(A₁=1₁) A₂
             }
             // ⋯ (A₀=1₀)∧⋯∧(A₇=1₇) A₈
-            { *u29x9_one.last().unwrap() } OP_EQUAL
+            { *one_limbs.last().unwrap() } OP_EQUAL
             // ⋯ (A₀=1₀)∧⋯∧(A₇=1₇) A₈=1₈
             OP_BOOLAND
             // ⋯ (A₀=1₀)∧⋯∧(A₈=1₈)
@@ -565,13 +573,19 @@ pub trait Fp254Impl {
             OP_TOALTSTACK
 
             { Self::push_modulus() }
-            { U254::lessthan(1, 0) }
+            { Self::BigInt::lessthan(1, 0) }
 
             OP_FROMALTSTACK OP_BOOLAND
         }
     }
 
     fn square() -> Script {
+        if Self::N_LIMBS != U254::N_LIMBS {
+            return script! {
+                { Self::copy(0) }
+                { Self::mul() }
+            };
+        }
         script! {
             // a ⋅ a  →  ❨a ⋅ a❩ᵐᵒᵈ2²⁶¹ ⌊2⁻²⁶¹⋅❨a ⋅ a❩⌋
             // ⋯ A₂₆₀…₀
@@ -580,11 +594,11 @@ pub trait Fp254Impl {
 
             // lo ⋅ p⁻¹
             // lo  <=>  ❨a ⋅ a❩ᵐᵒᵈ2²⁶¹
-            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV_261) }
+            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV.try_into().unwrap()) }
             // ⋯ ❨A₂₆₀…₀⋅A₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨A₂₆₀…₀⋅A₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀
             
             // ❨lo ⋅ p⁻¹❩ ⋅ p
-            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS) }
+            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS.try_into().unwrap()) }
             // ⋯ ❨A₂₆₀…₀⋅A₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨❨A₂₆₀…₀⋅A₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀⋅P₂₆₀…₀❩₅₂₁…₂₆₁
 
             // hi - ❨lo ⋅ p⁻¹❩ ⋅ p
@@ -594,20 +608,145 @@ pub trait Fp254Impl {
         }
     }
 
+    /// Computes a⁻¹ with the binary extended Euclidean algorithm of [`U254::inv_stage1`]. Its
+    /// intermediate values need headroom above the modulus, so fields wider than BN254 fall back
+    /// to [`Fp254Impl::inv_by_exponentiation`].
     fn inv() -> Script {
+        if Self::N_BITS != U254::N_BITS {
+            return Self::inv_by_exponentiation();
+        }
         let r = BigUint::from_str_radix(Self::MONTGOMERY_ONE, 16).unwrap();
         let p = BigUint::from_str_radix(Self::MODULUS, 16).unwrap();
         script! {
             { Self::push_modulus() }
             { Self::roll(1) }
-            { U254::inv_stage1() }
-            { U254::inv_stage2(Self::MODULUS) }
+            { Self::BigInt::inv_stage1() }
+            { Self::BigInt::inv_stage2(Self::MODULUS) }
             { Self::mul() }
             { Self::mul_by_constant(&Self::ConstantType::from(r.pow(3).rem(p))) }
         }
     }
 
+    /// Computes a⁻¹ as aᵖ⁻² by square-and-multiply over the constant exponent. The inverse of
+    /// zero is zero.
+    fn inv_by_exponentiation() -> Script {
+        let exponent = BigUint::from_str_radix(Self::MODULUS, 16).unwrap() - BigUint::from(2u32);
+        let n_bits = exponent.bits();
+        script! {
+            // the leading bit of the exponent is always set
+            { Self::copy(0) }
+            for i in (0..n_bits - 1).rev() {
+                { Self::square() }
+                if exponent.bit(i) {
+                    { Self::copy(1) }
+                    { Self::mul() }
+                }
+            }
+            { Self::roll(1) }
+            { Self::drop() }
+        }
+    }
+
+    /// Input Stack: [a, a⁻¹]
+    /// Output Stack: [a⁻¹]
+    ///
+    /// Checks an inverse that is supplied as a hint instead of computing it like
+    /// [`Fp254Impl::inv`], see [`crate::bn254::hints::inv_hint`]. The hint has to be reduced,
+    /// otherwise a + p would pass as well.
+    fn hinted_inv() -> Script {
+        script! {
+            { Self::is_field() }
+            OP_VERIFY
+            { Self::copy(0) }
+            { Self::roll(2) }
+            { Self::mul() }
+            { Self::is_one(0) }
+            OP_VERIFY
+        }
+    }
+
+    /// Input Stack: [a, b, a / b]
+    /// Output Stack: [a / b]
+    ///
+    /// Checks a quotient that is supplied as a hint, see [`crate::bn254::hints::div_hint`]. The
+    /// hint has to be reduced.
+    fn hinted_div() -> Script {
+        script! {
+            { Self::is_field() }
+            OP_VERIFY
+            { Self::copy(0) }
+            { Self::roll(2) }
+            { Self::mul() }
+            { Self::roll(2) }
+            { Self::equalverify(1, 0) }
+        }
+    }
+
+    /// Input Stack: [a₀, a₁, ..., aₙ₋₁]
+    /// Output Stack: [a₀⁻¹, a₁⁻¹, ..., aₙ₋₁⁻¹]
+    ///
+    /// Montgomery's trick: a single inversion of the product and 3⋅(n - 1) multiplications.
+    fn batch_inv(n: u32) -> Script { Self::batch_inv_with(n, Self::inv()) }
+
+    /// Input Stack: [a₀, a₁, ..., aₙ₋₁, (a₀ ⋅ a₁ ⋯ aₙ₋₁)⁻¹]
+    /// Output Stack: [a₀⁻¹, a₁⁻¹, ..., aₙ₋₁⁻¹]
+    ///
+    /// [`Fp254Impl::batch_inv`] with the inverse of the product supplied as a hint, see
+    /// [`crate::bn254::hints::batch_inv_hint`].
+    fn hinted_batch_inv(n: u32) -> Script {
+        script! {
+            { Self::toaltstack() }
+            { Self::batch_inv_with(n, script! {
+                { Self::fromaltstack() }
+                { Self::hinted_inv() }
+            }) }
+        }
+    }
+
+    /// Montgomery's trick with `inv` inverting the product of all elements on top of the stack.
+    fn batch_inv_with(n: u32, inv: Script) -> Script {
+        assert!(n > 0);
+        script! {
+            // prefix products p₁, ..., pₙ₋₁ with pᵢ = a₀ ⋯ aᵢ, aᵢ stays at depth n - 2
+            for i in 1..n {
+                if i == 1 {
+                    { Self::copy(n - 1) }
+                } else {
+                    { Self::copy(0) }
+                }
+                { Self::copy(n - 1) }
+                { Self::mul() }
+            }
+            // [a₀, ..., aₙ₋₁, p₁, ..., pₙ₋₂, t = pₙ₋₁⁻¹]
+            { inv }
+
+            // aᵢ⁻¹ = t ⋅ pᵢ₋₁ and t ← t ⋅ aᵢ, the inverses go to the altstack
+            for i in (1..n).rev() {
+                { Self::copy(0) }
+                if i == 1 {
+                    // p₀ = a₀
+                    { Self::roll(3) }
+                } else {
+                    { Self::roll(2) }
+                }
+                { Self::mul() }
+                { Self::toaltstack() }
+                { Self::roll(if i == 1 { 1 } else { i - 1 }) }
+                { Self::mul() }
+            }
+            for _ in 1..n {
+                { Self::fromaltstack() }
+            }
+        }
+    }
+
     fn mul_by_constant(constant: &Self::ConstantType) -> Script {
+        if Self::N_LIMBS != U254::N_LIMBS {
+            return script! {
+                { Self::push_constant(constant) }
+                { Self::mul() }
+            };
+        }
 
         // Convert `PrimeField` to `[u29; 9]` in Montgomery form:
         let mut u29x9_montgomery = [0u32; 9];
@@ -632,11 +771,11 @@ pub trait Fp254Impl {
 
             // lo ⋅ p⁻¹
             // lo  <=>  ❨a ⋅ b❩ᵐᵒᵈ2²⁶¹
-            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV_261) }
+            { u29x9_mullo_karazuba_imm(Self::MODULUS_INV.try_into().unwrap()) }
             // ⋯ ❨A₂₆₀…₀⋅B₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨A₂₆₀…₀⋅B₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀
             
             // ❨lo ⋅ p⁻¹❩ ⋅ p
-            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS) }
+            { u29x9_mulhi_karazuba_imm(Self::MODULUS_LIMBS.try_into().unwrap()) }
             // ⋯ ❨A₂₆₀…₀⋅B₂₆₀…₀❩₅₂₁…₂₆₁ ❨❨❨A₂₆₀…₀⋅B₂₆₀…₀❩₂₆₀…₀⋅P⁻¹₂₆₀…₀❩₂₆₀…₀⋅P₂₆₀…₀❩₅₂₁…₂₆₁
 
             // hi - ❨lo ⋅ p⁻¹❩ ⋅ p
@@ -648,9 +787,9 @@ pub trait Fp254Impl {
 
     fn div2() -> Script {
         script! {
-            { U254::div2rem() }
+            { Self::BigInt::div2rem() }
             OP_IF
-                { U254::push_hex(Self::P_PLUS_ONE_DIV2) }
+                { Self::BigInt::push_hex(Self::P_PLUS_ONE_DIV2) }
                 { Self::add(1, 0) }
             OP_ENDIF
         }
@@ -658,16 +797,16 @@ pub trait Fp254Impl {
 
     fn div3() -> Script {
         script! {
-            { U254::div3rem() }
+            { Self::BigInt::div3rem() }
             OP_DUP
             0 OP_GREATERTHAN
             OP_IF
                 OP_1SUB
                 OP_IF
-                    { U254::push_hex(Self::P_PLUS_TWO_DIV3) }
+                    { Self::BigInt::push_hex(Self::P_PLUS_TWO_DIV3) }
                     { Self::add(1, 0) }
                 OP_ELSE
-                    { U254::push_hex(Self::TWO_P_PLUS_ONE_DIV3) }
+                    { Self::BigInt::push_hex(Self::TWO_P_PLUS_ONE_DIV3) }
                     { Self::add(1, 0) }
                 OP_ENDIF
             OP_ELSE
@@ -1068,7 +1207,116 @@ pub trait Fp254Impl {
         }
     }
 
-    fn toaltstack() -> Script { U254::toaltstack() }
+    fn toaltstack() -> Script { Self::BigInt::toaltstack() }
 
-    fn fromaltstack() -> Script { U254::fromaltstack() }
+    fn fromaltstack() -> Script { Self::BigInt::fromaltstack() }
+}
+
+/// Splits a sum of up to three limbs into its low 29 bits and the carry.
+fn limb_split_carry() -> Script {
+    script! {
+        // x
+        OP_DUP { 0x20000000 } OP_GREATERTHANOREQUAL
+        OP_IF
+            { 0x20000000 } OP_SUB 1
+        OP_ELSE
+            0
+        OP_ENDIF
+        // x-2²⁹⋅c c
+        OP_OVER { 0x20000000 } OP_GREATERTHANOREQUAL
+        OP_IF
+            OP_1ADD OP_SWAP { 0x20000000 } OP_SUB OP_SWAP
+        OP_ENDIF
+        // xᵐᵒᵈ2²⁹ ⌊2⁻²⁹⋅x⌋
+    }
+}
+
+/// Input: a b
+/// Output: a ⋅ b ⋅ R⁻¹ mod p + k ⋅ p < 2p with R = 2²⁹ⁿ for n limbs
+///
+/// Montgomery multiplication with coarsely integrated operand scanning: for every limb aᵢ of `a`,
+/// least significant first, the accumulator t becomes (t + aᵢ⋅b + m⋅p) / 2²⁹ with m chosen such
+/// that the division is exact. Both products are accumulated in the same pass over the limbs,
+/// each with its own carry so that no sum exceeds three limbs. The accumulator stays below 2p.
+pub fn montgomery_mul(modulus_limbs: &[u32], modulus_inv_0: u32) -> Script {
+    let n_limbs = modulus_limbs.len() as u32;
+    // -p⁻¹ mod 2²⁹
+    let m_factor = (0x20000000 - modulus_inv_0) % 0x20000000;
+
+    script! {
+        // ⋯ A B
+        for _ in 0..n_limbs {
+            { 2 * n_limbs - 1 } OP_ROLL
+        }
+        // ⋯ B A
+        for i in 0..n_limbs {
+            { n_limbs - 1 - i } OP_ROLL OP_TOALTSTACK
+        }
+        // ⋯ B | A₀ A₁ ⋯
+        { push_to_stack(0, n_limbs as usize) }
+        // ⋯ B T
+
+        for _ in 0..n_limbs {
+            OP_FROMALTSTACK
+            // ⋯ B T aᵢ
+
+            // t₀ + aᵢ⋅b₀
+            { n_limbs + 1 } OP_PICK OP_OVER u29_mul_carry_29
+            // ⋯ t₀ aᵢ (aᵢ⋅b₀)₂₈…₀ (aᵢ⋅b₀)₅₇…₂₉
+            OP_SWAP 3 OP_ROLL OP_ADD limb_split_carry
+            // ⋯ aᵢ (aᵢ⋅b₀)₅₇…₂₉ x₀ c
+            OP_ROT OP_ADD
+            // ⋯ aᵢ x₀ c₁
+
+            // m = x₀⋅❨-p⁻¹❩ mod 2²⁹
+            OP_OVER { m_factor } u29_mul_carry_29 OP_DROP
+            // ⋯ aᵢ x₀ c₁ m
+
+            // x₀ + m⋅p₀ ≡ 0 mod 2²⁹
+            OP_DUP { modulus_limbs[0] } u29_mul_carry_29
+            // ⋯ aᵢ x₀ c₁ m (m⋅p₀)₂₈…₀ (m⋅p₀)₅₇…₂₉
+            OP_SWAP 4 OP_ROLL OP_ADD OP_0NOTEQUAL OP_ADD
+            // ⋯ aᵢ c₁ m c₂
+            OP_ROT OP_SWAP
+            // ⋯ aᵢ m c₁ c₂
+
+            for j in 1..n_limbs {
+                // ⋯ B tⱼ aᵢ m c₁ c₂
+                // tⱼ + aᵢ⋅bⱼ + c₁
+                { n_limbs + 4 } OP_PICK 4 OP_PICK u29_mul_carry_29
+                // ⋯ tⱼ aᵢ m c₁ c₂ (aᵢ⋅bⱼ)₂₈…₀ (aᵢ⋅bⱼ)₅₇…₂₉
+                OP_SWAP 3 OP_ROLL OP_ADD 5 OP_ROLL OP_ADD limb_split_carry
+                // ⋯ aᵢ m c₂ (aᵢ⋅bⱼ)₅₇…₂₉ xⱼ c
+                OP_ROT OP_ADD OP_ROT OP_ROT
+                // ⋯ aᵢ m c₁ c₂ xⱼ
+
+                // xⱼ + m⋅pⱼ + c₂
+                3 OP_PICK { modulus_limbs[j as usize] } u29_mul_carry_29
+                // ⋯ aᵢ m c₁ c₂ xⱼ (m⋅pⱼ)₂₈…₀ (m⋅pⱼ)₅₇…₂₉
+                OP_SWAP OP_ROT OP_ADD OP_ROT OP_ADD limb_split_carry
+                // ⋯ aᵢ m c₁ (m⋅pⱼ)₅₇…₂₉ tⱼ₋₁ c
+                OP_SWAP OP_TOALTSTACK OP_ADD
+                // ⋯ aᵢ m c₁ c₂ | tⱼ₋₁ ⋯
+            }
+            // ⋯ B aᵢ m c₁ c₂
+            OP_ADD OP_TOALTSTACK OP_2DROP
+            // ⋯ B | tₙ₋₁ tₙ₋₂ ⋯ t₀
+            for _ in 0..n_limbs {
+                OP_FROMALTSTACK
+            }
+            // ⋯ B T
+        }
+
+        // ⋯ B T
+        for _ in 0..n_limbs {
+            { 2 * n_limbs - 1 } OP_ROLL
+        }
+        for _ in 0..n_limbs / 2 {
+            OP_2DROP
+        }
+        if n_limbs % 2 == 1 {
+            OP_DROP
+        }
+        // ⋯ T
+    }
 }
@@ -1,7 +1,7 @@
 use num_bigint::BigInt;
 use num_traits::{FromPrimitive, Num, ToPrimitive};
 
-use crate::bigint::BigIntImpl;
+use crate::bigint::{BigIntImpl, U254};
 use crate::bn254::fp254impl::Fp254Impl;
 use crate::pseudo::NMUL;
 use crate::treepp::*;
@@ -17,12 +17,12 @@ impl Fp254Impl for Fq {
         "dc83629563d44755301fa84819caa36fb90a6020ce148c34e8384eb157ccc21";
 
     // p = 0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47
-    const MODULUS_LIMBS: [u32; Self::N_LIMBS as usize] = [
+    const MODULUS_LIMBS: &'static [u32] = &[
         0x187cfd47, 0x10460b6, 0x1c72a34f, 0x2d522d0, 0x1585d978, 0x2db40c0, 0xa6e141, 0xe5c2634, 0x30644e
     ];
 
     // inv₂₆₁ p  <=>  0x100a85dd486e7773942750342fe7cc257f6121829ae1359536782df87d1b799c77
-    const MODULUS_INV_261: [u32; Self::N_LIMBS as usize] = [
+    const MODULUS_INV: &'static [u32] = &[
         0x1B799C77, 0x16FC3E8, 0xD654D9E, 0x30535C2, 0x257F612, 0x1A17F3E6, 0xE509D40, 0x90DCEEE, 0x100A85DD
     ];
 
@@ -34,6 +34,7 @@ impl Fp254Impl for Fq {
 
     const P_PLUS_TWO_DIV3: &'static str =
         "10216f7ba065e00de81ac1e7808072c9dd2b2385cd7b438469602eb24829a9c3";
+    type BigInt = U254;
     type ConstantType = ark_bn254::Fq;
 
 }
@@ -465,6 +465,55 @@ impl Fq12 {
         }
     }
 
+    /// Input Stack: [a]
+    /// Output Stack: [a, a is in the field]
+    ///
+    /// Checks both halves in Fq6.
+    pub fn is_field() -> Script {
+        script! {
+            for _ in 0..2 {
+                { Fq6::is_field() }
+                OP_TOALTSTACK
+                { Fq6::roll(6) }
+            }
+            OP_FROMALTSTACK OP_FROMALTSTACK OP_BOOLAND
+        }
+    }
+
+    /// Input Stack: [a, a⁻¹]
+    /// Output Stack: [a⁻¹]
+    ///
+    /// Checks an inverse that is supplied as a hint instead of computing it like
+    /// [`Fq12::inv`], see [`crate::bn254::hints::inv_hint`]. The hint has to be reduced.
+    pub fn hinted_inv() -> Script {
+        script! {
+            { Fq12::is_field() }
+            OP_VERIFY
+            { Fq12::copy(0) }
+            { Fq12::roll(24) }
+            { Fq12::mul(12, 0) }
+            { Fq12::push_one() }
+            { Fq12::equalverify() }
+        }
+    }
+
+    /// Input Stack: [a, b, a / b]
+    /// Output Stack: [a / b]
+    ///
+    /// Checks a quotient that is supplied as a hint, see [`crate::bn254::hints::div_hint`]. The
+    /// hint has to be reduced.
+    pub fn hinted_div() -> Script {
+        script! {
+            { Fq12::is_field() }
+            OP_VERIFY
+            { Fq12::copy(0) }
+            { Fq12::roll(24) }
+            { Fq12::mul(12, 0) }
+            { Fq12::roll(24) }
+            { Fq12::equalverify() }
+        }
+    }
+
     pub fn inv() -> Script {
         script! {
             // copy c1
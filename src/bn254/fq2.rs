@@ -71,6 +71,16 @@ impl Fq2 {
         }
     }
 
+    pub fn equal() -> Script {
+        script! {
+            { Fq::equal(3, 1) }
+            OP_TOALTSTACK
+            { Fq::equal(1, 0) }
+            OP_FROMALTSTACK
+            OP_BOOLAND
+        }
+    }
+
     pub fn roll(a: u32) -> Script {
         script! {
             { Fq::roll(a + 1) }
@@ -140,6 +150,55 @@ impl Fq2 {
         }
     }
 
+    /// Input Stack: [a]
+    /// Output Stack: [a, a is in the field]
+    ///
+    /// Both coefficients have to pass [`Fp254Impl::is_field`].
+    pub fn is_field() -> Script {
+        script! {
+            for _ in 0..2 {
+                { Fq::is_field() }
+                OP_TOALTSTACK
+                { Fq::roll(1) }
+            }
+            OP_FROMALTSTACK OP_FROMALTSTACK OP_BOOLAND
+        }
+    }
+
+    /// Input Stack: [a, a⁻¹]
+    /// Output Stack: [a⁻¹]
+    ///
+    /// Checks an inverse that is supplied as a hint instead of computing it like
+    /// [`Fq2::inv`], see [`crate::bn254::hints::inv_hint`]. The hint has to be reduced.
+    pub fn hinted_inv() -> Script {
+        script! {
+            { Fq2::is_field() }
+            OP_VERIFY
+            { Fq2::copy(0) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::push_one() }
+            { Fq2::equalverify() }
+        }
+    }
+
+    /// Input Stack: [a, b, a / b]
+    /// Output Stack: [a / b]
+    ///
+    /// Checks a quotient that is supplied as a hint, see [`crate::bn254::hints::div_hint`]. The
+    /// hint has to be reduced.
+    pub fn hinted_div() -> Script {
+        script! {
+            { Fq2::is_field() }
+            OP_VERIFY
+            { Fq2::copy(0) }
+            { Fq2::roll(4) }
+            { Fq2::mul(2, 0) }
+            { Fq2::roll(4) }
+            { Fq2::equalverify() }
+        }
+    }
+
     pub fn inv() -> Script {
         script! {
             // copy c1
@@ -503,6 +503,58 @@ impl Fq6 {
         }
     }
 
+    /// Input Stack: [a]
+    /// Output Stack: [a, a is in the field]
+    ///
+    /// Checks the three coefficients in Fq2.
+    pub fn is_field() -> Script {
+        script! {
+            for _ in 0..3 {
+                { Fq2::is_field() }
+                OP_TOALTSTACK
+                { Fq2::roll(4) }
+            }
+            OP_FROMALTSTACK
+            for _ in 1..3 {
+                OP_FROMALTSTACK OP_BOOLAND
+            }
+        }
+    }
+
+    /// Input Stack: [a, a⁻¹]
+    /// Output Stack: [a⁻¹]
+    ///
+    /// Checks an inverse that is supplied as a hint instead of computing it like
+    /// [`Fq6::inv`], see [`crate::bn254::hints::inv_hint`]. The hint has to be reduced.
+    pub fn hinted_inv() -> Script {
+        script! {
+            { Fq6::is_field() }
+            OP_VERIFY
+            { Fq6::copy(0) }
+            { Fq6::roll(12) }
+            { Fq6::mul(6, 0) }
+            { Fq6::push_one() }
+            { Fq6::equalverify() }
+        }
+    }
+
+    /// Input Stack: [a, b, a / b]
+    /// Output Stack: [a / b]
+    ///
+    /// Checks a quotient that is supplied as a hint, see [`crate::bn254::hints::div_hint`]. The
+    /// hint has to be reduced.
+    pub fn hinted_div() -> Script {
+        script! {
+            { Fq6::is_field() }
+            OP_VERIFY
+            { Fq6::copy(0) }
+            { Fq6::roll(12) }
+            { Fq6::mul(6, 0) }
+            { Fq6::roll(12) }
+            { Fq6::equalverify() }
+        }
+    }
+
     pub fn inv() -> Script {
         script! {
             // compute t0 = c0^2, t1 = c1^2, t2 = c2^2
@@ -1,3 +1,4 @@
+use crate::bigint::U254;
 use crate::bn254::fp254impl::Fp254Impl;
 
 pub struct Fr;
@@ -11,11 +12,11 @@ impl Fp254Impl for Fr {
         "dc83629563d44755301fa84819caa8075bba827a494b01a2fd4e1568fffff57";
 
     // p = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
-    const MODULUS_LIMBS: [u32; Self::N_LIMBS as usize] = [
+    const MODULUS_LIMBS: &'static [u32] = &[
         0x10000001, 0x1f0fac9f, 0xe5c2450, 0x7d090f3, 0x1585d283, 0x2db40c0, 0xa6e141, 0xe5c2634, 0x30644e
     ];
     // inv₂₆₁ p  <=>  0xd8c07d0e2f27cbe4d1c6567d766f9dc6e9a7979b4b396ee4c3d1e0a6c10000001
-    const MODULUS_INV_261: [u32; Self::N_LIMBS as usize] = [
+    const MODULUS_INV: &'static [u32] = &[
         0x10000001, 0x8f05360, 0x5bb930f, 0x12f36967, 0x1dc6e9a7, 0x13ebb37c, 0x19347195, 0x1c5e4f97, 0xd8c07d0
     ];
 
@@ -27,6 +28,7 @@ impl Fp254Impl for Fr {
 
     const P_PLUS_TWO_DIV3: &'static str =
         "10216f7ba065e00de81ac1e7808072c9b8114d6d7de87adb16a0a73150000001";
+    type BigInt = U254;
     type ConstantType = ark_bn254::Fr;
 
 }
@@ -35,6 +37,7 @@ impl Fp254Impl for Fr {
 mod test {
     use crate::bn254::fp254impl::Fp254Impl;
     use crate::bn254::fr::Fr;
+    use crate::bn254::hints::batch_inv_hint;
     use crate::treepp::*;
     use ark_ff::{BigInteger, Field, PrimeField};
     use ark_std::UniformRand;
@@ -233,6 +236,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_batch_inv() {
+        println!("Fr.batch_inv(8): {} bytes", Fr::batch_inv(8).len());
+        println!("Fr.hinted_batch_inv(8): {} bytes", Fr::hinted_batch_inv(8).len());
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        for n in 1..=4 {
+            let a: Vec<_> = (0..n).map(|_| ark_bn254::Fr::rand(&mut prng)).collect();
+
+            for hinted in [false, true] {
+                let script = script! {
+                    for a in a.iter() {
+                        { Fr::push_u32_le(&BigUint::from(*a).to_u32_digits()) }
+                    }
+                    if hinted {
+                        { Fr::push_u32_le(&BigUint::from(batch_inv_hint(&a)).to_u32_digits()) }
+                        { Fr::hinted_batch_inv(n) }
+                    } else {
+                        { Fr::batch_inv(n) }
+                    }
+                    for a in a.iter().rev() {
+                        { Fr::push_u32_le(&BigUint::from(a.inverse().unwrap()).to_u32_digits()) }
+                        { Fr::equalverify(1, 0) }
+                    }
+                    OP_TRUE
+                };
+                let exec_result = execute_script(script);
+                assert!(exec_result.success);
+            }
+        }
+    }
+
     #[test]
     fn test_div2() {
         println!("Fr.div2: {} bytes", Fr::div2().len());
@@ -0,0 +1,500 @@
+//
+// Differential fuzzing of script gadgets against arkworks
+//
+// A gadget is checked by pushing random inputs, running the gadget and comparing its output
+// with the arkworks reference on the stack. A failing input is shrunk towards small field
+// elements. Saved regressions in `fuzz_regressions/<name>.txt` are replayed before any random
+// case. Failing inputs are only appended to it if saving is enabled, so a plain test run
+// doesn't write to the source tree.
+//
+
+use std::fmt::Debug;
+use std::fs;
+use std::path::PathBuf;
+
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use num_bigint::BigUint;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::bn254::curves::{G1Projective, ProjectiveCurve};
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fq::Fq;
+use crate::bn254::fq12::Fq12;
+use crate::bn254::fq2::Fq2;
+use crate::bn254::fq6::Fq6;
+use crate::bn254::fr::Fr;
+use crate::bn254::utils::{fq12_push, fq2_push, fq6_push};
+use crate::treepp::*;
+use crate::{execute_script_without_stack_limit, ExecuteInfo};
+
+/// A gadget input that can be generated, pushed and shrunk.
+pub trait FuzzValue: Clone + Debug + PartialEq {
+    fn random<R: Rng>(rng: &mut R) -> Self;
+
+    fn push(&self) -> Script;
+
+    /// Simpler values to try when this one makes a gadget fail.
+    fn shrink(&self) -> Vec<Self>;
+
+    /// The base prime field elements, used to save regressions.
+    fn to_fields(&self) -> Vec<BigUint>;
+
+    fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self;
+}
+
+/// A gadget output that can be compared on the stack.
+pub trait FuzzOutput: FuzzValue {
+    /// Consumes the gadget output and the expected output and fails if they differ.
+    fn equalverify() -> Script;
+}
+
+// Shrink candidates of a prime field element, all of them smaller than `value`.
+fn shrink_prime<F: PrimeField>(value: &F) -> Vec<F> {
+    let n = BigUint::from(*value);
+    let negated = BigUint::from(-*value);
+    let zero = BigUint::from(0u32);
+    let mut candidates = vec![zero.clone(), BigUint::from(1u32), &n >> 1, negated];
+    if n > zero {
+        candidates.push(&n - 1u32);
+    }
+    let mut shrunk: Vec<F> = vec![];
+    for candidate in candidates {
+        if candidate < n && !shrunk.contains(&F::from(candidate.clone())) {
+            shrunk.push(F::from(candidate));
+        }
+    }
+    shrunk
+}
+
+// Replaces one coefficient at a time with its shrink candidates.
+fn shrink_coefficients<F: PrimeField>(coefficients: &[F]) -> Vec<Vec<F>> {
+    let mut shrunk = vec![];
+    for (i, coefficient) in coefficients.iter().enumerate() {
+        for candidate in shrink_prime(coefficient) {
+            let mut coefficients = coefficients.to_vec();
+            coefficients[i] = candidate;
+            shrunk.push(coefficients);
+        }
+    }
+    shrunk
+}
+
+fn next_field<F: PrimeField>(fields: &mut dyn Iterator<Item = BigUint>) -> F {
+    F::from(fields.next().expect("missing field element in regression"))
+}
+
+macro_rules! impl_fuzz_prime_field {
+    ($ark:ty, $gadget:ty) => {
+        impl FuzzValue for $ark {
+            fn random<R: Rng>(rng: &mut R) -> Self { <$ark>::rand(rng) }
+
+            fn push(&self) -> Script {
+                <$gadget>::push_u32_le(&BigUint::from(*self).to_u32_digits())
+            }
+
+            fn shrink(&self) -> Vec<Self> { shrink_prime(self) }
+
+            fn to_fields(&self) -> Vec<BigUint> { vec![BigUint::from(*self)] }
+
+            fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self {
+                next_field(fields)
+            }
+        }
+
+        impl FuzzOutput for $ark {
+            fn equalverify() -> Script { <$gadget>::equalverify(1, 0) }
+        }
+    };
+}
+
+impl_fuzz_prime_field!(ark_bn254::Fq, Fq);
+impl_fuzz_prime_field!(ark_bn254::Fr, Fr);
+
+fn fq2_from(c: &[ark_bn254::Fq]) -> ark_bn254::Fq2 { ark_bn254::Fq2::new(c[0], c[1]) }
+
+fn fq6_from(c: &[ark_bn254::Fq]) -> ark_bn254::Fq6 {
+    ark_bn254::Fq6::new(fq2_from(&c[0..2]), fq2_from(&c[2..4]), fq2_from(&c[4..6]))
+}
+
+fn fq12_from(c: &[ark_bn254::Fq]) -> ark_bn254::Fq12 {
+    ark_bn254::Fq12::new(fq6_from(&c[0..6]), fq6_from(&c[6..12]))
+}
+
+macro_rules! impl_fuzz_extension_field {
+    ($ark:ty, $gadget:ty, $push:ident, $from:ident, $degree:expr) => {
+        impl FuzzValue for $ark {
+            fn random<R: Rng>(rng: &mut R) -> Self { <$ark>::rand(rng) }
+
+            fn push(&self) -> Script { $push(*self) }
+
+            fn shrink(&self) -> Vec<Self> {
+                let coefficients: Vec<ark_bn254::Fq> = self.to_base_prime_field_elements().collect();
+                shrink_coefficients(&coefficients)
+                    .iter()
+                    .map(|coefficients| $from(coefficients))
+                    .collect()
+            }
+
+            fn to_fields(&self) -> Vec<BigUint> {
+                self.to_base_prime_field_elements().map(BigUint::from).collect()
+            }
+
+            fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self {
+                let coefficients: Vec<ark_bn254::Fq> =
+                    (0..$degree).map(|_| next_field(fields)).collect();
+                $from(&coefficients)
+            }
+        }
+
+        impl FuzzOutput for $ark {
+            fn equalverify() -> Script { <$gadget>::equalverify() }
+        }
+    };
+}
+
+impl_fuzz_extension_field!(ark_bn254::Fq2, Fq2, fq2_push, fq2_from, 2);
+impl_fuzz_extension_field!(ark_bn254::Fq6, Fq6, fq6_push, fq6_from, 6);
+impl_fuzz_extension_field!(ark_bn254::Fq12, Fq12, fq12_push, fq12_from, 12);
+
+impl FuzzValue for ark_bn254::G1Projective {
+    fn random<R: Rng>(rng: &mut R) -> Self { ark_bn254::G1Projective::rand(rng) }
+
+    fn push(&self) -> Script { G1Projective::push(*self) }
+
+    // Points can't be shrunk coordinate-wise, the identity and the generator are the simplest
+    // points to try.
+    fn shrink(&self) -> Vec<Self> {
+        let generator = ark_bn254::G1Projective::new_unchecked(
+            ark_bn254::Fq::from(1u32),
+            ark_bn254::Fq::from(2u32),
+            ark_bn254::Fq::from(1u32),
+        );
+        if self.is_zero() {
+            vec![]
+        } else if *self == generator {
+            vec![ark_bn254::G1Projective::zero()]
+        } else {
+            vec![ark_bn254::G1Projective::zero(), generator]
+        }
+    }
+
+    fn to_fields(&self) -> Vec<BigUint> {
+        vec![BigUint::from(self.x), BigUint::from(self.y), BigUint::from(self.z)]
+    }
+
+    fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self {
+        let x = next_field(fields);
+        let y = next_field(fields);
+        let z = next_field(fields);
+        ark_bn254::G1Projective::new_unchecked(x, y, z)
+    }
+}
+
+impl FuzzOutput for ark_bn254::G1Projective {
+    fn equalverify() -> Script { G1Projective::equalverify() }
+}
+
+/// An element of the cyclotomic subgroup of Fq12, the input domain of the cyclotomic gadgets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cyclotomic(pub ark_bn254::Fq12);
+
+impl Cyclotomic {
+    /// Maps `f` into the cyclotomic subgroup with the easy part of the final exponentiation,
+    /// f^((p⁶ - 1)(p² + 1)).
+    pub fn from_fq12(f: ark_bn254::Fq12) -> Self {
+        let Some(inverse) = f.inverse() else {
+            return Self(ark_bn254::Fq12::ONE);
+        };
+        let mut f1 = f;
+        f1.conjugate_in_place();
+        f1 *= inverse;
+        let mut f2 = f1;
+        f2.frobenius_map_in_place(2);
+        Self(f2 * f1)
+    }
+}
+
+impl FuzzValue for Cyclotomic {
+    fn random<R: Rng>(rng: &mut R) -> Self { Self::from_fq12(ark_bn254::Fq12::rand(rng)) }
+
+    fn push(&self) -> Script { fq12_push(self.0) }
+
+    // Shrunk coefficients leave the subgroup, one is the simplest element of it.
+    fn shrink(&self) -> Vec<Self> {
+        if self.0 == ark_bn254::Fq12::ONE {
+            vec![]
+        } else {
+            vec![Self(ark_bn254::Fq12::ONE)]
+        }
+    }
+
+    fn to_fields(&self) -> Vec<BigUint> { self.0.to_fields() }
+
+    fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self {
+        Self(ark_bn254::Fq12::from_fields(fields))
+    }
+}
+
+macro_rules! impl_fuzz_tuple {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: FuzzValue),+> FuzzValue for ($($name,)+) {
+            fn random<R: Rng>(rng: &mut R) -> Self { ($($name::random(rng),)+) }
+
+            fn push(&self) -> Script {
+                script! {
+                    $({ self.$index.push() })+
+                }
+            }
+
+            fn shrink(&self) -> Vec<Self> {
+                let mut shrunk = vec![];
+                $(
+                    for candidate in self.$index.shrink() {
+                        let mut value = self.clone();
+                        value.$index = candidate;
+                        shrunk.push(value);
+                    }
+                )+
+                shrunk
+            }
+
+            fn to_fields(&self) -> Vec<BigUint> {
+                let mut fields = vec![];
+                $(fields.extend(self.$index.to_fields());)+
+                fields
+            }
+
+            fn from_fields(fields: &mut dyn Iterator<Item = BigUint>) -> Self {
+                ($($name::from_fields(fields),)+)
+            }
+        }
+    };
+}
+
+impl_fuzz_tuple!(A: 0);
+impl_fuzz_tuple!(A: 0, B: 1);
+impl_fuzz_tuple!(A: 0, B: 1, C: 2);
+
+/// An input for which the gadget and the reference disagree.
+#[derive(Debug)]
+pub struct Failure<I> {
+    /// The first failing input that was found.
+    pub original: I,
+    /// The simplest failing input found by shrinking `original`.
+    pub shrunk: I,
+    pub exec_result: ExecuteInfo,
+}
+
+pub struct Fuzzer {
+    name: String,
+    cases: usize,
+    seed: u64,
+    max_shrink_steps: usize,
+    save_regressions: bool,
+}
+
+impl Fuzzer {
+    /// `name` identifies the regression file of the gadget.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cases: 8,
+            seed: 0,
+            max_shrink_steps: 256,
+            save_regressions: false,
+        }
+    }
+
+    /// Number of random cases to run in addition to the saved regressions.
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn max_shrink_steps(mut self, max_shrink_steps: usize) -> Self {
+        self.max_shrink_steps = max_shrink_steps;
+        self
+    }
+
+    /// Appends failing inputs to the regression file.
+    pub fn with_saving(mut self) -> Self {
+        self.save_regressions = true;
+        self
+    }
+
+    pub fn regression_file(&self) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("fuzz_regressions")
+            .join(format!("{}.txt", self.name))
+    }
+
+    /// Panics with the shrunk input if `gadget` and `reference` disagree on any input.
+    pub fn check<I: FuzzValue, O: FuzzOutput>(&self, gadget: Script, reference: impl Fn(&I) -> O) {
+        if let Some(failure) = self.find_failure(gadget, reference) {
+            let saved = if self.save_regressions {
+                self.save_regression(&failure.shrunk);
+                format!(", saved to {}", self.regression_file().display())
+            } else {
+                String::new()
+            };
+            panic!(
+                "{} failed for {:?} (shrunk from {:?}){}\n{}",
+                self.name, failure.shrunk, failure.original, saved, failure.exec_result
+            );
+        }
+    }
+
+    /// Runs the saved regressions and the random cases and shrinks the first failing input.
+    pub fn find_failure<I: FuzzValue, O: FuzzOutput>(
+        &self,
+        gadget: Script,
+        reference: impl Fn(&I) -> O,
+    ) -> Option<Failure<I>> {
+        let run = |input: &I| {
+            let exec_result = execute_script_without_stack_limit(script! {
+                { input.push() }
+                { gadget.clone() }
+                { reference(input).push() }
+                { O::equalverify() }
+                OP_TRUE
+            });
+            (!exec_result.success).then_some(exec_result)
+        };
+
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        let regressions = self.load_regressions::<I>();
+        let inputs = regressions
+            .into_iter()
+            .chain((0..self.cases).map(|_| I::random(&mut rng)));
+        for input in inputs {
+            let Some(mut exec_result) = run(&input) else {
+                continue;
+            };
+
+            let mut shrunk = input.clone();
+            let mut steps = 0;
+            'shrink: while steps < self.max_shrink_steps {
+                for candidate in shrunk.shrink() {
+                    steps += 1;
+                    if let Some(candidate_result) = run(&candidate) {
+                        shrunk = candidate;
+                        exec_result = candidate_result;
+                        continue 'shrink;
+                    }
+                    if steps >= self.max_shrink_steps {
+                        break;
+                    }
+                }
+                break;
+            }
+            return Some(Failure { original: input, shrunk, exec_result });
+        }
+        None
+    }
+
+    fn load_regressions<I: FuzzValue>(&self) -> Vec<I> {
+        let Ok(content) = fs::read_to_string(self.regression_file()) else {
+            return vec![];
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                I::from_fields(&mut line.split_whitespace().map(|field| {
+                    field.parse::<BigUint>().expect("invalid field element in regression")
+                }))
+            })
+            .collect()
+    }
+
+    fn save_regression<I: FuzzValue>(&self, input: &I) {
+        if self.load_regressions::<I>().contains(input) {
+            return;
+        }
+        let path = self.regression_file();
+        fs::create_dir_all(path.parent().unwrap()).expect("Unable to create regression dir");
+        let mut content = fs::read_to_string(&path).unwrap_or_default();
+        let fields: Vec<String> = input.to_fields().iter().map(|field| field.to_string()).collect();
+        content.push_str(&fields.join(" "));
+        content.push('\n');
+        fs::write(&path, content).expect("Unable to write regression file");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{AdditiveGroup, CyclotomicMultSubgroup};
+    use std::ops::{Add, Mul};
+
+    #[test]
+    fn test_fuzz_fq2_mul() {
+        Fuzzer::new("fq2_mul").cases(4).check(Fq2::mul(2, 0), |(a, b): &(
+            ark_bn254::Fq2,
+            ark_bn254::Fq2,
+        )| a.mul(b));
+    }
+
+    #[test]
+    fn test_fuzz_g1_projective_add() {
+        Fuzzer::new("g1_projective_add").cases(4).check(
+            G1Projective::add(),
+            |(a, b): &(ark_bn254::G1Projective, ark_bn254::G1Projective)| a.add(b),
+        );
+    }
+
+    #[test]
+    fn test_fuzz_fq12_cyclotomic_square() {
+        Fuzzer::new("fq12_cyclotomic_square")
+            .cases(2)
+            .check(Fq12::cyclotomic_square(), |(a,): &(Cyclotomic,)| {
+                a.0.cyclotomic_square()
+            });
+    }
+
+    #[test]
+    fn test_cyclotomic_inputs() {
+        // The cyclotomic subgroup has order p⁴ - p² + 1
+        let p = BigUint::from(ark_bn254::Fq::MODULUS);
+        let order = p.pow(4) - p.pow(2) + 1u32;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        for _ in 0..2 {
+            let a = Cyclotomic::random(&mut rng);
+            assert_ne!(a.0, ark_bn254::Fq12::ONE);
+            assert_eq!(a.0.pow(order.to_u64_digits()), ark_bn254::Fq12::ONE);
+            // Squaring only agrees with the cyclotomic square inside the subgroup
+            assert_eq!(a.0.cyclotomic_square(), a.0.square());
+        }
+    }
+
+    #[test]
+    fn test_fuzz_shrinks_failures() {
+        // The reference is off by one for every input.
+        let failure = Fuzzer::new("fq_add_wrong")
+            .cases(1)
+            .find_failure(Fq::add(1, 0), |(a, b): &(ark_bn254::Fq, ark_bn254::Fq)| {
+                a.add(b).add(ark_bn254::Fq::ONE)
+            })
+            .expect("the wrong reference has to be found");
+        assert_eq!(failure.shrunk, (ark_bn254::Fq::ZERO, ark_bn254::Fq::ZERO));
+        assert!(!failure.exec_result.success);
+    }
+
+    #[test]
+    fn test_regression_roundtrip() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let value = <(ark_bn254::Fq12, ark_bn254::G1Projective, ark_bn254::Fr)>::random(&mut rng);
+        let fields = value.to_fields();
+        assert_eq!(fields.len(), 12 + 3 + 1);
+        let decoded = <(ark_bn254::Fq12, ark_bn254::G1Projective, ark_bn254::Fr)>::from_fields(
+            &mut fields.into_iter(),
+        );
+        assert_eq!(decoded, value);
+    }
+}
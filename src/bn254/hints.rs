@@ -0,0 +1,246 @@
+use crate::bn254::domain::Domain;
+use ark_ff::{AdditiveGroup, Field, Zero};
+
+// Off-chain witnesses for the `hinted_*` gadgets. The prover computes them next to the script
+// and pushes them with `Fq::push_u32_le`, `fq2_push`, `fq6_push` or `fq12_push`.
+//
+// `inv` and `G1Projective::into_affine` keep computing the inverse in script. Their callers, like
+// `msm` or the inversion of Fq12 through Fq6 and Fq2, build the script before the values to invert
+// exist and take no witness, so a hint would change their interface rather than only their cost.
+
+// Hint of `hinted_inv` for any field of the tower
+pub fn inv_hint<F: Field>(a: F) -> F { a.inverse().expect("zero has no inverse") }
+
+// Hint of `hinted_div` for any field of the tower
+pub fn div_hint<F: Field>(a: F, b: F) -> F { a * inv_hint(b) }
+
+// Hint of `hinted_batch_inv`, the inverse of the product of all elements
+pub fn batch_inv_hint<F: Field>(a: &[F]) -> F { inv_hint(a.iter().product()) }
+
+// Hint of `Domain::hinted_lagranges`, the inverse of the product of the denominators z - ωⁱ
+pub fn lagranges_hint(domain: &Domain, z: ark_bn254::Fr, indices: &[u64]) -> ark_bn254::Fr {
+    let denominators: Vec<_> = indices.iter().map(|i| z - domain.element(*i)).collect();
+    batch_inv_hint(&denominators)
+}
+
+// Hint of `G1Projective::hinted_into_affine`, z⁻¹ or zero for the point at infinity
+pub fn into_affine_hint(p: ark_bn254::G1Projective) -> ark_bn254::Fq {
+    if p.is_zero() {
+        ark_bn254::Fq::ZERO
+    } else {
+        inv_hint(p.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bigint::U254;
+    use crate::bn254::curves::{G1Affine, G1Projective};
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::bn254::fq::Fq;
+    use crate::bn254::fq12::Fq12;
+    use crate::bn254::fq2::Fq2;
+    use crate::bn254::fq6::Fq6;
+    use crate::bn254::utils::{fq12_push, fq2_push, fq6_push};
+    use crate::execute_script;
+    use crate::treepp::*;
+    use ark_ec::CurveGroup;
+    use ark_ff::PrimeField;
+    use ark_std::UniformRand;
+    use num_bigint::BigUint;
+    use num_traits::Num;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn fq_push(a: ark_bn254::Fq) -> Script {
+        script! {
+            { Fq::push_u32_le(&BigUint::from(a).to_u32_digits()) }
+        }
+    }
+
+    #[test]
+    fn test_hinted_inv() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        println!("Fq.hinted_inv: {} bytes, Fq.inv: {} bytes", Fq::hinted_inv().len(), Fq::inv().len());
+        println!("Fq12.hinted_inv: {} bytes, Fq12.inv: {} bytes", Fq12::hinted_inv().len(), Fq12::inv().len());
+
+        let a = ark_bn254::Fq::rand(&mut prng);
+        let a2 = ark_bn254::Fq2::rand(&mut prng);
+        let a6 = ark_bn254::Fq6::rand(&mut prng);
+        let a12 = ark_bn254::Fq12::rand(&mut prng);
+
+        let script = script! {
+            { fq_push(a) }
+            { fq_push(inv_hint(a)) }
+            { Fq::hinted_inv() }
+            { fq_push(a.inverse().unwrap()) }
+            { Fq::equalverify(1, 0) }
+
+            { fq2_push(a2) }
+            { fq2_push(inv_hint(a2)) }
+            { Fq2::hinted_inv() }
+            { fq2_push(a2.inverse().unwrap()) }
+            { Fq2::equalverify() }
+
+            { fq6_push(a6) }
+            { fq6_push(inv_hint(a6)) }
+            { Fq6::hinted_inv() }
+            { fq6_push(a6.inverse().unwrap()) }
+            { Fq6::equalverify() }
+
+            { fq12_push(a12) }
+            { fq12_push(inv_hint(a12)) }
+            { Fq12::hinted_inv() }
+            { fq12_push(a12.inverse().unwrap()) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_hinted_inv_rejects_wrong_hint() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bn254::Fq12::rand(&mut prng);
+        let b = ark_bn254::Fq12::rand(&mut prng);
+
+        let script = script! {
+            { fq12_push(a) }
+            { fq12_push(inv_hint(b)) }
+            { Fq12::hinted_inv() }
+            { Fq12::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+
+    // a + p in Montgomery form, the multiplication treats it like a
+    fn fq_push_unreduced(a: ark_bn254::Fq) -> Script {
+        let p = BigUint::from(ark_bn254::Fq::MODULUS);
+        let r = BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap();
+        script! {
+            { U254::push_u32_le(&(BigUint::from(a) * r % &p + &p).to_u32_digits()) }
+        }
+    }
+
+    #[test]
+    fn test_hinted_inv_rejects_unreduced_hint() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let a = ark_bn254::Fq::rand(&mut prng);
+        let a2 = ark_bn254::Fq2::rand(&mut prng);
+        let (b, c) = (ark_bn254::Fq::rand(&mut prng), ark_bn254::Fq::rand(&mut prng));
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+
+        let scripts = [
+            script! {
+                { fq_push(a) }
+                { fq_push_unreduced(inv_hint(a)) }
+                { Fq::hinted_inv() }
+                { Fq::drop() }
+            },
+            script! {
+                { fq2_push(a2) }
+                { fq_push_unreduced(inv_hint(a2).c0) }
+                { fq_push(inv_hint(a2).c1) }
+                { Fq2::hinted_inv() }
+                { Fq2::drop() }
+            },
+            script! {
+                { fq_push(b) }
+                { fq_push(c) }
+                { fq_push_unreduced(div_hint(b, c)) }
+                { Fq::hinted_div() }
+                { Fq::drop() }
+            },
+            script! {
+                { G1Projective::push(p) }
+                { fq_push_unreduced(into_affine_hint(p)) }
+                { G1Projective::hinted_into_affine() }
+                { Fq::drop() }
+                { Fq::drop() }
+            },
+        ];
+        for script in scripts {
+            let exec_result = execute_script(script! {
+                { script }
+                OP_TRUE
+            });
+            assert!(!exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_hinted_div() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let (a, b) = (ark_bn254::Fq::rand(&mut prng), ark_bn254::Fq::rand(&mut prng));
+        let (a2, b2) = (ark_bn254::Fq2::rand(&mut prng), ark_bn254::Fq2::rand(&mut prng));
+        let (a6, b6) = (ark_bn254::Fq6::rand(&mut prng), ark_bn254::Fq6::rand(&mut prng));
+        let (a12, b12) = (ark_bn254::Fq12::rand(&mut prng), ark_bn254::Fq12::rand(&mut prng));
+
+        let script = script! {
+            { fq_push(a) }
+            { fq_push(b) }
+            { fq_push(div_hint(a, b)) }
+            { Fq::hinted_div() }
+            { fq_push(a / b) }
+            { Fq::equalverify(1, 0) }
+
+            { fq2_push(a2) }
+            { fq2_push(b2) }
+            { fq2_push(div_hint(a2, b2)) }
+            { Fq2::hinted_div() }
+            { fq2_push(a2 / b2) }
+            { Fq2::equalverify() }
+
+            { fq6_push(a6) }
+            { fq6_push(b6) }
+            { fq6_push(div_hint(a6, b6)) }
+            { Fq6::hinted_div() }
+            { fq6_push(a6 / b6) }
+            { Fq6::equalverify() }
+
+            { fq12_push(a12) }
+            { fq12_push(b12) }
+            { fq12_push(div_hint(a12, b12)) }
+            { Fq12::hinted_div() }
+            { fq12_push(a12 / b12) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_hinted_into_affine() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+        let zero = ark_bn254::G1Projective::zero();
+        println!(
+            "G1Projective.hinted_into_affine: {} bytes, G1Projective.into_affine: {} bytes",
+            G1Projective::hinted_into_affine().len(),
+            G1Projective::into_affine().len()
+        );
+
+        let script = script! {
+            { G1Projective::push(p) }
+            { fq_push(into_affine_hint(p)) }
+            { G1Projective::hinted_into_affine() }
+            { fq_push(p.into_affine().x) }
+            { fq_push(p.into_affine().y) }
+            { G1Affine::equalverify() }
+
+            { G1Projective::push(zero) }
+            { fq_push(into_affine_hint(zero)) }
+            { G1Projective::hinted_into_affine() }
+            { G1Affine::identity() }
+            { G1Affine::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -10,6 +10,11 @@ pub mod pairing;
 
 pub mod ell_coeffs;
 
+pub mod domain;
+pub mod encoding;
 pub mod fp254impl;
+pub mod fuzz;
+pub mod hints;
 pub mod msm;
+pub mod scalar_mul;
 pub mod utils;
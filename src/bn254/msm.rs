@@ -1,7 +1,10 @@
-use crate::bn254::curves::G1Projective;
+use crate::bigint::U254;
+use crate::bn254::curves::{G1Projective, ProjectiveCurve};
 use crate::bn254::fp254impl::Fp254Impl;
 use crate::bn254::fq::Fq;
 use crate::bn254::fr::Fr;
+use crate::bn254::scalar_mul::ScalarMulMethod;
+use crate::chunker::limits::MAX_STACK_ELEMENTS;
 use std::ops::Mul;
 use crate::treepp::{script, Script};
 use ark_ff::Field;
@@ -23,11 +26,198 @@ fn fr_push(scalar: ark_bn254::Fr) -> Script {
 // Will compute msm and return the affine point
 // Output Stack: [x,y]
 pub fn msm(bases: &[ark_bn254::G1Affine], scalars: &[ark_bn254::Fr]) -> Script {
+    assert_eq!(bases.len(), scalars.len());
+    match pippenger_window(bases.len()) {
+        Some(window) => script! {
+            for i in 0..scalars.len() {
+                { fr_push(scalars[i]) }
+            }
+            { pippenger(bases, window) }
+            { G1Projective::into_affine() }
+        },
+        None => msm_with_method(bases, scalars, ScalarMulMethod::Window),
+    }
+}
+
+// Returns the window size for which `pippenger` needs the fewest group operations, or None if a
+// separate scalar multiplication per base is cheaper.
+fn pippenger_window(len: usize) -> Option<u32> {
+    let len = len as u32;
+    let windows = |c: u32| (Fr::N_BITS + c - 1) / c;
+    // 4-bit windows, a table of 15 multiples and one addition for the sum per base
+    let separate = len * (windows(4) + 15 + Fr::N_BITS);
+    // the buckets of a window are summed up with 2 * (2^c - 1) - 1 additions
+    (1..=PIPPENGER_MAX_WINDOW)
+        .filter(|&c| len as usize <= pippenger_max_len(c))
+        .map(|c| (c, windows(c) * (len + (1 << (c + 1)) - 3) + Fr::N_BITS))
+        .min_by_key(|&(_, cost)| cost)
+        .filter(|&(_, cost)| cost < separate)
+        .map(|(c, _)| c)
+}
+
+// 2^4 - 1 buckets already take 405 stack items
+const PIPPENGER_MAX_WINDOW: u32 = 4;
+
+// Stack items taken by the temporaries of `G1Projective::complete_add` on top of its summands,
+// i.e. the copies of both points on the altstack and the field elements of `nonzero_add`.
+const PIPPENGER_ADD_HEADROOM: usize = 150;
+
+// Returns the largest number of bases `pippenger` handles with `window` within the stack limit.
+// Each scalar keeps its 9 limbs on the stack and its current digit on the altstack, next to the
+// accumulator, the 2^window - 1 buckets and the base that is being added.
+fn pippenger_max_len(window: u32) -> usize {
+    let points = 27 * ((1 << window) + 1);
+    (MAX_STACK_ELEMENTS - PIPPENGER_ADD_HEADROOM - points) / (U254::N_LIMBS as usize + 1)
+}
+
+// Input Stack: [scalar_0, ..., scalar_{n-1}]
+// Output Stack: [g1projective]
+//
+// Pippenger's bucket method over `window`-bit windows of the scalars, most significant window
+// first. Each window sorts the constant bases into 2^window - 1 buckets by their digit and adds
+// the buckets weighted by their index to the accumulator, so all terms share the doublings.
+pub fn pippenger(bases: &[ark_bn254::G1Affine], window: u32) -> Script {
+    assert!((1..=PIPPENGER_MAX_WINDOW).contains(&window));
+    assert!(bases.len() <= pippenger_max_len(window), "too many bases for the stack");
+    let bases: Vec<ark_ec::short_weierstrass::Projective<ark_bn254::g1::Config>> =
+        bases.iter().map(|&p| p.into()).collect();
+    let len = bases.len() as u32;
+    let n_buckets = (1 << window) - 1;
+
+    // the most significant window takes the remaining bits
+    let n_windows = (Fr::N_BITS + window - 1) / window;
+    let mut widths = vec![window; n_windows as usize];
+    widths[0] = Fr::N_BITS - window * (n_windows - 1);
+
+    script! {
+        for _ in 0..len {
+            { Fr::roll(len - 1) }
+            { Fr::decode_montgomery() }
+        }
+        { G1Projective::push_zero() }
+
+        for (i, width) in widths.into_iter().enumerate() {
+            if i > 0 {
+                for _ in 0..width {
+                    { G1Projective::double() }
+                }
+            }
+
+            // move the digits of this window to the altstack
+            for _ in 0..len {
+                { Fr::roll(len + 2) }
+                { pop_msb_digit(width) }
+                OP_TOALTSTACK
+            }
+            for _ in 0..3 {
+                { Fq::roll(len + 2) }
+            }
+
+            // [acc, bucket_1, ..., bucket_{2^window - 1}]
+            for _ in 0..n_buckets {
+                { G1Projective::push_zero() }
+            }
+            for j in (0..len as usize).rev() {
+                { add_to_bucket(bases[j], n_buckets) }
+            }
+
+            // running sum: acc + Σ j * bucket_j = acc + Σ_j Σ_{k >= j} bucket_k
+            { G1Projective::copy(0) }
+            for _ in 1..n_buckets {
+                { G1Projective::roll(2) }
+                { G1Projective::roll(2) }
+                { G1Projective::complete_add() }
+                { G1Projective::copy(0) }
+                { G1Projective::roll(2) }
+                { G1Projective::complete_add() }
+            }
+            { G1Projective::roll(1) }
+            { G1Projective::drop() }
+            { G1Projective::complete_add() }
+        }
+
+        { G1Projective::toaltstack() }
+        for _ in 0..len {
+            { Fr::drop() }
+        }
+        { G1Projective::fromaltstack() }
+    }
+}
+
+// Input Stack: [a]
+// Output Stack: [a << width mod 2^254, a >> (254 - width)]
+fn pop_msb_digit(width: u32) -> Script {
+    script! {
+        0
+        for _ in 0..width {
+            OP_DUP OP_ADD
+            { U254::N_LIMBS } OP_PICK
+            { 1 << (U254::HEAD - 1) } OP_GREATERTHANOREQUAL
+            OP_ADD
+            OP_TOALTSTACK
+            { U254::double(0) }
+            OP_FROMALTSTACK
+        }
+    }
+}
+
+// Input Stack: [bucket_1, ..., bucket_n]
+// Input Altstack: [digit]
+// Output Stack: [bucket_1, ..., bucket_digit + base, ..., bucket_n]
+fn add_to_bucket(base: ark_bn254::G1Projective, n_buckets: u32) -> Script {
+    script! {
+        OP_FROMALTSTACK
+        OP_DUP
+        OP_IF
+            // the bucket sits n - digit points deep
+            OP_NEGATE { n_buckets } OP_ADD
+            OP_DUP OP_TOALTSTACK
+            { roll_point() }
+            { G1Projective::push(base) }
+            { G1Projective::complete_add() }
+
+            // move the buckets above it back on top
+            for j in 0..n_buckets - 1 {
+                OP_FROMALTSTACK OP_DUP OP_TOALTSTACK
+                { j } OP_GREATERTHAN
+                OP_IF
+                    OP_FROMALTSTACK OP_DUP OP_TOALTSTACK
+                    { roll_point() }
+                OP_ENDIF
+            }
+            OP_FROMALTSTACK OP_DROP
+        OP_ELSE
+            OP_DROP
+        OP_ENDIF
+    }
+}
+
+// Input Stack: [..., point, ..., depth]
+// Output Stack: [..., ..., point]
+fn roll_point() -> Script {
+    script! {
+        // 27 * depth + 26
+        for _ in 0..3 {
+            OP_DUP OP_DUP OP_ADD OP_ADD
+        }
+        { 26 } OP_ADD
+        for _ in 0..26 { OP_DUP }
+        for _ in 0..26 { OP_TOALTSTACK }
+        OP_ROLL
+        for _ in 0..26 { OP_FROMALTSTACK OP_ROLL }
+    }
+}
+
+// Will compute msm with the given scalar multiplication and return the affine point
+// Output Stack: [x,y]
+pub fn msm_with_method(
+    bases: &[ark_bn254::G1Affine], scalars: &[ark_bn254::Fr], method: ScalarMulMethod,
+) -> Script {
     assert_eq!(bases.len(), scalars.len());
     let bases: Vec<ark_ec::short_weierstrass::Projective<ark_bn254::g1::Config>> =
         bases.iter().map(|&p| p.into()).collect();
     let len = bases.len();
-    let scalar_mul = G1Projective::scalar_mul();
+    let scalar_mul = G1Projective::scalar_mul_with(method);
 
     script! {
         // 1. init the sum=0;
@@ -37,6 +227,7 @@ pub fn msm(bases: &[ark_bn254::G1Affine], scalars: &[ark_bn254::Fr]) -> Script {
             { G1Projective::push(bases[i]) }
             if scalars[i] != ark_bn254::Fr::ONE {
                 { fr_push(scalars[i]) }
+                { G1Projective::scalar_mul_hint(method, scalars[i]) }
                 { scalar_mul.clone() }
             }
 
@@ -48,6 +239,27 @@ pub fn msm(bases: &[ark_bn254::G1Affine], scalars: &[ark_bn254::Fr]) -> Script {
     }
 }
 
+// Input Stack: [scalar_0, ..., scalar_{n-1}]
+// Output Stack: [g1projective]
+//
+// Same as `msm`, but the scalars are read from the stack, so the script only depends on the bases.
+pub fn msm_with_scalars_in_stack(bases: &[ark_bn254::G1Affine]) -> Script {
+    let len = bases.len() as u32;
+    match pippenger_window(bases.len()) {
+        Some(window) => pippenger(bases, window),
+        None => script! {
+            { G1Projective::push_zero() }
+            for i in 0..len {
+                { G1Projective::push(bases[i as usize].into()) }
+                // the scalar below the remaining ones, the sum and the base
+                { Fr::roll(len - i + 5) }
+                { G1Projective::scalar_mul() }
+                { G1Projective::add() }
+            }
+        },
+    }
+}
+
 // Will compute msm assuming bases are constant and return the affine point
 // Output Stack: [x,y]
 pub fn msm_with_constant_bases(bases: &[ark_bn254::G1Affine], scalars: &[ark_bn254::Fr]) -> Script {
@@ -56,7 +268,6 @@ pub fn msm_with_constant_bases(bases: &[ark_bn254::G1Affine], scalars: &[ark_bn2
         bases.iter().map(|&p| p.into()).collect();
     let len = bases.len();
 
-    println!("len: {len}");
     script! {
         // 1. init the sum=0;
         {G1Projective::push_zero()}
@@ -117,6 +328,121 @@ mod test {
         assert!(exec_result.success);
     }
 
+    #[test]
+    fn test_pippenger() {
+        let rng = &mut test_rng();
+        let base = ark_bn254::G1Projective::rand(rng).into_affine();
+        // the repeated base ends up in the same bucket twice for every matching digit
+        let bases = vec![base, base, ark_bn254::G1Projective::rand(rng).into_affine()];
+        let scalars = vec![
+            ark_bn254::Fr::rand(rng),
+            ark_bn254::Fr::rand(rng),
+            ark_bn254::Fr::rand(rng),
+        ];
+        let expect = ark_bn254::G1Projective::msm(&bases, &scalars).unwrap();
+
+        for window in 1..=PIPPENGER_MAX_WINDOW {
+            let pippenger = pippenger(&bases, window);
+            println!("msm::pippenger({}) = {} bytes", window, pippenger.len());
+
+            let script = script! {
+                for i in 0..scalars.len() {
+                    { fr_push(scalars[i]) }
+                }
+                { pippenger }
+                { G1Projective::push(expect) }
+                { G1Projective::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_pippenger_max_len() {
+        let rng = &mut test_rng();
+        let window = PIPPENGER_MAX_WINDOW;
+        let n = pippenger_max_len(window);
+        assert_eq!(pippenger_window(n), Some(window));
+        // none of the windows fits the stack beyond the bound of the smallest one
+        assert_eq!(pippenger_window(pippenger_max_len(1) + 1), None);
+
+        let scalars = (0..n).map(|_| ark_bn254::Fr::rand(rng)).collect::<Vec<_>>();
+        let bases = (0..n)
+            .map(|_| ark_bn254::G1Projective::rand(rng).into_affine())
+            .collect::<Vec<_>>();
+        let expect = ark_bn254::G1Projective::msm(&bases, &scalars).unwrap();
+
+        let script = script! {
+            for i in 0..scalars.len() {
+                { fr_push(scalars[i]) }
+            }
+            { pippenger(&bases, window) }
+            { G1Projective::push(expect) }
+            { G1Projective::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+        assert!(exec_result.stats.max_nb_stack_items <= MAX_STACK_ELEMENTS);
+    }
+
+    #[test]
+    fn test_msm_with_scalars_in_stack() {
+        let rng = &mut test_rng();
+
+        for n in [1, 2, 8] {
+            let scalars = (0..n).map(|_| ark_bn254::Fr::rand(rng)).collect::<Vec<_>>();
+            let bases = (0..n)
+                .map(|_| ark_bn254::G1Projective::rand(rng).into_affine())
+                .collect::<Vec<_>>();
+            let expect = ark_bn254::G1Projective::msm(&bases, &scalars).unwrap();
+
+            let msm = msm_with_scalars_in_stack(&bases);
+            println!("msm::msm_with_scalars_in_stack({}) = {} bytes", n, msm.len());
+
+            let script = script! {
+                for i in 0..scalars.len() {
+                    { fr_push(scalars[i]) }
+                }
+                { msm }
+                { G1Projective::push(expect) }
+                { G1Projective::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_msm_pippenger_script() {
+        let k = 3;
+        let n = 1 << k;
+        let rng = &mut test_rng();
+
+        let scalars = (0..n).map(|_| ark_bn254::Fr::rand(rng)).collect::<Vec<_>>();
+
+        let bases = (0..n)
+            .map(|_| ark_bn254::G1Projective::rand(rng).into_affine())
+            .collect::<Vec<_>>();
+
+        let expect = ark_bn254::G1Projective::msm(&bases, &scalars).unwrap();
+        let expect = expect.into_affine();
+        assert!(pippenger_window(n).is_some());
+
+        let script = script! {
+            { msm(&bases, &scalars) }
+            { g1_affine_push(expect) }
+            { G1Affine::equalverify() }
+            OP_TRUE
+        };
+        println!("msm::test_msm_pippenger_script = {} bytes", script.len());
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
     #[test]
     fn test_msm_with_constant_bases_script() {
         let k = 0;
@@ -148,4 +474,43 @@ mod test {
         end_timer!(start);
         assert!(exec_result.success);
     }
+
+    #[test]
+    fn test_msm_with_method_script() {
+        // the shape of the msm in `groth16::Verifier::prepare_inputs` with one public input
+        let rng = &mut test_rng();
+        let scalars = vec![ark_bn254::Fr::ONE, ark_bn254::Fr::rand(rng)];
+        let bases = (0..2)
+            .map(|_| ark_bn254::G1Projective::rand(rng).into_affine())
+            .collect::<Vec<_>>();
+
+        let expect = ark_bn254::G1Projective::msm(&bases, &scalars).unwrap();
+        let expect = expect.into_affine();
+
+        println!(
+            "msm_with_constant_bases = {} bytes",
+            msm_with_constant_bases(&bases, &scalars).len()
+        );
+        for method in [
+            ScalarMulMethod::Window,
+            ScalarMulMethod::Wnaf(3),
+            ScalarMulMethod::Wnaf(4),
+            ScalarMulMethod::Wnaf(5),
+            ScalarMulMethod::Glv(3),
+            ScalarMulMethod::Glv(4),
+            ScalarMulMethod::Glv(5),
+        ] {
+            let msm = msm_with_method(&bases, &scalars, method);
+            println!("msm_with_method({:?}) = {} bytes", method, msm.len());
+
+            let script = script! {
+                { msm }
+                { g1_affine_push(expect) }
+                { G1Affine::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
 }
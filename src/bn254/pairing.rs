@@ -240,7 +240,39 @@ impl Pairing {
     //     [L(Q1), L(Q2), L(Q3), L(Q4)] (line coefficients in affine mode)
     pub fn quad_miller_loop_with_c_wi(constants: Vec<G2Prepared>) -> Script {
         assert_eq!(constants.len(), 4);
-        let num_line_groups = constants.len();
+        Self::quad_miller_loop(constants, false)
+    }
+
+    // same as `quad_miller_loop_with_c_wi`, but the line coefficients of Q4 are provided by prover
+    // as well, so the script only depends on Q1, Q2 and Q3
+    //
+    // input on stack:
+    //     [L(Q4), beta_12, beta_13, beta_22, P1', P2', P3', P4', Q4, c, c_inv, wi, T4]
+    //     L(Q4) are the (alpha, -bias) of all lines of Q4 (4 elements each), the first line in
+    //     the order of the miller loop is on top, see `q4_lines_push`
+    //
+    // input of parameters:
+    //     [L(Q1), L(Q2), L(Q3)] (line coefficients in affine mode)
+    pub fn quad_miller_loop_with_c_wi_and_q4_lines_in_stack(constants: Vec<G2Prepared>) -> Script {
+        assert_eq!(constants.len(), 3);
+        Self::quad_miller_loop(constants, true)
+    }
+
+    // pushes the line coefficients of Q4 for `quad_miller_loop_with_c_wi_and_q4_lines_in_stack`
+    pub fn q4_lines_push(q4: ark_bn254::G2Affine) -> Script {
+        let line_coeffs = utils::collect_line_coeffs(vec![G2Prepared::from_affine(q4)]);
+        script! {
+            for line_coeff in line_coeffs.iter().rev() {
+                for (_, c3, c4) in line_coeff[0].iter().rev() {
+                    { utils::fq2_push(*c3) }
+                    { utils::fq2_push(*c4) }
+                }
+            }
+        }
+    }
+
+    fn quad_miller_loop(constants: Vec<G2Prepared>, q4_lines_in_stack: bool) -> Script {
+        let num_line_groups = 4;
         let num_constant = 3;
 
         let line_coeffs = utils::collect_line_coeffs(constants);
@@ -278,77 +310,87 @@ impl Pairing {
 
                 // update f with double line evaluation
                 for j in 0..num_line_groups {
-                    // copy P_j(p1, p2, p3, p4) to stack
-                    { Fq2::copy((26 + 36 - j * 2) as u32) }
-                    // update f with double line evaluation
-                    { utils::ell_by_constant_affine(&line_coeffs[num_lines - (i + 2)][j][0]) }
-                    // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
-
-                    // non-fixed part
-                    if j == num_constant {
-                        // check line coeff is satisfied with T4
-                        { Fq12::toaltstack() }
-                        // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
-                        { Fq2::copy(2) }
-                        { Fq2::copy(2) }
-                        // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), T4(4) | f(12)]
-                        { utils::check_tangent_line(line_coeffs[num_lines - (i + 2)][j][0].1, line_coeffs[num_lines - (i + 2)][j][0].2) }
-                        // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
-
-                        // update T4
-                        // drop T4.y, leave T4.x
-                        { Fq2::drop() }
-                        // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2) | f(12)]
-                        { utils::affine_double_line(line_coeffs[num_lines - (i + 2)][j][0].1, line_coeffs[num_lines - (i + 2)][j][0].2) }
-                        // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
-                        { Fq12::fromaltstack() }
+                    if j == num_constant && q4_lines_in_stack {
+                        { Self::q4_double_line_in_stack() }
                         // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
-                    }
-                }
-
-                // update f with add line evaluation
-                if ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == 1 || ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == -1 {
-                    for j in 0..num_line_groups {
+                    } else {
                         // copy P_j(p1, p2, p3, p4) to stack
                         { Fq2::copy((26 + 36 - j * 2) as u32) }
-                        // update f with adding line evaluation
-                        { utils::ell_by_constant_affine(&line_coeffs[num_lines - (i + 2)][j][1]) }
+                        // update f with double line evaluation
+                        { utils::ell_by_constant_affine(&line_coeffs[num_lines - (i + 2)][j][0]) }
                         // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
 
                         // non-fixed part
                         if j == num_constant {
+                            // check line coeff is satisfied with T4
                             { Fq12::toaltstack() }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
-
-                            // copy T4
                             { Fq2::copy(2) }
                             { Fq2::copy(2) }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), T4(4) | f(12)]
-
-                            // copy Q4
-                            { Fq2::copy(10 + 36) }
-                            { Fq2::copy(10 + 36) }
-                            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), T4(4), Q4(4) | f(12)]
-                            if ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == -1 {
-                                { Fq2::neg(0) }
-                            }
-                            { utils::check_chord_line(line_coeffs[num_lines - (i + 2)][j][1].1, line_coeffs[num_lines - (i + 2)][j][1].2) }
+                            { utils::check_tangent_line(line_coeffs[num_lines - (i + 2)][j][0].1, line_coeffs[num_lines - (i + 2)][j][0].2) }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
 
                             // update T4
                             // drop T4.y, leave T4.x
                             { Fq2::drop() }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2) | f(12)]
-                            // copy Q4.x
-                            { Fq2::copy(4 + 36) }
-                            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2), Q4.x(2) | f(12)]
-                            { utils::affine_add_line(line_coeffs[num_lines - (i + 2)][j][1].1, line_coeffs[num_lines - (i + 2)][j][1].2) }
+                            { utils::affine_double_line(line_coeffs[num_lines - (i + 2)][j][0].1, line_coeffs[num_lines - (i + 2)][j][0].2) }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
                             { Fq12::fromaltstack() }
                             // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
                         }
                     }
                 }
+
+                // update f with add line evaluation
+                if ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == 1 || ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == -1 {
+                    for j in 0..num_line_groups {
+                        if j == num_constant && q4_lines_in_stack {
+                            { Self::q4_add_line_in_stack(ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == -1) }
+                            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
+                        } else {
+                            // copy P_j(p1, p2, p3, p4) to stack
+                            { Fq2::copy((26 + 36 - j * 2) as u32) }
+                            // update f with adding line evaluation
+                            { utils::ell_by_constant_affine(&line_coeffs[num_lines - (i + 2)][j][1]) }
+                            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
+
+                            // non-fixed part
+                            if j == num_constant {
+                                { Fq12::toaltstack() }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
+
+                                // copy T4
+                                { Fq2::copy(2) }
+                                { Fq2::copy(2) }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), T4(4) | f(12)]
+
+                                // copy Q4
+                                { Fq2::copy(10 + 36) }
+                                { Fq2::copy(10 + 36) }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), T4(4), Q4(4) | f(12)]
+                                if ark_bn254::Config::ATE_LOOP_COUNT[i - 1] == -1 {
+                                    { Fq2::neg(0) }
+                                }
+                                { utils::check_chord_line(line_coeffs[num_lines - (i + 2)][j][1].1, line_coeffs[num_lines - (i + 2)][j][1].2) }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
+
+                                // update T4
+                                // drop T4.y, leave T4.x
+                                { Fq2::drop() }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2) | f(12)]
+                                // copy Q4.x
+                                { Fq2::copy(4 + 36) }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2), Q4.x(2) | f(12)]
+                                { utils::affine_add_line(line_coeffs[num_lines - (i + 2)][j][1].1, line_coeffs[num_lines - (i + 2)][j][1].2) }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | f(12)]
+                                { Fq12::fromaltstack() }
+                                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
+                            }
+                        }
+                    }
+                }
             }
 
             // update f with frobenius of c, say f = f * c_inv^p * c^{p^2}
@@ -373,11 +415,14 @@ impl Pairing {
 
             // update f with add line evaluation of one-time of frobenius map on Q4
             for j in 0..num_line_groups {
-                // copy P_j(p1, p2, p3, p4) to stack
-                { Fq2::copy((26 - j * 2) as u32) }
-                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12), P_j(2)]
-                { utils::ell_by_constant_affine(&line_coeffs[num_lines - 2][j][0]) }
-                // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12)]
+                // the line evaluation of Q4 follows its check if the line is provided by prover
+                if j != num_constant || !q4_lines_in_stack {
+                    // copy P_j(p1, p2, p3, p4) to stack
+                    { Fq2::copy((26 - j * 2) as u32) }
+                    // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12), P_j(2)]
+                    { utils::ell_by_constant_affine(&line_coeffs[num_lines - 2][j][0]) }
+                    // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12)]
+                }
 
                 // non-fixed part
                 if j == num_constant {
@@ -408,42 +453,89 @@ impl Pairing {
                     // phi(Q4) = (Q4.x', Q4.y')
                     // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4) | f(12)]
 
-                    // check chord line
-                    { Fq2::copy(6) }
-                    { Fq2::copy(6) }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), T4(4) | f(12)]
-                    { Fq2::copy(6) }
-                    { Fq2::copy(6) }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), T4(4), phi(Q4)(4) | f(12)]
-                    { utils::check_chord_line(line_coeffs[num_lines - 2][j][0].1, line_coeffs[num_lines - 2][j][0].2) }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4) | f(12)]
+                    if q4_lines_in_stack {
+                        // the line of T4 and phi(Q4) is on top of the remaining lines of Q4, below beta_22
+                        { Fq2::roll(24) }
+                        { Fq2::roll(24) }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), L(4) | f(12)]
+
+                        // check chord line
+                        for _ in 0..6 {
+                            { Fq2::copy(10) }
+                        }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), L(4), T4(4), phi(Q4)(4), L(4) | f(12)]
+                        { utils::check_chord_line_in_stack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), L(4) | f(12)]
+                        { Fq2::copy(2) }
+                        { Fq2::copy(2) }
+                        { Fq2::toaltstack() }
+                        { Fq2::toaltstack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), L(4) | L(4), f(12)]
+
+                        // update T4
+                        { Fq2::roll(4) }
+                        { Fq2::drop() }
+                        { Fq2::roll(6) }
+                        { Fq2::drop() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4.x(2), phi(Q4).x(2), L(4) | L(4), f(12)]
+                        { utils::affine_add_line_in_stack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4) | L(4), f(12)]
+                        { Fq2::fromaltstack() }
+                        { Fq2::fromaltstack() }
+                        { Fq12::fromaltstack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), L(4), f(12)]
+
+                        // update f with the line evaluation at P4
+                        { Fq2::copy(24) }
+                        { Fq2::roll(16) }
+                        { Fq2::roll(16) }
+                        { utils::ell_affine() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12)]
+                    } else {
+                        // check chord line
+                        { Fq2::copy(6) }
+                        { Fq2::copy(6) }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), T4(4) | f(12)]
+                        { Fq2::copy(6) }
+                        { Fq2::copy(6) }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4), T4(4), phi(Q4)(4) | f(12)]
+                        { utils::check_chord_line(line_coeffs[num_lines - 2][j][0].1, line_coeffs[num_lines - 2][j][0].2) }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4)(4) | f(12)]
 
-                    // update T4
-                    { Fq2::drop() }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4).x(2) | f(12)]
-                    { Fq2::toaltstack() }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4) | phi(Q4).x(2), f(12)]
-                    { Fq2::drop() }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4.x(2) | phi(Q4).x(2), f(12)]
-                    { Fq2::fromaltstack() }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4.x(2), phi(Q4).x(2) | f(12)]
-                    { utils::affine_add_line(line_coeffs[num_lines - 2][j][0].1, line_coeffs[num_lines - 2][j][0].2) }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4) | f(12)]
-                    { Fq12::fromaltstack() }
-                    // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12)]
+                        // update T4
+                        { Fq2::drop() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), phi(Q4).x(2) | f(12)]
+                        { Fq2::toaltstack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4) | phi(Q4).x(2), f(12)]
+                        { Fq2::drop() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4.x(2) | phi(Q4).x(2), f(12)]
+                        { Fq2::fromaltstack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4.x(2), phi(Q4).x(2) | f(12)]
+                        { utils::affine_add_line(line_coeffs[num_lines - 2][j][0].1, line_coeffs[num_lines - 2][j][0].2) }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4) | f(12)]
+                        { Fq12::fromaltstack() }
+                        // [beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), T4(4), f(12)]
+                    }
                 }
             }
 
             // update f with add line evaluation of two-times of frobenius map on Q4
             for j in 0..num_line_groups {
-                // update f with adding line evaluation by rolling each Pi(2) element to the right(stack top)
-                { Fq2::roll((26 - j * 2) as u32) }
-                { utils::ell_by_constant_affine(&line_coeffs[num_lines - 1][j][0]) }
-                // [beta_22(2), Q4(4), T4(4), f(12)]
+                if j != num_constant || !q4_lines_in_stack {
+                    // update f with adding line evaluation by rolling each Pi(2) element to the right(stack top)
+                    { Fq2::roll((26 - j * 2) as u32) }
+                    { utils::ell_by_constant_affine(&line_coeffs[num_lines - 1][j][0]) }
+                    // [beta_22(2), Q4(4), T4(4), f(12)]
+                }
 
                 // non-fixed part(Q4)
                 if j == num_constant {
                     { Fq12::toaltstack() }
+                    if q4_lines_in_stack {
+                        // keep P4 for the line evaluation
+                        { Fq2::roll(8) }
+                        { Fq2::toaltstack() }
+                    }
                     // [beta_22(2), Q4(4), T4(4) | f(12)]
                     { Fq2::roll(8) }
                     // [Q4(4), T4(4), beta_22(2) | f(12)]
@@ -459,15 +551,141 @@ impl Pairing {
                     // [T4(4), phi(Q4)^2(4) | f(12)]
 
                     // check whether the chord line through T4 and phi(Q4)^2
-                    { utils::check_chord_line(line_coeffs[num_lines - 1][j][0].1, line_coeffs[num_lines - 1][j][0].2) }
-                    // [ | f(12)]
-                    { Fq12::fromaltstack() }
-                    // [f(12)]
+                    if q4_lines_in_stack {
+                        // the last line of Q4
+                        { Fq2::roll(10) }
+                        { Fq2::roll(10) }
+                        { Fq2::copy(2) }
+                        { Fq2::copy(2) }
+                        { Fq2::toaltstack() }
+                        { Fq2::toaltstack() }
+                        // [T4(4), phi(Q4)^2(4), L(4) | L(4), P4(2), f(12)]
+                        { utils::check_chord_line_in_stack() }
+                        { Fq2::fromaltstack() }
+                        { Fq2::fromaltstack() }
+                        { Fq2::fromaltstack() }
+                        { Fq12::fromaltstack() }
+                        // [L(4), P4(2), f(12)]
+
+                        // update f with the line evaluation at P4
+                        { Fq2::roll(12) }
+                        { Fq2::roll(16) }
+                        { Fq2::roll(16) }
+                        { utils::ell_affine() }
+                        // [f(12)]
+                    } else {
+                        { utils::check_chord_line(line_coeffs[num_lines - 1][j][0].1, line_coeffs[num_lines - 1][j][0].2) }
+                        // [ | f(12)]
+                        { Fq12::fromaltstack() }
+                        // [f(12)]
+                    }
                 }
             }
         };
         script
     }
+
+    // update T4 with its tangent line provided by prover, and f with the line evaluation at P4
+    //
+    // input on stack:
+    //     [L(Q4), beta_12, beta_13, beta_22, P1', P2', P3', P4', Q4, c, c_inv, wi, T4, f]
+    // output on stack:
+    //     [L(Q4) without its first line, beta_12, beta_13, beta_22, P1', P2', P3', P4', Q4, c, c_inv, wi, 2 * T4, f]
+    fn q4_double_line_in_stack() -> Script {
+        script! {
+            { Fq12::toaltstack() }
+            // the line of T4 is on top of the remaining lines of Q4, below beta_12
+            { Fq2::roll(60) }
+            { Fq2::roll(60) }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4) | f(12)]
+
+            // check line coeff is satisfied with T4
+            for _ in 0..4 {
+                { Fq2::copy(6) }
+            }
+            { utils::check_tangent_line_in_stack() }
+            { Fq2::copy(2) }
+            { Fq2::copy(2) }
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4) | L(4), f(12)]
+
+            // update T4
+            // drop T4.y, leave T4.x
+            { Fq2::roll(4) }
+            { Fq2::drop() }
+            { utils::affine_double_line_in_stack() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4) | L(4), f(12)]
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq12::fromaltstack() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4), f(12)]
+
+            // update f with the line evaluation at P4
+            { Fq2::copy(60) }
+            { Fq2::roll(16) }
+            { Fq2::roll(16) }
+            { utils::ell_affine() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
+        }
+    }
+
+    // update T4 with its chord line through Q4 (-Q4 if `negate`) provided by prover, and f with the
+    // line evaluation at P4
+    //
+    // input on stack:
+    //     [L(Q4), beta_12, beta_13, beta_22, P1', P2', P3', P4', Q4, c, c_inv, wi, T4, f]
+    // output on stack:
+    //     [L(Q4) without its first line, beta_12, beta_13, beta_22, P1', P2', P3', P4', Q4, c, c_inv, wi, T4 ± Q4, f]
+    fn q4_add_line_in_stack(negate: bool) -> Script {
+        script! {
+            { Fq12::toaltstack() }
+            // the line of T4 and Q4 is on top of the remaining lines of Q4, below beta_12
+            { Fq2::roll(60) }
+            { Fq2::roll(60) }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4) | f(12)]
+
+            // check line coeff is satisfied with T4 and Q4
+            { Fq2::copy(6) }
+            { Fq2::copy(6) }
+            { Fq2::copy(14 + 36) }
+            { Fq2::copy(14 + 36) }
+            if negate {
+                { Fq2::neg(0) }
+            }
+            { Fq2::copy(10) }
+            { Fq2::copy(10) }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4), T4(4), Q4(4), L(4) | f(12)]
+            { utils::check_chord_line_in_stack() }
+            { Fq2::copy(2) }
+            { Fq2::copy(2) }
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4) | L(4), f(12)]
+
+            // update T4
+            // drop T4.y, leave T4.x
+            { Fq2::roll(4) }
+            { Fq2::drop() }
+            // copy Q4.x
+            { Fq2::copy(8 + 36) }
+            { Fq2::roll(4) }
+            { Fq2::roll(4) }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4.x(2), Q4.x(2), L(4) | L(4), f(12)]
+            { utils::affine_add_line_in_stack() }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq12::fromaltstack() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), L(4), f(12)]
+
+            // update f with the line evaluation at P4
+            { Fq2::copy(60) }
+            { Fq2::roll(16) }
+            { Fq2::roll(16) }
+            { utils::ell_affine() }
+            // [beta_12(2), beta_13(2), beta_22(2), P1(2), P2(2), P3(2), P4(2), Q4(4), c(12), c_inv(12), wi(12), T4(4), f(12)]
+        }
+    }
 }
 
 #[cfg(test)]
@@ -702,6 +920,111 @@ mod test {
         assert!(exec_result.success);
     }
 
+    #[test]
+    fn test_quad_miller_loop_with_c_wi_and_q4_lines_in_stack() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        // exp = 6x + 2 + p - p^2 = lambda - p^3
+        let p_pow3 = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap().pow(3_u32);
+        let lambda = BigUint::from_str(
+            "10486551571378427818905133077457505975146652579011797175399169355881771981095211883813744499745558409789005132135496770941292989421431235276221147148858384772096778432243207188878598198850276842458913349817007302752534892127325269"
+        ).unwrap();
+        let (exp, sign) = if lambda > p_pow3 {
+            (lambda - p_pow3, true)
+        } else {
+            (p_pow3 - lambda, false)
+        };
+        // random c and wi
+        let c = ark_bn254::Fq12::rand(&mut prng);
+        let c_inv = c.inverse().unwrap();
+        let wi = ark_bn254::Fq12::rand(&mut prng);
+
+        let p1 = ark_bn254::G1Affine::rand(&mut prng);
+        let p2 = ark_bn254::G1Affine::rand(&mut prng);
+        let p3 = ark_bn254::G1Affine::rand(&mut prng);
+        let p4 = ark_bn254::G1Affine::rand(&mut prng);
+
+        let q1 = ark_bn254::g2::G2Affine::rand(&mut prng);
+        let q2 = ark_bn254::g2::G2Affine::rand(&mut prng);
+        let q3 = ark_bn254::g2::G2Affine::rand(&mut prng);
+        let q4 = ark_bn254::g2::G2Affine::rand(&mut prng);
+        let q1_prepared = G2Prepared::from_affine(q1);
+        let q2_prepared = G2Prepared::from_affine(q2);
+        let q3_prepared = G2Prepared::from_affine(q3);
+
+        let t4 = q4;
+
+        let quad_miller_loop_affine_script = Pairing::quad_miller_loop_with_c_wi_and_q4_lines_in_stack(
+            [q1_prepared, q2_prepared, q3_prepared].to_vec(),
+        );
+        println!(
+            "Pairing.quad_miller_loop_with_c_wi_and_q4_lines_in_stack: {} bytes",
+            quad_miller_loop_affine_script.len()
+        );
+
+        let f = Bn254::multi_miller_loop_affine([p1, p2, p3, p4], [q1, q2, q3, q4]).0;
+        println!("Bn254::multi_miller_loop_affine done!");
+
+        let hint = if sign {
+            f * wi * (c_inv.pow(exp.to_u64_digits()))
+        } else {
+            f * wi * (c_inv.pow(exp.to_u64_digits()).inverse().unwrap())
+        };
+
+        // [L(Q4), beta_12, beta_13, beta_22, P1, P2, P3, P4, Q4, c,  c_inv, wi, T4]: p1-p4: (-p.x / p.y, 1 / p.y)
+        let script = script! {
+            // line coefficients of q4
+            { Pairing::q4_lines_push(q4) }
+
+            // beta_12
+            { Fq::push_u32_le(&BigUint::from_str("21575463638280843010398324269430826099269044274347216827212613867836435027261").unwrap().to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from_str("10307601595873709700152284273816112264069230130616436755625194854815875713954").unwrap().to_u32_digits()) }
+            // beta_13
+            { Fq::push_u32_le(&BigUint::from_str("2821565182194536844548159561693502659359617185244120367078079554186484126554").unwrap().to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from_str("3505843767911556378687030309984248845540243509899259641013678093033130930403").unwrap().to_u32_digits()) }
+            // beta_22
+            { Fq::push_u32_le(&BigUint::from_str("21888242871839275220042445260109153167277707414472061641714758635765020556616").unwrap().to_u32_digits()) }
+            { Fq::push_u32_le(&BigUint::from_str("0").unwrap().to_u32_digits()) }
+
+            // p1, p2, p3, p4
+            { utils::from_eval_point(p1) }
+            { utils::from_eval_point(p2) }
+            { utils::from_eval_point(p3) }
+            { utils::from_eval_point(p4) }
+
+            // q4
+            { fq2_push(q4.x) }
+            { fq2_push(q4.y) }
+
+            // c, c_inv, wi
+            { fq12_push(c) }
+            { fq12_push(c_inv) }
+            { fq12_push(wi) }
+
+            // t4
+            { fq2_push(t4.x) }
+            { fq2_push(t4.y) }
+
+            { quad_miller_loop_affine_script.clone() }
+
+            { fq12_push(hint) }
+
+            { Fq12::equalverify() }
+
+            OP_TRUE
+        };
+        let exec_result = execute_script_without_stack_limit(script);
+        println!("{}", exec_result);
+        if !exec_result.success {
+            println!(
+                "Remaining script size: {}, last opcode: {}",
+                exec_result.remaining_script.len(),
+                exec_result.last_opcode.unwrap().to_string(),
+            );
+        }
+        assert!(exec_result.success);
+    }
+
     #[test]
     fn test_mul_by_char() {
         let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
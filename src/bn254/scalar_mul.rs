@@ -0,0 +1,505 @@
+use crate::bigint::{BigIntImpl, U254};
+use crate::bn254::curves::{G1Projective, ProjectiveCurve};
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fq::Fq;
+use crate::bn254::fr::Fr;
+use crate::treepp::{script, Script};
+use ark_ff::PrimeField;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{Num, One, Signed, ToPrimitive, Zero};
+
+// The digits of a hint are summed up in two's complement. Neither a full scalar nor a GLV half
+// recoded with a window of at most 6 bits gets close to 2²⁶¹ in absolute value, so the sums
+// cannot wrap.
+type U262 = BigIntImpl<262, 29>;
+
+/// Digits of the wNAF of a full scalar, the recoding is at most one digit longer than the scalar.
+const WNAF_DIGITS: u32 = Fr::N_BITS + 1;
+/// Digits of the wNAF of a GLV half, both halves stay below 2¹²⁷ in absolute value.
+const GLV_DIGITS: u32 = 128;
+
+// φ(x, y) = (β⋅x, y) = [λ](x, y) on G1
+const BETA: &str = "59e26bcea0d48bacd4f263f1acdb5c4f5763473177fffffe";
+const LAMBDA: &str = "b3c4d79d41a917585bfc41088d8daaa78b17ea66b99c90dd";
+
+// Short basis of the lattice {(a, b) : a + b⋅λ ≡ 0 mod r}
+const V1: (&str, &str) = ("9931322734385697763", "-147946756881789319000765030803803410728");
+const V2: (&str, &str) = ("147946756881789319010696353538189108491", "9931322734385697763");
+
+/// Scalar multiplication algorithms for [`G1Projective`], see
+/// [`G1Projective::scalar_mul_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarMulMethod {
+    /// 4-bit windows over the bits of the scalar, [`G1Projective::scalar_mul`].
+    Window,
+    /// Width-w NAF of the scalar over the odd multiples P, 3P, …, (2ʷ⁻¹ - 1)P.
+    Wnaf(u32),
+    /// GLV split k = k₁ + λ⋅k₂ with both halves recoded as width-w NAF and added in the same
+    /// loop, which halves the number of doublings.
+    Glv(u32),
+}
+
+impl G1Projective {
+    /// Input Stack: [g1projective, scalar, hint]
+    /// Output Stack: [scalar * g1projective]
+    ///
+    /// `hint` is [`G1Projective::scalar_mul_hint`] for the same method and scalar.
+    pub fn scalar_mul_with(method: ScalarMulMethod) -> Script {
+        match method {
+            ScalarMulMethod::Window => G1Projective::scalar_mul(),
+            ScalarMulMethod::Wnaf(window) => G1Projective::scalar_mul_wnaf(window),
+            ScalarMulMethod::Glv(window) => G1Projective::scalar_mul_glv(window),
+        }
+    }
+
+    /// Pushes the signed digits [`G1Projective::scalar_mul_with`] expects above the scalar. The
+    /// plain window method takes no hint.
+    pub fn scalar_mul_hint(method: ScalarMulMethod, scalar: ark_bn254::Fr) -> Script {
+        let scalar = BigInt::from(BigUint::from(scalar));
+        match method {
+            ScalarMulMethod::Window => script! {},
+            ScalarMulMethod::Wnaf(window) => push_digits(&wnaf(&scalar, window, WNAF_DIGITS)),
+            ScalarMulMethod::Glv(window) => {
+                let (k1, k2) = glv_decompose(&scalar);
+                script! {
+                    { push_digits(&wnaf(&k1, window, GLV_DIGITS)) }
+                    { push_digits(&wnaf(&k2, window, GLV_DIGITS)) }
+                }
+            }
+        }
+    }
+
+    /// Input Stack: [g1projective, scalar, hint]
+    /// Output Stack: [scalar * g1projective]
+    ///
+    /// The hint is the width-`window` NAF of the scalar. Its digits are summed up and compared
+    /// to the scalar before they select the odd multiples of the point.
+    pub fn scalar_mul_wnaf(window: u32) -> Script {
+        let n = table_size(window);
+        script! {
+            for _ in 0..WNAF_DIGITS {
+                OP_TOALTSTACK
+            }
+            { Fr::decode_montgomery() }
+            { U254::resize::<262>() }
+            { recombine_digits(WNAF_DIGITS, n) }
+            for _ in 0..U262::N_LIMBS {
+                { WNAF_DIGITS + 2 * U262::N_LIMBS - 1 } OP_ROLL
+            }
+            { U262::equalverify(1, 0) }
+            for _ in 0..WNAF_DIGITS {
+                OP_TOALTSTACK
+            }
+
+            { odd_multiples(n) }
+            { G1Projective::push_zero() }
+            for i in 0..WNAF_DIGITS {
+                if i > 0 {
+                    { G1Projective::double() }
+                }
+                { add_digit(1, n) }
+            }
+
+            { G1Projective::toaltstack() }
+            for _ in 0..n {
+                { G1Projective::drop() }
+            }
+            { G1Projective::fromaltstack() }
+        }
+    }
+
+    /// Input Stack: [g1projective, scalar, hint]
+    /// Output Stack: [scalar * g1projective]
+    ///
+    /// The hint holds the width-`window` NAFs of k₁ and k₂ with k = k₁ + λ⋅k₂ mod r. Once the
+    /// split is checked, the odd multiples of P and of φ(P) = [λ]P are added in a single
+    /// double-and-add loop over the 128 digits of the halves.
+    pub fn scalar_mul_glv(window: u32) -> Script {
+        let n = table_size(window);
+        let lambda = ark_bn254::Fr::from(BigUint::from_str_radix(LAMBDA, 16).unwrap());
+        script! {
+            for _ in 0..2 * GLV_DIGITS {
+                OP_TOALTSTACK
+            }
+            { Fr::decode_montgomery() }
+            // [k, k₁ digits, k₁, k₂ digits, k₂]
+            { recombine_digits(GLV_DIGITS, n) }
+            { recombine_digits(GLV_DIGITS, n) }
+
+            // λ⋅k₂ + k₁ mod r
+            { signed_abs() }
+            OP_TOALTSTACK
+            { Fr::mul_by_constant(&lambda) }
+            OP_FROMALTSTACK
+            OP_IF
+                { Fr::neg(0) }
+            OP_ENDIF
+            for _ in 0..U262::N_LIMBS {
+                { U254::N_LIMBS + GLV_DIGITS + U262::N_LIMBS - 1 } OP_ROLL
+            }
+            { signed_abs() }
+            OP_IF
+                { Fr::neg(0) }
+            OP_ENDIF
+            { Fr::add(1, 0) }
+            for _ in 0..U254::N_LIMBS {
+                { 2 * U254::N_LIMBS + 2 * GLV_DIGITS - 1 } OP_ROLL
+            }
+            { Fr::equalverify(1, 0) }
+
+            // interleave the digits, the most significant digit of k₁ ends up on top
+            for i in 0..GLV_DIGITS {
+                OP_TOALTSTACK
+                { GLV_DIGITS - i - 1 } OP_ROLL
+                OP_TOALTSTACK
+            }
+
+            // [P, 3P, ..., φ(P), φ(3P), ...]
+            { odd_multiples(n) }
+            for _ in 0..n {
+                { G1Projective::copy(n - 1) }
+                { G1Projective::endomorphism() }
+            }
+
+            { G1Projective::push_zero() }
+            for i in 0..GLV_DIGITS {
+                if i > 0 {
+                    { G1Projective::double() }
+                }
+                { add_digit(n + 1, n) }
+                { add_digit(1, n) }
+            }
+
+            { G1Projective::toaltstack() }
+            for _ in 0..2 * n {
+                { G1Projective::drop() }
+            }
+            { G1Projective::fromaltstack() }
+        }
+    }
+
+    /// Input Stack: [x, y, z]
+    /// Output Stack: [β⋅x, y, z]
+    ///
+    /// The endomorphism φ of G1, which acts as multiplication by λ on the points of order r.
+    pub fn endomorphism() -> Script {
+        let beta = ark_bn254::Fq::from(BigUint::from_str_radix(BETA, 16).unwrap());
+        script! {
+            { Fq::roll(2) }
+            { Fq::mul_by_constant(&beta) }
+            { Fq::roll(2) }
+            { Fq::roll(2) }
+        }
+    }
+}
+
+fn table_size(window: u32) -> u32 {
+    assert!((2..=6).contains(&window), "the window has to be between 2 and 6 bits");
+    1 << (window - 2)
+}
+
+/// Signed width-`window` NAF of `k`, least significant digit first and padded to `n_digits`.
+///
+/// The digits are encoded as sign(d)⋅(|d| + 1) / 2, the position of the odd multiple |d|⋅P in
+/// the table, with 0 standing for a zero digit.
+fn wnaf(k: &BigInt, window: u32, n_digits: u32) -> Vec<i32> {
+    let modulus = BigInt::one() << window;
+    let mut k = k.clone();
+    let mut digits = Vec::new();
+    while !k.is_zero() {
+        let mut digit = 0;
+        if !(&k % 2u32).is_zero() {
+            digit = (&k % &modulus).to_i32().unwrap();
+            if digit < 0 {
+                digit += 1 << window;
+            }
+            if digit >= 1 << (window - 1) {
+                digit -= 1 << window;
+            }
+            k -= digit;
+        }
+        digits.push(digit.signum() * ((digit.abs() + 1) / 2));
+        k /= 2;
+    }
+    assert!(digits.len() <= n_digits as usize, "too many digits");
+    digits.resize(n_digits as usize, 0);
+    digits
+}
+
+/// Splits `k` into k₁ + λ⋅k₂ ≡ k mod r with |k₁|, |k₂| < 2¹²⁷.
+fn glv_decompose(k: &BigInt) -> (BigInt, BigInt) {
+    let parse = |s: &str| BigInt::from_str_radix(s, 10).unwrap();
+    let (a1, b1) = (parse(V1.0), parse(V1.1));
+    let (a2, b2) = (parse(V2.0), parse(V2.1));
+    let r = BigInt::from(BigUint::from(ark_bn254::Fr::MODULUS));
+
+    // ⌊(2n + r) / 2r⌋ rounds n / r, the numerators are not negative
+    let round = |n: BigInt| (2 * n + &r) / (2 * &r);
+    let c1 = round(&b2 * k);
+    let c2 = round(-&b1 * k);
+
+    let k1 = k - &c1 * &a1 - &c2 * &a2;
+    let k2 = -&c1 * &b1 - &c2 * &b2;
+    debug_assert!(k1.abs().bits() < 128 && k2.abs().bits() < 128);
+    (k1, k2)
+}
+
+// Pushes the most significant digit first.
+fn push_digits(digits: &[i32]) -> Script {
+    script! {
+        for i in (0..digits.len()).rev() {
+            { digits[i] }
+        }
+    }
+}
+
+/// Input Stack: []
+/// Input Altstack: [e₀, …, eₙ₋₁]
+/// Output Stack: [eₙ₋₁, …, e₀, Σ dᵢ⋅2ⁱ]
+///
+/// Sums up the digits dᵢ encoded by `wnaf` into a `U262` and keeps the encoded digits on the
+/// stack, in reverse order. Every encoded digit has to select one of the `n` odd multiples, i.e.
+/// |eᵢ| ≤ n, or the script fails.
+fn recombine_digits(n_digits: u32, n: u32) -> Script {
+    script! {
+        { U262::push_zero() }
+        for _ in 0..n_digits {
+            OP_FROMALTSTACK
+            OP_DUP OP_ABS { n } OP_LESSTHANOREQUAL OP_VERIFY
+
+            // keep a copy of the digit below the sum
+            OP_DUP
+            for _ in 0..U262::N_LIMBS {
+                { U262::N_LIMBS + 1 } OP_ROLL
+            }
+            { U262::N_LIMBS } OP_ROLL
+
+            // d = sign(e)⋅(2⋅|e| - 1)
+            OP_DUP 0 OP_LESSTHAN OP_TOALTSTACK
+            OP_ABS
+            OP_DUP
+            OP_IF
+                OP_DUP OP_ADD OP_1SUB
+            OP_ENDIF
+            OP_TOALTSTACK
+
+            // 2⋅sum + d
+            { U262::double(0) }
+            { U262::push_zero() }
+            OP_DROP
+            OP_FROMALTSTACK
+            OP_FROMALTSTACK
+            OP_IF
+                { U262::sub(1, 0) }
+            OP_ELSE
+                { U262::add(1, 0) }
+            OP_ENDIF
+        }
+    }
+}
+
+/// Input Stack: [v]
+/// Output Stack: [|v|, v < 0]
+///
+/// `v` is a `U262` in two's complement, `|v|` is returned as a `U254`.
+fn signed_abs() -> Script {
+    script! {
+        { U262::is_negative(0) }
+        OP_DUP
+        OP_TOALTSTACK
+        OP_IF
+            { U262::push_zero() }
+            { U262::sub(0, 1) }
+        OP_ENDIF
+        { U262::resize::<254>() }
+        OP_FROMALTSTACK
+    }
+}
+
+/// Input Stack: [p]
+/// Output Stack: [p, 3p, ..., (2n - 1)p]
+fn odd_multiples(n: u32) -> Script {
+    script! {
+        if n > 1 {
+            { G1Projective::copy(0) }
+            { G1Projective::double() }
+            { G1Projective::toaltstack() }
+            for _ in 1..n {
+                { G1Projective::copy(0) }
+                { G1Projective::fromaltstack() }
+                { G1Projective::copy(0) }
+                { G1Projective::toaltstack() }
+                { G1Projective::add() }
+            }
+            { G1Projective::fromaltstack() }
+            { G1Projective::drop() }
+        }
+    }
+}
+
+/// Input Stack: [table, ..., acc]
+/// Input Altstack: [e]
+/// Output Stack: [table, ..., acc + d⋅p]
+///
+/// `table` holds the `n` odd multiples of p, its last entry sits `top` points below `acc`. The
+/// digit has been checked to lie in the table by `recombine_digits`.
+fn add_digit(top: u32, n: u32) -> Script {
+    script! {
+        OP_FROMALTSTACK
+        OP_DUP
+        OP_IF
+            OP_DUP 0 OP_LESSTHAN OP_TOALTSTACK
+
+            // the entry |d|⋅p sits top + n - |e| points deep
+            OP_ABS OP_NEGATE { top + n } OP_ADD
+            for _ in 0..3 {
+                OP_DUP OP_DUP OP_ADD OP_ADD
+            }
+            { 26 } OP_ADD
+
+            // simulate {G1Projective::pick()}
+            for _ in 0..26 { OP_DUP }
+            for _ in 0..26 { OP_TOALTSTACK }
+            OP_PICK
+            for _ in 0..26 { OP_FROMALTSTACK OP_PICK }
+
+            OP_FROMALTSTACK
+            OP_IF
+                { G1Projective::neg() }
+            OP_ENDIF
+            { G1Projective::add() }
+        OP_ELSE
+            OP_DROP
+        OP_ENDIF
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execute_script;
+    use ark_std::UniformRand;
+    use core::ops::Mul;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_wnaf_and_glv_hints() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let r = BigInt::from(BigUint::from(ark_bn254::Fr::MODULUS));
+        let lambda = BigInt::from_str_radix(LAMBDA, 16).unwrap();
+        let decode = |digits: &[i32]| {
+            digits.iter().rev().fold(BigInt::zero(), |acc, &e| {
+                let d = if e == 0 { 0 } else { e.signum() * (2 * e.abs() - 1) };
+                2 * acc + d
+            })
+        };
+
+        for _ in 0..20 {
+            let k = BigInt::from(BigUint::from(ark_bn254::Fr::rand(&mut prng)));
+            for window in 2..=6 {
+                assert_eq!(decode(&wnaf(&k, window, WNAF_DIGITS)), k);
+            }
+
+            let (k1, k2) = glv_decompose(&k);
+            assert!(k1.abs().bits() < 128 && k2.abs().bits() < 128);
+            assert_eq!(((&k1 + &k2 * &lambda - &k) % &r), BigInt::zero());
+            assert_eq!(decode(&wnaf(&k1, 4, GLV_DIGITS)), k1);
+            assert_eq!(decode(&wnaf(&k2, 4, GLV_DIGITS)), k2);
+        }
+    }
+
+    #[test]
+    fn test_endomorphism() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let lambda = ark_bn254::Fr::from(BigUint::from_str_radix(LAMBDA, 16).unwrap());
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+
+        let script = script! {
+            { G1Projective::push(p) }
+            { G1Projective::endomorphism() }
+            { G1Projective::push(p.mul(lambda)) }
+            { G1Projective::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_scalar_mul_methods() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+        let scalar = ark_bn254::Fr::rand(&mut prng);
+
+        for method in [
+            ScalarMulMethod::Window,
+            ScalarMulMethod::Wnaf(2),
+            ScalarMulMethod::Wnaf(4),
+            ScalarMulMethod::Glv(2),
+            ScalarMulMethod::Glv(4),
+        ] {
+            let scalar_mul = G1Projective::scalar_mul_with(method);
+            println!("G1Projective::scalar_mul_with({:?}) = {} bytes", method, scalar_mul.len());
+
+            let script = script! {
+                { G1Projective::push(p) }
+                { Fr::push_u32_le(&BigUint::from(scalar).to_u32_digits()) }
+                { G1Projective::scalar_mul_hint(method, scalar) }
+                { scalar_mul }
+                { G1Projective::push(p.mul(scalar)) }
+                { G1Projective::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_wrong_hint() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+        let scalar = ark_bn254::Fr::rand(&mut prng);
+        let other = ark_bn254::Fr::rand(&mut prng);
+
+        for method in [ScalarMulMethod::Wnaf(4), ScalarMulMethod::Glv(4)] {
+            let script = script! {
+                { G1Projective::push(p) }
+                { Fr::push_u32_le(&BigUint::from(scalar).to_u32_digits()) }
+                { G1Projective::scalar_mul_hint(method, other) }
+                { G1Projective::scalar_mul_with(method) }
+                { G1Projective::drop() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(!exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_digit_out_of_range() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let p = ark_bn254::G1Projective::rand(&mut prng);
+        let method = ScalarMulMethod::Wnaf(4);
+
+        // a single digit sums up to the scalar, but only 7P is in the table of P, 3P, 5P, 7P
+        for (k, digit, valid) in [(7u32, 4, true), (9u32, 5, false)] {
+            let scalar = ark_bn254::Fr::from(k);
+            let mut digits = vec![digit];
+            digits.resize(WNAF_DIGITS as usize, 0);
+
+            let script = script! {
+                { G1Projective::push(p) }
+                { Fr::push_u32_le(&BigUint::from(scalar).to_u32_digits()) }
+                { push_digits(&digits) }
+                { G1Projective::scalar_mul_with(method) }
+                { G1Projective::push(p.mul(scalar)) }
+                { G1Projective::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert_eq!(exec_result.success, valid);
+        }
+    }
+}
@@ -111,6 +111,36 @@ pub fn ell_by_constant_affine(constant: &EllCoeff) -> Script {
     }
 }
 
+// Same as `ell_by_constant_affine`, but the line coefficients are read from the stack.
+//
+// stack input:
+//  f            12 elements
+//  x': -p.x / p.y   1 element
+//  y': 1 / p.y      1 element
+//  c3: alpha        2 elements
+//  c4: -bias        2 elements
+//
+// output:
+//  new f        12 elements
+pub fn ell_affine() -> Script {
+    script! {
+        // [f, x', y', c3, c4]
+        // update c3, c3' = x' * c3
+        { Fq::roll(5) }
+        { Fq2::mul_by_fq(3, 0) }
+        // [f, y', c4, x' * c3]
+
+        // update c4, c4' = y' * c4
+        { Fq::roll(4) }
+        { Fq2::mul_by_fq(3, 0) }
+        // [f, c3', c4']
+
+        // compute the new f with c3' and c4', where c0 is trival value 1
+        { Fq12::mul_by_34() }
+        // [f]
+    }
+}
+
 pub fn collect_line_coeffs(
     constants: Vec<G2Prepared>,
 ) -> Vec<Vec<Vec<(ark_bn254::Fq2, ark_bn254::Fq2, ark_bn254::Fq2)>>> {
@@ -445,6 +475,172 @@ pub fn check_chord_line(c3: ark_bn254::Fq2, c4: ark_bn254::Fq2) -> Script {
     }
 }
 
+/// Same as `affine_add_line`, but the line coefficients are read from the stack.
+///
+/// input on stack:
+///     T.x (2 elements)
+///     Q.x (2 elements)
+///     c3: alpha (2 elements)
+///     c4: -bias (2 elements)
+///
+/// output on stack:
+///     T'.x (2 elements)
+///     T'.y (2 elements)
+pub fn affine_add_line_in_stack() -> Script {
+    script! {
+        // [T.x, Q.x, alpha, -bias]
+        { Fq2::roll(6) }
+        { Fq2::roll(6) }
+        // [alpha, -bias, T.x, Q.x]
+        { Fq2::add(2, 0) }
+        { Fq2::neg(0) }
+        // [alpha, -bias, -T.x - Q.x]
+        { affine_line_from_x_sum() }
+        // [x', y']
+    }
+}
+
+/// Same as `affine_double_line`, but the line coefficients are read from the stack.
+///
+/// input on stack:
+///     T.x (2 elements)
+///     c3: alpha (2 elements)
+///     c4: -bias (2 elements)
+///
+/// output on stack:
+///     T'.x (2 elements)
+///     T'.y (2 elements)
+pub fn affine_double_line_in_stack() -> Script {
+    script! {
+        // [T.x, alpha, -bias]
+        { Fq2::roll(4) }
+        { Fq2::double(0) }
+        { Fq2::neg(0) }
+        // [alpha, -bias, - 2 * T.x]
+        { affine_line_from_x_sum() }
+        // [x', y']
+    }
+}
+
+// input on stack:
+//     [alpha, -bias, -(T.x + Q.x)]
+// output on stack:
+//     x' = alpha^2 - T.x - Q.x
+//     y' = -bias - alpha * x'
+fn affine_line_from_x_sum() -> Script {
+    script! {
+        { Fq2::copy(4) }
+        { Fq2::square() }
+        { Fq2::add(2, 0) }
+        // [alpha, -bias, x']
+        { Fq2::copy(0) }
+        { Fq2::mul(6, 0) }
+        { Fq2::neg(0) }
+        // [-bias, x', -alpha * x']
+        { Fq2::add(4, 0) }
+        // [x', y']
+    }
+}
+
+/// Same as `check_line_through_point`, but the line coefficients are read from the stack.
+///
+/// input on stack:
+///     x (2 elements)
+///     y (2 elements)
+///     c3: alpha (2 elements)
+///     c4: -bias (2 elements)
+///
+/// output:
+///     true or false (consumed on stack)
+pub fn check_line_through_point_in_stack() -> Script {
+    script! {
+        // [x, y, alpha, -bias]
+        { Fq2::roll(6) }
+        // [y, alpha, -bias, x]
+        { Fq2::mul(4, 0) }
+        // [y, -bias, alpha * x]
+        { Fq2::neg(0) }
+        { Fq2::add(4, 0) }
+        // [-bias, y - alpha * x]
+        { Fq2::add(2, 0) }
+        // [y - alpha * x - bias]
+
+        { Fq2::push_zero() }
+        // [y - alpha * x - bias, 0]
+        { Fq2::equalverify() }
+    }
+}
+
+/// Same as `check_tangent_line`, but the line coefficients are read from the stack.
+///
+/// input on stack:
+///     T.x (2 element)
+///     T.y (2 element)
+///     c3: alpha (2 elements)
+///     c4: -bias (2 elements)
+///
+/// output:
+///     true or false (consumed on stack)
+pub fn check_tangent_line_in_stack() -> Script {
+    script! {
+        // alpha * (2 * T.y) = 3 * T.x^2
+        { Fq2::copy(4) }
+        { Fq2::double(0) }
+        { Fq2::copy(4) }
+        { Fq2::mul(2, 0) }
+        // [T.x, T.y, alpha, -bias, alpha * (2 * T.y)]
+        { Fq2::copy(8) }
+        { Fq2::square() }
+        { Fq2::copy(0) }
+        { Fq2::double(0) }
+        { Fq2::add(2, 0) }
+        // [T.x, T.y, alpha, -bias, alpha * (2 * T.y), 3 * T.x^2]
+        { Fq2::neg(0) }
+        { Fq2::add(2, 0) }
+        { Fq2::push_zero() }
+        { Fq2::equalverify() }
+        // [T.x, T.y, alpha, -bias]
+
+        // check: T.y - alpha * T.x - bias = 0
+        { check_line_through_point_in_stack() }
+        // []
+    }
+}
+
+/// Same as `check_chord_line`, but the line coefficients are read from the stack.
+///
+/// input on stack:
+///     T.x (2 elements)
+///     T.y (2 elements)
+///     Q.x (2 elements)
+///     Q.y (2 elements)
+///     c3: alpha (2 elements)
+///     c4: -bias (2 elements)
+///
+/// output:
+///     true or false (consumed on stack)
+pub fn check_chord_line_in_stack() -> Script {
+    script! {
+        // [T, Q, alpha, -bias]
+        { Fq2::copy(2) }
+        { Fq2::copy(2) }
+        // [T, Q, alpha, -bias, alpha, -bias]
+        { Fq2::roll(10) }
+        { Fq2::roll(10) }
+        // [T, alpha, -bias, alpha, -bias, Q]
+        { Fq2::roll(6) }
+        { Fq2::roll(6) }
+        // [T, alpha, -bias, Q, alpha, -bias]
+
+        // check: Q.y - alpha * Q.x - bias = 0
+        { check_line_through_point_in_stack() }
+        // [T, alpha, -bias]
+        // check: T.y - alpha * T.x - bias = 0
+        { check_line_through_point_in_stack() }
+        // []
+    }
+}
+
 // stack data: beta^{2 * (p - 1) / 6}, beta^{3 * (p - 1) / 6}, beta^{2 * (p^2 - 1) / 6}, 1/2, B,
 // P1, P2, P3, P4, Q4, c, c', wi, f, Px, Py, Tx, Ty, Tz, Qx, Qy
 // [..., Fq12, Fq12, Fq12, Fq12, Fq, Fq, (Fq, Fq), (Fq, Fq), (Fq, Fq), (Fq, Fq), (Fq, Fq)]
@@ -1067,4 +1263,101 @@ mod test {
         let exec_result = execute_script(script);
         assert!(exec_result.success);
     }
+
+    #[test]
+    fn test_ell_affine() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        let f = ark_bn254::Fq12::rand(&mut prng);
+        let b = ark_bn254::g2::G2Affine::rand(&mut prng);
+        let p = ark_bn254::g1::G1Affine::rand(&mut prng);
+        println!("Pairing.ell_affine: {} bytes", ell_affine().len());
+
+        let coeffs = G2Prepared::from_affine(b);
+        let script = script! {
+            { fq12_push(f) }
+            { from_eval_point(p) }
+            { ell_by_constant_affine(&coeffs.ell_coeffs[0]) }
+            { fq12_push(f) }
+            { from_eval_point(p) }
+            { fq2_push(coeffs.ell_coeffs[0].1) }
+            { fq2_push(coeffs.ell_coeffs[0].2) }
+            { ell_affine() }
+            { Fq12::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_affine_lines_in_stack() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let t = ark_bn254::G2Affine::rand(&mut prng);
+        let q = ark_bn254::G2Affine::rand(&mut prng);
+
+        // chord through t and q
+        let alpha = (t.y - q.y) / (t.x - q.x);
+        let bias_minus = alpha * t.x - t.y;
+        let x = alpha.square() - t.x - q.x;
+        let y = bias_minus - alpha * x;
+
+        // tangent at t
+        let tangent_alpha = t.x.square() * ark_bn254::Fq2::from(3u64) / t.y.double();
+        let tangent_bias_minus = tangent_alpha * t.x - t.y;
+        let x2 = tangent_alpha.square() - t.x.double();
+        let y2 = tangent_bias_minus - tangent_alpha * x2;
+
+        let script = script! {
+            { fq2_push(t.x) }
+            { fq2_push(t.y) }
+            { fq2_push(q.x) }
+            { fq2_push(q.y) }
+            { fq2_push(alpha) }
+            { fq2_push(bias_minus) }
+            { check_chord_line_in_stack() }
+
+            { fq2_push(t.x) }
+            { fq2_push(q.x) }
+            { fq2_push(alpha) }
+            { fq2_push(bias_minus) }
+            { affine_add_line_in_stack() }
+            { fq2_push(y) }
+            { Fq2::equalverify() }
+            { fq2_push(x) }
+            { Fq2::equalverify() }
+
+            { fq2_push(t.x) }
+            { fq2_push(t.y) }
+            { fq2_push(tangent_alpha) }
+            { fq2_push(tangent_bias_minus) }
+            { check_tangent_line_in_stack() }
+
+            { fq2_push(t.x) }
+            { fq2_push(tangent_alpha) }
+            { fq2_push(tangent_bias_minus) }
+            { affine_double_line_in_stack() }
+            { fq2_push(y2) }
+            { Fq2::equalverify() }
+            { fq2_push(x2) }
+            { Fq2::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+
+        // a tangent line does not pass as chord through t and q
+        let script = script! {
+            { fq2_push(t.x) }
+            { fq2_push(t.y) }
+            { fq2_push(q.x) }
+            { fq2_push(q.y) }
+            { fq2_push(tangent_alpha) }
+            { fq2_push(tangent_bias_minus) }
+            { check_chord_line_in_stack() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
 }
@@ -1,4 +1,7 @@
-use crate::treepp::script;
+use crate::{
+    chunker::{commitment::StackCommitment, disprove::DisproveLeaves},
+    treepp::script,
+};
 use bitcoin::{
     hashes::{ripemd160, Hash},
     key::Secp256k1,
@@ -25,7 +28,8 @@ pub struct ConnectorC {
     pub network: Network,
     pub operator_taproot_public_key: XOnlyPublicKey,
     lock_scripts: Vec<ScriptBuf>,
-    unlock_witnesses: Vec<UnlockWitnessData>,
+    // The witness items of every leaf, bottom first
+    unlock_witnesses: Vec<Vec<UnlockWitnessData>>,
 }
 
 impl ConnectorC {
@@ -40,7 +44,48 @@ impl ConnectorC {
         }
     }
 
+    /// Uses the leaves of a chunked script instead of the placeholder leaves. The witness of
+    /// leaf i spends it with the operator's `commitments` to the states after chunk i - 1 and
+    /// chunk i, see [`DisproveLeaves::unlock_witness`].
+    pub fn new_with_disprove_leaves(
+        network: Network,
+        operator_taproot_public_key: &XOnlyPublicKey,
+        disprove_leaves: &DisproveLeaves,
+        commitments: &[StackCommitment],
+    ) -> Self {
+        assert_eq!(
+            commitments.len(),
+            disprove_leaves.len(),
+            "Expected a commitment for every chunk"
+        );
+        let unlock_witnesses = (0..disprove_leaves.len())
+            .map(|index| {
+                let input = index.checked_sub(1).map(|input| &commitments[input]);
+                disprove_leaves.unlock_witness(index, input, &commitments[index])
+            })
+            .collect();
+
+        ConnectorC {
+            network,
+            operator_taproot_public_key: operator_taproot_public_key.clone(),
+            lock_scripts: disprove_leaves.lock_scripts(),
+            unlock_witnesses,
+        }
+    }
+
+    /// The single witness item that spends the placeholder leaf `leaf_index`. The leaves of
+    /// [`Self::new_with_disprove_leaves`] take several items, see
+    /// [`Self::generate_disprove_leaf_witness`].
     pub fn generate_taproot_leaf_script_witness(&self, leaf_index: u32) -> UnlockWitnessData {
+        let mut witness = self.generate_disprove_leaf_witness(leaf_index);
+        if witness.len() != 1 {
+            panic!("Leaf {} takes {} witness items.", leaf_index, witness.len())
+        }
+        witness.remove(0)
+    }
+
+    /// The witness items that spend leaf `leaf_index`, bottom first.
+    pub fn generate_disprove_leaf_witness(&self, leaf_index: u32) -> Vec<UnlockWitnessData> {
         let index = leaf_index.to_usize().unwrap();
         if index >= self.unlock_witnesses.len() {
             panic!("Invalid leaf index.")
@@ -100,7 +145,7 @@ fn disprove_leaf() -> DisproveLeaf {
     }
 }
 
-fn generate_assert_leaves() -> (Vec<ScriptBuf>, Vec<UnlockWitnessData>) {
+fn generate_assert_leaves() -> (Vec<ScriptBuf>, Vec<Vec<UnlockWitnessData>>) {
     // TODO: Scripts with n_of_n_public_key and one of the commitments disprove leaves in each leaf (Winternitz signatures)
     let mut locks = Vec::with_capacity(1000);
     let mut unlocks = Vec::with_capacity(1000);
@@ -108,7 +153,79 @@ fn generate_assert_leaves() -> (Vec<ScriptBuf>, Vec<UnlockWitnessData>) {
     let unlocking_template = disprove_leaf().unlock;
     for i in 0..1000 {
         locks.push(locking_template(i));
-        unlocks.push(unlocking_template(i));
+        unlocks.push(vec![unlocking_template(i)]);
     }
     (locks, unlocks)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execution_env::ExecutionEnv;
+
+    fn operator_key() -> XOnlyPublicKey {
+        let secp = Secp256k1::new();
+        let secret_key = bitcoin::secp256k1::SecretKey::from_slice(&[3; 32]).unwrap();
+        secret_key.x_only_public_key(&secp).0
+    }
+
+    fn spend(lock: ScriptBuf, witness: Vec<UnlockWitnessData>) -> bool {
+        ExecutionEnv::new().with_witness(witness).execute(lock).success
+    }
+
+    #[test]
+    fn test_placeholder_leaves() {
+        let connector = ConnectorC::new(Network::Regtest, &operator_key());
+        for index in [0, 999] {
+            let witness = connector.generate_taproot_leaf_script_witness(index);
+            assert_eq!(witness, format!("SECRET_{}", index).as_bytes());
+            assert!(spend(connector.generate_taproot_leaf_script(index), vec![witness]));
+        }
+    }
+
+    #[test]
+    fn test_disprove_leaves() {
+        let script = script! {
+            { 0x1000 }
+            { 0x2000 }
+            OP_ADD
+            { 0x3000 }
+            OP_EQUALVERIFY
+            { 0x4000 }
+            OP_DROP
+            OP_TRUE
+        };
+        let leaves = DisproveLeaves::new(script, 4, 0, "connector_c");
+        let mut commitments: Vec<_> = (0..leaves.len())
+            .map(|index| leaves.commitment(index).unwrap())
+            .collect();
+
+        let connector = ConnectorC::new_with_disprove_leaves(
+            Network::Regtest,
+            &operator_key(),
+            &leaves,
+            &commitments,
+        );
+        for index in 0..leaves.len() {
+            let witness = connector.generate_disprove_leaf_witness(index as u32);
+            let input = index.checked_sub(1).map(|input| &commitments[input]);
+            assert_eq!(witness, leaves.unlock_witness(index, input, &commitments[index]));
+            // The honest commitments can't be disproven
+            assert!(!spend(connector.generate_taproot_leaf_script(index as u32), witness));
+        }
+
+        // The operator commits to a wrong final state
+        let last = leaves.len() - 1;
+        let boundary = leaves.boundary(last);
+        commitments[last] =
+            StackCommitment::new(&leaves.keys(last), &[vec![2]], &boundary.altstack).unwrap();
+        let connector = ConnectorC::new_with_disprove_leaves(
+            Network::Regtest,
+            &operator_key(),
+            &leaves,
+            &commitments,
+        );
+        let witness = connector.generate_disprove_leaf_witness(last as u32);
+        assert!(spend(connector.generate_taproot_leaf_script(last as u32), witness));
+    }
+}
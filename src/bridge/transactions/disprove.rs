@@ -205,8 +205,42 @@ impl DisproveTransaction {
         let input_index = 1;
 
         // Push the unlocking witness
-        let unlock_witness = connector_c.generate_taproot_leaf_script_witness(input_script_index);
-        self.tx.input[input_index].witness.push(unlock_witness);
+        let unlock_witness = connector_c.generate_disprove_leaf_witness(input_script_index);
+        for item in unlock_witness {
+            self.tx.input[input_index].witness.push(item);
+        }
+
+        // Push script + control block
+        let script = connector_c.generate_taproot_leaf_script(input_script_index);
+        let taproot_spend_info = connector_c.generate_taproot_spend_info();
+        push_taproot_leaf_script_and_control_block_to_witness(
+            &mut self.tx,
+            input_index,
+            &taproot_spend_info,
+            &script,
+        );
+    }
+
+    /// Like [`Self::add_input_output`] with a witness other than the one of the connector, e.g.
+    /// from [`DisproveLeaves::unlock_witness`](crate::chunker::disprove::DisproveLeaves::unlock_witness)
+    /// with the operator's commitments.
+    pub fn add_input_output_with_witness(
+        &mut self,
+        connector_c: &ConnectorC,
+        input_script_index: u32,
+        unlock_witness: Vec<Vec<u8>>,
+        output_script_pubkey: ScriptBuf,
+    ) {
+        // Add output
+        let output_index = 1;
+        self.tx.output[output_index].script_pubkey = output_script_pubkey;
+
+        let input_index = 1;
+
+        // Push the unlocking witness
+        for item in unlock_witness {
+            self.tx.input[input_index].witness.push(item);
+        }
 
         // Push script + control block
         let script = connector_c.generate_taproot_leaf_script(input_script_index);
@@ -234,3 +268,76 @@ impl BaseTransaction for DisproveTransaction {
         self.tx.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{key::Secp256k1, secp256k1::SecretKey, OutPoint};
+
+    use super::*;
+    use crate::{
+        bridge::transactions::simulator::{simulate_transaction, InputVerdict},
+        chunker::{commitment::StackCommitment, disprove::DisproveLeaves},
+        treepp::*,
+    };
+
+    fn disprove_transaction(connector_c: &ConnectorC, script_index: u32) -> DisproveTransaction {
+        let secp = Secp256k1::new();
+        let n_of_n_key = SecretKey::from_slice(&[5; 32]).unwrap().x_only_public_key(&secp).0;
+        let connector_5 = Connector5::new(Network::Regtest, &n_of_n_key);
+        let input = || Input {
+            outpoint: OutPoint::null(),
+            amount: Amount::from_sat(100_000),
+        };
+        DisproveTransaction::new_for_validation(
+            Network::Regtest,
+            &connector_5,
+            connector_c,
+            input(),
+            input(),
+            script_index,
+        )
+    }
+
+    fn connector_c_verdict(tx: &DisproveTransaction) -> InputVerdict {
+        let report = simulate_transaction(&tx.finalize(), tx.prev_outs(), tx.prev_scripts());
+        report.inputs[1].verdict.clone()
+    }
+
+    #[test]
+    fn test_add_input_output_with_witness() {
+        let script = script! {
+            { 0x1000 }
+            { 0x2000 }
+            OP_ADD
+            { 0x3000 }
+            OP_EQUALVERIFY
+            OP_TRUE
+        };
+        let leaves = DisproveLeaves::new(script, 4, 0, "disprove");
+        let commitments: Vec<_> = (0..leaves.len())
+            .map(|index| leaves.commitment(index).unwrap())
+            .collect();
+        let operator_key = SecretKey::from_slice(&[3; 32]).unwrap();
+        let operator_key = operator_key.x_only_public_key(&Secp256k1::new()).0;
+        let connector_c = ConnectorC::new_with_disprove_leaves(
+            Network::Regtest,
+            &operator_key,
+            &leaves,
+            &commitments,
+        );
+
+        // The connector's witness uses the honest commitments, which can't be disproven
+        let last = leaves.len() - 1;
+        let mut tx = disprove_transaction(&connector_c, last as u32);
+        tx.add_input_output(&connector_c, last as u32, ScriptBuf::new());
+        assert!(matches!(connector_c_verdict(&tx), InputVerdict::Fail(_)));
+
+        // A commitment to a wrong final state can
+        let wrong_output = StackCommitment::new(&leaves.keys(last), &[vec![2]], &[]).unwrap();
+        let input = last.checked_sub(1).map(|input| &commitments[input]);
+        let witness = leaves.unlock_witness(last, input, &wrong_output);
+        let mut tx = disprove_transaction(&connector_c, last as u32);
+        tx.add_input_output_with_witness(&connector_c, last as u32, witness, ScriptBuf::new());
+        assert_eq!(connector_c_verdict(&tx), InputVerdict::Pass);
+    }
+}
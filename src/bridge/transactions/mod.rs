@@ -16,6 +16,7 @@ pub mod pre_signed_musig2;
 pub mod signing;
 pub mod signing_musig2;
 pub mod signing_winternitz;
+pub mod simulator;
 pub mod start_time;
 pub mod start_time_timeout;
 pub mod take_1;
@@ -3,6 +3,8 @@ use bitcoin::{
     TapSighashType, Transaction, TxOut,
 };
 
+use crate::{execution_env::ExecutionEnv, ExecuteInfo};
+
 use super::{
     super::contexts::base::BaseContext,
     signing::{
@@ -81,3 +83,17 @@ pub fn pre_sign_taproot_input_default<T: PreSignedTransaction>(
         keypairs,
     );
 }
+
+/// Executes the leaf script of the taproot script path spend at `input_index` offline, against
+/// the transaction and the outputs it spends. The input has to be signed already.
+pub fn execute_taproot_input<T: PreSignedTransaction>(tx: &T, input_index: usize) -> ExecuteInfo {
+    let (env, leaf_script) =
+        ExecutionEnv::from_script_path_spend(tx.tx(), tx.prev_outs(), input_index);
+    assert_eq!(
+        leaf_script,
+        tx.prev_scripts()[input_index],
+        "input {} does not spend the expected leaf",
+        input_index
+    );
+    env.execute(leaf_script)
+}
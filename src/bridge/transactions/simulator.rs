@@ -0,0 +1,508 @@
+//
+// Offline spend simulator
+//
+// Checks every input of a finalized transaction against the outputs it spends without
+// broadcasting it. Taproot script path spends are executed with the real spending transaction,
+// so signatures are checked against the tapscript sighash. Key path and P2WPKH signatures are
+// verified directly. P2WSH inputs are checked if they use one of the bridge's pay-to-pubkey
+// scripts, all other segwit v0 scripts are skipped and make the report invalid.
+//
+
+use core::fmt;
+
+use bitcoin::{
+    opcodes::all::{OP_CHECKSIG, OP_CSV, OP_DROP},
+    relative,
+    script::Instruction,
+    secp256k1::{Message, Secp256k1, VerifyOnly},
+    sighash::{Prevouts, SighashCache},
+    taproot::{ControlBlock, TAPROOT_ANNEX_PREFIX},
+    PublicKey, Script, ScriptBuf, Transaction, TxOut, XOnlyPublicKey,
+};
+
+use crate::execution_env::ExecutionEnv;
+
+use super::{base::BaseTransaction, pre_signed::PreSignedTransaction};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputKind {
+    TaprootScriptPath,
+    TaprootKeyPath,
+    P2wpkh,
+    P2wsh,
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputVerdict {
+    Pass,
+    Fail(String),
+    Skipped(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct InputReport {
+    pub input_index: usize,
+    pub kind: InputKind,
+    pub verdict: InputVerdict,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpendReport {
+    pub inputs: Vec<InputReport>,
+}
+
+impl SpendReport {
+    /// Every input passed. A skipped input makes the report invalid, since nothing was checked
+    /// for it.
+    pub fn is_valid(&self) -> bool {
+        self.inputs
+            .iter()
+            .all(|input| input.verdict == InputVerdict::Pass)
+    }
+
+    pub fn failures(&self) -> Vec<&InputReport> {
+        self.inputs
+            .iter()
+            .filter(|input| matches!(input.verdict, InputVerdict::Fail(_)))
+            .collect()
+    }
+
+    pub fn skipped(&self) -> Vec<&InputReport> {
+        self.inputs
+            .iter()
+            .filter(|input| matches!(input.verdict, InputVerdict::Skipped(_)))
+            .collect()
+    }
+}
+
+impl fmt::Display for SpendReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for input in &self.inputs {
+            match &input.verdict {
+                InputVerdict::Pass => {
+                    writeln!(f, "input {} ({:?}): pass", input.input_index, input.kind)?
+                }
+                InputVerdict::Fail(reason) => writeln!(
+                    f,
+                    "input {} ({:?}): FAIL {}",
+                    input.input_index, input.kind, reason
+                )?,
+                InputVerdict::Skipped(reason) => writeln!(
+                    f,
+                    "input {} ({:?}): skipped, {}",
+                    input.input_index, input.kind, reason
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finalizes `tx` and checks every input against `prev_outs()` and `prev_scripts()`.
+pub fn simulate_spend<T: PreSignedTransaction + BaseTransaction>(tx: &T) -> SpendReport {
+    simulate_transaction(&tx.finalize(), tx.prev_outs(), tx.prev_scripts())
+}
+
+/// Checks every input of `tx` against the outputs it spends. `prev_scripts` holds the leaf
+/// script of taproot inputs and the witness script of P2WSH inputs and may be empty.
+pub fn simulate_transaction(
+    tx: &Transaction,
+    prev_outs: &[TxOut],
+    prev_scripts: &[ScriptBuf],
+) -> SpendReport {
+    assert_eq!(
+        tx.input.len(),
+        prev_outs.len(),
+        "expected one prevout per input"
+    );
+
+    let secp = Secp256k1::verification_only();
+    let inputs = (0..tx.input.len())
+        .map(|input_index| {
+            let prev_out = &prev_outs[input_index];
+            let kind = input_kind(tx, input_index, prev_out);
+            let verdict = match kind {
+                InputKind::TaprootScriptPath => simulate_taproot_script_path(
+                    &secp,
+                    tx,
+                    prev_outs,
+                    prev_scripts.get(input_index),
+                    input_index,
+                ),
+                InputKind::TaprootKeyPath => {
+                    simulate_taproot_key_path(&secp, tx, prev_outs, input_index)
+                }
+                InputKind::P2wpkh => simulate_p2wpkh(&secp, tx, prev_out, input_index),
+                InputKind::P2wsh => simulate_p2wsh(&secp, tx, prev_out, input_index),
+                InputKind::Unknown => InputVerdict::Skipped(format!(
+                    "unsupported script pubkey {}",
+                    prev_out.script_pubkey
+                )),
+            };
+            InputReport { input_index, kind, verdict }
+        })
+        .collect();
+
+    SpendReport { inputs }
+}
+
+fn input_kind(tx: &Transaction, input_index: usize, prev_out: &TxOut) -> InputKind {
+    let script_pubkey = &prev_out.script_pubkey;
+    if script_pubkey.is_p2tr() {
+        if taproot_witness(tx, input_index).0.len() >= 2 {
+            InputKind::TaprootScriptPath
+        } else {
+            InputKind::TaprootKeyPath
+        }
+    } else if script_pubkey.is_p2wpkh() {
+        InputKind::P2wpkh
+    } else if script_pubkey.is_p2wsh() {
+        InputKind::P2wsh
+    } else {
+        InputKind::Unknown
+    }
+}
+
+// Splits off the annex of a taproot witness.
+fn taproot_witness(tx: &Transaction, input_index: usize) -> (Vec<&[u8]>, Option<&[u8]>) {
+    let mut witness: Vec<&[u8]> = tx.input[input_index].witness.iter().collect();
+    let annex = match witness.last() {
+        Some(item) if witness.len() >= 2 && item.first() == Some(&TAPROOT_ANNEX_PREFIX) => {
+            witness.pop()
+        }
+        _ => None,
+    };
+    (witness, annex)
+}
+
+fn output_key(prev_out: &TxOut) -> Result<XOnlyPublicKey, String> {
+    XOnlyPublicKey::from_slice(&prev_out.script_pubkey.as_bytes()[2..34])
+        .map_err(|error| format!("invalid output key: {}", error))
+}
+
+fn simulate_taproot_script_path(
+    secp: &Secp256k1<VerifyOnly>,
+    tx: &Transaction,
+    prev_outs: &[TxOut],
+    expected_script: Option<&ScriptBuf>,
+    input_index: usize,
+) -> InputVerdict {
+    let (witness, _) = taproot_witness(tx, input_index);
+    let leaf_script = Script::from_bytes(witness[witness.len() - 2]);
+    let control_block = match ControlBlock::decode(witness[witness.len() - 1]) {
+        Ok(control_block) => control_block,
+        Err(error) => return InputVerdict::Fail(format!("invalid control block: {}", error)),
+    };
+    let output_key = match output_key(&prev_outs[input_index]) {
+        Ok(output_key) => output_key,
+        Err(error) => return InputVerdict::Fail(error),
+    };
+    if !control_block.verify_taproot_commitment(secp, output_key, leaf_script) {
+        return InputVerdict::Fail("control block does not commit to the leaf script".to_string());
+    }
+    if let Some(expected_script) = expected_script {
+        if expected_script.as_script() != leaf_script {
+            return InputVerdict::Fail("witness spends a different leaf than expected".to_string());
+        }
+    }
+
+    let (env, leaf_script) = ExecutionEnv::from_script_path_spend(tx, prev_outs, input_index);
+    let exec_result = env.execute(leaf_script);
+    if exec_result.success {
+        InputVerdict::Pass
+    } else {
+        InputVerdict::Fail(format!(
+            "leaf script failed with {:?} at {:?}",
+            exec_result.error, exec_result.last_opcode
+        ))
+    }
+}
+
+fn simulate_taproot_key_path(
+    secp: &Secp256k1<VerifyOnly>,
+    tx: &Transaction,
+    prev_outs: &[TxOut],
+    input_index: usize,
+) -> InputVerdict {
+    let (witness, _) = taproot_witness(tx, input_index);
+    let Some(signature) = witness.first() else {
+        return InputVerdict::Fail("missing signature".to_string());
+    };
+    let signature = match bitcoin::taproot::Signature::from_slice(signature) {
+        Ok(signature) => signature,
+        Err(error) => return InputVerdict::Fail(format!("invalid signature: {}", error)),
+    };
+    let output_key = match output_key(&prev_outs[input_index]) {
+        Ok(output_key) => output_key,
+        Err(error) => return InputVerdict::Fail(error),
+    };
+    let sighash = match SighashCache::new(tx).taproot_key_spend_signature_hash(
+        input_index,
+        &Prevouts::All(prev_outs),
+        signature.sighash_type,
+    ) {
+        Ok(sighash) => sighash,
+        Err(error) => return InputVerdict::Fail(format!("sighash: {}", error)),
+    };
+    match secp.verify_schnorr(&signature.signature, &Message::from(sighash), &output_key) {
+        Ok(()) => InputVerdict::Pass,
+        Err(error) => InputVerdict::Fail(format!("signature: {}", error)),
+    }
+}
+
+fn simulate_p2wpkh(
+    secp: &Secp256k1<VerifyOnly>,
+    tx: &Transaction,
+    prev_out: &TxOut,
+    input_index: usize,
+) -> InputVerdict {
+    let witness: Vec<&[u8]> = tx.input[input_index].witness.iter().collect();
+    if witness.len() != 2 {
+        return InputVerdict::Fail(format!("expected 2 witness items, got {}", witness.len()));
+    }
+    let public_key = match PublicKey::from_slice(witness[1]) {
+        Ok(public_key) => public_key,
+        Err(error) => return InputVerdict::Fail(format!("invalid public key: {}", error)),
+    };
+    match public_key.wpubkey_hash() {
+        Ok(hash) if ScriptBuf::new_p2wpkh(&hash) == prev_out.script_pubkey => {}
+        _ => return InputVerdict::Fail("public key does not match the prevout".to_string()),
+    }
+    verify_ecdsa(
+        secp,
+        tx,
+        input_index,
+        &prev_out.script_pubkey,
+        prev_out,
+        witness[0],
+        &public_key,
+        true,
+    )
+}
+
+fn simulate_p2wsh(
+    secp: &Secp256k1<VerifyOnly>,
+    tx: &Transaction,
+    prev_out: &TxOut,
+    input_index: usize,
+) -> InputVerdict {
+    let witness: Vec<&[u8]> = tx.input[input_index].witness.iter().collect();
+    let Some(witness_script) = witness.last().map(|script| Script::from_bytes(script)) else {
+        return InputVerdict::Fail("missing witness script".to_string());
+    };
+    if ScriptBuf::new_p2wsh(&witness_script.wscript_hash()) != prev_out.script_pubkey {
+        return InputVerdict::Fail("witness script does not match the prevout".to_string());
+    }
+
+    let Some((csv, public_key)) = parse_pay_to_pubkey_script(witness_script) else {
+        return InputVerdict::Skipped("segwit v0 scripts are not executed".to_string());
+    };
+    if let Some(num_blocks) = csv {
+        let sequence = tx.input[input_index].sequence;
+        let satisfied = match sequence.to_relative_lock_time() {
+            Some(relative::LockTime::Blocks(height)) => height.value() as u32 >= num_blocks,
+            _ => false,
+        };
+        if tx.version.0 < 2 || !satisfied {
+            return InputVerdict::Fail(format!(
+                "sequence {} does not satisfy the timelock of {} blocks",
+                sequence, num_blocks
+            ));
+        }
+    }
+    if witness.len() != 2 {
+        return InputVerdict::Fail(format!("expected 2 witness items, got {}", witness.len()));
+    }
+    verify_ecdsa(
+        secp,
+        tx,
+        input_index,
+        witness_script,
+        prev_out,
+        witness[0],
+        &public_key,
+        false,
+    )
+}
+
+// Recognizes `<pubkey> OP_CHECKSIG` with an optional `<n> OP_CSV OP_DROP` prefix.
+fn parse_pay_to_pubkey_script(script: &Script) -> Option<(Option<u32>, PublicKey)> {
+    let instructions: Vec<Instruction> = script.instructions().collect::<Result<_, _>>().ok()?;
+    let (csv, rest) = match instructions.as_slice() {
+        [timelock, Instruction::Op(OP_CSV), Instruction::Op(OP_DROP), rest @ ..] => {
+            let num_blocks = match timelock {
+                Instruction::PushBytes(bytes) => {
+                    bitcoin::script::read_scriptint(bytes.as_bytes()).ok()?
+                }
+                Instruction::Op(op) => op.decode_pushnum()? as i64,
+            };
+            (Some(u32::try_from(num_blocks).ok()?), rest)
+        }
+        rest => (None, rest),
+    };
+    match rest {
+        [Instruction::PushBytes(public_key), Instruction::Op(OP_CHECKSIG)] => {
+            Some((csv, PublicKey::from_slice(public_key.as_bytes()).ok()?))
+        }
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_ecdsa(
+    secp: &Secp256k1<VerifyOnly>,
+    tx: &Transaction,
+    input_index: usize,
+    script: &Script,
+    prev_out: &TxOut,
+    signature: &[u8],
+    public_key: &PublicKey,
+    is_p2wpkh: bool,
+) -> InputVerdict {
+    let signature = match bitcoin::ecdsa::Signature::from_slice(signature) {
+        Ok(signature) => signature,
+        Err(error) => return InputVerdict::Fail(format!("invalid signature: {}", error)),
+    };
+    let mut sighash_cache = SighashCache::new(tx);
+    let sighash = if is_p2wpkh {
+        sighash_cache.p2wpkh_signature_hash(
+            input_index,
+            script,
+            prev_out.value,
+            signature.sighash_type,
+        )
+    } else {
+        sighash_cache.p2wsh_signature_hash(
+            input_index,
+            script,
+            prev_out.value,
+            signature.sighash_type,
+        )
+    };
+    let sighash = match sighash {
+        Ok(sighash) => sighash,
+        Err(error) => return InputVerdict::Fail(format!("sighash: {}", error)),
+    };
+    match secp.verify_ecdsa(&Message::from(sighash), &signature.signature, &public_key.inner) {
+        Ok(()) => InputVerdict::Pass,
+        Err(error) => InputVerdict::Fail(format!("signature: {}", error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute::LockTime,
+        key::{Keypair, TapTweak},
+        opcodes::all::OP_RETURN,
+        secp256k1::SecretKey,
+        transaction::Version,
+        Amount, OutPoint, Sequence, TapSighashType, TxIn, Witness,
+    };
+
+    use super::*;
+
+    fn spend(prev_out: &TxOut, sequence: Sequence) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: prev_out.value - Amount::from_sat(1000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_taproot_key_path() {
+        let secp = Secp256k1::new();
+        let keypair = Keypair::from_secret_key(&secp, &SecretKey::from_slice(&[7; 32]).unwrap());
+        let (internal_key, _) = keypair.x_only_public_key();
+        let prev_out = TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, internal_key, None),
+        };
+        let mut tx = spend(&prev_out, Sequence::MAX);
+
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&[prev_out.clone()]),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        let tweaked = keypair.tap_tweak(&secp, None).to_inner();
+        let signature = bitcoin::taproot::Signature {
+            signature: secp.sign_schnorr(&Message::from(sighash), &tweaked),
+            sighash_type: TapSighashType::Default,
+        };
+        tx.input[0].witness.push(signature.to_vec());
+
+        let report = simulate_transaction(&tx, &[prev_out.clone()], &[]);
+        assert_eq!(report.inputs[0].kind, InputKind::TaprootKeyPath);
+        assert!(report.is_valid(), "{}", report);
+
+        // Any change to the transaction invalidates the signature.
+        tx.output[0].value -= Amount::from_sat(1);
+        let report = simulate_transaction(&tx, &[prev_out], &[]);
+        assert_eq!(report.failures().len(), 1);
+    }
+
+    #[test]
+    fn test_skipped_input_is_invalid() {
+        let prev_out = TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: ScriptBuf::from_bytes(vec![OP_RETURN.to_u8()]),
+        };
+        let tx = spend(&prev_out, Sequence::MAX);
+
+        let report = simulate_transaction(&tx, &[prev_out], &[]);
+        assert_eq!(report.inputs[0].kind, InputKind::Unknown);
+        assert_eq!(report.skipped().len(), 1);
+        assert!(report.failures().is_empty());
+        assert!(!report.is_valid(), "{}", report);
+    }
+
+    #[test]
+    fn test_p2wsh_timelock() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[9; 32]).unwrap();
+        let public_key = PublicKey::new(secret_key.public_key(&secp));
+        let witness_script =
+            crate::bridge::scripts::generate_timelock_script(&public_key, 10);
+        let prev_out = TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: ScriptBuf::new_p2wsh(&witness_script.wscript_hash()),
+        };
+
+        for (blocks, valid) in [(10, true), (9, false)] {
+            let mut tx = spend(&prev_out, Sequence::from_height(blocks));
+            let sighash = SighashCache::new(&tx)
+                .p2wsh_signature_hash(
+                    0,
+                    &witness_script,
+                    prev_out.value,
+                    bitcoin::EcdsaSighashType::All,
+                )
+                .unwrap();
+            let signature = bitcoin::ecdsa::Signature {
+                signature: secp.sign_ecdsa(&Message::from(sighash), &secret_key),
+                sighash_type: bitcoin::EcdsaSighashType::All,
+            };
+            tx.input[0].witness.push(signature.to_vec());
+            tx.input[0].witness.push(witness_script.to_bytes());
+
+            let report =
+                simulate_transaction(&tx, &[prev_out.clone()], &[witness_script.clone()]);
+            assert_eq!(report.inputs[0].kind, InputKind::P2wsh);
+            assert_eq!(report.is_valid(), valid, "{}", report);
+        }
+    }
+}
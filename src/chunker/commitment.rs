@@ -0,0 +1,383 @@
+//
+// Signed stack hand-off between chunks
+//
+// At the end of a chunk the operator commits to the chunk's output stack and altstack by
+// signing a single 20-byte blake3 digest of all their elements with a one-time Winternitz key.
+// The next chunk starts with a prefix that verifies the signature, hashes the elements given in
+// the witness and compares the result with the signed digest. Every element is a single witness
+// item, so a boundary of n elements costs n items plus one signature.
+//
+// The elements are hashed in blocks of `MAX_BLOCK_ELEMENTS` as 4 little-endian bytes each, so
+// only non-negative elements below 2³¹ can be handed over, which covers the limb based gadgets
+// of this crate. The first block ends with the number of elements, every further block with the
+// digest of the previous one:
+//
+//   H₀ = blake3(block₀ ‖ n)    Hₖ = blake3(blockₖ ‖ Hₖ₋₁)
+//
+
+use core::fmt;
+
+use crate::hash::blake3::blake3_160_var_length;
+use crate::signatures::winternitz::{checksig_verify, generate_public_key, sign, PublicKey};
+use crate::treepp::*;
+
+/// Number of elements hashed with one call of `blake3_160_var_length`. Together with the
+/// digest of the previous block this keeps the message well within its 512 byte limit.
+pub const MAX_BLOCK_ELEMENTS: usize = 64;
+
+/// Number of stack items of a `winternitz` signature
+const SIGNATURE_LEN: usize = 2 * crate::signatures::winternitz::N as usize;
+
+/// Length of the signed digest
+const DIGEST_LEN: usize = 20;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitmentError {
+    /// A stack item that isn't a number in [0, 2³¹), e.g. a hash output.
+    UnsupportedElement(Vec<u8>),
+}
+
+impl fmt::Display for CommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitmentError::UnsupportedElement(item) => write!(
+                f,
+                "only non-negative elements below 2³¹ can be committed, got {:?}",
+                item
+            ),
+        }
+    }
+}
+
+/// One-time Winternitz key for the hand-off after chunk `chunk_index`.
+#[derive(Clone, Debug)]
+pub struct BoundaryKeys {
+    seed: String,
+    chunk_index: usize,
+}
+
+impl BoundaryKeys {
+    pub fn new(seed: &str, chunk_index: usize) -> Self {
+        Self { seed: seed.to_string(), chunk_index }
+    }
+
+    fn secret_key(&self) -> String {
+        let preimage = format!("{}/{}", self.seed, self.chunk_index);
+        hex::encode(&blake3::hash(preimage.as_bytes()).as_bytes()[0..20])
+    }
+
+    pub fn public_key(&self) -> PublicKey { generate_public_key(&self.secret_key()) }
+}
+
+/// The signed stack and altstack at a chunk boundary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackCommitment {
+    pub stack_len: usize,
+    pub altstack_len: usize,
+    // Minimally encoded witness items, bottom first
+    witness: Vec<Vec<u8>>,
+}
+
+impl StackCommitment {
+    /// Signs the given stack and altstack, both bottom first.
+    pub fn new(
+        keys: &BoundaryKeys,
+        stack: &[Vec<u8>],
+        altstack: &[Vec<u8>],
+    ) -> Result<Self, CommitmentError> {
+        // The altstack is rebuilt first from its bottom, so its bottom element ends on top of
+        // the witness and the stack below it.
+        let elements = altstack
+            .iter()
+            .chain(stack.iter().rev())
+            .map(|item| decode_element(item))
+            .collect::<Result<Vec<u32>, _>>()?;
+
+        let mut witness: Vec<Vec<u8>> =
+            elements.iter().rev().map(|element| encode_script_num(*element)).collect();
+        for signature in sign(&keys.secret_key(), &boundary_digest(&elements)) {
+            witness.push(signature.hash_bytes);
+            witness.push(encode_script_num(signature.message_digit as u32));
+        }
+
+        Ok(Self { stack_len: stack.len(), altstack_len: altstack.len(), witness })
+    }
+
+    /// The witness that unlocks [`verify_stack_commitment`], bottom first.
+    pub fn witness(&self) -> Vec<Vec<u8>> { self.witness.clone() }
+
+    /// Pushes the witness from within a script.
+    pub fn push(&self) -> Script {
+        script! {
+            for item in &self.witness {
+                if item.len() <= 4 {
+                    { script_num(item) }
+                } else {
+                    { item.clone() }
+                }
+            }
+        }
+    }
+}
+
+/// Verifies a [`StackCommitment`] and rebuilds the committed stack and altstack from it.
+///
+/// Input stack: the witness of the commitment, altstack empty
+/// Output stack: the committed stack and altstack
+pub fn verify_stack_commitment(
+    public_key: &PublicKey,
+    stack_len: usize,
+    altstack_len: usize,
+) -> Script {
+    let n = stack_len + altstack_len;
+    script! {
+        // ⋯ elements signature
+        { checksig_verify(public_key) }
+        for _ in 0..DIGEST_LEN {
+            OP_TOALTSTACK
+        }
+        // ⋯ elements | digest
+
+        for (block_index, (start, end)) in block_ranges(n).into_iter().enumerate() {
+            // The first block ends with the number of elements, the others with the digest of
+            // the previous block, which is on top of the elements.
+            if block_index == 0 {
+                for byte in (n as u32).to_le_bytes().iter().rev() {
+                    { *byte }
+                }
+            }
+            for (pushed, i) in (start..end).rev().enumerate() {
+                { i + chain_len(block_index) + 4 * pushed } OP_PICK
+                { element_to_le_bytes() }
+            }
+            { blake3_160_var_length(4 * (end - start) + chain_len(block_index)) }
+        }
+
+        // Compare the digest with the signed one
+        for _ in 0..DIGEST_LEN / 4 {
+            for j in 0..4 {
+                { 3 - j }
+                OP_ROLL
+                OP_FROMALTSTACK
+                OP_EQUALVERIFY
+            }
+        }
+
+        // The committed altstack is on top of the stack
+        for _ in 0..altstack_len {
+            OP_TOALTSTACK
+        }
+    }
+}
+
+/// Number of witness items of a commitment to `n` elements.
+pub fn witness_len(n: usize) -> usize { n + SIGNATURE_LEN }
+
+/// Element ranges of the hashed blocks. An empty boundary still hashes its length.
+fn block_ranges(n: usize) -> Vec<(usize, usize)> {
+    (0..n.max(1))
+        .step_by(MAX_BLOCK_ELEMENTS)
+        .map(|start| (start, n.min(start + MAX_BLOCK_ELEMENTS)))
+        .collect()
+}
+
+/// Number of bytes hashed after the elements of a block.
+fn chain_len(block_index: usize) -> usize { if block_index == 0 { 4 } else { DIGEST_LEN } }
+
+/// The digest computed by [`verify_stack_commitment`], `elements` in the order they are hashed.
+fn boundary_digest(elements: &[u32]) -> [u8; DIGEST_LEN] {
+    let mut digest: Option<[u8; DIGEST_LEN]> = None;
+    for (start, end) in block_ranges(elements.len()) {
+        let mut message: Vec<u8> =
+            elements[start..end].iter().flat_map(|element| element.to_le_bytes()).collect();
+        match digest {
+            None => message.extend((elements.len() as u32).to_le_bytes()),
+            // `blake3_160_var_length` leaves the last word of the digest on top of the stack
+            Some(previous) => message.extend(previous.chunks(4).rev().flatten()),
+        }
+        let mut block_digest = [0; DIGEST_LEN];
+        block_digest.copy_from_slice(&blake3::hash(&message).as_bytes()[0..DIGEST_LEN]);
+        digest = Some(block_digest);
+    }
+    digest.unwrap_or_else(|| unreachable!())
+}
+
+/// Splits an element into its bytes.
+///
+/// Input Stack: ⋯ x with 0 ≤ x < 2³¹
+/// Output Stack: ⋯ B₃ B₂ B₁ B₀ with x = B₃⋅2²⁴ + B₂⋅2¹⁶ + B₁⋅2⁸ + B₀
+fn element_to_le_bytes() -> Script {
+    script! {
+        OP_DUP 0 OP_GREATERTHANOREQUAL OP_VERIFY
+        for byte in (1..4).rev() {
+            // ⋯ x
+            0 OP_SWAP
+            // ⋯ Bᵢ x
+            for bit in (0..8).rev() {
+                // Bit 31 is zero for non-negative elements
+                if 8 * byte + bit < 31 {
+                    OP_DUP { 1 << (8 * byte + bit) } OP_GREATERTHANOREQUAL
+                    OP_IF
+                        { 1 << (8 * byte + bit) } OP_SUB
+                        OP_SWAP { 1 << bit } OP_ADD OP_SWAP
+                    OP_ENDIF
+                }
+            }
+        }
+        // ⋯ B₃ B₂ B₁ B₀
+    }
+}
+
+fn decode_element(item: &[u8]) -> Result<u32, CommitmentError> {
+    if item.len() > 4 || item.last().map_or(false, |byte| byte & 0x80 != 0) {
+        return Err(CommitmentError::UnsupportedElement(item.to_vec()));
+    }
+    Ok(script_num(item))
+}
+
+// Little-endian magnitude of a non-negative number of at most 4 bytes
+fn script_num(item: &[u8]) -> u32 {
+    item.iter().rev().fold(0, |acc, byte| (acc << 8) | *byte as u32)
+}
+
+fn encode_script_num(value: u32) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes().to_vec();
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    if bytes.last().map_or(false, |byte| byte & 0x80 != 0) {
+        bytes.push(0);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn items(values: &[u32]) -> Vec<Vec<u8>> {
+        values.iter().map(|value| encode_script_num(*value)).collect()
+    }
+
+    fn verify(
+        commitment: &StackCommitment,
+        public_key: &PublicKey,
+        stack: &[u32],
+        altstack: &[u32],
+    ) -> bool {
+        execute_script(script! {
+            { commitment.push() }
+            { verify_stack_commitment(public_key, stack.len(), altstack.len()) }
+            for value in stack.iter().rev() {
+                { *value } OP_EQUALVERIFY
+            }
+            for value in altstack.iter().rev() {
+                OP_FROMALTSTACK { *value } OP_EQUALVERIFY
+            }
+            OP_TRUE
+        })
+        .success
+    }
+
+    #[test]
+    fn test_script_num_encoding() {
+        for value in [0, 1, 0x7f, 0x80, 0xff, 0x100, 0x1fffffff, 0x7fffffff] {
+            assert_eq!(decode_element(&encode_script_num(value)), Ok(value));
+        }
+        assert_eq!(encode_script_num(0), Vec::<u8>::new());
+        assert_eq!(encode_script_num(0x80), vec![0x80, 0x00]);
+    }
+
+    #[test]
+    fn test_element_to_le_bytes() {
+        for value in [0u32, 1, 0xff, 0x100, 0x12345678, 0x7fffffff] {
+            run(script! {
+                { value }
+                { element_to_le_bytes() }
+                for byte in value.to_le_bytes() {
+                    { byte } OP_EQUALVERIFY
+                }
+                OP_TRUE
+            });
+        }
+        // Negative elements are rejected
+        let exec_result = execute_script(script! {
+            { -1 }
+            { element_to_le_bytes() }
+            OP_2DROP OP_2DROP
+            OP_TRUE
+        });
+        assert!(!exec_result.success);
+    }
+
+    #[test]
+    fn test_block_ranges() {
+        assert_eq!(block_ranges(0), vec![(0, 0)]);
+        assert_eq!(block_ranges(3), vec![(0, 3)]);
+        assert_eq!(
+            block_ranges(MAX_BLOCK_ELEMENTS + 1),
+            vec![(0, MAX_BLOCK_ELEMENTS), (MAX_BLOCK_ELEMENTS, MAX_BLOCK_ELEMENTS + 1)]
+        );
+    }
+
+    #[test]
+    fn test_stack_commitment_roundtrip() {
+        let keys = BoundaryKeys::new("test", 0);
+        let stack = [0x1fffffff, 0, 7, 0x80, 0x12345678];
+        let altstack = [42, 0xff];
+        let commitment = StackCommitment::new(&keys, &items(&stack), &items(&altstack)).unwrap();
+        assert_eq!(commitment.witness().len(), witness_len(stack.len() + altstack.len()));
+        assert!(verify(&commitment, &keys.public_key(), &stack, &altstack));
+    }
+
+    #[test]
+    fn test_stack_commitment_several_blocks() {
+        let keys = BoundaryKeys::new("test", 0);
+        let stack: Vec<u32> = (0..2 * MAX_BLOCK_ELEMENTS as u32 + 3)
+            .map(|i| i.wrapping_mul(0x9e3779b9) & 0x1fffffff)
+            .collect();
+        let altstack = [1, 2, 3];
+        let commitment = StackCommitment::new(&keys, &items(&stack), &items(&altstack)).unwrap();
+        assert!(verify(&commitment, &keys.public_key(), &stack, &altstack));
+    }
+
+    #[test]
+    fn test_stack_commitment_empty() {
+        let keys = BoundaryKeys::new("test", 0);
+        let commitment = StackCommitment::new(&keys, &[], &[]).unwrap();
+        assert!(verify(&commitment, &keys.public_key(), &[], &[]));
+    }
+
+    #[test]
+    fn test_stack_commitment_rejects_other_keys() {
+        let stack = [1, 2, 3];
+        let commitment =
+            StackCommitment::new(&BoundaryKeys::new("test", 0), &items(&stack), &[]).unwrap();
+        let public_key = BoundaryKeys::new("test", 1).public_key();
+        assert!(!verify(&commitment, &public_key, &stack, &[]));
+    }
+
+    #[test]
+    fn test_stack_commitment_rejects_other_elements() {
+        let keys = BoundaryKeys::new("test", 0);
+        let stack = [1, 2, 3];
+        let mut commitment = StackCommitment::new(&keys, &items(&stack), &[]).unwrap();
+        // The bottom element is the first witness item
+        commitment.witness[0] = encode_script_num(4);
+        assert!(!verify(&commitment, &keys.public_key(), &[4, 2, 3], &[]));
+    }
+
+    #[test]
+    fn test_stack_commitment_rejects_unsupported_elements() {
+        let keys = BoundaryKeys::new("test", 0);
+        assert_eq!(
+            StackCommitment::new(&keys, &[vec![0x81]], &[]),
+            Err(CommitmentError::UnsupportedElement(vec![0x81]))
+        );
+        let hash = blake3::hash(b"boundary").as_bytes()[0..20].to_vec();
+        assert_eq!(
+            StackCommitment::new(&keys, &[vec![1], hash.clone()], &[]),
+            Err(CommitmentError::UnsupportedElement(hash))
+        );
+    }
+}
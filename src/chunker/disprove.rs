@@ -0,0 +1,626 @@
+//
+// Disprove leaves for a chunked script
+//
+// The script is split into chunks f₁…fₙ and the operator commits to every intermediate
+// state zᵢ = fᵢ(zᵢ₋₁) with the signed hand-off of `commitment`. The leaf of chunk i verifies
+// the commitments to zᵢ₋₁ and zᵢ, recomputes fᵢ(zᵢ₋₁) and succeeds if the result differs
+// from the committed zᵢ or if one of the checks of fᵢ fails on zᵢ₋₁.
+//
+
+use bitcoin::opcodes::all::*;
+use bitcoin::script::Instruction;
+use bitcoin::{Opcode, ScriptBuf};
+use bitcoin_scriptexec::Stack;
+
+use super::commitment::{
+    verify_stack_commitment, witness_len, BoundaryKeys, CommitmentError, StackCommitment,
+};
+use crate::execution_env::ExecutionEnv;
+use crate::signatures::winternitz::PublicKey;
+use crate::treepp::*;
+
+/// The stack and altstack after a chunk, both bottom first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundaryStack {
+    pub stack: Vec<Vec<u8>>,
+    pub altstack: Vec<Vec<u8>>,
+}
+
+pub struct DisproveLeaves {
+    seed: String,
+    chunks: Vec<ScriptBuf>,
+    // The honest intermediate states z₁…zₙ
+    boundaries: Vec<BoundaryStack>,
+}
+
+impl DisproveLeaves {
+    /// Splits `script` into chunks and executes them to learn the shape of every intermediate
+    /// state. The commitment keys are derived from `seed`.
+    pub fn new(script: Script, target_chunk_size: usize, tolerance: usize, seed: &str) -> Self {
+        let (_, chunks) = script.compile_to_chunks(target_chunk_size, tolerance);
+        assert!(chunks.len() > 0, "No chunks to execute");
+
+        // TODO: Remove this when we are sure we are in script size limit for groth16
+        let env = ExecutionEnv::new().without_stack_limit();
+
+        let mut boundaries = vec![];
+        let mut next_stack = Stack::new();
+        let mut next_altstack = Stack::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut exec =
+                env.exec_with_stack(chunk.clone(), next_stack.clone(), next_altstack.clone());
+            while exec.exec_next().is_ok() {}
+            assert!(
+                exec.result().unwrap().error.is_none(),
+                "Chunk {} fails on the honest input: {:?}",
+                i,
+                exec.result().unwrap().error
+            );
+
+            next_stack = exec.stack().clone();
+            next_altstack = exec.altstack().clone();
+            boundaries.push(BoundaryStack {
+                stack: next_stack.iter_str().collect(),
+                altstack: next_altstack.iter_str().collect(),
+            });
+        }
+
+        Self { seed: seed.to_string(), chunks, boundaries }
+    }
+
+    pub fn len(&self) -> usize { self.chunks.len() }
+
+    /// The honest state after chunk `index`.
+    pub fn boundary(&self, index: usize) -> &BoundaryStack { &self.boundaries[index] }
+
+    pub fn keys(&self, index: usize) -> BoundaryKeys { BoundaryKeys::new(&self.seed, index) }
+
+    /// The operator's commitment to the honest state after chunk `index`.
+    pub fn commitment(&self, index: usize) -> Result<StackCommitment, CommitmentError> {
+        let boundary = &self.boundaries[index];
+        StackCommitment::new(&self.keys(index), &boundary.stack, &boundary.altstack)
+    }
+
+    /// The leaf of chunk `index`.
+    ///
+    /// Input stack: commitment to zᵢ, commitment to zᵢ₋₁ (omitted for the first chunk)
+    /// Succeeds iff fᵢ fails on zᵢ₋₁ or fᵢ(zᵢ₋₁) != zᵢ
+    pub fn lock_script(&self, index: usize) -> ScriptBuf {
+        let mut lock = vec![];
+        let mut input_len = 0;
+        if index > 0 {
+            let input = &self.boundaries[index - 1];
+            input_len = input.stack.len() + input.altstack.len();
+            let public_key = self.keys(index - 1).public_key();
+            lock.extend(
+                verify_stack_commitment(&public_key, input.stack.len(), input.altstack.len())
+                    .compile()
+                    .into_bytes(),
+            );
+        }
+
+        let output = &self.boundaries[index];
+        let public_key = self.keys(index).public_key();
+        let differs = verify_output_differs(&public_key, output.stack.len(), output.altstack.len())
+            .compile()
+            .into_bytes();
+        let nodes = parse_chunk(&self.chunks[index]);
+        match flag_failures(&nodes) {
+            // A failed check of the chunk is a disprove on its own
+            Some(chunk) => {
+                // Below the state of the chunk lies the commitment to its output. The input
+                // altstack may have been moved to the stack as well.
+                let max_depth = witness_len(output.stack.len() + output.altstack.len())
+                    + input_len
+                    + max_growth(&nodes).max(0) as usize;
+                lock.extend(chunk);
+                lock.push(OP_IF.to_u8());
+                lock.extend(script! { { clear_stack(max_depth) } OP_TRUE }.compile().into_bytes());
+                lock.push(OP_ELSE.to_u8());
+                lock.extend(differs);
+                lock.push(OP_ENDIF.to_u8());
+            }
+            None => {
+                lock.extend_from_slice(self.chunks[index].as_bytes());
+                lock.extend(differs);
+            }
+        }
+        ScriptBuf::from_bytes(lock)
+    }
+
+    pub fn lock_scripts(&self) -> Vec<ScriptBuf> {
+        (0..self.len()).map(|index| self.lock_script(index)).collect()
+    }
+
+    /// The witness to spend the leaf of chunk `index` with the operator's commitments to its
+    /// input and output state, bottom first. `input` is ignored for the first chunk.
+    pub fn unlock_witness(
+        &self,
+        index: usize,
+        input: Option<&StackCommitment>,
+        output: &StackCommitment,
+    ) -> Vec<Vec<u8>> {
+        let mut witness = output.witness();
+        if index > 0 {
+            witness.extend(input.expect("missing commitment to the chunk input").witness());
+        }
+        witness
+    }
+}
+
+// A chunk as a tree of its branches
+enum Node {
+    // An opcode or push that never aborts on its own
+    Op(Vec<u8>),
+    // The non-aborting variant of a verifying opcode, which leaves the result of the check
+    Check(Opcode),
+    Branch { opcode: Opcode, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+// The non-aborting variant of a verifying opcode
+fn check_of(opcode: Opcode) -> Option<Opcode> {
+    if opcode == OP_VERIFY {
+        Some(OP_0NOTEQUAL)
+    } else if opcode == OP_EQUALVERIFY {
+        Some(OP_EQUAL)
+    } else if opcode == OP_NUMEQUALVERIFY {
+        Some(OP_NUMEQUAL)
+    } else if opcode == OP_CHECKSIGVERIFY {
+        Some(OP_CHECKSIG)
+    } else if opcode == OP_RETURN {
+        Some(OP_PUSHBYTES_0)
+    } else {
+        None
+    }
+}
+
+// Parses the instructions up to the next OP_ELSE or OP_ENDIF, which is returned as well
+fn parse_block(
+    instructions: &mut impl Iterator<Item = (Option<Opcode>, Vec<u8>)>,
+) -> (Vec<Node>, Option<Opcode>) {
+    let mut nodes = vec![];
+    while let Some((opcode, bytes)) = instructions.next() {
+        match opcode {
+            Some(opcode) if opcode == OP_IF || opcode == OP_NOTIF => {
+                let (then, end) = parse_block(instructions);
+                let otherwise = match end {
+                    Some(end) if end == OP_ELSE => {
+                        let (otherwise, end) = parse_block(instructions);
+                        assert!(end == Some(OP_ENDIF), "Unbalanced OP_ELSE in chunk");
+                        otherwise
+                    }
+                    Some(end) if end == OP_ENDIF => vec![],
+                    _ => panic!("Unbalanced {} in chunk", opcode),
+                };
+                nodes.push(Node::Branch { opcode, then, otherwise });
+            }
+            Some(opcode) if opcode == OP_ELSE || opcode == OP_ENDIF => {
+                return (nodes, Some(opcode))
+            }
+            Some(opcode) if check_of(opcode).is_some() => {
+                nodes.push(Node::Check(check_of(opcode).unwrap()))
+            }
+            _ => nodes.push(Node::Op(bytes)),
+        }
+    }
+    (nodes, None)
+}
+
+fn has_check(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Op(_) => false,
+        Node::Check(_) => true,
+        Node::Branch { then, otherwise, .. } => has_check(then) || has_check(otherwise),
+    })
+}
+
+fn emit_verbatim(nodes: &[Node], out: &mut Vec<u8>) {
+    for node in nodes {
+        match node {
+            Node::Op(bytes) => out.extend_from_slice(bytes),
+            Node::Check(_) => unreachable!("checks are flagged"),
+            Node::Branch { opcode, then, otherwise } => {
+                out.push(opcode.to_u8());
+                emit_verbatim(then, out);
+                if !otherwise.is_empty() {
+                    out.push(OP_ELSE.to_u8());
+                    emit_verbatim(otherwise, out);
+                }
+                out.push(OP_ENDIF.to_u8());
+            }
+        }
+    }
+}
+
+// Every check opens an OP_IF that continues with the rest of the block if the check passes.
+// The failing side of all of them is closed at the end of the block, so the block leaves 0
+// on top if all of its checks pass and 1 otherwise.
+fn emit_flagged(nodes: &[Node], out: &mut Vec<u8>) {
+    let mut opened = 0;
+    for node in nodes {
+        match node {
+            Node::Op(bytes) => out.extend_from_slice(bytes),
+            Node::Check(opcode) => {
+                out.push(opcode.to_u8());
+                out.push(OP_IF.to_u8());
+                opened += 1;
+            }
+            Node::Branch { then, otherwise, .. } if !has_check(then) && !has_check(otherwise) => {
+                emit_verbatim(std::slice::from_ref(node), out)
+            }
+            Node::Branch { opcode, then, otherwise } => {
+                out.push(opcode.to_u8());
+                emit_flagged(then, out);
+                out.push(OP_ELSE.to_u8());
+                emit_flagged(otherwise, out);
+                out.push(OP_ENDIF.to_u8());
+                out.push(OP_NOTIF.to_u8());
+                opened += 1;
+            }
+        }
+    }
+    out.push(OP_PUSHBYTES_0.to_u8());
+    for _ in 0..opened {
+        out.push(OP_ELSE.to_u8());
+        out.push(OP_PUSHNUM_1.to_u8());
+        out.push(OP_ENDIF.to_u8());
+    }
+}
+
+// The chunk as a tree, its branches have to be balanced
+fn parse_chunk(chunk: &ScriptBuf) -> Vec<Node> {
+    let bytes = chunk.as_bytes();
+    let indices: Vec<_> = chunk
+        .instruction_indices()
+        .collect::<Result<_, _>>()
+        .expect("Chunk is not a valid script");
+    let mut instructions = indices.iter().enumerate().map(|(i, (start, instruction))| {
+        let end = indices.get(i + 1).map_or(bytes.len(), |(next, _)| *next);
+        let opcode = match instruction {
+            Instruction::Op(opcode) => Some(*opcode),
+            Instruction::PushBytes(_) => None,
+        };
+        (opcode, bytes[*start..end].to_vec())
+    });
+    let (nodes, end) = parse_block(&mut instructions);
+    assert!(end.is_none(), "Unbalanced {} in chunk", end.unwrap());
+    nodes
+}
+
+/// Rewrites the chunk to leave 1 on top of its result instead of aborting on a failed
+/// `OP_VERIFY`, `OP_EQUALVERIFY`, `OP_NUMEQUALVERIFY`, `OP_CHECKSIGVERIFY` or `OP_RETURN`,
+/// and 0 if all of its checks pass. Returns `None` if the chunk has no checks.
+fn flag_failures(nodes: &[Node]) -> Option<Vec<u8>> {
+    if !has_check(nodes) {
+        return None;
+    }
+    let mut out = vec![];
+    emit_flagged(nodes, &mut out);
+    Some(out)
+}
+
+// The change of the stack depth by an opcode. Elements taken from the altstack count as new
+// ones, and OP_IFDUP as if it duplicated.
+fn depth_change(opcode: Opcode) -> isize {
+    match opcode {
+        OP_PUSHNUM_NEG1 | OP_FROMALTSTACK | OP_DEPTH | OP_DUP | OP_IFDUP | OP_OVER | OP_TUCK
+        | OP_SIZE => 1,
+        OP_2DUP | OP_2OVER => 2,
+        OP_3DUP => 3,
+        OP_IF | OP_NOTIF | OP_VERIFY | OP_TOALTSTACK | OP_DROP | OP_NIP | OP_ROLL | OP_EQUAL
+        | OP_ADD | OP_SUB | OP_BOOLAND | OP_BOOLOR | OP_NUMEQUAL | OP_NUMNOTEQUAL
+        | OP_LESSTHAN | OP_GREATERTHAN | OP_LESSTHANOREQUAL | OP_GREATERTHANOREQUAL | OP_MIN
+        | OP_MAX | OP_CHECKSIG | OP_CAT => -1,
+        OP_2DROP | OP_EQUALVERIFY | OP_NUMEQUALVERIFY | OP_CHECKSIGVERIFY | OP_WITHIN
+        | OP_CHECKSIGADD => -2,
+        // Pushes of the numbers 1 to 16
+        _ if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&opcode.to_u8()) => 1,
+        // Unary operators, hashes, OP_PICK and the ones that don't touch the stack
+        _ => 0,
+    }
+}
+
+// The largest growth of the stack depth over any path through the nodes, and the largest
+// change at their end.
+fn growth(nodes: &[Node]) -> (isize, isize) {
+    let (mut max, mut depth) = (0, 0);
+    for node in nodes {
+        match node {
+            Node::Op(bytes) => {
+                let opcode = Opcode::from(bytes[0]);
+                // Any pushed data
+                depth += if opcode.to_u8() <= OP_PUSHDATA4.to_u8() {
+                    1
+                } else {
+                    depth_change(opcode)
+                };
+            }
+            Node::Check(opcode) => depth += depth_change(*opcode),
+            Node::Branch { then, otherwise, .. } => {
+                let (then_max, then_end) = growth(then);
+                let (otherwise_max, otherwise_end) = growth(otherwise);
+                // The condition is dropped before either branch
+                max = max.max(depth - 1 + then_max.max(otherwise_max));
+                depth += -1 + then_end.max(otherwise_end);
+            }
+        }
+        max = max.max(depth);
+    }
+    (max, depth)
+}
+
+// The largest number of elements the chunk adds to the stack at any point
+fn max_growth(nodes: &[Node]) -> isize { growth(nodes).0 }
+
+// Drops everything from the main stack, which holds at most `max_depth` elements
+fn clear_stack(max_depth: usize) -> Script {
+    script! {
+        for _ in 0..max_depth / 2 {
+            OP_DEPTH OP_1 OP_GREATERTHAN
+            OP_IF OP_2DROP OP_ENDIF
+        }
+        OP_DEPTH
+        OP_IF OP_DROP OP_ENDIF
+    }
+}
+
+/// Verifies the commitment to the expected output below the computed output and succeeds
+/// iff they differ. Both sides are compared as numbers, so a non-minimal encoding of a
+/// committed value doesn't count as a different state.
+///
+/// Input stack: commitment, computed stack | computed altstack
+/// Output stack: 1 if the committed and the computed state differ, 0 otherwise
+fn verify_output_differs(public_key: &PublicKey, stack_len: usize, altstack_len: usize) -> Script {
+    let n = stack_len + altstack_len;
+    script! {
+        for _ in 0..stack_len {
+            OP_TOALTSTACK
+        }
+        // commitment | F_alt F_stack
+        { verify_stack_commitment(public_key, stack_len, altstack_len) }
+        // Z_stack | F_alt F_stack Z_alt
+        for _ in 0..altstack_len + stack_len + altstack_len {
+            OP_FROMALTSTACK
+        }
+        // Z_stack Z_alt F_stack F_alt
+
+        for k in (1..=n).rev() {
+            { k } OP_ROLL
+            OP_0 OP_ADD
+            OP_SWAP
+            // Committed values are at most 4 bytes, anything longer differs anyway
+            OP_SIZE 5 OP_LESSTHAN
+            OP_IF OP_0 OP_ADD OP_ENDIF
+            OP_EQUAL
+            OP_TOALTSTACK
+        }
+        OP_TRUE
+        for _ in 0..n {
+            OP_FROMALTSTACK
+            OP_BOOLAND
+        }
+        OP_NOT
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spend(lock: ScriptBuf, witness: Vec<Vec<u8>>) -> bool {
+        ExecutionEnv::new().with_witness(witness).execute(lock).success
+    }
+
+    fn chunked_script() -> Script {
+        let push_script = script! {
+            { 0x1000 }
+            { 0x2000 }
+        };
+        let altstack_script = script! {
+            OP_TOALTSTACK
+            OP_TOALTSTACK
+        };
+        let add_script = script! {
+            OP_FROMALTSTACK
+            OP_ADD
+        };
+        script! {
+            { push_script.clone() }
+            { push_script.clone() }
+            { altstack_script.clone() }
+            { add_script.clone() }
+            { add_script.clone() }
+            OP_2DROP
+            OP_TRUE
+        }
+    }
+
+    #[test]
+    fn test_honest_commitments_cannot_be_disproven() {
+        let leaves = DisproveLeaves::new(chunked_script(), 4, 0, "disprove");
+        assert!(leaves.len() > 1);
+        for index in 0..leaves.len() {
+            let input = (index > 0).then(|| leaves.commitment(index - 1).unwrap());
+            let witness =
+                leaves.unlock_witness(index, input.as_ref(), &leaves.commitment(index).unwrap());
+            assert!(!spend(leaves.lock_script(index), witness), "leaf {}", index);
+        }
+    }
+
+    #[test]
+    fn test_wrong_commitment_can_be_disproven() {
+        let leaves = DisproveLeaves::new(chunked_script(), 4, 0, "disprove");
+        for index in 0..leaves.len() {
+            let boundary = leaves.boundary(index);
+            if boundary.stack.is_empty() {
+                continue;
+            }
+            // The operator claims a wrong top stack element.
+            let mut stack = boundary.stack.clone();
+            let top = stack.last_mut().unwrap();
+            *top = if top.is_empty() { vec![1] } else { vec![] };
+            let wrong_output =
+                StackCommitment::new(&leaves.keys(index), &stack, &boundary.altstack).unwrap();
+
+            let input = (index > 0).then(|| leaves.commitment(index - 1).unwrap());
+            let witness = leaves.unlock_witness(index, input.as_ref(), &wrong_output);
+            assert!(spend(leaves.lock_script(index), witness), "leaf {}", index);
+        }
+    }
+
+    // Runs the flagged `chunk` and checks the flag and the remaining stack
+    fn run_flagged(chunk: Script, failed: bool, expected: Script) -> bool {
+        let flagged = flag_failures(&parse_chunk(&chunk.compile())).unwrap();
+        let check = script! {
+            { failed as u32 } OP_EQUALVERIFY
+            { expected }
+            OP_TRUE
+        };
+        let script = [flagged, check.compile().into_bytes()].concat();
+        spend(ScriptBuf::from_bytes(script), vec![])
+    }
+
+    #[test]
+    fn test_flag_failures() {
+        let chunk = |a: u32, b: u32| {
+            script! {
+                { 7 }
+                { a } { b } OP_EQUALVERIFY
+                OP_1
+                OP_IF
+                    { 8 }
+                    { a } { b } OP_NUMEQUAL OP_VERIFY
+                OP_ELSE
+                    OP_RETURN
+                OP_ENDIF
+                OP_DUP OP_0NOTEQUAL
+                OP_IF OP_1ADD OP_ENDIF
+            }
+        };
+        assert!(run_flagged(chunk(3, 3), false, script! { 9 OP_EQUALVERIFY 7 OP_EQUALVERIFY }));
+        // Fails at the top level check, the stack is left as it was at the failure
+        assert!(run_flagged(chunk(3, 4), true, script! { 7 OP_EQUALVERIFY }));
+
+        // Fails inside a branch
+        let chunk = script! {
+            OP_0
+            OP_NOTIF
+                { 5 } { 6 } OP_EQUALVERIFY
+            OP_ENDIF
+        };
+        assert!(run_flagged(chunk, true, script! {}));
+
+        // The branch that isn't taken doesn't count
+        let chunk = script! {
+            OP_1
+            OP_NOTIF
+                OP_RETURN
+            OP_ENDIF
+            { 5 }
+        };
+        assert!(run_flagged(chunk, false, script! { 5 OP_EQUALVERIFY }));
+
+        let chunk = script! { OP_1 OP_IF OP_ADD OP_ENDIF };
+        assert!(flag_failures(&parse_chunk(&chunk.compile())).is_none());
+    }
+
+    #[test]
+    fn test_max_growth() {
+        let growth_of = |chunk: Script| max_growth(&parse_chunk(&chunk.compile()));
+        assert_eq!(growth_of(script! { OP_1 OP_2 OP_2DUP OP_ADD OP_DROP }), 4);
+        assert_eq!(growth_of(script! { OP_DROP OP_DROP }), 0);
+        assert_eq!(growth_of(script! { { vec![0xab; 80] } OP_SHA256 OP_3DUP }), 4);
+        // The larger branch counts, after the condition is dropped
+        let chunk = script! {
+            OP_1
+            OP_IF
+                OP_1 OP_1 OP_1
+            OP_ELSE
+                OP_1
+            OP_ENDIF
+            OP_1 OP_EQUALVERIFY
+        };
+        assert_eq!(growth_of(chunk), 4);
+    }
+
+    #[test]
+    fn test_clear_stack() {
+        for depth in [0, 1, 2, 7, 100] {
+            let script = script! {
+                for i in 0..depth {
+                    { i }
+                }
+                { clear_stack(depth) }
+                OP_DEPTH OP_0 OP_EQUALVERIFY
+                OP_TRUE
+            };
+            assert!(spend(script.compile(), vec![]), "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn test_failed_check_can_be_disproven() {
+        let script = script! {
+            for i in 0..4 {
+                { 0x1000 + i }
+            }
+            for i in (0..4).rev() {
+                { 0x1000 + i }
+                OP_EQUALVERIFY
+            }
+            OP_TRUE
+        };
+        let leaves = DisproveLeaves::new(script, 4, 0, "disprove");
+        for index in 0..leaves.len() {
+            let input = (index > 0).then(|| leaves.commitment(index - 1).unwrap());
+            let witness =
+                leaves.unlock_witness(index, input.as_ref(), &leaves.commitment(index).unwrap());
+            assert!(!spend(leaves.lock_script(index), witness), "leaf {}", index);
+        }
+
+        let mut checked = 0;
+        for index in 1..leaves.len() {
+            let has_equalverify = leaves.chunks[index].instructions().any(|instruction| {
+                matches!(instruction, Ok(Instruction::Op(op)) if op == OP_EQUALVERIFY)
+            });
+            let boundary = leaves.boundary(index - 1);
+            if !has_equalverify || boundary.stack.is_empty() {
+                continue;
+            }
+            // The operator commits to a wrong input on which the checks of the chunk fail,
+            // but to the honest output.
+            let mut stack = boundary.stack.clone();
+            *stack.last_mut().unwrap() = vec![];
+            let wrong_input =
+                StackCommitment::new(&leaves.keys(index - 1), &stack, &boundary.altstack).unwrap();
+
+            let output = leaves.commitment(index).unwrap();
+            let witness = leaves.unlock_witness(index, Some(&wrong_input), &output);
+            assert!(spend(leaves.lock_script(index), witness), "leaf {}", index);
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_non_minimal_output_cannot_be_disproven() {
+        // [0x00] is committed as 0, which is encoded as the empty vector
+        let script = script! {
+            { vec![0u8] }
+            { 0x1000 }
+            { 0x2000 }
+            OP_ADD
+            OP_DROP
+            OP_DROP
+            OP_TRUE
+        };
+        let leaves = DisproveLeaves::new(script, 4, 0, "disprove");
+        assert!((0..leaves.len()).any(|index| leaves.boundary(index).stack.contains(&vec![0])));
+        for index in 0..leaves.len() {
+            let input = (index > 0).then(|| leaves.commitment(index - 1).unwrap());
+            let witness =
+                leaves.unlock_witness(index, input.as_ref(), &leaves.commitment(index).unwrap());
+            assert!(!spend(leaves.lock_script(index), witness), "leaf {}", index);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//
+// Consensus and policy limits for chunked scripts
+//
+// Every chunk ends up as a tapscript leaf that is spent by its own transaction. The spending
+// transaction has to stay below the standard weight of 400k weight units, and while the chunk
+// executes the stack and altstack together may not hold more than 1000 elements, none of them
+// larger than 520 bytes. The chunks are executed with the stack limit turned off so that every
+// violation is reported instead of aborting at the first one.
+//
+
+use core::fmt;
+
+use bitcoin::{consensus::Encodable, ScriptBuf, VarInt};
+use bitcoin_scriptexec::Stack;
+
+use crate::execution_env::ExecutionEnv;
+use crate::treepp::Script;
+
+/// Maximum weight of a standard transaction.
+pub const MAX_STANDARD_TX_WEIGHT: usize = 400_000;
+/// Maximum number of elements on the stack and altstack combined.
+pub const MAX_STACK_ELEMENTS: usize = 1000;
+/// Maximum size of a single stack element in bytes.
+pub const MAX_ELEMENT_SIZE: usize = 520;
+
+/// Weight of a transaction with a single input and a single P2TR output, without the witness.
+const BASE_TX_WEIGHT: usize = 4 * (4 + 1 + 41 + 1 + 43 + 4) + 2;
+/// Size of the control block of a leaf at depth 0.
+const CONTROL_BLOCK_SIZE: usize = 33;
+/// Largest chunk that still fits a standard transaction together with the control block and
+/// a full input stack of numbers of up to 4 bytes.
+pub const MAX_CHUNK_SIZE: usize =
+    MAX_STANDARD_TX_WEIGHT - BASE_TX_WEIGHT - CONTROL_BLOCK_SIZE - 16 - MAX_STACK_ELEMENTS * 5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LimitViolation {
+    /// The estimated weight of the transaction spending the chunk.
+    TxWeight(usize),
+    /// The number of stack and altstack elements after the opcode at `opcode_index`.
+    StackElements { count: usize, opcode_index: usize },
+    /// The size of an element created by the opcode at `opcode_index`, or of an input
+    /// element if `opcode_index` is `None`.
+    ElementSize { size: usize, opcode_index: Option<usize> },
+    /// The chunk failed for a reason other than the stack limit.
+    Execution(String),
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitViolation::TxWeight(weight) => write!(
+                f,
+                "tx weight {} exceeds {}",
+                weight, MAX_STANDARD_TX_WEIGHT
+            ),
+            LimitViolation::StackElements { count, opcode_index } => write!(
+                f,
+                "{} stack elements exceed {} at opcode {}",
+                count, MAX_STACK_ELEMENTS, opcode_index
+            ),
+            LimitViolation::ElementSize { size, opcode_index: Some(opcode_index) } => write!(
+                f,
+                "element of {} bytes exceeds {} at opcode {}",
+                size, MAX_ELEMENT_SIZE, opcode_index
+            ),
+            LimitViolation::ElementSize { size, opcode_index: None } => write!(
+                f,
+                "input element of {} bytes exceeds {}",
+                size, MAX_ELEMENT_SIZE
+            ),
+            LimitViolation::Execution(error) => write!(f, "execution failed: {}", error),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkLimits {
+    pub chunk_index: usize,
+    pub script_size: usize,
+    /// Estimated weight of a transaction that spends the chunk as a depth 0 leaf with the
+    /// input stack as witness.
+    pub tx_weight: usize,
+    /// Number of stack and altstack elements handed over from the previous chunk.
+    pub input_elements: usize,
+    pub max_stack_elements: usize,
+    pub max_element_size: usize,
+    /// Only the first violation of each kind is recorded.
+    pub violations: Vec<LimitViolation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitReport {
+    pub chunks: Vec<ChunkLimits>,
+}
+
+impl LimitReport {
+    pub fn is_within_limits(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.violations.is_empty())
+    }
+
+    /// Chunk indices together with their violations.
+    pub fn violations(&self) -> Vec<(usize, &LimitViolation)> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.violations.iter().map(|violation| (chunk.chunk_index, violation)))
+            .collect()
+    }
+
+    pub fn max_stack_elements(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.max_stack_elements).max().unwrap_or(0)
+    }
+
+    pub fn max_tx_weight(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.tx_weight).max().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for LimitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} chunks, max tx weight: {}, max stack elements: {}",
+            self.chunks.len(),
+            self.max_tx_weight(),
+            self.max_stack_elements()
+        )?;
+        for (chunk_index, violation) in self.violations() {
+            writeln!(f, "chunk {}: {}", chunk_index, violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `script` into chunks like [`crate::execute_script_as_chunks`] and checks every chunk
+/// against the weight, stack size and element size limits.
+pub fn check_chunk_limits(
+    script: Script,
+    target_chunk_size: usize,
+    tolerance: usize,
+) -> LimitReport {
+    let (_, chunks) = script.compile_to_chunks(target_chunk_size, tolerance);
+    assert!(chunks.len() > 0, "No chunks to execute");
+
+    let env = ExecutionEnv::new().without_stack_limit();
+    let mut next_stack = Stack::new();
+    let mut next_altstack = Stack::new();
+    let mut reports = vec![];
+    for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+        let input_elements = next_stack.len() + next_altstack.len();
+        let mut report = ChunkLimits {
+            chunk_index,
+            script_size: chunk.len(),
+            tx_weight: tx_weight(&chunk, &next_stack, &next_altstack),
+            input_elements,
+            max_stack_elements: input_elements,
+            max_element_size: 0,
+            violations: vec![],
+        };
+        if report.tx_weight > MAX_STANDARD_TX_WEIGHT {
+            report.violations.push(LimitViolation::TxWeight(report.tx_weight));
+        }
+        for item in next_stack.iter_str().chain(next_altstack.iter_str()) {
+            report.max_element_size = report.max_element_size.max(item.len());
+        }
+        if report.max_element_size > MAX_ELEMENT_SIZE {
+            report.violations.push(LimitViolation::ElementSize {
+                size: report.max_element_size,
+                opcode_index: None,
+            });
+        }
+
+        let mut exec = env.exec_with_stack(chunk, next_stack.clone(), next_altstack.clone());
+        let mut opcode_index = 0;
+        let mut stack_violation = false;
+        let mut size_violation = report.max_element_size > MAX_ELEMENT_SIZE;
+        while exec.exec_next().is_ok() {
+            let count = exec.stack().len() + exec.altstack().len();
+            report.max_stack_elements = report.max_stack_elements.max(count);
+            if count > MAX_STACK_ELEMENTS && !stack_violation {
+                stack_violation = true;
+                report
+                    .violations
+                    .push(LimitViolation::StackElements { count, opcode_index });
+            }
+
+            // New elements are only ever created on top of one of the stacks.
+            let top_size = [exec.stack(), exec.altstack()]
+                .iter()
+                .filter(|stack| stack.len() > 0)
+                .map(|stack| stack.get(stack.len() - 1).len())
+                .max()
+                .unwrap_or(0);
+            report.max_element_size = report.max_element_size.max(top_size);
+            if top_size > MAX_ELEMENT_SIZE && !size_violation {
+                size_violation = true;
+                report.violations.push(LimitViolation::ElementSize {
+                    size: top_size,
+                    opcode_index: Some(opcode_index),
+                });
+            }
+            opcode_index += 1;
+        }
+
+        let res = exec.result().unwrap();
+        if let Some(error) = &res.error {
+            report
+                .violations
+                .push(LimitViolation::Execution(format!("{:?}", error)));
+            reports.push(report);
+            break;
+        }
+
+        next_stack = exec.stack().clone();
+        next_altstack = exec.altstack().clone();
+        reports.push(report);
+    }
+
+    LimitReport { chunks: reports }
+}
+
+// The witness holds the input stack and altstack, the leaf script and the control block.
+fn tx_weight(chunk: &ScriptBuf, stack: &Stack, altstack: &Stack) -> usize {
+    let mut items: Vec<Vec<u8>> = stack.iter_str().chain(altstack.iter_str()).collect();
+    items.push(chunk.to_bytes());
+    items.push(vec![0; CONTROL_BLOCK_SIZE]);
+
+    let mut witness = vec![];
+    VarInt(items.len() as u64)
+        .consensus_encode(&mut witness)
+        .expect("writing to a vec");
+    for item in items {
+        item.consensus_encode(&mut witness).expect("writing to a vec");
+    }
+    BASE_TX_WEIGHT + witness.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treepp::script;
+
+    #[test]
+    fn test_chunks_within_limits() {
+        let script = script! {
+            for _ in 0..10 {
+                OP_1 OP_1 OP_ADD
+            }
+            for _ in 0..9 {
+                OP_ADD
+            }
+            20 OP_EQUAL
+        };
+        let report = check_chunk_limits(script, 10, 0);
+        assert!(report.chunks.len() > 1);
+        assert!(report.is_within_limits(), "{}", report);
+        assert_eq!(report.max_stack_elements(), 11);
+    }
+
+    #[test]
+    fn test_stack_elements_violation() {
+        let script = script! {
+            for _ in 0..MAX_STACK_ELEMENTS + 1 {
+                OP_1
+            }
+            for _ in 0..MAX_STACK_ELEMENTS {
+                OP_DROP
+            }
+        };
+        let report = check_chunk_limits(script, 2000, 0);
+        assert_eq!(
+            report.violations(),
+            vec![(
+                0,
+                &LimitViolation::StackElements {
+                    count: MAX_STACK_ELEMENTS + 1,
+                    opcode_index: MAX_STACK_ELEMENTS,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_tx_weight_violation() {
+        let script = script! {
+            for _ in 0..MAX_STANDARD_TX_WEIGHT {
+                OP_NOP
+            }
+            OP_TRUE
+        };
+        let report = check_chunk_limits(script, 2 * MAX_STANDARD_TX_WEIGHT, 0);
+        assert_eq!(report.chunks.len(), 1);
+        assert!(matches!(
+            report.violations()[..],
+            [(0, LimitViolation::TxWeight(weight))] if weight > MAX_STANDARD_TX_WEIGHT
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod commitment;
+pub mod disprove;
+pub mod limits;
@@ -0,0 +1,501 @@
+use std::collections::BTreeSet;
+
+use bitcoin::{
+    hex::DisplayHex,
+    opcodes::all::{OP_DROP, OP_RESERVED},
+    script::{self, Instruction},
+    ScriptBuf,
+};
+use bitcoin_scriptexec::{Exec, Stack};
+
+use crate::execution_env::ExecutionEnv;
+use crate::source_map::SourceLocation;
+use crate::treepp::{script, Script};
+use crate::{ExecuteInfo, FmtStack};
+
+/// A `DEBUG` marker found in the compiled script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugMarker {
+    /// Name attached to the marker with [`debug_marker`], if any.
+    pub name: Option<String>,
+    /// Index of the opcode that is executed right after the marker.
+    pub opcode_index: usize,
+}
+
+/// The state of the interpreter right after an opcode was executed.
+#[derive(Debug)]
+pub struct StepSnapshot {
+    pub opcode_index: usize,
+    pub byte_offset: usize,
+    pub instruction: String,
+    pub stack: FmtStack,
+    pub altstack: FmtStack,
+}
+
+/// How a step changed a stack: `popped` elements were removed from the top and `pushed` were
+/// pushed in their place, bottom first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StackDelta {
+    pub popped: usize,
+    pub pushed: Vec<Vec<u8>>,
+}
+
+impl StackDelta {
+    fn apply(&self, stack: &mut Vec<Vec<u8>>) {
+        stack.truncate(stack.len() - self.popped);
+        stack.extend(self.pushed.iter().cloned());
+    }
+}
+
+/// A recorded step. Only the changes of the stacks are kept, see [`Debugger::history_stacks`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub opcode_index: usize,
+    pub byte_offset: usize,
+    pub instruction: String,
+    pub stack: StackDelta,
+    pub altstack: StackDelta,
+}
+
+struct History {
+    // the stacks when the recording started
+    base: (Vec<Vec<u8>>, Vec<Vec<u8>>),
+    // the stacks after the last recorded step, to compute the next deltas
+    current: (Vec<Vec<u8>>, Vec<Vec<u8>>),
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// Stopped right before executing the opcode at this index.
+    Breakpoint(usize),
+    /// Stopped right before the opcode following the named marker.
+    Marker(String),
+    /// The script ran to completion or failed.
+    Finished,
+}
+
+/// Prefix of the pushed name of a [`debug_marker`], so that no other push followed by `OP_DROP`
+/// and `DEBUG` is taken for a name.
+pub const DEBUG_MARKER_TAG: &[u8] = b"\xdbDEBUG_MARKER:";
+
+/// Returns a named `DEBUG` marker. The tagged name is pushed and dropped again so the marker does
+/// not change the stack. [`execute_script`](crate::execute_script) halts on it like on a plain
+/// `DEBUG`, while the [`Debugger`] strips it and allows to run up to it by name.
+pub fn debug_marker(name: &str) -> Script {
+    script! {
+        { [DEBUG_MARKER_TAG, name.as_bytes()].concat() }
+        OP_DROP
+        DEBUG
+    }
+}
+
+/// Step-through debugger on top of `bitcoin_scriptexec::Exec`.
+///
+/// All `DEBUG` opcodes are removed from the compiled script before execution and recorded as
+/// [`DebugMarker`]s. Opcode indices used for breakpoints refer to the script without them.
+pub struct Debugger {
+    exec: Exec,
+    // (byte offset, human readable instruction) for every opcode of the executed script
+    instructions: Vec<(usize, String)>,
+    // byte offset of every opcode in the original script, which still contains the markers
+    source_offsets: Vec<usize>,
+    source: Option<Script>,
+    markers: Vec<DebugMarker>,
+    breakpoints: BTreeSet<usize>,
+    position: usize,
+    // the position `run_until` stopped at, which it doesn't stop at again when resumed
+    stopped_at: Option<usize>,
+    history: Option<History>,
+}
+
+impl Debugger {
+    pub fn new(script: Script) -> Result<Self, script::Error> {
+        let mut debugger = Self::from_script_buf(script.clone().compile())?;
+        debugger.source = Some(script);
+        Ok(debugger)
+    }
+
+    /// Fails if the script can't be decoded, e.g. if a push runs past the end of the script.
+    pub fn from_script_buf(script: ScriptBuf) -> Result<Self, script::Error> {
+        let (script, instructions, source_offsets, markers) = strip_debug_markers(&script)?;
+
+        let exec = ExecutionEnv::new().exec(script);
+
+        Ok(Self {
+            exec,
+            instructions,
+            source_offsets,
+            source: None,
+            markers,
+            breakpoints: BTreeSet::new(),
+            position: 0,
+            stopped_at: None,
+            history: None,
+        })
+    }
+
+    /// Keep the changes of the stacks for every executed step. Each step compares the stacks
+    /// with the previous ones, which is slow for large scripts.
+    pub fn record_history(&mut self, enabled: bool) {
+        match (enabled, self.history.is_some()) {
+            (true, false) => {
+                let stacks = (stack_items(self.exec.stack()), stack_items(self.exec.altstack()));
+                self.history = Some(History {
+                    base: stacks.clone(),
+                    current: stacks,
+                    entries: vec![],
+                })
+            }
+            (false, true) => self.history = None,
+            _ => {}
+        }
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        self.history.as_ref().map_or(&[], |history| &history.entries)
+    }
+
+    /// The stack and altstack right after the `step`-th recorded step, replayed from the deltas.
+    pub fn history_stacks(&self, step: usize) -> Option<(Vec<Vec<u8>>, Vec<Vec<u8>>)> {
+        let history = self.history.as_ref()?;
+        let entries = history.entries.get(..=step)?;
+        let (mut stack, mut altstack) = history.base.clone();
+        for entry in entries {
+            entry.stack.apply(&mut stack);
+            entry.altstack.apply(&mut altstack);
+        }
+        Some((stack, altstack))
+    }
+
+    pub fn markers(&self) -> &[DebugMarker] { &self.markers }
+
+    pub fn opcode_count(&self) -> usize { self.instructions.len() }
+
+    /// Index of the next opcode to be executed.
+    pub fn position(&self) -> usize { self.position }
+
+    /// The next opcode to be executed as `(byte offset, instruction)`.
+    pub fn current_instruction(&self) -> Option<&(usize, String)> {
+        self.instructions.get(self.position)
+    }
+
+    /// The builder call stack of the next opcode. Only available if the debugger was created
+    /// from a structured [`Script`].
+    pub fn source_location(&self) -> Option<SourceLocation> {
+        let offset = *self.source_offsets.get(self.position)?;
+        self.source.as_ref().map(|script| SourceLocation::locate(script, offset))
+    }
+
+    pub fn set_breakpoint(&mut self, opcode_index: usize) { self.breakpoints.insert(opcode_index); }
+
+    pub fn clear_breakpoint(&mut self, opcode_index: usize) {
+        self.breakpoints.remove(&opcode_index);
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &usize> { self.breakpoints.iter() }
+
+    pub fn is_finished(&self) -> bool { self.exec.result().is_some() }
+
+    pub fn stack(&self) -> FmtStack { FmtStack(self.exec.stack().clone()) }
+
+    pub fn altstack(&self) -> FmtStack { FmtStack(self.exec.altstack().clone()) }
+
+    /// Executes a single opcode and returns the state right after it.
+    /// Returns `None` once the script is finished.
+    pub fn step(&mut self) -> Option<StepSnapshot> {
+        if self.is_finished() {
+            return None;
+        }
+        let Some((byte_offset, instruction)) = self.instructions.get(self.position).cloned()
+        else {
+            // No opcodes left, let the interpreter evaluate the final stack.
+            let _ = self.exec.exec_next();
+            return None;
+        };
+        let _ = self.exec.exec_next();
+        let snapshot = StepSnapshot {
+            opcode_index: self.position,
+            byte_offset,
+            instruction,
+            stack: self.stack(),
+            altstack: self.altstack(),
+        };
+        self.position += 1;
+        if let Some(history) = self.history.as_mut() {
+            let stack = stack_delta(&mut history.current.0, self.exec.stack());
+            let altstack = stack_delta(&mut history.current.1, self.exec.altstack());
+            history.entries.push(HistoryEntry {
+                opcode_index: snapshot.opcode_index,
+                byte_offset: snapshot.byte_offset,
+                instruction: snapshot.instruction.clone(),
+                stack,
+                altstack,
+            });
+        }
+        Some(snapshot)
+    }
+
+    /// Runs until the next breakpoint or until the script is finished.
+    pub fn resume(&mut self) -> StopReason { self.run_until(None) }
+
+    /// Runs until the opcode following the marker with the given name.
+    /// Returns `None` if no such marker exists.
+    pub fn run_to_marker(&mut self, name: &str) -> Option<StopReason> {
+        let target = self
+            .markers
+            .iter()
+            .find(|marker| marker.name.as_deref() == Some(name) && marker.opcode_index >= self.position)?
+            .opcode_index;
+        if target == self.position {
+            return Some(StopReason::Marker(name.to_string()));
+        }
+        Some(match self.run_until(Some(target)) {
+            StopReason::Breakpoint(index) if index == target => StopReason::Marker(name.to_string()),
+            reason => reason,
+        })
+    }
+
+    /// Runs the remaining script ignoring breakpoints.
+    pub fn finish(mut self) -> ExecuteInfo {
+        let mut last_position = self.position;
+        while !self.is_finished() {
+            last_position = self.position;
+            self.position += 1;
+            let _ = self.exec.exec_next();
+        }
+        self.position = last_position;
+        let source_location = match self.exec.result().unwrap().error {
+            Some(_) => self.source_location(),
+            None => None,
+        };
+        let res = self.exec.result().unwrap();
+        ExecuteInfo {
+            success: res.success,
+            error: res.error.clone(),
+            last_opcode: res.opcode,
+            final_stack: FmtStack(self.exec.stack().clone()),
+            remaining_script: self.exec.remaining_script().to_asm_string(),
+            stats: self.exec.stats().clone(),
+            source_location,
+        }
+    }
+
+    // Breakpoints are checked before each opcode, so one at the first opcode stops before
+    // anything is executed. A breakpoint that was just stopped at doesn't stop again.
+    fn run_until(&mut self, target: Option<usize>) -> StopReason {
+        let resumed_at = self.stopped_at.take();
+        while !self.is_finished() {
+            let stop = self.breakpoints.contains(&self.position) || target == Some(self.position);
+            if stop && resumed_at != Some(self.position) {
+                self.stopped_at = Some(self.position);
+                return StopReason::Breakpoint(self.position);
+            }
+            if self.step().is_none() {
+                break;
+            }
+        }
+        StopReason::Finished
+    }
+}
+
+fn stack_items(stack: &Stack) -> Vec<Vec<u8>> { stack.iter_str().collect() }
+
+// The delta from `previous` to `stack`, `previous` is updated to `stack`
+fn stack_delta(previous: &mut Vec<Vec<u8>>, stack: &Stack) -> StackDelta {
+    let common = (0..previous.len().min(stack.len()))
+        .find(|&i| previous[i] != stack.get(i))
+        .unwrap_or(previous.len().min(stack.len()));
+    let delta = StackDelta {
+        popped: previous.len() - common,
+        pushed: (common..stack.len()).map(|i| stack.get(i)).collect(),
+    };
+    delta.apply(previous);
+    delta
+}
+
+/// Removes all `DEBUG` opcodes from the script and returns the stripped script, its
+/// instructions, their offsets in the original script and the markers that were removed.
+#[allow(clippy::type_complexity)]
+fn strip_debug_markers(
+    script: &ScriptBuf,
+) -> Result<(ScriptBuf, Vec<(usize, String)>, Vec<usize>, Vec<DebugMarker>), script::Error> {
+    let bytes = script.as_bytes();
+    let indices = script.instruction_indices().collect::<Result<Vec<_>, _>>()?;
+
+    let mut stripped = vec![];
+    let mut instructions: Vec<(usize, String)> = vec![];
+    let mut source_offsets = vec![];
+    let mut markers = vec![];
+    // the name of a tagged push right before, and of a tagged push and OP_DROP right before
+    let mut pushed_name: Option<String> = None;
+    let mut name: Option<String> = None;
+    for (i, (offset, instruction)) in indices.iter().enumerate() {
+        let end = indices.get(i + 1).map_or(bytes.len(), |(next, _)| *next);
+        match instruction {
+            Instruction::Op(OP_RESERVED) => {
+                markers.push(DebugMarker { name: name.take(), opcode_index: instructions.len() });
+                pushed_name = None;
+                continue;
+            }
+            Instruction::Op(OP_DROP) => name = pushed_name.take(),
+            Instruction::PushBytes(push) => {
+                pushed_name = push
+                    .as_bytes()
+                    .strip_prefix(DEBUG_MARKER_TAG)
+                    .and_then(|name| String::from_utf8(name.to_vec()).ok());
+                name = None;
+            }
+            Instruction::Op(_) => {
+                pushed_name = None;
+                name = None;
+            }
+        }
+        instructions.push((stripped.len(), format_instruction(instruction)));
+        source_offsets.push(*offset);
+        stripped.extend_from_slice(&bytes[*offset..end]);
+    }
+    Ok((ScriptBuf::from_bytes(stripped), instructions, source_offsets, markers))
+}
+
+fn format_instruction(instruction: &Instruction) -> String {
+    match instruction {
+        Instruction::Op(opcode) => format!("{:?}", opcode),
+        Instruction::PushBytes(push) if push.is_empty() => "OP_0".to_string(),
+        Instruction::PushBytes(push) => format!("<0x{}>", push.as_bytes().as_hex()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_step_and_snapshots() {
+        let script = script! {
+            OP_1
+            OP_2
+            OP_TOALTSTACK
+            OP_FROMALTSTACK
+            OP_ADD
+            OP_3
+            OP_EQUAL
+        };
+        let mut debugger = Debugger::new(script).unwrap();
+        assert_eq!(debugger.opcode_count(), 7);
+
+        let snapshot = debugger.step().unwrap();
+        assert_eq!(snapshot.opcode_index, 0);
+        assert_eq!(snapshot.instruction, "OP_PUSHNUM_1");
+        assert_eq!(snapshot.stack.len(), 1);
+
+        debugger.step();
+        let snapshot = debugger.step().unwrap();
+        assert_eq!(snapshot.stack.len(), 1);
+        assert_eq!(snapshot.altstack.len(), 1);
+
+        assert_eq!(debugger.resume(), StopReason::Finished);
+        assert!(debugger.step().is_none());
+        assert!(debugger.finish().success);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let script = script! {
+            for i in 0..10 {
+                { i }
+            }
+            for _ in 0..9 {
+                OP_DROP
+            }
+            OP_DROP
+            OP_TRUE
+        };
+        let mut debugger = Debugger::new(script).unwrap();
+        debugger.set_breakpoint(10);
+        debugger.set_breakpoint(15);
+        debugger.record_history(true);
+
+        assert_eq!(debugger.resume(), StopReason::Breakpoint(10));
+        assert_eq!(debugger.stack().len(), 10);
+        assert_eq!(debugger.history().len(), 10);
+        assert_eq!(debugger.history()[9].stack, StackDelta { popped: 0, pushed: vec![vec![9]] });
+        let (stack, altstack) = debugger.history_stacks(9).unwrap();
+        assert_eq!(stack.len(), 10);
+        assert!(altstack.is_empty());
+
+        assert_eq!(debugger.resume(), StopReason::Breakpoint(15));
+        assert_eq!(debugger.stack().len(), 5);
+        assert_eq!(debugger.history()[14].stack, StackDelta { popped: 1, pushed: vec![] });
+        assert_eq!(debugger.history_stacks(14).unwrap().0.len(), 5);
+
+        debugger.clear_breakpoint(15);
+        assert_eq!(debugger.resume(), StopReason::Finished);
+        assert!(debugger.finish().success);
+    }
+
+    #[test]
+    fn test_breakpoint_at_first_opcode() {
+        let script = script! {
+            OP_1
+            OP_2
+            OP_ADD
+            OP_3
+            OP_EQUAL
+        };
+        let mut debugger = Debugger::new(script).unwrap();
+        debugger.set_breakpoint(0);
+        debugger.set_breakpoint(2);
+
+        assert_eq!(debugger.resume(), StopReason::Breakpoint(0));
+        assert_eq!(debugger.stack().len(), 0);
+        assert_eq!(debugger.resume(), StopReason::Breakpoint(2));
+        assert_eq!(debugger.stack().len(), 2);
+        assert_eq!(debugger.resume(), StopReason::Finished);
+        assert!(debugger.finish().success);
+    }
+
+    #[test]
+    fn test_run_to_marker() {
+        let script = script! {
+            OP_1
+            OP_2
+            { debug_marker("after_push") }
+            OP_ADD
+            DEBUG
+            // an untagged push isn't a name
+            { b"not_a_marker".to_vec() }
+            OP_DROP
+            DEBUG
+            OP_3
+            OP_EQUAL
+        };
+        let mut debugger = Debugger::new(script).unwrap();
+        assert_eq!(
+            debugger.markers(),
+            &[
+                DebugMarker { name: Some("after_push".to_string()), opcode_index: 4 },
+                DebugMarker { name: None, opcode_index: 5 },
+                DebugMarker { name: None, opcode_index: 7 },
+            ]
+        );
+
+        assert_eq!(
+            debugger.run_to_marker("after_push"),
+            Some(StopReason::Marker("after_push".to_string()))
+        );
+        assert_eq!(debugger.stack().len(), 2);
+        assert_eq!(debugger.current_instruction().unwrap().1, "OP_ADD");
+        assert_eq!(debugger.run_to_marker("missing"), None);
+
+        let exec_result = debugger.finish();
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_malformed_script() {
+        // OP_PUSHDATA1 without the length byte
+        let script = ScriptBuf::from_bytes(vec![0x51, 0x4c]);
+        assert!(Debugger::from_script_buf(script).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use bitcoin::{
+    hashes::Hash,
+    taproot::{LeafVersion, TAPROOT_ANNEX_PREFIX},
+    ScriptBuf, TapLeafHash, Transaction, TxOut,
+};
+use bitcoin_scriptexec::{Exec, ExecCtx, Options, Stack, TxTemplate};
+
+use crate::{ExecuteInfo, FmtStack};
+
+/// The transaction context a script is executed in.
+///
+/// The default environment is an empty version 2 transaction without inputs, prevouts or
+/// witness, which is enough for scripts that don't inspect the spending transaction. Scripts
+/// that use `OP_CHECKSIG`, `OP_CSV` or `OP_CLTV` need the real spending transaction and the
+/// outputs it spends.
+#[derive(Clone)]
+pub struct ExecutionEnv {
+    tx: Transaction,
+    prevouts: Vec<TxOut>,
+    input_index: usize,
+    witness: Vec<Vec<u8>>,
+    leaf_hash: TapLeafHash,
+    annex: Option<Vec<u8>>,
+    options: Options,
+}
+
+impl Default for ExecutionEnv {
+    fn default() -> Self {
+        Self {
+            tx: Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            },
+            prevouts: vec![],
+            input_index: 0,
+            witness: vec![],
+            leaf_hash: TapLeafHash::all_zeros(),
+            annex: None,
+            options: Options::default(),
+        }
+    }
+}
+
+impl ExecutionEnv {
+    pub fn new() -> Self { Self::default() }
+
+    /// The environment of a taproot script path spend of input `input_index` of `tx`.
+    /// Splits the input's witness into the initial stack, the annex, the leaf script and the
+    /// control block. Returns the environment together with the leaf script.
+    pub fn from_script_path_spend(
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        input_index: usize,
+    ) -> (Self, ScriptBuf) {
+        let mut witness: Vec<Vec<u8>> = tx.input[input_index]
+            .witness
+            .iter()
+            .map(|item| item.to_vec())
+            .collect();
+        let annex = match witness.last() {
+            Some(item) if witness.len() >= 2 && item.first() == Some(&TAPROOT_ANNEX_PREFIX) => {
+                witness.pop()
+            }
+            _ => None,
+        };
+        assert!(
+            witness.len() >= 2,
+            "input {} is not a taproot script path spend",
+            input_index
+        );
+        let _control_block = witness.pop();
+        let leaf_script = ScriptBuf::from_bytes(witness.pop().unwrap());
+
+        let env = Self::new()
+            .with_transaction(tx.clone())
+            .with_prevouts(prevouts.to_vec())
+            .with_input_index(input_index)
+            .with_witness(witness)
+            .with_leaf_script(&leaf_script)
+            .with_annex(annex);
+        (env, leaf_script)
+    }
+
+    pub fn with_transaction(mut self, tx: Transaction) -> Self {
+        self.tx = tx;
+        self
+    }
+
+    pub fn with_prevouts(mut self, prevouts: Vec<TxOut>) -> Self {
+        self.prevouts = prevouts;
+        self
+    }
+
+    pub fn with_input_index(mut self, input_index: usize) -> Self {
+        self.input_index = input_index;
+        self
+    }
+
+    /// The initial stack, bottom first.
+    pub fn with_witness(mut self, witness: Vec<Vec<u8>>) -> Self {
+        self.witness = witness;
+        self
+    }
+
+    pub fn with_leaf_hash(mut self, leaf_hash: TapLeafHash) -> Self {
+        self.leaf_hash = leaf_hash;
+        self
+    }
+
+    /// Sets the leaf hash to the one of `leaf_script` in a tapscript leaf.
+    pub fn with_leaf_script(self, leaf_script: &bitcoin::Script) -> Self {
+        self.with_leaf_hash(TapLeafHash::from_script(leaf_script, LeafVersion::TapScript))
+    }
+
+    pub fn with_annex(mut self, annex: Option<Vec<u8>>) -> Self {
+        self.annex = annex;
+        self
+    }
+
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Do not enforce the stack limit. Only for test purposes.
+    pub fn without_stack_limit(mut self) -> Self {
+        self.options.enforce_stack_limit = false;
+        self
+    }
+
+    pub fn tx(&self) -> &Transaction { &self.tx }
+
+    pub fn prevouts(&self) -> &[TxOut] { &self.prevouts }
+
+    pub fn input_index(&self) -> usize { self.input_index }
+
+    pub fn witness(&self) -> &[Vec<u8>] { &self.witness }
+
+    pub fn leaf_hash(&self) -> TapLeafHash { self.leaf_hash }
+
+    pub fn options(&self) -> &Options { &self.options }
+
+    pub fn tx_template(&self) -> TxTemplate {
+        TxTemplate {
+            tx: self.tx.clone(),
+            prevouts: self.prevouts.clone(),
+            input_idx: self.input_index,
+            taproot_annex_scriptleaf: Some((self.leaf_hash, self.annex.clone())),
+        }
+    }
+
+    /// Creates an executor for `script` with the witness as initial stack.
+    pub fn exec(&self, script: ScriptBuf) -> Exec {
+        Exec::new(
+            ExecCtx::Tapscript,
+            self.options.clone(),
+            self.tx_template(),
+            script,
+            self.witness.clone(),
+        )
+        .expect("error creating exec")
+    }
+
+    /// Creates an executor for `script` that continues from the given stacks. The witness is
+    /// ignored.
+    pub fn exec_with_stack(&self, script: ScriptBuf, stack: Stack, altstack: Stack) -> Exec {
+        Exec::with_stack(
+            ExecCtx::Tapscript,
+            self.options.clone(),
+            self.tx_template(),
+            script,
+            vec![],
+            stack,
+            altstack,
+        )
+        .expect("error creating exec")
+    }
+
+    /// Executes a compiled script in this environment.
+    pub fn execute(&self, script: ScriptBuf) -> ExecuteInfo {
+        let mut exec = self.exec(script);
+        loop {
+            if exec.exec_next().is_err() {
+                break;
+            }
+        }
+        let res = exec.result().unwrap();
+        ExecuteInfo {
+            success: res.success,
+            error: res.error.clone(),
+            last_opcode: res.opcode,
+            final_stack: FmtStack(exec.stack().clone()),
+            remaining_script: exec.remaining_script().to_asm_string(),
+            stats: exec.stats().clone(),
+            source_location: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, OutPoint, Sequence, TxIn, Witness,
+    };
+
+    use super::*;
+    use crate::treepp::*;
+
+    fn spending_tx(lock_time: u32, sequence: u32) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::from_height(lock_time).unwrap(),
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence(sequence),
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn test_timelocks_use_the_spending_tx() {
+        let script = script! {
+            { 10 } OP_CSV OP_DROP
+            { 100 } OP_CLTV OP_DROP
+            OP_TRUE
+        }
+        .compile();
+
+        let env = ExecutionEnv::new().with_transaction(spending_tx(100, 10));
+        assert!(env.execute(script.clone()).success);
+
+        let env = ExecutionEnv::new().with_transaction(spending_tx(99, 10));
+        assert!(!env.execute(script.clone()).success);
+
+        let env = ExecutionEnv::new().with_transaction(spending_tx(100, 9));
+        assert!(!env.execute(script).success);
+    }
+
+    #[test]
+    fn test_witness_is_the_initial_stack() {
+        let env = ExecutionEnv::new().with_witness(vec![vec![2], vec![3]]);
+        let exec_result = env.execute(script! { OP_ADD 5 OP_EQUAL }.compile());
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_from_script_path_spend() {
+        let leaf_script = script! { OP_ADD 5 OP_EQUAL }.compile();
+        let mut tx = spending_tx(0, 0);
+        tx.input[0].witness = Witness::from_slice(&[
+            vec![2],
+            vec![3],
+            leaf_script.to_bytes(),
+            vec![0xc0; 33],
+        ]);
+        let prevouts = vec![TxOut {
+            value: Amount::from_sat(1000),
+            script_pubkey: ScriptBuf::new(),
+        }];
+
+        let (env, script) = ExecutionEnv::from_script_path_spend(&tx, &prevouts, 0);
+        assert_eq!(script, leaf_script);
+        assert_eq!(env.witness(), &[vec![2], vec![3]]);
+        assert_eq!(
+            env.leaf_hash(),
+            TapLeafHash::from_script(&leaf_script, LeafVersion::TapScript)
+        );
+        assert!(env.execute(script).success);
+    }
+}
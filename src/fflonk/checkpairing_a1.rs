@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use crate::bn254::curves::G1Projective;
+    use crate::bn254::curves::{G1Projective, ProjectiveCurve};
     use crate::bn254::fp254impl::Fp254Impl;
     use crate::bn254::fq::Fq;
     use crate::bn254::fr::Fr;
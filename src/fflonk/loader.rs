@@ -0,0 +1,165 @@
+//
+// Loaders for fflonk verifying keys and proofs exported by snarkjs
+//
+// The layout follows the Groth16 files of snarkjs, see `groth16::loader`: decimal strings,
+// points in projective form [x, y, z] with z = 1, or z = 0 for the point at infinity, and Fq2
+// elements as [c0, c1]. Public inputs are the same `public.json`, which is loaded by
+// `groth16::loader::public_inputs_from_snarkjs_json`.
+//
+
+use crate::fflonk::verifier::{FflonkEvaluations, FflonkProof, FflonkVerifyingKey};
+use crate::groth16::loader::{
+    check_snarkjs_protocol, from_decimal, snarkjs_g1, snarkjs_g2, LoadError,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct SnarkjsVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    power: u32,
+    k1: String,
+    k2: String,
+    w: String,
+    w3: String,
+    w4: String,
+    w8: String,
+    wr: String,
+    #[serde(rename = "X_2")]
+    x_2: [[String; 2]; 3],
+    #[serde(rename = "C0")]
+    c0: [String; 3],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct SnarkjsPolynomials {
+    c1: [String; 3],
+    c2: [String; 3],
+    w1: [String; 3],
+    w2: [String; 3],
+}
+
+#[derive(Deserialize)]
+struct SnarkjsEvaluations {
+    ql: String,
+    qr: String,
+    qm: String,
+    qo: String,
+    qc: String,
+    s1: String,
+    s2: String,
+    s3: String,
+    a: String,
+    b: String,
+    c: String,
+    z: String,
+    zw: String,
+    t1w: String,
+    t2w: String,
+    inv: String,
+}
+
+#[derive(Deserialize)]
+struct SnarkjsProof {
+    protocol: String,
+    curve: String,
+    polynomials: SnarkjsPolynomials,
+    evaluations: SnarkjsEvaluations,
+}
+
+/// Loads the `verification_key.json` of snarkjs.
+pub fn vk_from_snarkjs_json(json: &str) -> Result<FflonkVerifyingKey, LoadError> {
+    let vk: SnarkjsVerifyingKey = serde_json::from_str(json)?;
+    check_snarkjs_protocol("fflonk", &vk.protocol, &vk.curve)?;
+    // The domain size 2^power has to fit the two-adicity of Fr
+    if vk.power > 28 {
+        return Err(LoadError::InvalidNumber(vk.power.to_string()));
+    }
+    Ok(FflonkVerifyingKey {
+        n_public: vk.n_public,
+        power: vk.power,
+        k1: from_decimal(&vk.k1)?,
+        k2: from_decimal(&vk.k2)?,
+        w: from_decimal(&vk.w)?,
+        w3: from_decimal(&vk.w3)?,
+        w4: from_decimal(&vk.w4)?,
+        w8: from_decimal(&vk.w8)?,
+        wr: from_decimal(&vk.wr)?,
+        x_2: snarkjs_g2(&vk.x_2)?,
+        c0: snarkjs_g1(&vk.c0)?,
+    })
+}
+
+/// Loads the `proof.json` of snarkjs.
+pub fn proof_from_snarkjs_json(json: &str) -> Result<FflonkProof, LoadError> {
+    let proof: SnarkjsProof = serde_json::from_str(json)?;
+    check_snarkjs_protocol("fflonk", &proof.protocol, &proof.curve)?;
+    let evaluations = &proof.evaluations;
+    Ok(FflonkProof {
+        c1: snarkjs_g1(&proof.polynomials.c1)?,
+        c2: snarkjs_g1(&proof.polynomials.c2)?,
+        w1: snarkjs_g1(&proof.polynomials.w1)?,
+        w2: snarkjs_g1(&proof.polynomials.w2)?,
+        evaluations: FflonkEvaluations {
+            ql: from_decimal(&evaluations.ql)?,
+            qr: from_decimal(&evaluations.qr)?,
+            qm: from_decimal(&evaluations.qm)?,
+            qo: from_decimal(&evaluations.qo)?,
+            qc: from_decimal(&evaluations.qc)?,
+            s1: from_decimal(&evaluations.s1)?,
+            s2: from_decimal(&evaluations.s2)?,
+            s3: from_decimal(&evaluations.s3)?,
+            a: from_decimal(&evaluations.a)?,
+            b: from_decimal(&evaluations.b)?,
+            c: from_decimal(&evaluations.c)?,
+            z: from_decimal(&evaluations.z)?,
+            zw: from_decimal(&evaluations.zw)?,
+            t1w: from_decimal(&evaluations.t1w)?,
+            t2w: from_decimal(&evaluations.t2w)?,
+            inv: from_decimal(&evaluations.inv)?,
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::Field;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_load_snarkjs_json() {
+        let vk = vk_from_snarkjs_json(include_str!("circom_ref/verification_key.json")).unwrap();
+        assert_eq!(vk.n_public, 2);
+        assert_eq!(vk.power, 18);
+        assert_eq!(vk.k1, ark_bn254::Fr::from(2));
+        assert_eq!(vk.w.pow([1 << vk.power]), ark_bn254::Fr::ONE);
+        assert_eq!(vk.w8.pow([8]), ark_bn254::Fr::ONE);
+
+        let proof = proof_from_snarkjs_json(include_str!("circom_ref/proof.json")).unwrap();
+        assert_eq!(
+            proof.w2.x,
+            ark_bn254::Fq::from_str(
+                "11695827642347470645483614914520090101440686332033956264171712726147972703435"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            proof.evaluations.inv,
+            ark_bn254::Fr::from_str(
+                "21247383512588455895834686692756529012394058115069710447132959660051940541361"
+            )
+            .unwrap()
+        );
+
+        // A Groth16 key isn't an fflonk key
+        let json = include_str!("circom_ref/verification_key.json").replace("fflonk", "groth16");
+        assert!(matches!(
+            vk_from_snarkjs_json(&json),
+            Err(LoadError::UnsupportedProtocol(_))
+        ));
+    }
+}
@@ -16,4 +16,7 @@ pub mod compute_pi;
 pub mod compute_r0;
 pub mod compute_r1;
 pub mod compute_r2;
+pub mod loader;
 pub mod verifier;
+
+pub use verifier::{FflonkEvaluations, FflonkProof, FflonkVerifyingKey, Verifier, VerifierError};
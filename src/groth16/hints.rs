@@ -0,0 +1,148 @@
+use crate::bn254::utils::fq12_push;
+use crate::groth16::offchain_checker::{compute_c_wi, compute_w, is_r_th_residue};
+use crate::treepp::{script, Script};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing as ark_Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::Field;
+use ark_groth16::{Proof, VerifyingKey};
+use core::ops::Neg;
+
+// Hints of the final exponentiation from "On Proving Pairings" (https://eprint.iacr.org/2024/640.pdf)
+//
+// Instead of computing f^{(p^12 - 1) / r} the verifier checks c^lambda = f * wi, where f is the
+// result of the miller loop, c is a lambda-th root of f * wi and wi is 1, w or w^2 of
+// `compute_w`. The hints don't depend on the verifying key, so they are pushed by the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hints {
+    pub c: ark_bn254::Fq12,
+    pub c_inv: ark_bn254::Fq12,
+    pub wi: ark_bn254::Fq12,
+}
+
+impl Hints {
+    pub fn new(
+        public_inputs: &Vec<<Bn254 as ark_Pairing>::ScalarField>,
+        proof: &Proof<Bn254>,
+        vk: &VerifyingKey<Bn254>,
+    ) -> Self {
+        let (p, q) = pairing_points(public_inputs, proof, vk);
+        Self::from_miller_loop(Bn254::multi_miller_loop_affine(p, q).0)
+    }
+
+    pub fn from_miller_loop(f: ark_bn254::Fq12) -> Self {
+        Self::try_from_miller_loop(f).expect("invalid proof, the pairing check fails")
+    }
+
+    // None if the pairing check fails, then f isn't an r-th residue and there is no c
+    pub fn try_from_miller_loop(f: ark_bn254::Fq12) -> Option<Self> {
+        if !is_r_th_residue(f) {
+            return None;
+        }
+        let (c, wi) = compute_c_wi(f);
+        Some(Hints {
+            c,
+            c_inv: c.inverse().unwrap(),
+            wi,
+        })
+    }
+
+    // Output Stack: [c, c_inv, i] with wi == w^i
+    //
+    // wi itself is pushed by the verifier, the prover only selects 1, w or w^2.
+    pub fn push(&self) -> Script {
+        let w = compute_w();
+        let i = [ark_bn254::Fq12::ONE, w, w * w]
+            .iter()
+            .position(|wi| *wi == self.wi)
+            .expect("wi isn't 1, w or w^2!");
+        script! {
+            { fq12_push(self.c) }
+            { fq12_push(self.c_inv) }
+            { i as u32 }
+        }
+    }
+}
+
+// The points of the pairing check e(P1,Q1)*e(P2,Q2)*e(P3,Q3)*e(P4,Q4)=1 of a proof
+pub fn pairing_points(
+    public_inputs: &Vec<<Bn254 as ark_Pairing>::ScalarField>,
+    proof: &Proof<Bn254>,
+    vk: &VerifyingKey<Bn254>,
+) -> ([ark_bn254::G1Affine; 4], [ark_bn254::G2Affine; 4]) {
+    let scalars = [
+        vec![<Bn254 as ark_Pairing>::ScalarField::ONE],
+        public_inputs.clone(),
+    ]
+    .concat();
+    let msm_g1 =
+        ark_bn254::G1Projective::msm(&vk.gamma_abc_g1, &scalars).expect("failed to calculate msm");
+    (
+        [msm_g1.into_affine(), proof.c, vk.alpha_g1, proof.a],
+        [
+            vk.gamma_g2.into_group().neg().into_affine(),
+            vk.delta_g2.into_group().neg().into_affine(),
+            -vk.beta_g2,
+            proof.b,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::groth16::constants::LAMBDA;
+    use ark_ff::{One, PrimeField};
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_hints_from_miller_loop() {
+        // e(2 * g1, g2) * e(g1, 3 * g2) * e(4 * g1, g2) * e(-g1, 9 * g2) = 1
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let g1 = ark_bn254::G1Affine::rand(&mut prng);
+        let g2 = ark_bn254::G2Affine::rand(&mut prng);
+        let f = Bn254::multi_miller_loop_affine(
+            [
+                g1.mul_bigint([2_u64]).into_affine(),
+                g1,
+                g1.mul_bigint([4_u64]).into_affine(),
+                -g1,
+            ],
+            [
+                g2,
+                g2.mul_bigint([3_u64]).into_affine(),
+                g2,
+                g2.mul_bigint([9_u64]).into_affine(),
+            ],
+        )
+        .0;
+
+        let hints = Hints::from_miller_loop(f);
+        let w = compute_w();
+        assert!(hints.wi == w || hints.wi == w * w);
+        assert_eq!(hints.c.pow(LAMBDA.to_u64_digits()), f * hints.wi);
+    }
+
+    #[test]
+    fn test_hints_from_cubic_residue() {
+        // f = z^{3r} is an r-th residue and a cubic residue, so wi = 1
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let z = ark_bn254::Fq12::rand(&mut prng);
+        let f = z.pow(ark_bn254::Fr::MODULUS).pow([3_u64]);
+
+        let hints = Hints::from_miller_loop(f);
+        assert!(hints.wi.is_one());
+        assert!((hints.c * hints.c_inv).is_one());
+        assert_eq!(hints.c.pow(LAMBDA.to_u64_digits()), f * hints.wi);
+    }
+
+    #[test]
+    fn test_hints_from_invalid_miller_loop() {
+        // a random f is no r-th residue, as for a failing pairing check
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let f = ark_bn254::Fq12::rand(&mut prng);
+        assert_eq!(Hints::try_from_miller_loop(f), None);
+    }
+}
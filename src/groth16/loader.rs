@@ -0,0 +1,629 @@
+//
+// Loaders for Groth16 verifying keys, proofs and public inputs exported by other toolchains
+//
+// snarkjs (circom) writes `verification_key.json`, `proof.json` and `public.json` with decimal
+// strings. Points are given in projective form [x, y, z] with z = 1, or z = 0 for the point at
+// infinity, and Fq2 elements as [c0, c1].
+//
+// gnark writes its `WriteTo` binary encoding of gnark-crypto points. The flags of the first byte
+// differ from `bn254::encoding`:
+//
+//   0x00  uncompressed x ‖ y, all zero for the point at infinity
+//   0x40  compressed point at infinity
+//   0x80  compressed, y is the smaller one of y and -y
+//   0xc0  compressed, y is the larger one of y and -y
+//
+// Coordinates and the order c1 ‖ c0 of Fq2 elements are the same, so the points are decoded by
+// `bn254::encoding` after mapping the flags. Keys and proofs with commitments (gnark's
+// `frontend.Committer`) are not supported.
+//
+// All points are checked to be on the curve and in the prime order subgroup.
+//
+
+use crate::bn254::encoding::{g1_from_bytes, g2_from_bytes, EncodingError, FQ_BYTES};
+use ark_bn254::Bn254;
+use ark_ec::AffineRepr;
+use ark_ff::{One, PrimeField, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use core::fmt;
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde::Deserialize;
+
+const GNARK_FLAG_MASK: u8 = 0xc0;
+const GNARK_UNCOMPRESSED: u8 = 0x00;
+const GNARK_COMPRESSED_INFINITY: u8 = 0x40;
+const GNARK_COMPRESSED_SMALLEST: u8 = 0x80;
+const GNARK_COMPRESSED_LARGEST: u8 = 0xc0;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    Json(String),
+    /// The protocol or curve of a snarkjs file is not the expected one over BN254.
+    UnsupportedProtocol(String),
+    InvalidNumber(String),
+    /// A snarkjs point with z other than 0 or 1.
+    InvalidProjectivePoint,
+    /// The number of public inputs does not match the verifying key.
+    InvalidPublicInputCount { expected: usize, actual: usize },
+    Encoding(EncodingError),
+    NotInSubgroup,
+    /// gnark keys and proofs with commitments.
+    UnsupportedCommitments,
+    UnexpectedEnd,
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Json(e) => write!(f, "invalid json: {}", e),
+            LoadError::UnsupportedProtocol(protocol) => {
+                write!(f, "unsupported protocol or curve {}", protocol)
+            }
+            LoadError::InvalidNumber(s) => write!(f, "invalid number {:?}", s),
+            LoadError::InvalidProjectivePoint => write!(f, "projective point with z other than 0 or 1"),
+            LoadError::InvalidPublicInputCount { expected, actual } => {
+                write!(f, "expected {} public inputs, got {}", expected, actual)
+            }
+            LoadError::Encoding(e) => write!(f, "{}", e),
+            LoadError::NotInSubgroup => write!(f, "point is not in the prime order subgroup"),
+            LoadError::UnsupportedCommitments => write!(f, "commitments are not supported"),
+            LoadError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            LoadError::TrailingBytes(n) => write!(f, "{} trailing bytes", n),
+        }
+    }
+}
+
+impl From<EncodingError> for LoadError {
+    fn from(e: EncodingError) -> Self { LoadError::Encoding(e) }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> Self { LoadError::Json(e.to_string()) }
+}
+
+fn check_subgroup<P: AffineRepr>(p: P) -> Result<P, LoadError> {
+    if !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(LoadError::NotInSubgroup);
+    }
+    Ok(p)
+}
+
+// Parses a decimal string smaller than the modulus of F
+pub(crate) fn from_decimal<F: PrimeField>(s: &str) -> Result<F, LoadError> {
+    let value = BigUint::from_str_radix(s, 10).map_err(|_| LoadError::InvalidNumber(s.to_string()))?;
+    if value >= F::MODULUS.into() {
+        return Err(EncodingError::NotInField.into());
+    }
+    Ok(F::from(value))
+}
+
+#[derive(Deserialize)]
+struct SnarkjsVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    vk_alpha_1: [String; 3],
+    vk_beta_2: [[String; 2]; 3],
+    vk_gamma_2: [[String; 2]; 3],
+    vk_delta_2: [[String; 2]; 3],
+    #[serde(rename = "IC")]
+    ic: Vec<[String; 3]>,
+}
+
+#[derive(Deserialize)]
+struct SnarkjsProof {
+    protocol: String,
+    curve: String,
+    pi_a: [String; 3],
+    pi_b: [[String; 2]; 3],
+    pi_c: [String; 3],
+}
+
+pub(crate) fn check_snarkjs_protocol(
+    expected: &str,
+    protocol: &str,
+    curve: &str,
+) -> Result<(), LoadError> {
+    if protocol != expected || curve != "bn128" {
+        return Err(LoadError::UnsupportedProtocol(format!("{}/{}", protocol, curve)));
+    }
+    Ok(())
+}
+
+// Returns whether the point is the point at infinity
+fn snarkjs_is_infinity(z_is_zero: bool, z_is_one: bool) -> Result<bool, LoadError> {
+    match (z_is_zero, z_is_one) {
+        (true, _) => Ok(true),
+        (_, true) => Ok(false),
+        _ => Err(LoadError::InvalidProjectivePoint),
+    }
+}
+
+pub(crate) fn snarkjs_g1(p: &[String; 3]) -> Result<ark_bn254::G1Affine, LoadError> {
+    let z: ark_bn254::Fq = from_decimal(&p[2])?;
+    if snarkjs_is_infinity(z.is_zero(), z.is_one())? {
+        return Ok(ark_bn254::G1Affine::zero());
+    }
+    let p = ark_bn254::G1Affine::new_unchecked(from_decimal(&p[0])?, from_decimal(&p[1])?);
+    if !p.is_on_curve() {
+        return Err(EncodingError::NotOnCurve.into());
+    }
+    check_subgroup(p)
+}
+
+fn snarkjs_fq2(a: &[String; 2]) -> Result<ark_bn254::Fq2, LoadError> {
+    Ok(ark_bn254::Fq2::new(from_decimal(&a[0])?, from_decimal(&a[1])?))
+}
+
+pub(crate) fn snarkjs_g2(p: &[[String; 2]; 3]) -> Result<ark_bn254::G2Affine, LoadError> {
+    let z = snarkjs_fq2(&p[2])?;
+    if snarkjs_is_infinity(z.is_zero(), z.is_one())? {
+        return Ok(ark_bn254::G2Affine::zero());
+    }
+    let p = ark_bn254::G2Affine::new_unchecked(snarkjs_fq2(&p[0])?, snarkjs_fq2(&p[1])?);
+    if !p.is_on_curve() {
+        return Err(EncodingError::NotOnCurve.into());
+    }
+    check_subgroup(p)
+}
+
+/// Loads the `verification_key.json` of snarkjs.
+pub fn vk_from_snarkjs_json(json: &str) -> Result<VerifyingKey<Bn254>, LoadError> {
+    let vk: SnarkjsVerifyingKey = serde_json::from_str(json)?;
+    check_snarkjs_protocol("groth16", &vk.protocol, &vk.curve)?;
+    if vk.ic.len() != vk.n_public + 1 {
+        return Err(LoadError::InvalidPublicInputCount {
+            expected: vk.n_public,
+            actual: vk.ic.len().saturating_sub(1),
+        });
+    }
+    Ok(VerifyingKey {
+        alpha_g1: snarkjs_g1(&vk.vk_alpha_1)?,
+        beta_g2: snarkjs_g2(&vk.vk_beta_2)?,
+        gamma_g2: snarkjs_g2(&vk.vk_gamma_2)?,
+        delta_g2: snarkjs_g2(&vk.vk_delta_2)?,
+        gamma_abc_g1: vk.ic.iter().map(snarkjs_g1).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Loads the `proof.json` of snarkjs.
+pub fn proof_from_snarkjs_json(json: &str) -> Result<Proof<Bn254>, LoadError> {
+    let proof: SnarkjsProof = serde_json::from_str(json)?;
+    check_snarkjs_protocol("groth16", &proof.protocol, &proof.curve)?;
+    Ok(Proof {
+        a: snarkjs_g1(&proof.pi_a)?,
+        b: snarkjs_g2(&proof.pi_b)?,
+        c: snarkjs_g1(&proof.pi_c)?,
+    })
+}
+
+/// Loads the `public.json` of snarkjs.
+pub fn public_inputs_from_snarkjs_json(json: &str) -> Result<Vec<ark_bn254::Fr>, LoadError> {
+    let inputs: Vec<String> = serde_json::from_str(json)?;
+    inputs.iter().map(|s| from_decimal(s)).collect()
+}
+
+// Reads gnark's binary encoding front to back
+struct GnarkReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> GnarkReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], LoadError> {
+        if self.bytes.len() < n {
+            return Err(LoadError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<usize, LoadError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    // Returns the bytes of a point of `size` coordinates in the flags of `bn254::encoding`, and
+    // whether they are compressed
+    fn point(&mut self, size: usize) -> Result<(Vec<u8>, bool), LoadError> {
+        let flags = *self.bytes.first().ok_or(LoadError::UnexpectedEnd)? & GNARK_FLAG_MASK;
+        if flags == GNARK_UNCOMPRESSED {
+            let mut bytes = self.take(2 * size * FQ_BYTES)?.to_vec();
+            // the point at infinity of `bn254::encoding`
+            if bytes.iter().all(|b| *b == 0) {
+                bytes[0] = 0x40;
+            }
+            return Ok((bytes, false));
+        }
+
+        let mut bytes = self.take(size * FQ_BYTES)?.to_vec();
+        bytes[0] &= !GNARK_FLAG_MASK;
+        bytes[0] |= match flags {
+            GNARK_COMPRESSED_INFINITY => 0x40,
+            GNARK_COMPRESSED_SMALLEST => 0x00,
+            GNARK_COMPRESSED_LARGEST => 0x80,
+            _ => unreachable!(),
+        };
+        Ok((bytes, true))
+    }
+
+    fn g1(&mut self) -> Result<ark_bn254::G1Affine, LoadError> {
+        let (bytes, compressed) = self.point(1)?;
+        check_subgroup(g1_from_bytes(&bytes, compressed)?)
+    }
+
+    fn g2(&mut self) -> Result<ark_bn254::G2Affine, LoadError> {
+        let (bytes, compressed) = self.point(2)?;
+        check_subgroup(g2_from_bytes(&bytes, compressed)?)
+    }
+
+    fn g1_vec(&mut self) -> Result<Vec<ark_bn254::G1Affine>, LoadError> {
+        (0..self.u32()?).map(|_| self.g1()).collect()
+    }
+
+    fn fr(&mut self) -> Result<ark_bn254::Fr, LoadError> {
+        let value = BigUint::from_bytes_be(self.take(32)?);
+        if value >= BigUint::from(ark_bn254::Fr::MODULUS) {
+            return Err(EncodingError::NotInField.into());
+        }
+        Ok(ark_bn254::Fr::from(value))
+    }
+
+    fn is_empty(&self) -> bool { self.bytes.is_empty() }
+
+    fn finish(&self) -> Result<(), LoadError> {
+        if !self.is_empty() {
+            return Err(LoadError::TrailingBytes(self.bytes.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Loads a verifying key written by gnark's `VerifyingKey.WriteTo` or `WriteRawTo`.
+///
+/// [α]₁, [β]₁, [β]₂, [γ]₂, [δ]₁, [δ]₂ and the length-prefixed [K]₁, which are the `gamma_abc_g1`
+/// of arkworks. Newer versions of gnark append the commitment keys, which have to be empty.
+pub fn vk_from_gnark_bytes(bytes: &[u8]) -> Result<VerifyingKey<Bn254>, LoadError> {
+    let mut reader = GnarkReader { bytes };
+    let alpha_g1 = reader.g1()?;
+    let _beta_g1 = reader.g1()?;
+    let beta_g2 = reader.g2()?;
+    let gamma_g2 = reader.g2()?;
+    let _delta_g1 = reader.g1()?;
+    let delta_g2 = reader.g2()?;
+    let gamma_abc_g1 = reader.g1_vec()?;
+    if !reader.is_empty() {
+        // PublicAndCommitmentCommitted and CommitmentKeys
+        if reader.u32()? != 0 || reader.u32()? != 0 {
+            return Err(LoadError::UnsupportedCommitments);
+        }
+    }
+    reader.finish()?;
+    Ok(VerifyingKey { alpha_g1, beta_g2, gamma_g2, delta_g2, gamma_abc_g1 })
+}
+
+/// Loads a proof written by gnark's `Proof.WriteTo` or `WriteRawTo`.
+///
+/// Ar, Bs and Krs, which are `a`, `b` and `c` of arkworks. Newer versions of gnark append the
+/// commitments, which have to be empty, and their proof of knowledge.
+pub fn proof_from_gnark_bytes(bytes: &[u8]) -> Result<Proof<Bn254>, LoadError> {
+    let mut reader = GnarkReader { bytes };
+    let a = reader.g1()?;
+    let b = reader.g2()?;
+    let c = reader.g1()?;
+    if !reader.is_empty() {
+        if reader.u32()? != 0 {
+            return Err(LoadError::UnsupportedCommitments);
+        }
+        let _commitment_pok = reader.g1()?;
+    }
+    reader.finish()?;
+    Ok(Proof { a, b, c })
+}
+
+/// Loads the public inputs of a witness written by gnark's `Witness.MarshalBinary`, usually of
+/// `Witness.Public()`.
+///
+/// The number of public and secret elements as u32, then the length-prefixed vector of 32-byte
+/// big-endian field elements, the public ones first.
+pub fn public_inputs_from_gnark_bytes(bytes: &[u8]) -> Result<Vec<ark_bn254::Fr>, LoadError> {
+    let mut reader = GnarkReader { bytes };
+    let n_public = reader.u32()?;
+    let n_secret = reader.u32()?;
+    let n = reader.u32()?;
+    if n != n_public + n_secret {
+        return Err(LoadError::InvalidPublicInputCount { expected: n_public + n_secret, actual: n });
+    }
+    let inputs = (0..n).map(|_| reader.fr()).collect::<Result<Vec<_>, _>>()?;
+    reader.finish()?;
+    Ok(inputs[..n_public].to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execute_script_without_stack_limit;
+    use crate::groth16::hints::Hints;
+    use crate::groth16::test::DummyCircuit;
+    use crate::groth16::verifier::Verifier;
+    use crate::treepp::*;
+    use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+    use ark_groth16::Groth16;
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use serde_json::{json, Value};
+
+    fn groth16_proof() -> (VerifyingKey<Bn254>, Proof<Bn254>, Vec<ark_bn254::Fr>) {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let circuit = DummyCircuit::<ark_bn254::Fr> {
+            a: Some(ark_bn254::Fr::rand(&mut prng)),
+            b: Some(ark_bn254::Fr::rand(&mut prng)),
+            num_variables: 10,
+            num_constraints: 1 << 6,
+        };
+        let (pk, vk) = Groth16::<Bn254>::setup(circuit, &mut prng).unwrap();
+        let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut prng).unwrap();
+        (vk, proof, vec![circuit.a.unwrap() * circuit.b.unwrap()])
+    }
+
+    fn decimal<F: PrimeField>(a: F) -> String { Into::<BigUint>::into(a).to_string() }
+
+    fn snarkjs_g1_json(p: ark_bn254::G1Affine) -> Value {
+        if p.infinity {
+            return json!(["0", "1", "0"]);
+        }
+        json!([decimal(p.x), decimal(p.y), "1"])
+    }
+
+    fn snarkjs_g2_json(p: ark_bn254::G2Affine) -> Value {
+        json!([
+            [decimal(p.x.c0), decimal(p.x.c1)],
+            [decimal(p.y.c0), decimal(p.y.c1)],
+            ["1", "0"]
+        ])
+    }
+
+    // In the layout of snarkjs, including the fields which are not read
+    fn snarkjs_json(
+        vk: &VerifyingKey<Bn254>,
+        proof: &Proof<Bn254>,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> (String, String, String) {
+        let vk = json!({
+            "protocol": "groth16",
+            "curve": "bn128",
+            "nPublic": vk.gamma_abc_g1.len() - 1,
+            "vk_alpha_1": snarkjs_g1_json(vk.alpha_g1),
+            "vk_beta_2": snarkjs_g2_json(vk.beta_g2),
+            "vk_gamma_2": snarkjs_g2_json(vk.gamma_g2),
+            "vk_delta_2": snarkjs_g2_json(vk.delta_g2),
+            "vk_alphabeta_12": [],
+            "IC": vk.gamma_abc_g1.iter().map(|p| snarkjs_g1_json(*p)).collect::<Vec<_>>(),
+        });
+        let proof = json!({
+            "pi_a": snarkjs_g1_json(proof.a),
+            "pi_b": snarkjs_g2_json(proof.b),
+            "pi_c": snarkjs_g1_json(proof.c),
+            "protocol": "groth16",
+            "curve": "bn128",
+        });
+        let public_inputs: Vec<_> = public_inputs.iter().map(|x| decimal(*x)).collect();
+        (vk.to_string(), proof.to_string(), json!(public_inputs).to_string())
+    }
+
+    // gnark-crypto's `marshal.go` for BN254, written independently of `bn254::encoding` so that a
+    // misread layout cannot be hidden by the decoder: 32-byte big-endian coordinates, Fq2 as
+    // A1 ‖ A0, and the flags in the top two bits of x
+    fn gnark_be(a: BigUint) -> Vec<u8> {
+        let bytes = a.to_bytes_be();
+        [vec![0u8; 32 - bytes.len()], bytes].concat()
+    }
+
+    // y > -y as integers, on A1 for Fq2 unless it is zero
+    fn gnark_largest(y: ark_bn254::Fq) -> bool { BigUint::from(y) > BigUint::from(-y) }
+
+    fn gnark_compressed(mut x: Vec<u8>, largest: bool) -> Vec<u8> {
+        x[0] |= if largest { 0xc0 } else { 0x80 };
+        x
+    }
+
+    fn gnark_g1(p: ark_bn254::G1Affine, compressed: bool) -> Vec<u8> {
+        let x = gnark_be(p.x.into());
+        match (compressed, p.infinity) {
+            (false, true) => vec![0u8; 64],
+            (false, false) => [x, gnark_be(p.y.into())].concat(),
+            (true, true) => [vec![0x40], vec![0u8; 31]].concat(),
+            (true, false) => gnark_compressed(x, gnark_largest(p.y)),
+        }
+    }
+
+    fn gnark_g2(p: ark_bn254::G2Affine, compressed: bool) -> Vec<u8> {
+        let fq2 = |a: ark_bn254::Fq2| [gnark_be(a.c1.into()), gnark_be(a.c0.into())].concat();
+        let largest =
+            if p.y.c1.is_zero() { gnark_largest(p.y.c0) } else { gnark_largest(p.y.c1) };
+        match (compressed, p.infinity) {
+            (false, true) => vec![0u8; 128],
+            (false, false) => [fq2(p.x), fq2(p.y)].concat(),
+            (true, true) => [vec![0x40], vec![0u8; 63]].concat(),
+            (true, false) => gnark_compressed(fq2(p.x), largest),
+        }
+    }
+
+    // In the layout of gnark with the commitment sections of newer versions
+    fn gnark_bytes(
+        vk: &VerifyingKey<Bn254>,
+        proof: &Proof<Bn254>,
+        public_inputs: &[ark_bn254::Fr],
+        compressed: bool,
+    ) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let mut vk_bytes = [
+            gnark_g1(vk.alpha_g1, compressed),
+            gnark_g1(ark_bn254::G1Affine::generator(), compressed),
+            gnark_g2(vk.beta_g2, compressed),
+            gnark_g2(vk.gamma_g2, compressed),
+            gnark_g1(ark_bn254::G1Affine::generator(), compressed),
+            gnark_g2(vk.delta_g2, compressed),
+            (vk.gamma_abc_g1.len() as u32).to_be_bytes().to_vec(),
+        ]
+        .concat();
+        for p in vk.gamma_abc_g1.iter() {
+            vk_bytes.extend(gnark_g1(*p, compressed));
+        }
+        vk_bytes.extend([0u8; 8]);
+
+        let proof_bytes = [
+            gnark_g1(proof.a, compressed),
+            gnark_g2(proof.b, compressed),
+            gnark_g1(proof.c, compressed),
+            vec![0u8; 4],
+            gnark_g1(ark_bn254::G1Affine::zero(), compressed),
+        ]
+        .concat();
+
+        let mut witness_bytes = [public_inputs.len() as u32, 0, public_inputs.len() as u32]
+            .iter()
+            .flat_map(|n| n.to_be_bytes())
+            .collect::<Vec<_>>();
+        for x in public_inputs {
+            witness_bytes.extend(gnark_be((*x).into()));
+        }
+        (vk_bytes, proof_bytes, witness_bytes)
+    }
+
+    #[test]
+    fn test_load_snarkjs_json() {
+        let (vk, proof, public_inputs) = groth16_proof();
+        let (vk_json, proof_json, public_json) = snarkjs_json(&vk, &proof, &public_inputs);
+
+        let loaded_vk = vk_from_snarkjs_json(&vk_json).unwrap();
+        let loaded_proof = proof_from_snarkjs_json(&proof_json).unwrap();
+        let loaded_inputs = public_inputs_from_snarkjs_json(&public_json).unwrap();
+        assert_eq!(loaded_vk, vk);
+        assert_eq!(loaded_proof, proof);
+        assert_eq!(loaded_inputs, public_inputs);
+
+        let script = script! {
+            { Hints::new(&loaded_inputs, &loaded_proof, &loaded_vk).push() }
+            { Verifier::verify_proof(&loaded_inputs, &loaded_proof, &loaded_vk) }
+        };
+        let exec_result = execute_script_without_stack_limit(script);
+        assert!(exec_result.success);
+
+        // the public inputs of the fflonk reference
+        let fflonk_inputs =
+            public_inputs_from_snarkjs_json(include_str!("../fflonk/circom_ref/public.json")).unwrap();
+        assert_eq!(fflonk_inputs[0], ark_bn254::Fr::from(246513590391103489634602289097178521809u128));
+        assert_eq!(fflonk_inputs[1], ark_bn254::Fr::from(138371009144214353742010089705444713455u128));
+    }
+
+    #[test]
+    fn test_load_snarkjs_json_errors() {
+        let (vk, proof, public_inputs) = groth16_proof();
+        let (vk_json, proof_json, _) = snarkjs_json(&vk, &proof, &public_inputs);
+
+        assert!(matches!(
+            vk_from_snarkjs_json(include_str!("../fflonk/circom_ref/verification_key.json")),
+            Err(LoadError::Json(_))
+        ));
+        assert_eq!(
+            proof_from_snarkjs_json(&proof_json.replace("groth16", "plonk")),
+            Err(LoadError::UnsupportedProtocol("plonk/bn128".to_string()))
+        );
+
+        let mut value: Value = serde_json::from_str(&vk_json).unwrap();
+        value["nPublic"] = json!(2);
+        assert_eq!(
+            vk_from_snarkjs_json(&value.to_string()),
+            Err(LoadError::InvalidPublicInputCount { expected: 2, actual: 1 })
+        );
+
+        let mut value: Value = serde_json::from_str(&proof_json).unwrap();
+        value["pi_a"][1] = json!(decimal(proof.a.y + ark_bn254::Fq::from(1u64)));
+        assert_eq!(
+            proof_from_snarkjs_json(&value.to_string()),
+            Err(LoadError::Encoding(EncodingError::NotOnCurve))
+        );
+        value["pi_a"][1] = json!(BigUint::from(ark_bn254::Fq::MODULUS).to_string());
+        assert_eq!(
+            proof_from_snarkjs_json(&value.to_string()),
+            Err(LoadError::Encoding(EncodingError::NotInField))
+        );
+        value["pi_a"][1] = json!("0x1");
+        assert_eq!(
+            proof_from_snarkjs_json(&value.to_string()),
+            Err(LoadError::InvalidNumber("0x1".to_string()))
+        );
+
+        // a point on the twist but not in the prime order subgroup
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let b = loop {
+            let x = ark_bn254::Fq2::rand(&mut prng);
+            if let Some(p) = ark_bn254::G2Affine::get_point_from_x_unchecked(x, false) {
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    break p;
+                }
+            }
+        };
+        let mut value: Value = serde_json::from_str(&proof_json).unwrap();
+        value["pi_b"] = snarkjs_g2_json(b);
+        assert_eq!(proof_from_snarkjs_json(&value.to_string()), Err(LoadError::NotInSubgroup));
+    }
+
+    #[test]
+    fn test_gnark_generators() {
+        // the generators in gnark's compressed encoding: y = 2 and y.A1 are the smaller roots
+        let g1 = "8000000000000000000000000000000000000000000000000000000000000001";
+        let g2 = "998e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                  1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed";
+        assert_eq!(hex::encode(gnark_g1(ark_bn254::G1Affine::generator(), true)), g1);
+        assert_eq!(hex::encode(gnark_g2(ark_bn254::G2Affine::generator(), true)), g2);
+
+        let bytes = hex::decode(g1).unwrap();
+        assert_eq!(GnarkReader { bytes: &bytes }.g1().unwrap(), ark_bn254::G1Affine::generator());
+        let bytes = hex::decode(g2).unwrap();
+        assert_eq!(GnarkReader { bytes: &bytes }.g2().unwrap(), ark_bn254::G2Affine::generator());
+
+        let mut bytes = hex::decode(g1).unwrap();
+        bytes[0] |= 0x40;
+        assert_eq!(
+            GnarkReader { bytes: &bytes }.g1().unwrap(),
+            -ark_bn254::G1Affine::generator()
+        );
+    }
+
+    #[test]
+    fn test_load_gnark_bytes() {
+        let (vk, proof, public_inputs) = groth16_proof();
+        for compressed in [true, false] {
+            let (vk_bytes, proof_bytes, witness_bytes) =
+                gnark_bytes(&vk, &proof, &public_inputs, compressed);
+
+            assert_eq!(vk_from_gnark_bytes(&vk_bytes).unwrap(), vk);
+            assert_eq!(proof_from_gnark_bytes(&proof_bytes).unwrap(), proof);
+            assert_eq!(public_inputs_from_gnark_bytes(&witness_bytes).unwrap(), public_inputs);
+
+            // older versions of gnark without the commitment sections
+            let g1_size = if compressed { 32 } else { 64 };
+            assert_eq!(vk_from_gnark_bytes(&vk_bytes[..vk_bytes.len() - 8]).unwrap(), vk);
+            assert_eq!(
+                proof_from_gnark_bytes(&proof_bytes[..proof_bytes.len() - 4 - g1_size]).unwrap(),
+                proof
+            );
+
+            assert_eq!(
+                vk_from_gnark_bytes(&vk_bytes[..vk_bytes.len() - 9]),
+                Err(LoadError::UnexpectedEnd)
+            );
+            assert_eq!(
+                proof_from_gnark_bytes(&[proof_bytes.clone(), vec![0]].concat()),
+                Err(LoadError::TrailingBytes(1))
+            );
+            let mut with_commitment = vk_bytes.clone();
+            *with_commitment.last_mut().unwrap() = 1;
+            assert_eq!(vk_from_gnark_bytes(&with_commitment), Err(LoadError::UnsupportedCommitments));
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod constants;
+pub mod hints;
+pub mod loader;
 pub mod offchain_checker;
 
 
@@ -4,7 +4,7 @@ use crate::bn254::fq::Fq;
 
 use crate::groth16::constants::LAMBDA;
 use ark_ff::UniformRand;
-use ark_ff::{Field, One};
+use ark_ff::{Field, One, PrimeField};
 use num_bigint::BigUint;
 use num_traits::{Num, ToPrimitive};
 use rand::SeedableRng;
@@ -59,6 +59,38 @@ fn tonelli_shanks_cubic(
     r
 }
 
+// The 27-th root of unity w which is cubic non-residue, wi of `compute_c_wi` is 1, w or w^2.
+// It is sampled from a fixed seed, so a verifier can rely on the same w.
+pub fn compute_w() -> ark_bn254::Fq12 {
+    let p = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+    let s = 3_u32;
+    let t = (p.pow(12_u32) - 1_u32) / 3_u32.pow(s);
+    let cofactor_cubic = 3_u32.pow(s - 1) * &t;
+
+    // sample a proper scalar w which is cubic non-residue
+    let mut prng = ChaCha20Rng::seed_from_u64(0);
+    let (mut w, mut z) = (ark_bn254::Fq12::ONE, ark_bn254::Fq12::ONE);
+    while w == ark_bn254::Fq12::ONE {
+        // choose z which is 3-th non-residue
+        let mut legendre = ark_bn254::Fq12::ONE;
+        while legendre == ark_bn254::Fq12::ONE {
+            z = ark_bn254::Fq12::rand(&mut prng);
+            legendre = z.pow(cofactor_cubic.to_u64_digits());
+        }
+        // obtain w which is t-th power of z
+        w = z.pow(t.to_u64_digits());
+    }
+    w
+}
+
+// Whether f is an r-th residue, which the result of the miller loop is exactly when the pairing
+// check holds. Only then `compute_c_wi` finds c.
+pub fn is_r_th_residue(f: ark_bn254::Fq12) -> bool {
+    let p = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+    let h = (p.pow(12_u32) - 1_u32) / BigUint::from(ark_bn254::Fr::MODULUS);
+    f.pow(h.to_u64_digits()) == ark_bn254::Fq12::ONE
+}
+
 // Finding C
 // refer from Algorithm 5 of "On Proving Pairings"(https://eprint.iacr.org/2024/640.pdf)
 pub fn compute_c_wi(f: ark_bn254::Fq12) -> (ark_bn254::Fq12, ark_bn254::Fq12) {
@@ -76,41 +108,22 @@ pub fn compute_c_wi(f: ark_bn254::Fq12) -> (ark_bn254::Fq12, ark_bn254::Fq12) {
     let d = 3_u32;
     let mm = &m / d;
 
-    let mut prng = ChaCha20Rng::seed_from_u64(0);
     let cofactor_cubic = 3_u32.pow(s - 1) * &t;
 
-    // make f is r-th residue, but it's not cubic residue
+    // make sure f is r-th residue
     assert_eq!(f.pow(h.to_u64_digits()), ark_bn254::Fq12::ONE);
-    assert_ne!(f.pow(cofactor_cubic.to_u64_digits()), ark_bn254::Fq12::ONE);
 
-    // sample a proper scalar w which is cubic non-residue
-    let w = {
-        let (mut w, mut z) = (ark_bn254::Fq12::ONE, ark_bn254::Fq12::ONE);
-        while w == ark_bn254::Fq12::ONE {
-            // choose z which is 3-th non-residue
-            let mut legendre = ark_bn254::Fq12::ONE;
-            while legendre == ark_bn254::Fq12::ONE {
-                z = ark_bn254::Fq12::rand(&mut prng);
-                legendre = z.pow(cofactor_cubic.to_u64_digits());
-            }
-            // obtain w which is t-th power of z
-            w = z.pow(t.to_u64_digits());
-        }
-        w
-    };
+    let w = compute_w();
     // make sure 27-th root w, is 3-th non-residue and r-th residue
     assert_ne!(w.pow(cofactor_cubic.to_u64_digits()), ark_bn254::Fq12::ONE);
     assert_eq!(w.pow(h.to_u64_digits()), ark_bn254::Fq12::ONE);
 
-    // just two option, w and w^2, since w^3 must be cubic residue, leading f*w^3 must not be cubic residue
-    let mut wi = w;
-    if (f * wi).pow(cofactor_cubic.to_u64_digits()) != ark_bn254::Fq12::ONE {
-        assert_eq!(
-            (f * w * w).pow(cofactor_cubic.to_u64_digits()),
-            ark_bn254::Fq12::ONE
-        );
-        wi = w * w;
-    }
+    // f * wi is a cubic residue for exactly one of 1, w and w^2, as w^3 is a cubic residue. f
+    // itself may already be one, then wi = 1.
+    let wi = [ark_bn254::Fq12::ONE, w, w * w]
+        .into_iter()
+        .find(|wi| (f * wi).pow(cofactor_cubic.to_u64_digits()) == ark_bn254::Fq12::ONE)
+        .unwrap();
     assert_eq!(wi.pow(h.to_u64_digits()), ark_bn254::Fq12::ONE);
 
     assert_eq!(LAMBDA.clone(), d * &mm * &r);
@@ -121,18 +134,15 @@ pub fn compute_c_wi(f: ark_bn254::Fq12) -> (ark_bn254::Fq12, ark_bn254::Fq12) {
     let r_inv = r.modinv(&h).unwrap();
     assert_ne!(r_inv, BigUint::one());
     let f2 = f1.pow(r_inv.to_u64_digits());
-    assert_ne!(f2, ark_bn254::Fq12::ONE);
 
     // m'-th root of f, say f3
     let mm_inv = mm.modinv(&(r * h)).unwrap();
     assert_ne!(mm_inv, BigUint::one());
     let f3 = f2.pow(mm_inv.to_u64_digits());
     assert_eq!(f3.pow(cofactor_cubic.to_u64_digits()), ark_bn254::Fq12::ONE);
-    assert_ne!(f3, ark_bn254::Fq12::ONE);
 
     // d-th (cubic) root, say c
     let c = tonelli_shanks_cubic(f3, w, s, t, k);
-    assert_ne!(c, ark_bn254::Fq12::ONE);
     assert_eq!(c.pow(LAMBDA.to_u64_digits()), f * wi);
 
     (c, wi)
@@ -1,17 +1,32 @@
-use crate::{execute_script_as_chunks, execute_script_without_stack_limit};
-use crate::groth16::verifier::Verifier;
+use crate::chunker::limits::{
+    check_chunk_limits, LimitViolation, MAX_CHUNK_SIZE, MAX_STACK_ELEMENTS,
+};
+use crate::{
+    execute_script_as_chunks_without_stack_limit, execute_script_without_stack_limit, treepp::*,
+};
+use crate::bigint::U254;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fr::Fr;
+use crate::bn254::msm::g1_affine_push;
+use crate::bn254::pairing::Pairing as ScriptPairing;
+use crate::bn254::utils;
+use crate::groth16::hints::{pairing_points, Hints};
+use crate::groth16::offchain_checker::compute_w;
+use crate::groth16::verifier::{ProofChecks, Verifier};
 use ark_bn254::Bn254;
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
 use ark_groth16::Groth16;
 use ark_relations::lc;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::{end_timer, start_timer, test_rng, UniformRand};
+use num_bigint::BigUint;
+use num_traits::Num;
 use rand::{RngCore, SeedableRng};
 
 #[derive(Copy)]
-struct DummyCircuit<F: PrimeField> {
+pub(crate) struct DummyCircuit<F: PrimeField> {
     pub a: Option<F>,
     pub b: Option<F>,
     pub num_variables: usize,
@@ -78,12 +93,152 @@ fn test_groth16_verifier() {
     println!("groth16::test_verify_proof = {} bytes", script.len());
 
     let start = start_timer!(|| "execute_script");
-    let exec_result = execute_script_without_stack_limit(script);
+    let exec_result = execute_script_without_stack_limit(script! {
+        { Hints::new(&vec![c], &proof, &vk).push() }
+        { script }
+    });
     end_timer!(start);
 
     assert!(exec_result.success);
 }
 
+#[test]
+fn test_groth16_verifier_with_proof_checks() {
+    type E = Bn254;
+    let k = 6;
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = DummyCircuit::<<E as Pairing>::ScalarField> {
+        a: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        b: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        num_variables: 10,
+        num_constraints: 1 << k,
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit, &mut rng).unwrap();
+
+    let c = circuit.a.unwrap() * circuit.b.unwrap();
+
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let unchecked = Verifier::verify_proof(&vec![c], &proof, &vk).len();
+    for (name, checks) in [
+        ("field_range", ProofChecks { field_range: true, ..Default::default() }),
+        ("on_curve", ProofChecks { on_curve: true, ..Default::default() }),
+        ("subgroup", ProofChecks { subgroup: true, ..Default::default() }),
+    ] {
+        let checked = Verifier::verify_proof_with_checks(&vec![c], &proof, &vk, checks).len();
+        println!("groth16::ProofChecks::{} = {} bytes", name, checked - unchecked);
+    }
+
+    let script = Verifier::verify_proof_with_checks(&vec![c], &proof, &vk, ProofChecks::all());
+    println!("groth16::test_verify_proof_with_proof_checks = {} bytes", script.len());
+
+    let exec_result = execute_script_without_stack_limit(script! {
+        { Hints::new(&vec![c], &proof, &vk).push() }
+        { script }
+    });
+    assert!(exec_result.success);
+}
+
+#[test]
+fn test_groth16_compiled_verifier() {
+    type E = Bn254;
+    let k = 6;
+    // a fixed seed, so that the proofs below are the same on every run
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+    let circuit = DummyCircuit::<<E as Pairing>::ScalarField> {
+        a: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        b: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        num_variables: 10,
+        num_constraints: 1 << k,
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit, &mut rng).unwrap();
+
+    let start = start_timer!(|| "compile");
+    let verifier = Verifier::compile(&vk);
+    end_timer!(start);
+    println!("groth16::test_compiled_verifier = {} bytes", verifier.len());
+
+    // Each wi = w^i is hit by about a third of the proofs. Of the proofs from the fixed seed the
+    // first one for each wi is used.
+    let proofs = (0..30)
+        .map(|_| {
+            let circuit = DummyCircuit::<<E as Pairing>::ScalarField> {
+                a: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+                b: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+                ..circuit
+            };
+            let c = circuit.a.unwrap() * circuit.b.unwrap();
+            let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+            let wi = Hints::new(&vec![c], &proof, &vk).wi;
+            (c, proof, wi)
+        })
+        .collect::<Vec<_>>();
+
+    // the same script verifies proofs for different public inputs, one for each wi
+    let w = compute_w();
+    for (i, wi) in [<E as Pairing>::TargetField::ONE, w, w * w].into_iter().enumerate() {
+        let (c, proof) = proofs
+            .iter()
+            .find(|(_, _, proof_wi)| *proof_wi == wi)
+            .map(|(c, proof, _)| (*c, proof.clone()))
+            .unwrap_or_else(|| panic!("no proof with wi = w^{}", i));
+
+        let witness = Verifier::witness(&vec![c], &proof, &vk);
+        println!("groth16::test_compiled_verifier witness = {} bytes", witness.len());
+
+        let exec_result = execute_script_without_stack_limit(script! {
+            { witness.clone() }
+            { verifier.clone() }
+        });
+        assert!(exec_result.success, "wi = w^{}", i);
+
+        // another wi doesn't satisfy c^lambda = f * wi
+        let exec_result = execute_script_without_stack_limit(script! {
+            { witness }
+            OP_NOT
+            { verifier.clone() }
+        });
+        assert!(!exec_result.success, "wi = w^{}", i);
+    }
+}
+
+#[test]
+fn test_groth16_compiled_verifier_rejects_unreduced_input() {
+    type E = Bn254;
+    let k = 6;
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = DummyCircuit::<<E as Pairing>::ScalarField> {
+        a: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        b: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        num_variables: 10,
+        num_constraints: 1 << k,
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit, &mut rng).unwrap();
+    let c = circuit.a.unwrap() * circuit.b.unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    // the witness of `Verifier::witness` with c + r in place of c
+    let r = BigUint::from(<E as Pairing>::ScalarField::MODULUS);
+    let montgomery_one = BigUint::from_str_radix(Fr::MONTGOMERY_ONE, 16).unwrap();
+    let unreduced = BigUint::from(c) * montgomery_one % &r + &r;
+    let ([_, p2, _, p4], [_, _, _, q4]) = pairing_points(&vec![c], &proof, &vk);
+    let witness = script! {
+        { ScriptPairing::q4_lines_push(q4) }
+        { U254::push_u32_le(&unreduced.to_u32_digits()) }
+        { g1_affine_push(p2) }
+        { g1_affine_push(p4) }
+        { utils::fq2_push(q4.x) }
+        { utils::fq2_push(q4.y) }
+        { Hints::new(&vec![c], &proof, &vk).push() }
+    };
+
+    let exec_result = execute_script_without_stack_limit(script! {
+        { witness }
+        { Verifier::compile(&vk) }
+    });
+    assert!(!exec_result.success);
+}
+
 #[test]
 fn test_groth16_verifier_as_chunks() {
     type E = Bn254;
@@ -110,8 +265,62 @@ fn test_groth16_verifier_as_chunks() {
     let interval = script.max_op_if_interval();
     println!("Max if interval: {:?} difference: {}, debug info: {}, {}", interval, interval.1 - interval.0, script.debug_info(interval.0), script.debug_info(interval.1));
     let start = start_timer!(|| "execute_script");
-    let exec_result = execute_script_as_chunks(script, 3_000_000, 3_000_000);
+    let script = script! {
+        { Hints::new(&vec![c], &proof, &vk).push() }
+        { script }
+    };
+    let exec_result = execute_script_as_chunks_without_stack_limit(script, 3_000_000, 3_000_000);
     end_timer!(start);
 
     assert!(exec_result.success);
 }
+
+// The verifier doesn't fit the stack limit yet. Chunking it still executes every chunk, it
+// only exceeds the stack limit and the weight of the chunks that take over too large a stack.
+#[test]
+fn test_groth16_verifier_chunk_limits() {
+    type E = Bn254;
+    let k = 6;
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = DummyCircuit::<<E as Pairing>::ScalarField> {
+        a: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        b: Some(<E as Pairing>::ScalarField::rand(&mut rng)),
+        num_variables: 10,
+        num_constraints: 1 << k,
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit, &mut rng).unwrap();
+
+    let c = circuit.a.unwrap() * circuit.b.unwrap();
+
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    let script = script! {
+        { Hints::new(&vec![c], &proof, &vk).push() }
+        { Verifier::verify_proof(&vec![c], &proof, &vk) }
+    };
+
+    let start = start_timer!(|| "check_chunk_limits");
+    let report = check_chunk_limits(script, MAX_CHUNK_SIZE, 0);
+    end_timer!(start);
+
+    println!("{}", report);
+    let mut stack_violations = 0;
+    for chunk in &report.chunks {
+        for violation in &chunk.violations {
+            match violation {
+                LimitViolation::StackElements { count, .. } => {
+                    assert!(*count > MAX_STACK_ELEMENTS);
+                    stack_violations += 1;
+                }
+                LimitViolation::TxWeight(_) => assert!(
+                    chunk.input_elements > MAX_STACK_ELEMENTS,
+                    "chunk {}: {}",
+                    chunk.chunk_index,
+                    violation
+                ),
+                _ => panic!("chunk {}: {}", chunk.chunk_index, violation),
+            }
+        }
+    }
+    assert!(stack_violations > 0);
+    assert!(report.max_stack_elements() > MAX_STACK_ELEMENTS);
+}
@@ -1,33 +1,134 @@
+use crate::bn254::curves::{G1Affine, G1Projective, G2Affine, G2Projective, ProjectiveCurve};
 use crate::bn254::ell_coeffs::G2Prepared;
 use crate::bn254::fp254impl::Fp254Impl;
 use crate::bn254::fq::Fq;
 use crate::bn254::fq12::Fq12;
-use crate::bn254::msm::msm_with_constant_bases;
+use crate::bn254::fq2::Fq2;
+use crate::bn254::fr::Fr;
+use crate::bn254::msm::{g1_affine_push, msm_with_constant_bases, msm_with_scalars_in_stack};
 use crate::bn254::pairing::Pairing;
 use crate::bn254::utils;
 use crate::bn254::utils::fq12_push;
-use crate::groth16::constants::{LAMBDA, P_POW3};
-use crate::groth16::offchain_checker::compute_c_wi;
+use crate::groth16::hints::{pairing_points, Hints};
+use crate::groth16::offchain_checker::compute_w;
 use crate::treepp::{script, Script};
-use ark_bn254::{Bn254, G1Projective};
+use ark_bn254::Bn254;
 use ark_ec::pairing::Pairing as ark_Pairing;
 use ark_ec::short_weierstrass::Projective;
 use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::Field;
 use ark_groth16::{Proof, VerifyingKey};
 use core::ops::Neg;
+use num_bigint::BigUint;
+
+/// In-script validation of the proof points `a`, `b` and `c`, which are the only points of the
+/// pairing check not fixed by the verifying key. The checks only matter where the points come
+/// from the witness, i.e. for [`Verifier::compile`], which reads the proof from the stack and runs
+/// all checks. The other entry points embed the proof as constants into the script, where the
+/// checks only repeat what the script's author can check off-chain, so they are off by default.
+///
+/// Costs, for the whole proof:
+/// - `field_range`: one `Fq::is_field` per pushed coordinate, eight in total, and one
+///   `Fr::is_field` per public input read from the stack. Each is a limb-wise comparison against
+///   the modulus, negligible next to the pairing.
+/// - `on_curve`: `y² = x³ + 3` for `a` and `c`, two `Fq` squarings and a multiplication each, and
+///   `y² = x³ + b'` on the twist for `b`, two `Fq2` squarings and a multiplication. G1 has
+///   cofactor one, so this also proves subgroup membership of `a` and `c`.
+/// - `subgroup`: `[r] b = 0` for `b`, a constant scalar multiplication with 253 G2 doublings and
+///   additions. This dominates all other checks by far.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProofChecks {
+    pub field_range: bool,
+    pub on_curve: bool,
+    pub subgroup: bool,
+}
+
+impl ProofChecks {
+    pub fn all() -> Self {
+        Self {
+            field_range: true,
+            on_curve: true,
+            subgroup: true,
+        }
+    }
+
+    // Input Stack: [x, y]
+    // Output Stack: [x, y]
+    fn g1_script(&self) -> Script {
+        script! {
+            if self.field_range {
+                { Fq::copy(1) }
+                { Fq::is_field() }
+                OP_VERIFY
+                { Fq::copy(0) }
+                { Fq::is_field() }
+                OP_VERIFY
+            }
+            if self.on_curve {
+                { Fq::copy(1) }
+                { Fq::copy(1) }
+                { G1Affine::is_on_curve() }
+                OP_VERIFY
+            }
+        }
+    }
+
+    // Input Stack: [x, y]
+    // Output Stack: [x, y]
+    fn g2_script(&self) -> Script {
+        script! {
+            if self.field_range {
+                for i in (0..4).rev() {
+                    { Fq::copy(i) }
+                    { Fq::is_field() }
+                    OP_VERIFY
+                }
+            }
+            if self.on_curve {
+                { Fq2::copy(2) }
+                { Fq2::copy(2) }
+                { G2Affine::is_on_curve() }
+                OP_VERIFY
+            }
+            if self.subgroup {
+                { Fq2::copy(2) }
+                { Fq2::copy(2) }
+                { G2Affine::into_projective() }
+                { G2Projective::is_in_subgroup() }
+                OP_VERIFY
+            }
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct Verifier;
 
 impl Verifier {
+    // Input Stack: [c, c_inv, i], the final exponentiation hints pushed by `Hints::push`
+    // Output Stack: [OP_TRUE]
+    //
+    // The proof points are constants of the script, not witness data, so they are not checked.
+    // A proof that isn't on the curve or in the subgroup has to be rejected off-chain, or the
+    // script compiled with `Verifier::compile` instead.
     pub fn verify_proof(
         public_inputs: &Vec<<Bn254 as ark_Pairing>::ScalarField>,
         proof: &Proof<Bn254>,
         vk: &VerifyingKey<Bn254>,
     ) -> Script {
-        let (msm_script, msm_g1) = Self::prepare_inputs(public_inputs, vk);
-        Self::verify_proof_with_prepared_inputs(proof, vk, msm_script, msm_g1)
+        Self::verify_proof_with_checks(public_inputs, proof, vk, ProofChecks::default())
+    }
+
+    // Like `Verifier::verify_proof`, but checks the embedded proof points in script. Useful to
+    // measure the cost of the checks, they can't reject anything a check off-chain doesn't.
+    pub fn verify_proof_with_checks(
+        public_inputs: &Vec<<Bn254 as ark_Pairing>::ScalarField>,
+        proof: &Proof<Bn254>,
+        vk: &VerifyingKey<Bn254>,
+        checks: ProofChecks,
+    ) -> Script {
+        let (msm_script, _) = Self::prepare_inputs(public_inputs, vk);
+        Self::verify_proof_with_prepared_inputs_and_checks(proof, vk, msm_script, checks)
     }
 
     pub fn prepare_inputs(
@@ -40,7 +141,7 @@ impl Verifier {
         ]
         .concat();
         let sum_ai_abc_gamma =
-            G1Projective::msm(&vk.gamma_abc_g1, &scalars).expect("failed to calculate msm");
+            ark_bn254::G1Projective::msm(&vk.gamma_abc_g1, &scalars).expect("failed to calculate msm");
         (msm_with_constant_bases(&vk.gamma_abc_g1, &scalars), sum_ai_abc_gamma)
     }
 
@@ -48,34 +149,24 @@ impl Verifier {
         proof: &Proof<Bn254>,
         vk: &VerifyingKey<Bn254>,
         msm_script: Script,
-        msm_g1: Projective<ark_bn254::g1::Config>,
     ) -> Script {
-        let (exp, sign) = if LAMBDA.gt(&P_POW3) {
-            (&*LAMBDA - &*P_POW3, true)
-        } else {
-            (&*P_POW3 - &*LAMBDA, false)
-        };
-
-        // G1/G2 points for pairings
-        let (p1, p2, p3, p4) = (msm_g1.into_affine(), proof.c, vk.alpha_g1, proof.a);
+        Self::verify_proof_with_prepared_inputs_and_checks(proof, vk, msm_script, ProofChecks::default())
+    }
+
+    pub fn verify_proof_with_prepared_inputs_and_checks(
+        proof: &Proof<Bn254>,
+        vk: &VerifyingKey<Bn254>,
+        msm_script: Script,
+        checks: ProofChecks,
+    ) -> Script {
+        // G1/G2 points for pairings, p1 is computed in script by `msm_script`
+        let (p2, p3, p4) = (proof.c, vk.alpha_g1, proof.a);
         let (q1, q2, q3, q4) = (
             vk.gamma_g2.into_group().neg().into_affine(),
             vk.delta_g2.into_group().neg().into_affine(),
             -vk.beta_g2,
             proof.b,
         );
-        let t4 = q4;
-
-        // hint from arkworks
-        let f = Bn254::multi_miller_loop_affine([p1, p2, p3, p4], [q1, q2, q3, q4]).0;
-        let (c, wi) = compute_c_wi(f);
-        let c_inv = c.inverse().unwrap();
-        let hint = if sign {
-            f * wi * (c_inv.pow((exp).to_u64_digits()))
-        } else {
-            f * wi * (c_inv.pow((exp).to_u64_digits()).inverse().unwrap())
-        };
-        assert_eq!(hint, c.pow(P_POW3.to_u64_digits()), "hint isn't correct!");
 
         let q_prepared = vec![
             G2Prepared::from_affine(q1),
@@ -84,6 +175,9 @@ impl Verifier {
             G2Prepared::from_affine(q4),
         ];
         script! {
+            // proofs for verifying final exp, provided by `Hints::push`
+            { hints_toaltstack() }
+
             // constants
             { constants() }
 
@@ -97,30 +191,170 @@ impl Verifier {
             { Fq::roll(1) }
 
             // variants of G1 points
-            { utils::from_eval_point(p2) }
+            { checked_eval_point(p2, &checks) }
             { utils::from_eval_point(p3) }
-            { utils::from_eval_point(p4) }
+            { checked_eval_point(p4, &checks) }
 
             // the only non-fixed G2 point, say q4
             { utils::fq2_push(q4.x) }
             { utils::fq2_push(q4.y) }
+            { checks.g2_script() }
 
-            // proofs for verifying final exp
-            { utils::fq12_push(c) }
-            { utils::fq12_push(c_inv) }
-            { utils::fq12_push(wi) }
+            // c, c_inv, wi
+            { Fq12::fromaltstack() }
+            { Fq12::fromaltstack() }
+            { Fq12::fromaltstack() }
 
-            // accumulator of q4, say t4
-            { utils::fq2_push(t4.x) }
-            { utils::fq2_push(t4.y) }
+            // accumulator of q4, say t4, starts at the copy of q4 on the stack
+            { Fq2::copy(38) }
+            { Fq2::copy(38) }
             // stack: [beta_12, beta_13, beta_22, P1, P2, P3, P4, Q4, c, c_inv, wi, T4]
 
             // 3. verify pairing
-            { check_pairing(&q_prepared, hint) }
+            { check_pairing(&q_prepared) }
+        }
+    }
+
+    // Compiles the verifier for a verifying key once, the proof and the public inputs are read
+    // from the stack. `Verifier::witness` pushes the matching stack for a proof.
+    //
+    // Input Stack: [L(Q4), x_1, ..., x_l, proof.c, proof.a, proof.b, c, c_inv, i]
+    //  L(Q4): line coefficients of proof.b in the miller loop, see `Pairing::q4_lines_push`
+    //  x_1, ..., x_l: public inputs, x_l on top
+    //  c, c_inv, i: the hints for the final exponentiation with wi == w^i, see `Hints::push`
+    // Output Stack: [OP_TRUE]
+    pub fn compile(vk: &VerifyingKey<Bn254>) -> Script {
+        Self::compile_with_checks(vk, ProofChecks::all())
+    }
+
+    pub fn compile_with_checks(vk: &VerifyingKey<Bn254>, checks: ProofChecks) -> Script {
+        let q_prepared = vec![
+            G2Prepared::from_affine(vk.gamma_g2.into_group().neg().into_affine()),
+            G2Prepared::from_affine(vk.delta_g2.into_group().neg().into_affine()),
+            G2Prepared::from_affine(-vk.beta_g2),
+        ];
+
+        script! {
+            { hints_toaltstack() }
+            // [L(Q4), x_1, ..., x_l, proof.c, proof.a, proof.b | c, c_inv, wi]
+
+            // the only non-fixed G2 point, say q4
+            { checks.g2_script() }
+            { Fq2::toaltstack() }
+            { Fq2::toaltstack() }
+
+            // variants of G1 points, p4 and p2
+            { checks.g1_script() }
+            { utils::from_eval_point_in_stack() }
+            { Fq2::toaltstack() }
+            { checks.g1_script() }
+            { utils::from_eval_point_in_stack() }
+            { Fq2::toaltstack() }
+
+            // x_i + r would pass the msm like x_i
+            if checks.field_range {
+                for _ in 1..vk.gamma_abc_g1.len() {
+                    { Fr::is_field() }
+                    OP_VERIFY
+                    { Fr::toaltstack() }
+                }
+                for _ in 1..vk.gamma_abc_g1.len() {
+                    { Fr::fromaltstack() }
+                }
+            }
+
+            // p1 = gamma_abc_g1[0] + x_1 * gamma_abc_g1[1] + ... + x_l * gamma_abc_g1[l]
+            if vk.gamma_abc_g1.len() > 1 {
+                { msm_with_scalars_in_stack(&vk.gamma_abc_g1[1..]) }
+                { G1Projective::push(vk.gamma_abc_g1[0].into_group()) }
+                { G1Projective::add() }
+                { G1Projective::into_affine() }
+            } else {
+                { g1_affine_push(vk.gamma_abc_g1[0]) }
+            }
+            { utils::from_eval_point_in_stack() }
+            { Fq2::toaltstack() }
+            // [L(Q4) | P1, P2, P4, Q4, c, c_inv, wi]
+
+            { constants() }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { utils::from_eval_point(vk.alpha_g1) }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq2::fromaltstack() }
+            { Fq12::fromaltstack() }
+            { Fq12::fromaltstack() }
+            { Fq12::fromaltstack() }
+
+            // accumulator of q4, say t4, starts at the copy of q4 on the stack
+            { Fq2::copy(38) }
+            { Fq2::copy(38) }
+            // stack: [L(Q4), beta_12, beta_13, beta_22, P1, P2, P3, P4, Q4, c, c_inv, wi, T4]
+
+            // verify pairing
+            { check_final_f(Pairing::quad_miller_loop_with_c_wi_and_q4_lines_in_stack(q_prepared)) }
+        }
+    }
+
+    // Pushes the input stack of `Verifier::compile` for a proof
+    pub fn witness(
+        public_inputs: &Vec<<Bn254 as ark_Pairing>::ScalarField>,
+        proof: &Proof<Bn254>,
+        vk: &VerifyingKey<Bn254>,
+    ) -> Script {
+        let ([_, p2, _, p4], [_, _, _, q4]) = pairing_points(public_inputs, proof, vk);
+        script! {
+            { Pairing::q4_lines_push(q4) }
+            for input in public_inputs {
+                { Fr::push_u32_le(&BigUint::from(*input).to_u32_digits()) }
+            }
+            { g1_affine_push(p2) }
+            { g1_affine_push(p4) }
+            { utils::fq2_push(q4.x) }
+            { utils::fq2_push(q4.y) }
+            { Hints::new(public_inputs, proof, vk).push() }
         }
     }
 }
 
+// Same as `utils::from_eval_point`, but the point itself is pushed and checked first. Without
+// any checks the point is not needed and `utils::from_eval_point` is used directly.
+//
+// output on stack:
+//      x' = -p.x / p.y
+//      y' = 1 / p.y
+fn checked_eval_point(p: ark_bn254::G1Affine, checks: &ProofChecks) -> Script {
+    if !checks.field_range && !checks.on_curve {
+        return utils::from_eval_point(p);
+    }
+
+    let py_inv = p.y().unwrap().inverse().unwrap();
+    script! {
+        { Fq::push_u32_le(&BigUint::from(p.x).to_u32_digits()) }
+        { Fq::push_u32_le(&BigUint::from(p.y).to_u32_digits()) }
+        { checks.g1_script() }
+        // [x, y, 1/y]
+        { Fq::push_u32_le(&BigUint::from(py_inv).to_u32_digits()) }
+        // check p.y.inv() is valid
+        { Fq::copy(0) }
+        { Fq::roll(2) }
+        { Fq::mul() }
+        { Fq::push_one() }
+        { Fq::equalverify(1, 0) }
+        // [x, 1/y]
+
+        // -p.x / p.y
+        { Fq::copy(0) }
+        { Fq::roll(2) }
+        { Fq::neg(0) }
+        { Fq::mul() }
+        // [1/y, -x/y]
+        { Fq::roll(1) }
+        // [-x/y, 1/y]
+    }
+}
+
 // Groth16's pairing verifier
 //
 // To verify e(P1,Q1)*e(P2,Q2)*e(P3,Q3)*e(P4,Q4)=1
@@ -132,24 +366,64 @@ impl Verifier {
 //  @P4: P4
 //  @Q4: Q4
 //  @lines: []precompute miller lines for Qi. Only support fixed Qi.
-//  @c: c^lambda = f*w^i
+//  @c: c^lambda = f*wi
 //  @c_inv: inverse of c
-//  @hint: expect final_f
 //
 // verify c^lambda = f * wi, namely c_inv^lambda * f * wi = 1
-pub fn check_pairing(precompute_lines: &Vec<G2Prepared>, hint: ark_bn254::Fq12) -> Script {
-    script! {
-        // Input stack: [beta_12, beta_13, beta_22, P1, P2, P3, P4, Q4, c, c_inv, wi, T4]
-        // Output stack: [final_f]
-        { Pairing::quad_miller_loop_with_c_wi(precompute_lines.to_vec()) }
+pub fn check_pairing(precompute_lines: &Vec<G2Prepared>) -> Script {
+    // Input stack: [beta_12, beta_13, beta_22, P1, P2, P3, P4, Q4, c, c_inv, wi, T4]
+    check_final_f(Pairing::quad_miller_loop_with_c_wi(precompute_lines.to_vec()))
+}
 
-        // check final_f == hint
-        { fq12_push(hint) }
+// Runs the miller loop, which ends with final_f = c_inv^{lambda - p^3} * f * wi, and checks
+// final_f == c^{p^3}, that is c^lambda = f * wi
+//
+// Input stack: [..., c, c_inv, wi, T4]
+// Output stack: [OP_TRUE]
+fn check_final_f(miller_loop: Script) -> Script {
+    script! {
+        { Fq12::copy(28) }
+        { Fq12::frobenius_map(3) }
+        { Fq12::toaltstack() }
+        { miller_loop }
+        // [final_f | c^{p^3}]
+        { Fq12::fromaltstack() }
         { Fq12::equalverify() }
         OP_TRUE
     }
 }
 
+// Input stack: [c, c_inv, i]
+// Output stack: [] and [c, c_inv, wi] on the altstack
+//
+// wi is w^i for the w of `compute_w`, the prover only selects i = 0, 1 or 2. Otherwise any f
+// would pass with wi = c^lambda / f.
+fn hints_toaltstack() -> Script {
+    let w = compute_w();
+    script! {
+        OP_DUP 0 3 OP_WITHIN OP_VERIFY
+        OP_DUP 2 OP_EQUAL
+        OP_IF
+            OP_DROP
+            { fq12_push(w * w) }
+        OP_ELSE
+            OP_IF
+                { fq12_push(w) }
+            OP_ELSE
+                { Fq12::push_one() }
+            OP_ENDIF
+        OP_ENDIF
+        { Fq12::toaltstack() }
+
+        // check c_inv
+        { Fq12::copy(12) }
+        { Fq12::roll(12) }
+        { Fq12::hinted_inv() }
+        { Fq12::toaltstack() }
+        { Fq12::toaltstack() }
+    }
+}
+
 // Push constants to stack
 // Return Stack: [beta_12, beta_13, beta_22, 1/2, B]
 fn constants() -> Script {
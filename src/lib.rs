@@ -7,19 +7,30 @@ pub mod treepp {
 }
 
 use core::fmt;
-use std::{cmp::min, fs::File, io::Write};
+use std::cmp::min;
 
-use bitcoin::{hashes::Hash, hex::DisplayHex, Opcode, ScriptBuf, TapLeafHash, Transaction};
-use bitcoin_scriptexec::{Exec, ExecCtx, ExecError, ExecStats, Options, Stack, TxTemplate};
+use bitcoin::{hex::DisplayHex, Opcode, ScriptBuf};
+use bitcoin_scriptexec::{ExecError, ExecStats, Stack};
+use execution_env::ExecutionEnv;
+use source_map::SourceLocation;
 
 pub mod bigint;
+pub mod bls12_381;
 pub mod bn254;
 pub mod bridge;
+pub mod chunker;
+pub mod debugger;
+pub mod execution_env;
 pub mod fflonk;
 pub mod groth16;
 pub mod hash;
+pub mod native;
+pub mod plonk;
+pub mod profiler;
 pub mod pseudo;
+pub mod secp256k1;
 pub mod signatures;
+pub mod source_map;
 pub mod u32;
 pub mod u4;
 
@@ -68,6 +79,8 @@ pub struct ExecuteInfo {
     pub remaining_script: String,
     pub last_opcode: Option<Opcode>,
     pub stats: ExecStats,
+    /// The builder call stack of the failing opcode, if it is known.
+    pub source_location: Option<SourceLocation>,
 }
 
 impl fmt::Display for ExecuteInfo {
@@ -100,32 +113,29 @@ impl fmt::Display for ExecuteInfo {
         if let Some(ref opcode) = self.last_opcode {
             writeln!(f, "Last Opcode: {:?}", opcode)?;
         }
+        if let Some(ref location) = self.source_location {
+            writeln!(f, "Failed {}", location)?;
+        }
         writeln!(f, "Stats: {:?}", self.stats)?;
         Ok(())
     }
 }
 
 pub fn execute_script(script: treepp::Script) -> ExecuteInfo {
-    let mut exec = Exec::new(
-        ExecCtx::Tapscript,
-        Options::default(),
-        TxTemplate {
-            tx: Transaction {
-                version: bitcoin::transaction::Version::TWO,
-                lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-                input: vec![],
-                output: vec![],
-            },
-            prevouts: vec![],
-            input_idx: 0,
-            taproot_annex_scriptleaf: Some((TapLeafHash::all_zeros(), None)),
-        },
-        script.compile(),
-        vec![],
-    )
-    .expect("error creating exec");
+    execute_script_with_env(script, &ExecutionEnv::new())
+}
 
+/// Executes the script in the given transaction context, e.g. against a real spending
+/// transaction for scripts that use `OP_CHECKSIG` or timelocks.
+pub fn execute_script_with_env(script: treepp::Script, env: &ExecutionEnv) -> ExecuteInfo {
+    let compiled_script = script.clone().compile();
+    let script_len = compiled_script.len();
+    let mut exec = env.exec(compiled_script);
+
+    // Byte offset of the opcode executed last
+    let mut offset;
     loop {
+        offset = script_len - exec.remaining_script().len();
         if exec.exec_next().is_err() {
             break;
         }
@@ -138,6 +148,21 @@ pub fn execute_script(script: treepp::Script) -> ExecuteInfo {
         final_stack: FmtStack(exec.stack().clone()),
         remaining_script: exec.remaining_script().to_asm_string(),
         stats: exec.stats().clone(),
+        source_location: failure_location(&script, res.error.is_some(), offset, script_len),
+    }
+}
+
+// Locates the failing opcode in the structured script, if execution stopped inside of it.
+fn failure_location(
+    script: &treepp::Script,
+    errored: bool,
+    offset: usize,
+    script_len: usize,
+) -> Option<SourceLocation> {
+    if errored && offset < script_len {
+        Some(SourceLocation::locate(script, offset))
+    } else {
+        None
     }
 }
 
@@ -157,102 +182,101 @@ pub fn run(script: treepp::Script) {
 //
 // NOTE: Only for test purposes.
 pub fn execute_script_without_stack_limit(script: treepp::Script) -> ExecuteInfo {
-    // Get the default options for the script exec.
-    let mut opts = Options::default();
-    // Do not enforce the stack limit.
-    opts.enforce_stack_limit = false;
-
-    let mut exec = Exec::new(
-        ExecCtx::Tapscript,
-        opts,
-        TxTemplate {
-            tx: Transaction {
-                version: bitcoin::transaction::Version::TWO,
-                lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-                input: vec![],
-                output: vec![],
-            },
-            prevouts: vec![],
-            input_idx: 0,
-            taproot_annex_scriptleaf: Some((TapLeafHash::all_zeros(), None)),
-        },
-        script.compile(),
-        vec![],
-    )
-    .expect("error creating exec");
-
-    loop {
-        if exec.exec_next().is_err() {
-            break;
-        }
-    }
-    let res = exec.result().unwrap();
-    ExecuteInfo {
-        success: res.success,
-        error: res.error.clone(),
-        last_opcode: res.opcode,
-        final_stack: FmtStack(exec.stack().clone()),
-        remaining_script: exec.remaining_script().to_asm_string(),
-        stats: exec.stats().clone(),
-    }
+    execute_script_with_env(script, &ExecutionEnv::new().without_stack_limit())
 }
 
-// TODO: Use signatures to copy over the stack from one chunk to the next.
+/// Seed of the one-time keys that sign the stacks handed over between chunks.
+///
+/// NOTE: Only for test purposes, the keys are public.
+const CHUNK_KEYS_SEED: &str = "execute_script_as_chunks";
+
+/// Executes the script as chunks where every intermediate stack is handed over with a signed
+/// commitment, see [`chunker::commitment`]. Each chunk but the first one starts with a prefix that
+/// verifies the commitment of the previous chunk's output. The compiled script is executed
+/// alongside and every committed stack has to match its stack at the chunk boundary.
 pub fn execute_script_as_chunks(
     script: treepp::Script,
     target_chunk_size: usize,
     tolerance: usize,
 ) -> ExecuteInfo {
-    let (chunk_sizes, scripts) = script
+    execute_script_as_chunks_with_env(script, target_chunk_size, tolerance, &ExecutionEnv::new())
+}
+
+// Execute a script as chunks without `MAX_STACK_SIZE` limit, see [`execute_script_as_chunks`].
+// Use [`chunker::limits::check_chunk_limits`] to find the chunks that exceed it.
+//
+// NOTE: Only for test purposes.
+pub fn execute_script_as_chunks_without_stack_limit(
+    script: treepp::Script,
+    target_chunk_size: usize,
+    tolerance: usize,
+) -> ExecuteInfo {
+    execute_script_as_chunks_with_env(
+        script,
+        target_chunk_size,
+        tolerance,
+        &ExecutionEnv::new().without_stack_limit(),
+    )
+}
+
+fn execute_script_as_chunks_with_env(
+    script: treepp::Script,
+    target_chunk_size: usize,
+    tolerance: usize,
+    env: &ExecutionEnv,
+) -> ExecuteInfo {
+    let (_, scripts) = script
         .clone()
         .compile_to_chunks(target_chunk_size, tolerance);
-    // TODO: Remove this when we are sure we are in script size limit for groth16
-    // Get the default options for the script exec.
-    let mut opts = Options::default();
-    // Do not enforce the stack limit.
-    opts.enforce_stack_limit = false;
-
     assert!(scripts.len() > 0, "No chunks to execute");
-    let mut stats_file = File::create("chunk_stats.txt").expect("Unable to create stats file");
-    writeln!(stats_file, "chunk sizes: {:?}", chunk_sizes).expect("Unable to write to stats file");
-    let num_chunks = scripts.len();
-    let mut scripts = scripts.into_iter();
-    let mut final_exec = None; // Only used to not initialize an obsolote Exec
-    let mut next_stack = Stack::new();
-    let mut next_altstack = Stack::new();
-    let mut chunk_stacks = vec![];
 
-    // Execute each chunk and copy over the stacks
-    for i in 0..num_chunks {
-        // Note: Exec::with_stack() currently overwrites the witness!
-        let mut exec = Exec::with_stack(
-            ExecCtx::Tapscript,
-            opts.clone(),
-            TxTemplate {
-                tx: Transaction {
-                    version: bitcoin::transaction::Version::TWO,
-                    lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-                    input: vec![],
-                    output: vec![],
-                },
-                prevouts: vec![],
-                input_idx: 0,
-                taproot_annex_scriptleaf: Some((TapLeafHash::all_zeros(), None)),
-            },
-            scripts.next().unwrap_or_else(|| unreachable!()),
-            vec![], // Note: If you put a witness here make sure to adjust
-                                    // Exec::with_stack() to not overwrite it!
-            next_stack.clone(),
-            next_altstack.clone(),
-        )
-        .expect("Failed to create Exec");
-        
-        // Execute the current chunk.
-        while exec.exec_next().is_ok() {
+    // The reference run only has to agree on the stacks at the boundaries.
+    let compiled_script = script.clone().compile();
+    let script_len = compiled_script.len();
+    let mut compiled_exec = ExecutionEnv::new().without_stack_limit().exec(compiled_script);
+
+    let num_chunks = scripts.len();
+    let mut chunk_end = 0;
+    let mut commitment: Option<(chunker::commitment::StackCommitment, _)> = None;
+    let mut final_exec = None;
+    let mut offset = None;
+    for (i, chunk) in scripts.into_iter().enumerate() {
+        let chunk_start = chunk_end;
+        chunk_end += chunk.len();
+
+        let (prefix, witness) = match &commitment {
+            Some((commitment, public_key)) => (
+                chunker::commitment::verify_stack_commitment(
+                    public_key,
+                    commitment.stack_len,
+                    commitment.altstack_len,
+                )
+                .compile(),
+                commitment.witness(),
+            ),
+            None => (ScriptBuf::new(), vec![]),
+        };
+        let prefix_len = prefix.len();
+        let mut chunk_script = prefix.into_bytes();
+        chunk_script.extend(chunk.into_bytes());
+        let chunk_script_len = chunk_script.len();
+
+        let mut exec = env
+            .clone()
+            .with_witness(witness)
+            .exec(ScriptBuf::from_bytes(chunk_script));
+        loop {
+            // Opcodes of the prefix aren't part of the script
+            offset = (chunk_script_len - exec.remaining_script().len())
+                .checked_sub(prefix_len)
+                .map(|offset| chunk_start + offset);
+            if exec.exec_next().is_err() {
+                break;
+            }
         }
 
-        if exec.result().unwrap().error.is_some() {
-            let res = exec.result().unwrap();
+        let res = exec.result().unwrap();
+        if res.error.is_some() {
             println!("Exec errored in chunk {}", i);
             return ExecuteInfo {
                 success: res.success,
@@ -261,25 +285,46 @@ pub fn execute_script_as_chunks(
                 final_stack: FmtStack(exec.stack().clone()),
                 remaining_script: exec.remaining_script().to_asm_string(),
                 stats: exec.stats().clone(),
+                source_location: offset.and_then(|offset| {
+                    failure_location(&script, true, offset, chunk_end)
+                }),
+            };
+        }
+
+        // Run the compiled script up to the same boundary.
+        while script_len - compiled_exec.remaining_script().len() < chunk_end {
+            if compiled_exec.exec_next().is_err() {
+                break;
             }
-        };
+        }
+        assert_eq!(exec.stack(), compiled_exec.stack(), "Stack differs after chunk {}", i);
+        assert_eq!(
+            exec.altstack(),
+            compiled_exec.altstack(),
+            "Altstack differs after chunk {}",
+            i
+        );
 
-        chunk_stacks.push(exec.stack().len() + exec.altstack().len());
-        // Copy over the stack for next iteration.
-        // TODO: Take the stack snapshot at the end of the chunk logic (before the stack is hashed and
-        // then dropped) BUT AFTER THE ALSTACK IS MOVED TO STACK
-        next_stack = exec.stack().clone();
-        // TODO: altstack should be empty
-        // TODO: Next altstack is generated from the stack entries
-        next_altstack = exec.altstack().clone();
+        if i + 1 < num_chunks {
+            let stack: Vec<Vec<u8>> = exec.stack().iter_str().collect();
+            let altstack: Vec<Vec<u8>> = exec.altstack().iter_str().collect();
+            let keys = chunker::commitment::BoundaryKeys::new(CHUNK_KEYS_SEED, i);
+            let stack_commitment =
+                chunker::commitment::StackCommitment::new(&keys, &stack, &altstack)
+                    .unwrap_or_else(|e| panic!("Can't commit to the stack after chunk {}: {}", i, e));
+            commitment = Some((stack_commitment, keys.public_key()));
+        }
         final_exec = Some(exec);
     }
+
     let final_exec = final_exec.unwrap_or_else(|| unreachable!());
     let res = final_exec.result().unwrap();
-    writeln!(stats_file,
-        "intermediate stack transfer sizes: {:?}",
-        chunk_stacks[0..chunk_stacks.len() - 1].to_vec()
-    ).expect("Unable to write into stats_file");
+    while compiled_exec.exec_next().is_ok() {}
+    assert_eq!(
+        res.success,
+        compiled_exec.result().unwrap().success,
+        "Chunked execution differs from the compiled script"
+    );
     ExecuteInfo {
         success: res.success,
         error: res.error.clone(),
@@ -287,10 +332,12 @@ pub fn execute_script_as_chunks(
         final_stack: FmtStack(final_exec.stack().clone()),
         remaining_script: final_exec.remaining_script().to_asm_string(),
         stats: final_exec.stats().clone(),
+        source_location: offset.and_then(|offset| {
+            failure_location(&script, res.error.is_some(), offset, chunk_end)
+        }),
     }
 }
 
-
 pub fn execute_script_as_chunks_vs_normal(
     script: treepp::Script,
     target_chunk_size: usize,
@@ -299,7 +346,7 @@ pub fn execute_script_as_chunks_vs_normal(
     let (chunk_sizes, scripts) = script
         .clone()
         .compile_to_chunks(target_chunk_size, tolerance);
-    let compiled_script = script.compile();
+    let compiled_script = script.clone().compile();
     let mut total_script = vec![];
     for script in &scripts {
         total_script.extend(script.clone().into_bytes());
@@ -321,53 +368,23 @@ pub fn execute_script_as_chunks_vs_normal(
     let mut next_stack = Stack::new();
     let mut next_altstack = Stack::new();
     let mut chunk_stacks = vec![];
-    let mut compiled_exec = Exec::new(
-        ExecCtx::Tapscript,
-        Options::default(),
-        TxTemplate {
-            tx: Transaction {
-                version: bitcoin::transaction::Version::TWO,
-                lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-                input: vec![],
-                output: vec![],
-            },
-            prevouts: vec![],
-            input_idx: 0,
-            taproot_annex_scriptleaf: Some((TapLeafHash::all_zeros(), None)),
-        },
-        compiled_script,
-        vec![], // Note: If you put a witness here make sure to adjust
-                // Exec::with_stack() to not overwrite it!
-    )
-    .expect("Failed to create Exec");
+    let env = ExecutionEnv::new();
+    let mut compiled_exec = env.exec(compiled_script);
+    let mut chunk_start = 0;
+    let mut offset = 0;
 
     // Execute each chunk and copy over the stacks
     for i in 0..num_chunks {
-        // Note: Exec::with_stack() currently overwrites the witness!
-        let mut exec = Exec::with_stack(
-            ExecCtx::Tapscript,
-            Options::default(),
-            TxTemplate {
-                tx: Transaction {
-                    version: bitcoin::transaction::Version::TWO,
-                    lock_time: bitcoin::locktime::absolute::LockTime::ZERO,
-                    input: vec![],
-                    output: vec![],
-                },
-                prevouts: vec![],
-                input_idx: 0,
-                taproot_annex_scriptleaf: Some((TapLeafHash::all_zeros(), None)),
-            },
-            scripts.next().unwrap_or_else(|| unreachable!()),
-            vec![], // Note: If you put a witness here make sure to adjust
-            // Exec::with_stack() to not overwrite it!
-            next_stack.clone(),
-            next_altstack.clone(),
-        )
-        .expect("Failed to create Exec");
+        let chunk = scripts.next().unwrap_or_else(|| unreachable!());
+        let chunk_len = chunk.len();
+        let mut exec = env.exec_with_stack(chunk, next_stack.clone(), next_altstack.clone());
 
         // Execute the current chunk.
-        while exec.exec_next().is_ok() {
+        loop {
+            offset = chunk_start + chunk_len - exec.remaining_script().len();
+            if exec.exec_next().is_err() {
+                break;
+            }
             // Execute the compiled script in parallel.
             if compiled_exec.exec_next().is_err() {
                 println!("compiled_exec error: {:?}", compiled_exec.result());
@@ -395,7 +412,7 @@ pub fn execute_script_as_chunks_vs_normal(
             println!("Exec errored in chunk {}", i);
             println!(
                 "intermediate stack transfer sizes: {:?}",
-                chunk_stacks[0..chunk_stacks.len() - 1].to_vec()
+                chunk_stacks[0..chunk_stacks.len().saturating_sub(1)].to_vec()
             );
             return ExecuteInfo {
                 success: res.success,
@@ -404,6 +421,7 @@ pub fn execute_script_as_chunks_vs_normal(
                 final_stack: FmtStack(exec.stack().clone()),
                 remaining_script: exec.remaining_script().to_asm_string(),
                 stats: exec.stats().clone(),
+                source_location: failure_location(&script, true, offset, chunk_start + chunk_len),
             }
         };
 
@@ -412,12 +430,13 @@ pub fn execute_script_as_chunks_vs_normal(
         next_stack = exec.stack().clone();
         next_altstack = exec.altstack().clone();
         final_exec = Some(exec);
+        chunk_start += chunk_len;
     }
     let final_exec = final_exec.unwrap_or_else(|| unreachable!());
     let res = final_exec.result().unwrap();
     println!(
         "intermediate stack transfer sizes: {:?}",
-        chunk_stacks[0..chunk_stacks.len() - 1].to_vec()
+        chunk_stacks[0..chunk_stacks.len().saturating_sub(1)].to_vec()
     );
     ExecuteInfo {
         success: res.success,
@@ -426,6 +445,7 @@ pub fn execute_script_as_chunks_vs_normal(
         final_stack: FmtStack(final_exec.stack().clone()),
         remaining_script: final_exec.remaining_script().to_asm_string(),
         stats: final_exec.stats().clone(),
+        source_location: failure_location(&script, res.error.is_some(), offset, chunk_start),
     }
 }
 
@@ -511,4 +531,37 @@ mod test {
         println!("{:?}", exec_result);
         assert!(exec_result.success);
     }
+
+    #[test]
+    fn test_execute_script_as_chunks_with_altstack() {
+        let push_script = script! {
+            { 0x1000 }
+            { 0x2000 }
+        };
+        let altstack_script = script! {
+            OP_TOALTSTACK
+            OP_TOALTSTACK
+        };
+        let add_script = script! {
+            OP_FROMALTSTACK
+            OP_ADD
+        };
+
+        let script = script! {
+            { push_script.clone() }
+            { push_script.clone() }
+            { push_script.clone() }
+            { altstack_script.clone() }
+            { altstack_script.clone() }
+            { add_script.clone() }
+            { add_script.clone() }
+            { add_script.clone() }
+            { add_script.clone() }
+            OP_2DROP
+            OP_TRUE
+        };
+        let exec_result = execute_script_as_chunks(script, 6, 0);
+        println!("{:?}", exec_result);
+        assert!(exec_result.success);
+    }
 }
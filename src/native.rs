@@ -0,0 +1,642 @@
+//
+// Native interpreter for numeric scripts
+//
+// The u4 hash gadgets built with `bitcoin_script_stack::StackTracker` only ever handle small
+// numbers. This interpreter runs such scripts on plain `i64` stacks, without the encoding,
+// signature and resource checks of `bitcoin_scriptexec`, which makes checking large hash inputs
+// much faster. `CrossChecker` runs it in lockstep with the real executor and compares the
+// values of watched `StackVariable`s at the points where they were defined.
+//
+
+use core::fmt;
+
+use bitcoin::{opcodes::all::*, script::Instruction, Opcode, ScriptBuf};
+use bitcoin_script_stack::stack::{StackTracker, StackVariable};
+
+use crate::execution_env::ExecutionEnv;
+
+/// Operands of numeric opcodes are limited to 4 bytes.
+const MAX_OPERAND: i64 = i32::MAX as i64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NativeError {
+    /// The opcode at `offset` is not supported by the native interpreter.
+    Unsupported { offset: usize, instruction: String },
+    StackUnderflow { offset: usize },
+    NumericOverflow { offset: usize },
+    /// An `OP_VERIFY` style opcode or `OP_RETURN` failed.
+    Verify { offset: usize },
+    UnbalancedConditional { offset: usize },
+    InvalidScript,
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NativeError::Unsupported { offset, instruction } => {
+                write!(f, "unsupported instruction {} at byte {}", instruction, offset)
+            }
+            NativeError::StackUnderflow { offset } => write!(f, "stack underflow at byte {}", offset),
+            NativeError::NumericOverflow { offset } => {
+                write!(f, "numeric operand overflow at byte {}", offset)
+            }
+            NativeError::Verify { offset } => write!(f, "verify failed at byte {}", offset),
+            NativeError::UnbalancedConditional { offset } => {
+                write!(f, "unbalanced conditional at byte {}", offset)
+            }
+            NativeError::InvalidScript => write!(f, "invalid script"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum NativeOp {
+    Push(i64),
+    Op(Opcode),
+}
+
+/// Executes a script of numeric pushes and stack, arithmetic and flow control opcodes.
+pub struct NativeExecutor {
+    // (byte offset, operation) for every opcode
+    ops: Vec<(usize, NativeOp)>,
+    script_len: usize,
+    pc: usize,
+    stack: Vec<i64>,
+    altstack: Vec<i64>,
+    // Whether each open `OP_IF` branch is executed
+    conditions: Vec<bool>,
+}
+
+impl NativeExecutor {
+    pub fn new(script: &ScriptBuf) -> Result<Self, NativeError> {
+        Self::with_stack(script, vec![], vec![])
+    }
+
+    /// Starts from the given stacks, both bottom first.
+    pub fn with_stack(
+        script: &ScriptBuf,
+        stack: Vec<i64>,
+        altstack: Vec<i64>,
+    ) -> Result<Self, NativeError> {
+        let mut ops = vec![];
+        for item in script.instruction_indices() {
+            let (offset, instruction) = item.map_err(|_| NativeError::InvalidScript)?;
+            let op = match instruction {
+                Instruction::PushBytes(bytes) => {
+                    let bytes = bytes.as_bytes();
+                    if bytes.len() > 4 {
+                        return Err(NativeError::Unsupported {
+                            offset,
+                            instruction: format!("push of {} bytes", bytes.len()),
+                        });
+                    }
+                    NativeOp::Push(decode_num(bytes))
+                }
+                Instruction::Op(op) => match op.decode_pushnum() {
+                    Some(n) => NativeOp::Push(n as i64),
+                    None if op == OP_PUSHNUM_NEG1 => NativeOp::Push(-1),
+                    None => NativeOp::Op(op),
+                },
+            };
+            ops.push((offset, op));
+        }
+        Ok(Self {
+            ops,
+            script_len: script.len(),
+            pc: 0,
+            stack,
+            altstack,
+            conditions: vec![],
+        })
+    }
+
+    /// Stack, bottom first.
+    pub fn stack(&self) -> &[i64] { &self.stack }
+
+    /// Altstack, bottom first.
+    pub fn altstack(&self) -> &[i64] { &self.altstack }
+
+    /// Byte offset of the next opcode.
+    pub fn offset(&self) -> usize {
+        self.ops.get(self.pc).map_or(self.script_len, |(offset, _)| *offset)
+    }
+
+    pub fn is_finished(&self) -> bool { self.pc >= self.ops.len() }
+
+    /// Executes the remaining script. Returns whether it left a true value on top of the stack.
+    pub fn run(&mut self) -> Result<bool, NativeError> {
+        while !self.is_finished() {
+            self.step()?;
+        }
+        if !self.conditions.is_empty() {
+            return Err(NativeError::UnbalancedConditional { offset: self.script_len });
+        }
+        Ok(self.stack.last().map_or(false, |top| *top != 0))
+    }
+
+    /// Executes the next opcode.
+    pub fn step(&mut self) -> Result<(), NativeError> {
+        let Some((offset, op)) = self.ops.get(self.pc).cloned() else {
+            return Ok(());
+        };
+        self.pc += 1;
+
+        let executing = self.conditions.iter().all(|condition| *condition);
+        let op = match op {
+            NativeOp::Push(n) => {
+                if executing {
+                    self.stack.push(n);
+                }
+                return Ok(());
+            }
+            NativeOp::Op(op) => op,
+        };
+
+        match op {
+            OP_IF | OP_NOTIF => {
+                let condition = if executing {
+                    let top = self.pop(offset)? != 0;
+                    top == (op == OP_IF)
+                } else {
+                    false
+                };
+                self.conditions.push(condition);
+                return Ok(());
+            }
+            OP_ELSE => {
+                let condition = self
+                    .conditions
+                    .last_mut()
+                    .ok_or(NativeError::UnbalancedConditional { offset })?;
+                *condition = !*condition;
+                return Ok(());
+            }
+            OP_ENDIF => {
+                self.conditions
+                    .pop()
+                    .ok_or(NativeError::UnbalancedConditional { offset })?;
+                return Ok(());
+            }
+            _ if !executing => return Ok(()),
+            _ => {}
+        }
+
+        match op {
+            OP_NOP => {}
+            OP_VERIFY => {
+                if self.pop(offset)? == 0 {
+                    return Err(NativeError::Verify { offset });
+                }
+            }
+            OP_RETURN => return Err(NativeError::Verify { offset }),
+
+            OP_TOALTSTACK => {
+                let a = self.pop(offset)?;
+                self.altstack.push(a);
+            }
+            OP_FROMALTSTACK => {
+                let a = self.altstack.pop().ok_or(NativeError::StackUnderflow { offset })?;
+                self.stack.push(a);
+            }
+            OP_DROP => {
+                self.pop(offset)?;
+            }
+            OP_2DROP => {
+                self.pop(offset)?;
+                self.pop(offset)?;
+            }
+            OP_DUP => self.copy(0, offset)?,
+            OP_2DUP => {
+                self.copy(1, offset)?;
+                self.copy(1, offset)?;
+            }
+            OP_3DUP => {
+                self.copy(2, offset)?;
+                self.copy(2, offset)?;
+                self.copy(2, offset)?;
+            }
+            OP_OVER => self.copy(1, offset)?,
+            OP_2OVER => {
+                self.copy(3, offset)?;
+                self.copy(3, offset)?;
+            }
+            OP_NIP => {
+                self.roll(1, offset)?;
+                self.pop(offset)?;
+            }
+            OP_SWAP => self.roll(1, offset)?,
+            OP_2SWAP => {
+                self.roll(3, offset)?;
+                self.roll(3, offset)?;
+            }
+            OP_ROT => self.roll(2, offset)?,
+            OP_2ROT => {
+                self.roll(5, offset)?;
+                self.roll(5, offset)?;
+            }
+            OP_TUCK => {
+                self.copy(0, offset)?;
+                self.roll(2, offset)?;
+                self.roll(2, offset)?;
+            }
+            OP_IFDUP => {
+                if *self.stack.last().ok_or(NativeError::StackUnderflow { offset })? != 0 {
+                    self.copy(0, offset)?;
+                }
+            }
+            OP_DEPTH => self.stack.push(self.stack.len() as i64),
+            OP_PICK | OP_ROLL => {
+                let n = self.pop_num(offset)?;
+                if n < 0 {
+                    return Err(NativeError::StackUnderflow { offset });
+                }
+                if op == OP_PICK {
+                    self.copy(n as usize, offset)?;
+                } else {
+                    self.roll(n as usize, offset)?;
+                }
+            }
+
+            OP_1ADD | OP_1SUB | OP_NEGATE | OP_ABS | OP_NOT | OP_0NOTEQUAL => {
+                let a = self.pop_num(offset)?;
+                self.stack.push(match op {
+                    OP_1ADD => a + 1,
+                    OP_1SUB => a - 1,
+                    OP_NEGATE => -a,
+                    OP_ABS => a.abs(),
+                    OP_NOT => (a == 0) as i64,
+                    _ => (a != 0) as i64,
+                });
+            }
+            OP_ADD | OP_SUB | OP_BOOLAND | OP_BOOLOR | OP_NUMEQUAL | OP_NUMEQUALVERIFY
+            | OP_NUMNOTEQUAL | OP_LESSTHAN | OP_GREATERTHAN | OP_LESSTHANOREQUAL
+            | OP_GREATERTHANOREQUAL | OP_MIN | OP_MAX | OP_EQUAL | OP_EQUALVERIFY => {
+                let b = self.pop_num(offset)?;
+                let a = self.pop_num(offset)?;
+                let result = match op {
+                    OP_ADD => a + b,
+                    OP_SUB => a - b,
+                    OP_BOOLAND => (a != 0 && b != 0) as i64,
+                    OP_BOOLOR => (a != 0 || b != 0) as i64,
+                    OP_NUMEQUAL | OP_NUMEQUALVERIFY | OP_EQUAL | OP_EQUALVERIFY => (a == b) as i64,
+                    OP_NUMNOTEQUAL => (a != b) as i64,
+                    OP_LESSTHAN => (a < b) as i64,
+                    OP_GREATERTHAN => (a > b) as i64,
+                    OP_LESSTHANOREQUAL => (a <= b) as i64,
+                    OP_GREATERTHANOREQUAL => (a >= b) as i64,
+                    OP_MIN => a.min(b),
+                    _ => a.max(b),
+                };
+                if op == OP_NUMEQUALVERIFY || op == OP_EQUALVERIFY {
+                    if result == 0 {
+                        return Err(NativeError::Verify { offset });
+                    }
+                } else {
+                    self.stack.push(result);
+                }
+            }
+            OP_WITHIN => {
+                let max = self.pop_num(offset)?;
+                let min = self.pop_num(offset)?;
+                let x = self.pop_num(offset)?;
+                self.stack.push((min <= x && x < max) as i64);
+            }
+
+            _ => {
+                return Err(NativeError::Unsupported { offset, instruction: format!("{:?}", op) })
+            }
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self, offset: usize) -> Result<i64, NativeError> {
+        self.stack.pop().ok_or(NativeError::StackUnderflow { offset })
+    }
+
+    fn pop_num(&mut self, offset: usize) -> Result<i64, NativeError> {
+        let a = self.pop(offset)?;
+        if a.abs() > MAX_OPERAND {
+            return Err(NativeError::NumericOverflow { offset });
+        }
+        Ok(a)
+    }
+
+    // Copies the element at `depth` (0 is the top) onto the top.
+    fn copy(&mut self, depth: usize, offset: usize) -> Result<(), NativeError> {
+        let len = self.stack.len();
+        if depth >= len {
+            return Err(NativeError::StackUnderflow { offset });
+        }
+        self.stack.push(self.stack[len - 1 - depth]);
+        Ok(())
+    }
+
+    // Moves the element at `depth` (0 is the top) onto the top.
+    fn roll(&mut self, depth: usize, offset: usize) -> Result<(), NativeError> {
+        let len = self.stack.len();
+        if depth >= len {
+            return Err(NativeError::StackUnderflow { offset });
+        }
+        let a = self.stack.remove(len - 1 - depth);
+        self.stack.push(a);
+        Ok(())
+    }
+}
+
+fn decode_num(bytes: &[u8]) -> i64 {
+    let Some(last) = bytes.last() else {
+        return 0;
+    };
+    let mut n = 0i64;
+    for (i, byte) in bytes.iter().enumerate() {
+        n |= (*byte as i64) << (8 * i);
+    }
+    if last & 0x80 != 0 {
+        -(n & !(0x80i64 << (8 * (bytes.len() - 1))))
+    } else {
+        n
+    }
+}
+
+/// A variable whose value is compared once execution reaches `offset`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub name: String,
+    /// Byte offset in the compiled script right after the variable's definition.
+    pub offset: usize,
+    /// Number of stack elements above the variable.
+    pub depth: usize,
+    /// Number of stack elements of the variable.
+    pub size: usize,
+}
+
+impl Checkpoint {
+    // The variable's elements, bottom first.
+    fn read(&self, stack: &[i64]) -> Vec<i64> {
+        let end = stack.len().saturating_sub(self.depth);
+        stack[end.saturating_sub(self.size)..end].to_vec()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CrossCheckError {
+    Native(NativeError),
+    /// The real executor failed while the native one did not.
+    Script { offset: usize, error: String },
+    /// The stack sizes of both executors differ after the opcode at `offset`.
+    Depth { offset: usize, native: usize, script: usize },
+    /// A watched variable has a different value in both executors.
+    Variable { name: String, offset: usize, native: Vec<i64>, script: Vec<i64> },
+}
+
+/// Runs a script natively and with `bitcoin_scriptexec` side by side.
+#[derive(Clone, Debug, Default)]
+pub struct CrossChecker {
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl CrossChecker {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn checkpoints(&self) -> &[Checkpoint] { &self.checkpoints }
+
+    pub fn watch(&mut self, checkpoint: Checkpoint) { self.checkpoints.push(checkpoint); }
+
+    /// Watches `var` in its current position. Has to be called right after the variable is
+    /// defined and before it is moved.
+    pub fn watch_var(&mut self, name: &str, stack: &StackTracker, var: &StackVariable) {
+        let size = var.size() as usize;
+        self.watch(Checkpoint {
+            name: name.to_string(),
+            offset: stack.get_script_len(),
+            depth: stack.get_offset(var) as usize + 1 - size,
+            size,
+        });
+    }
+
+    /// Executes `script` natively and returns the value of every watched variable, in the order
+    /// of their offsets.
+    pub fn native_values(&self, script: &ScriptBuf) -> Result<Vec<(String, Vec<i64>)>, NativeError> {
+        let mut native = NativeExecutor::new(script)?;
+        let mut checkpoints: Vec<&Checkpoint> = self.checkpoints.iter().collect();
+        checkpoints.sort_by_key(|checkpoint| checkpoint.offset);
+
+        let mut values = vec![];
+        for checkpoint in checkpoints {
+            while !native.is_finished() && native.offset() < checkpoint.offset {
+                native.step()?;
+            }
+            values.push((checkpoint.name.clone(), checkpoint.read(native.stack())));
+        }
+        Ok(values)
+    }
+
+    /// Executes `script` with both interpreters. The stack depth is compared after every opcode,
+    /// the watched variables once their offset is reached.
+    pub fn check(&self, script: &ScriptBuf) -> Result<(), CrossCheckError> {
+        let mut native = NativeExecutor::new(script).map_err(CrossCheckError::Native)?;
+        let mut exec = ExecutionEnv::new().without_stack_limit().exec(script.clone());
+        let mut checkpoints: Vec<&Checkpoint> = self.checkpoints.iter().collect();
+        checkpoints.sort_by_key(|checkpoint| checkpoint.offset);
+        let mut checkpoints = checkpoints.into_iter().peekable();
+
+        loop {
+            let offset = native.offset();
+            while let Some(checkpoint) = checkpoints.next_if(|checkpoint| checkpoint.offset <= offset)
+            {
+                let script_stack: Vec<i64> =
+                    exec.stack().iter_str().map(|item| decode_num(&item)).collect();
+                let native_value = checkpoint.read(native.stack());
+                let script_value = checkpoint.read(&script_stack);
+                if native_value != script_value {
+                    return Err(CrossCheckError::Variable {
+                        name: checkpoint.name.clone(),
+                        offset: checkpoint.offset,
+                        native: native_value,
+                        script: script_value,
+                    });
+                }
+            }
+            if native.is_finished() {
+                break;
+            }
+
+            native.step().map_err(CrossCheckError::Native)?;
+            if exec.exec_next().is_err() {
+                if let Some(error) = exec.result().and_then(|result| result.error.clone()) {
+                    return Err(CrossCheckError::Script { offset, error: format!("{:?}", error) });
+                }
+            }
+            let (native_depth, script_depth) = (native.stack().len(), exec.stack().len());
+            if native_depth != script_depth {
+                return Err(CrossCheckError::Depth {
+                    offset,
+                    native: native_depth,
+                    script: script_depth,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::blake3::{blake3_hash_equalverify, blake3_var_length, push_bytes_hex};
+    use crate::hash::blake3_u4::blake3;
+    use crate::hash::sha256_u4_stack::sha256_stack;
+    use crate::u32::u32_std::u32_push;
+    use crate::u4::u4_std::u4_hex_to_nibbles;
+    use crate::treepp::*;
+    use sha2::{Digest, Sha256};
+
+    // Compares the nibbles on top of the stack with `hex`, most significant nibble deepest
+    fn nibbles_equalverify(hex: &str) -> Script {
+        script! {
+            { u4_hex_to_nibbles(hex) }
+            for _ in 0..hex.len() {
+                OP_TOALTSTACK
+            }
+            for i in 1..hex.len() {
+                { i }
+                OP_ROLL
+            }
+            for _ in 0..hex.len() {
+                OP_FROMALTSTACK
+                OP_EQUALVERIFY
+            }
+        }
+    }
+
+    // Hashes `hex_in` with a u4 gadget and compares the result with `hex_out`
+    fn u4_hash_script(
+        hex_in: &str,
+        hex_out: &str,
+        hash: impl FnOnce(&mut StackTracker, u32),
+    ) -> ScriptBuf {
+        let mut stack = StackTracker::new();
+        stack.custom(script! { { u4_hex_to_nibbles(hex_in) } }, 0, false, 0, "message");
+        hash(&mut stack, hex_in.len() as u32 / 2);
+        script! {
+            { stack.get_script() }
+            { nibbles_equalverify(hex_out) }
+            OP_TRUE
+        }
+        .compile()
+    }
+
+    #[test]
+    fn test_decode_num() {
+        for n in [0i64, 1, -1, 127, 128, -128, 255, 0x7fffffff, -0x7fffffff] {
+            let encoded = script! { { n } }.compile();
+            let mut executor = NativeExecutor::new(&encoded).unwrap();
+            executor.run().unwrap();
+            assert_eq!(executor.stack(), &[n]);
+        }
+    }
+
+    #[test]
+    fn test_native_matches_script() {
+        let script = script! {
+            1 2 OP_2DUP OP_ADD OP_TOALTSTACK
+            OP_SWAP OP_SUB
+            OP_DUP 0 OP_LESSTHAN
+            OP_IF
+                OP_NEGATE
+            OP_ELSE
+                OP_1ADD
+            OP_ENDIF
+            OP_FROMALTSTACK
+            3 OP_EQUALVERIFY
+            2 OP_NUMEQUAL
+        }
+        .compile();
+        let mut native = NativeExecutor::new(&script).unwrap();
+        assert!(native.run().unwrap());
+        assert!(CrossChecker::new().check(&script).is_ok());
+    }
+
+    #[test]
+    fn test_native_blake3() {
+        let hex_out = "11b4167bd0184b9fc8b3474a4c29d08e801cbc1596b63a5ab380ce0fc83a15cd";
+        let script = script! {
+            for _ in 0..15 {
+                { u32_push(1) }
+            }
+            { blake3_var_length(60) }
+            { push_bytes_hex(hex_out) }
+            { blake3_hash_equalverify() }
+            OP_TRUE
+        }
+        .compile();
+
+        let mut native = NativeExecutor::new(&script).unwrap();
+        assert!(native.run().unwrap());
+        assert_eq!(CrossChecker::new().check(&script), Ok(()));
+    }
+
+    #[test]
+    fn test_native_blake3_u4() {
+        let hex_in = "00000001".repeat(16);
+        let hex_out = "86ca95aefdee3d969af9bcc78b48a5c1115be5d66cafc2fc106bbd982d820e70";
+        let script = u4_hash_script(&hex_in, hex_out, |stack, num_bytes| {
+            blake3(stack, num_bytes, 8)
+        });
+        let mut native = NativeExecutor::new(&script).unwrap();
+        assert_eq!(native.run(), Ok(true));
+        assert_eq!(CrossChecker::new().check(&script), Ok(()));
+
+        // A wrong digest fails natively as well
+        let wrong_out = hex_out.replace('8', '9');
+        let script = u4_hash_script(&hex_in, &wrong_out, |stack, num_bytes| {
+            blake3(stack, num_bytes, 8)
+        });
+        let mut native = NativeExecutor::new(&script).unwrap();
+        assert!(matches!(native.run(), Err(NativeError::Verify { .. })));
+    }
+
+    #[test]
+    fn test_native_sha256_u4_stack() {
+        for message in ["Hello.", "This is a longer message that needs a second block of sha256"] {
+            let hex_in = hex::encode(message);
+            let hex_out = hex::encode(Sha256::digest(message.as_bytes()));
+            let script = u4_hash_script(&hex_in, &hex_out, |stack, num_bytes| {
+                sha256_stack(stack, num_bytes);
+            });
+            let mut native = NativeExecutor::new(&script).unwrap();
+            assert_eq!(native.run(), Ok(true), "{}", message);
+            assert_eq!(CrossChecker::new().check(&script), Ok(()), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_cross_check_stack_tracker_variables() {
+        let mut stack = StackTracker::new();
+        let mut checker = CrossChecker::new();
+        let a = stack.number(5);
+        checker.watch_var("a", &stack, &a);
+        let b = stack.number(7);
+        checker.watch_var("b", &stack, &b);
+        let sum = stack.op_add();
+        checker.watch_var("sum", &stack, &sum);
+
+        let script = stack.get_script().compile();
+        assert!(checker.check(&script).is_ok());
+        assert_eq!(
+            checker.native_values(&script).unwrap(),
+            vec![
+                ("a".to_string(), vec![5]),
+                ("b".to_string(), vec![7]),
+                ("sum".to_string(), vec![12])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_opcode() {
+        let script = script! { 1 OP_SHA256 }.compile();
+        let mut native = NativeExecutor::new(&script).unwrap();
+        assert!(matches!(native.run(), Err(NativeError::Unsupported { offset: 1, .. })));
+    }
+}
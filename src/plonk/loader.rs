@@ -0,0 +1,240 @@
+//
+// Loaders for PLONK verifying keys and proofs exported by snarkjs
+//
+// The layout follows the Groth16 files of snarkjs, see `groth16::loader`: decimal strings,
+// points in projective form [x, y, z] with z = 1, or z = 0 for the point at infinity, and Fq2
+// elements as [c0, c1]. Public inputs are the same `public.json`, which is loaded by
+// `groth16::loader::public_inputs_from_snarkjs_json`.
+//
+
+use crate::groth16::loader::{
+    check_snarkjs_protocol, from_decimal, snarkjs_g1, snarkjs_g2, LoadError,
+};
+use crate::plonk::verifier::{PlonkProof, PlonkVerifyingKey};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct SnarkjsVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    power: u32,
+    k1: String,
+    k2: String,
+    #[serde(rename = "Qm")]
+    qm: [String; 3],
+    #[serde(rename = "Ql")]
+    ql: [String; 3],
+    #[serde(rename = "Qr")]
+    qr: [String; 3],
+    #[serde(rename = "Qo")]
+    qo: [String; 3],
+    #[serde(rename = "Qc")]
+    qc: [String; 3],
+    #[serde(rename = "S1")]
+    s1: [String; 3],
+    #[serde(rename = "S2")]
+    s2: [String; 3],
+    #[serde(rename = "S3")]
+    s3: [String; 3],
+    #[serde(rename = "X_2")]
+    x_2: [[String; 2]; 3],
+    w: String,
+}
+
+#[derive(Deserialize)]
+struct SnarkjsProof {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "A")]
+    a: [String; 3],
+    #[serde(rename = "B")]
+    b: [String; 3],
+    #[serde(rename = "C")]
+    c: [String; 3],
+    #[serde(rename = "Z")]
+    z: [String; 3],
+    #[serde(rename = "T1")]
+    t1: [String; 3],
+    #[serde(rename = "T2")]
+    t2: [String; 3],
+    #[serde(rename = "T3")]
+    t3: [String; 3],
+    #[serde(rename = "Wxi")]
+    wxi: [String; 3],
+    #[serde(rename = "Wxiw")]
+    wxiw: [String; 3],
+    eval_a: String,
+    eval_b: String,
+    eval_c: String,
+    eval_s1: String,
+    eval_s2: String,
+    eval_zw: String,
+}
+
+/// Loads the `verification_key.json` of snarkjs.
+pub fn vk_from_snarkjs_json(json: &str) -> Result<PlonkVerifyingKey, LoadError> {
+    let vk: SnarkjsVerifyingKey = serde_json::from_str(json)?;
+    check_snarkjs_protocol("plonk", &vk.protocol, &vk.curve)?;
+    // The domain size 2^power has to fit the two-adicity of Fr
+    if vk.power > 28 {
+        return Err(LoadError::InvalidNumber(vk.power.to_string()));
+    }
+    Ok(PlonkVerifyingKey {
+        n_public: vk.n_public,
+        power: vk.power,
+        k1: from_decimal(&vk.k1)?,
+        k2: from_decimal(&vk.k2)?,
+        qm: snarkjs_g1(&vk.qm)?,
+        ql: snarkjs_g1(&vk.ql)?,
+        qr: snarkjs_g1(&vk.qr)?,
+        qo: snarkjs_g1(&vk.qo)?,
+        qc: snarkjs_g1(&vk.qc)?,
+        s1: snarkjs_g1(&vk.s1)?,
+        s2: snarkjs_g1(&vk.s2)?,
+        s3: snarkjs_g1(&vk.s3)?,
+        x_2: snarkjs_g2(&vk.x_2)?,
+        w: from_decimal(&vk.w)?,
+    })
+}
+
+/// Loads the `proof.json` of snarkjs.
+pub fn proof_from_snarkjs_json(json: &str) -> Result<PlonkProof, LoadError> {
+    let proof: SnarkjsProof = serde_json::from_str(json)?;
+    check_snarkjs_protocol("plonk", &proof.protocol, &proof.curve)?;
+    Ok(PlonkProof {
+        a: snarkjs_g1(&proof.a)?,
+        b: snarkjs_g1(&proof.b)?,
+        c: snarkjs_g1(&proof.c)?,
+        z: snarkjs_g1(&proof.z)?,
+        t1: snarkjs_g1(&proof.t1)?,
+        t2: snarkjs_g1(&proof.t2)?,
+        t3: snarkjs_g1(&proof.t3)?,
+        wxi: snarkjs_g1(&proof.wxi)?,
+        wxiw: snarkjs_g1(&proof.wxiw)?,
+        eval_a: from_decimal(&proof.eval_a)?,
+        eval_b: from_decimal(&proof.eval_b)?,
+        eval_c: from_decimal(&proof.eval_c)?,
+        eval_s1: from_decimal(&proof.eval_s1)?,
+        eval_s2: from_decimal(&proof.eval_s2)?,
+        eval_zw: from_decimal(&proof.eval_zw)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::PrimeField;
+    use ark_std::UniformRand;
+    use num_bigint::BigUint;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use serde_json::{json, Value};
+
+    fn decimal<F: PrimeField>(a: F) -> String { Into::<BigUint>::into(a).to_string() }
+
+    fn snarkjs_g1_json(p: ark_bn254::G1Affine) -> Value { json!([decimal(p.x), decimal(p.y), "1"]) }
+
+    #[test]
+    fn test_load_snarkjs_json() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let g1 = ark_bn254::G1Affine::generator();
+        let points: Vec<_> = (0..17)
+            .map(|_| (g1 * ark_bn254::Fr::rand(&mut prng)).into_affine())
+            .collect();
+        let scalars: Vec<_> = (0..8).map(|_| ark_bn254::Fr::rand(&mut prng)).collect();
+        let x_2 = (ark_bn254::G2Affine::generator() * scalars[7]).into_affine();
+
+        let vk_json = json!({
+            "protocol": "plonk",
+            "curve": "bn128",
+            "nPublic": 1,
+            "power": 10,
+            "k1": "2",
+            "k2": "3",
+            "Qm": snarkjs_g1_json(points[0]),
+            "Ql": snarkjs_g1_json(points[1]),
+            "Qr": snarkjs_g1_json(points[2]),
+            "Qo": snarkjs_g1_json(points[3]),
+            "Qc": snarkjs_g1_json(points[4]),
+            "S1": snarkjs_g1_json(points[5]),
+            "S2": snarkjs_g1_json(points[6]),
+            "S3": snarkjs_g1_json(points[7]),
+            "X_2": [
+                [decimal(x_2.x.c0), decimal(x_2.x.c1)],
+                [decimal(x_2.y.c0), decimal(x_2.y.c1)],
+                ["1", "0"]
+            ],
+            "w": decimal(scalars[6]),
+        });
+        let vk = vk_from_snarkjs_json(&vk_json.to_string()).unwrap();
+        assert_eq!(
+            vk,
+            PlonkVerifyingKey {
+                n_public: 1,
+                power: 10,
+                k1: ark_bn254::Fr::from(2),
+                k2: ark_bn254::Fr::from(3),
+                qm: points[0],
+                ql: points[1],
+                qr: points[2],
+                qo: points[3],
+                qc: points[4],
+                s1: points[5],
+                s2: points[6],
+                s3: points[7],
+                x_2,
+                w: scalars[6],
+            }
+        );
+
+        let proof_json = json!({
+            "A": snarkjs_g1_json(points[8]),
+            "B": snarkjs_g1_json(points[9]),
+            "C": snarkjs_g1_json(points[10]),
+            "Z": snarkjs_g1_json(points[11]),
+            "T1": snarkjs_g1_json(points[12]),
+            "T2": snarkjs_g1_json(points[13]),
+            "T3": snarkjs_g1_json(points[14]),
+            "Wxi": snarkjs_g1_json(points[15]),
+            "Wxiw": snarkjs_g1_json(points[16]),
+            "eval_a": decimal(scalars[0]),
+            "eval_b": decimal(scalars[1]),
+            "eval_c": decimal(scalars[2]),
+            "eval_s1": decimal(scalars[3]),
+            "eval_s2": decimal(scalars[4]),
+            "eval_zw": decimal(scalars[5]),
+            "protocol": "plonk",
+            "curve": "bn128",
+        });
+        let proof = proof_from_snarkjs_json(&proof_json.to_string()).unwrap();
+        assert_eq!(
+            proof,
+            PlonkProof {
+                a: points[8],
+                b: points[9],
+                c: points[10],
+                z: points[11],
+                t1: points[12],
+                t2: points[13],
+                t3: points[14],
+                wxi: points[15],
+                wxiw: points[16],
+                eval_a: scalars[0],
+                eval_b: scalars[1],
+                eval_c: scalars[2],
+                eval_s1: scalars[3],
+                eval_s2: scalars[4],
+                eval_zw: scalars[5],
+            }
+        );
+
+        // An fflonk key isn't a PLONK key
+        assert!(matches!(
+            vk_from_snarkjs_json(&vk_json.to_string().replace("plonk", "fflonk")),
+            Err(LoadError::UnsupportedProtocol(_))
+        ));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod loader;
+pub mod verifier;
+
+pub use verifier::{PlonkProof, PlonkVerifyingKey, Verifier, VerifierError};
@@ -0,0 +1,1004 @@
+//
+// PLONK verifier for snarkjs proofs over BN254
+//
+// The script follows `plonk_verify` of snarkjs: it recomputes the challenges beta, gamma, alpha,
+// xi, v and u, the Lagrange evaluations and the public input polynomial at xi, and checks the
+// batched KZG opening
+//
+//   e(-(Wxi + u * Wxiw), [x]_2) * e(xi * Wxi + u * xi * w * Wxiw + F - E, [1]_2) = 1
+//
+// The transcript is the one of snarkjs with blake3 instead of keccak and compressed points, like
+// the fflonk verifier, so that a challenge fits into a single `blake3_var_length` call. The final
+// exponentiation is replaced by the hints of `groth16::hints::Hints`, computed off-chain from the
+// two points, so the script is specific to one proof.
+//
+
+use crate::bn254::curves::{G1Affine, G1Projective, ProjectiveCurve};
+use crate::bn254::domain::Domain;
+use crate::bn254::ell_coeffs::G2Prepared;
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::bn254::fq::Fq;
+use crate::bn254::fq12::Fq12;
+use crate::bn254::fq2::Fq2;
+use crate::bn254::fr::Fr;
+use crate::bn254::pairing::Pairing;
+use crate::bn254::utils;
+use crate::fflonk::verifier::{fq_push, fr_push, from_stack, try_final_stack};
+use crate::groth16::hints::Hints;
+use crate::hash::blake3::blake3_var_length;
+use crate::treepp::*;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing as ArkPairing;
+use ark_ec::AffineRepr;
+use ark_ff::Field;
+use core::fmt;
+
+/// Largest number of public inputs, the first challenge hashes (11 + nPublic) * 32 bytes and
+/// `blake3_var_length` takes at most 512.
+pub const MAX_PUBLIC: usize = 5;
+
+/// Why `Verifier::verify_proof` can't build a script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifierError {
+    /// The number of public inputs does not match the verifying key.
+    InvalidPublicInputCount { expected: usize, actual: usize },
+    /// More public inputs than `MAX_PUBLIC`.
+    TooManyPublicInputs(usize),
+    /// The proof doesn't verify, so there are no hints for the final exponentiation.
+    InvalidProof,
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::InvalidPublicInputCount { expected, actual } => {
+                write!(f, "expected {} public inputs, got {}", expected, actual)
+            }
+            VerifierError::TooManyPublicInputs(n) => {
+                write!(f, "{} public inputs, at most {} are supported", n, MAX_PUBLIC)
+            }
+            VerifierError::InvalidProof => write!(f, "invalid proof"),
+        }
+    }
+}
+
+/// Verifying key of a snarkjs PLONK circuit, see `plonk::loader::vk_from_snarkjs_json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlonkVerifyingKey {
+    pub n_public: usize,
+    /// log2 of the domain size
+    pub power: u32,
+    pub k1: ark_bn254::Fr,
+    pub k2: ark_bn254::Fr,
+    pub qm: ark_bn254::G1Affine,
+    pub ql: ark_bn254::G1Affine,
+    pub qr: ark_bn254::G1Affine,
+    pub qo: ark_bn254::G1Affine,
+    pub qc: ark_bn254::G1Affine,
+    pub s1: ark_bn254::G1Affine,
+    pub s2: ark_bn254::G1Affine,
+    pub s3: ark_bn254::G1Affine,
+    pub x_2: ark_bn254::G2Affine,
+    /// Generator of the domain
+    pub w: ark_bn254::Fr,
+}
+
+/// Proof of a snarkjs PLONK circuit, see `plonk::loader::proof_from_snarkjs_json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlonkProof {
+    pub a: ark_bn254::G1Affine,
+    pub b: ark_bn254::G1Affine,
+    pub c: ark_bn254::G1Affine,
+    pub z: ark_bn254::G1Affine,
+    pub t1: ark_bn254::G1Affine,
+    pub t2: ark_bn254::G1Affine,
+    pub t3: ark_bn254::G1Affine,
+    pub wxi: ark_bn254::G1Affine,
+    pub wxiw: ark_bn254::G1Affine,
+    pub eval_a: ark_bn254::Fr,
+    pub eval_b: ark_bn254::Fr,
+    pub eval_c: ark_bn254::Fr,
+    pub eval_s1: ark_bn254::Fr,
+    pub eval_s2: ark_bn254::Fr,
+    pub eval_zw: ark_bn254::Fr,
+}
+
+pub struct Verifier;
+
+impl Verifier {
+    /// Returns a script that verifies the proof for the public inputs and leaves `OP_TRUE`.
+    ///
+    /// Fails if the circuit has more than `MAX_PUBLIC` public inputs, or if the proof is invalid,
+    /// since then there are no hints for the final exponentiation.
+    pub fn verify_proof(
+        vk: &PlonkVerifyingKey,
+        proof: &PlonkProof,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Result<Script, VerifierError> {
+        let points_script = Self::points_script(vk, proof, public_inputs)?;
+        let (p2, p1) = Self::compute_points(points_script.clone())?;
+
+        let g2 = ark_bn254::G2Affine::generator();
+        let f = Bn254::multi_miller_loop_affine([p2, p1], [g2, vk.x_2]).0;
+        let hints = Hints::try_from_miller_loop(f).ok_or(VerifierError::InvalidProof)?;
+
+        Ok(script! {
+            { points_script }
+            { Self::pairing_script(vk, &hints) }
+        })
+    }
+
+    // Input Stack: [P2.x, P2.y, P1.x, P1.y]
+    // Output Stack: [OP_TRUE] if e(P2, [1]_2) * e(P1, [x]_2) = 1 with the hints
+    fn pairing_script(vk: &PlonkVerifyingKey, hints: &Hints) -> Script {
+        let g2 = ark_bn254::G2Affine::generator();
+        let mut c_p3 = hints.c;
+        c_p3.frobenius_map_in_place(3);
+
+        script! {
+            { Fq2::roll(1) }
+            { utils::from_eval_point_in_stack() }
+            { Fq2::roll(1) }
+            { utils::from_eval_point_in_stack() }
+            // [P2.x', P2.y', P1.x', P1.y']
+            { utils::fq12_push(hints.c) }
+            { utils::fq12_push(hints.c_inv) }
+            { utils::fq12_push(hints.wi) }
+            { Pairing::dual_miller_loop_with_c_wi(
+                &G2Prepared::from_affine(g2),
+                &G2Prepared::from_affine(vk.x_2),
+                true,
+            ) }
+            { utils::fq12_push(c_p3) }
+            { Fq12::equalverify() }
+            OP_TRUE
+        }
+    }
+
+    // Output Stack: [P2.x, P2.y, P1.x, P1.y] with
+    //   P1 = -(Wxi + u * Wxiw)
+    //   P2 = xi * Wxi + u * xi * w * Wxiw + F - E
+    fn points_script(
+        vk: &PlonkVerifyingKey,
+        proof: &PlonkProof,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Result<Script, VerifierError> {
+        if public_inputs.len() != vk.n_public {
+            return Err(VerifierError::InvalidPublicInputCount {
+                expected: vk.n_public,
+                actual: public_inputs.len(),
+            });
+        }
+        if vk.n_public > MAX_PUBLIC {
+            return Err(VerifierError::TooManyPublicInputs(vk.n_public));
+        }
+        let domain = Domain::new(1 << vk.power);
+        assert_eq!(domain.generator(), vk.w, "w isn't the generator of the domain");
+
+        Ok(script! {
+            { compute_challenges(vk, proof, public_inputs) }
+            // [beta, gamma, alpha, xi, v, u]
+
+            { compute_lagranges_and_pi(&domain, public_inputs) }
+            // [beta, gamma, alpha, xi, v, u, zh, L1, PI]
+
+            { compute_scalars_toaltstack(vk, proof) }
+            // [ | u, -e, v^5, v^4, v^3, v^2, v, -zh * xin^2, -zh * xin, -zh, -s3_scalar, z_scalar, u * xi * w, xi]
+
+            { compute_p2(vk, proof) }
+            // [P2 | u]
+
+            { compute_p1(proof) }
+            // [P2, P1]
+
+            { G1Projective::into_affine() }
+            { Fq::toaltstack() }
+            { Fq::toaltstack() }
+            { G1Projective::into_affine() }
+            { Fq::fromaltstack() }
+            { Fq::fromaltstack() }
+        })
+    }
+
+    // Runs the script off-chain to get P2 and P1, which the hints of the pairing check depend on.
+    fn compute_points(
+        points_script: Script,
+    ) -> Result<(ark_bn254::G1Affine, ark_bn254::G1Affine), VerifierError> {
+        let stack = try_final_stack(points_script).map_err(|_| VerifierError::InvalidProof)?;
+        Ok((
+            ark_bn254::G1Affine::new(
+                from_stack::<Fq, _>(&stack, 3),
+                from_stack::<Fq, _>(&stack, 2),
+            ),
+            ark_bn254::G1Affine::new(
+                from_stack::<Fq, _>(&stack, 1),
+                from_stack::<Fq, _>(&stack, 0),
+            ),
+        ))
+    }
+}
+
+fn g1_push(p: ark_bn254::G1Affine) -> Script {
+    script! {
+        { fq_push(p.x) }
+        { fq_push(p.y) }
+    }
+}
+
+// The first element of a transcript message ends on top of the stack, so the elements are
+// pushed and converted to bytes from the last one to the first one.
+//
+// Output Stack: [beta, gamma, alpha, xi, v, u]
+fn compute_challenges(
+    vk: &PlonkVerifyingKey,
+    proof: &PlonkProof,
+    public_inputs: &[ark_bn254::Fr],
+) -> Script {
+    let commitments = [vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, vk.s1, vk.s2, vk.s3];
+    let evaluations = [
+        proof.eval_a,
+        proof.eval_b,
+        proof.eval_c,
+        proof.eval_s1,
+        proof.eval_s2,
+        proof.eval_zw,
+    ];
+    script! {
+        // beta = H(Qm, Ql, Qr, Qo, Qc, S1, S2, S3, public inputs, A, B, C)
+        for p in [proof.c, proof.b, proof.a] {
+            { g1_push(p) }
+            { G1Affine::convert_to_compressed() }
+        }
+        for input in public_inputs.iter().rev() {
+            { fr_push(*input) }
+            { Fr::convert_to_be_bytes() }
+        }
+        for p in commitments.iter().rev() {
+            { g1_push(*p) }
+            { G1Affine::convert_to_compressed() }
+        }
+        { blake3_var_length((11 + public_inputs.len()) * 32) }
+        { Fr::from_hash() }
+        // [beta]
+
+        // gamma = H(beta)
+        { Fr::copy(0) }
+        { Fr::convert_to_be_bytes() }
+        { blake3_var_length(32) }
+        { Fr::from_hash() }
+        // [beta, gamma]
+
+        // alpha = H(beta, gamma, Z)
+        { Fr::copy(1) }
+        { Fr::toaltstack() }
+        { Fr::copy(0) }
+        { Fr::toaltstack() }
+        { g1_push(proof.z) }
+        { G1Affine::convert_to_compressed() }
+        { Fr::fromaltstack() }
+        { Fr::convert_to_be_bytes() }
+        { Fr::fromaltstack() }
+        { Fr::convert_to_be_bytes() }
+        { blake3_var_length(96) }
+        { Fr::from_hash() }
+        // [beta, gamma, alpha]
+
+        // xi = H(alpha, T1, T2, T3)
+        { Fr::copy(0) }
+        { Fr::toaltstack() }
+        for p in [proof.t3, proof.t2, proof.t1] {
+            { g1_push(p) }
+            { G1Affine::convert_to_compressed() }
+        }
+        { Fr::fromaltstack() }
+        { Fr::convert_to_be_bytes() }
+        { blake3_var_length(128) }
+        { Fr::from_hash() }
+        // [beta, gamma, alpha, xi]
+
+        // v = H(xi, a, b, c, s1, s2, zw)
+        { Fr::copy(0) }
+        { Fr::toaltstack() }
+        for evaluation in evaluations.iter().rev() {
+            { fr_push(*evaluation) }
+            { Fr::convert_to_be_bytes() }
+        }
+        { Fr::fromaltstack() }
+        { Fr::convert_to_be_bytes() }
+        { blake3_var_length(224) }
+        { Fr::from_hash() }
+        // [beta, gamma, alpha, xi, v]
+
+        // u = H(Wxi, Wxiw)
+        for p in [proof.wxiw, proof.wxi] {
+            { g1_push(p) }
+            { G1Affine::convert_to_compressed() }
+        }
+        { blake3_var_length(64) }
+        { Fr::from_hash() }
+        // [beta, gamma, alpha, xi, v, u]
+    }
+}
+
+// Input Stack: [beta, gamma, alpha, xi, v, u]
+// Output Stack: [beta, gamma, alpha, xi, v, u, zh, L1, PI]
+//
+// L1 is the Lagrange polynomial of the first domain element and PI = -Σ input_i * L(i+1)
+fn compute_lagranges_and_pi(domain: &Domain, public_inputs: &[ark_bn254::Fr]) -> Script {
+    let m = public_inputs.len().max(1) as u32;
+    let indices: Vec<u64> = (0..m as u64).collect();
+    script! {
+        { Fr::copy(2) }
+        { domain.lagranges(&indices) }
+        // [..., zh, L1, ..., Lm]
+
+        { Fr::push_zero() }
+        for (i, input) in public_inputs.iter().enumerate() {
+            { Fr::copy(m - i as u32) }
+            { Fr::mul_by_constant(input) }
+            { Fr::sub(1, 0) }
+        }
+        // [..., zh, L1, ..., Lm, PI]
+
+        for _ in 1..m {
+            { Fr::roll(1) }
+            { Fr::drop() }
+        }
+    }
+}
+
+// Input Stack: [beta, gamma, alpha, xi, v, u, zh, L1, PI]
+// Output Stack: [ | u, -e, v^5, v^4, v^3, v^2, v, -zh * xin^2, -zh * xin, -zh, -s3_scalar, z_scalar, u * xi * w, xi]
+//
+// The scalars of F - E and of the opening proofs in the order `compute_p2` and `compute_p1`
+// take them from the altstack, with
+//   K = alpha * (a + beta * s1 + gamma) * (b + beta * s2 + gamma) * zw
+//   r0 = PI - L1 * alpha^2 - K * (c + gamma)
+//   e = -r0 + v * a + v^2 * b + v^3 * c + v^4 * s1 + v^5 * s2 + u * zw
+//   s3_scalar = K * beta
+//   z_scalar = alpha * (a + beta * xi + gamma) * (b + beta * k1 * xi + gamma) * (c + beta * k2 * xi + gamma)
+//              + L1 * alpha^2 + u
+fn compute_scalars_toaltstack(vk: &PlonkVerifyingKey, proof: &PlonkProof) -> Script {
+    let evaluations = [
+        proof.eval_a,
+        proof.eval_b,
+        proof.eval_c,
+        proof.eval_s1,
+        proof.eval_s2,
+    ];
+    script! {
+        // K
+        { Fr::copy(8) }
+        { Fr::mul_by_constant(&proof.eval_s1) }
+        { Fr::copy(8) }
+        { Fr::add(1, 0) }
+        { fr_push(proof.eval_a) }
+        { Fr::add(1, 0) }
+        { Fr::copy(9) }
+        { Fr::mul_by_constant(&proof.eval_s2) }
+        { Fr::copy(9) }
+        { Fr::add(1, 0) }
+        { fr_push(proof.eval_b) }
+        { Fr::add(1, 0) }
+        { Fr::mul() }
+        { Fr::copy(7) }
+        { Fr::mul() }
+        { Fr::mul_by_constant(&proof.eval_zw) }
+        // [beta, gamma, alpha, xi, v, u, zh, L1, PI, K]
+
+        // L1 * alpha^2
+        { Fr::copy(7) }
+        { Fr::square() }
+        { Fr::copy(3) }
+        { Fr::mul() }
+        // [beta, gamma, alpha, xi, v, u, zh, L1, PI, K, L1 * alpha^2]
+
+        // r0
+        { Fr::copy(9) }
+        { fr_push(proof.eval_c) }
+        { Fr::add(1, 0) }
+        { Fr::copy(2) }
+        { Fr::mul() }
+        { Fr::copy(3) }
+        { Fr::copy(2) }
+        { Fr::sub(1, 0) }
+        { Fr::sub(0, 1) }
+        // [beta, gamma, alpha, xi, v, u, zh, L1, PI, K, L1 * alpha^2, r0]
+        { Fr::roll(3) }
+        { Fr::drop() }
+        { Fr::roll(3) }
+        { Fr::drop() }
+        // [beta, gamma, alpha, xi, v, u, zh, K, L1 * alpha^2, r0]
+
+        // v, v^2, v^3, v^4, v^5
+        { Fr::copy(5) }
+        for k in 2..6 {
+            { Fr::copy(0) }
+            { Fr::copy(5 + k) }
+            { Fr::mul() }
+        }
+        // [beta, gamma, alpha, xi, v, u, zh, K, L1 * alpha^2, r0, v, v^2, v^3, v^4, v^5]
+
+        // -e
+        { Fr::copy(5) }
+        for (j, evaluation) in evaluations.iter().enumerate() {
+            { Fr::copy(5 - j as u32) }
+            { Fr::mul_by_constant(evaluation) }
+            { Fr::sub(1, 0) }
+        }
+        { Fr::copy(10) }
+        { Fr::mul_by_constant(&proof.eval_zw) }
+        { Fr::sub(1, 0) }
+        // [beta, gamma, alpha, xi, v, u, zh, K, L1 * alpha^2, r0, v, v^2, v^3, v^4, v^5, -e]
+
+        { Fr::copy(10) }
+        { Fr::toaltstack() }
+        for _ in 0..6 {
+            { Fr::toaltstack() }
+        }
+        { Fr::drop() }
+        { Fr::roll(4) }
+        { Fr::drop() }
+        // [beta, gamma, alpha, xi, u, zh, K, L1 * alpha^2 | u, -e, v^5, v^4, v^3, v^2, v]
+
+        // -zh * xin^2, -zh * xin, -zh with xin = zh + 1
+        { Fr::copy(2) }
+        { Fr::push_one() }
+        { Fr::add(1, 0) }
+        { Fr::copy(3) }
+        { Fr::neg(0) }
+        { Fr::copy(0) }
+        { Fr::copy(2) }
+        { Fr::mul() }
+        { Fr::copy(0) }
+        { Fr::roll(3) }
+        { Fr::mul() }
+        // [beta, gamma, alpha, xi, u, zh, K, L1 * alpha^2, -zh, -zh * xin, -zh * xin^2]
+        for _ in 0..3 {
+            { Fr::toaltstack() }
+        }
+        { Fr::roll(2) }
+        { Fr::drop() }
+        // [beta, gamma, alpha, xi, u, K, L1 * alpha^2]
+
+        // -s3_scalar
+        { Fr::roll(1) }
+        { Fr::copy(6) }
+        { Fr::mul() }
+        { Fr::neg(0) }
+        { Fr::toaltstack() }
+        // [beta, gamma, alpha, xi, u, L1 * alpha^2]
+
+        // z_scalar
+        { Fr::copy(5) }
+        { Fr::copy(3) }
+        { Fr::mul() }
+        // [beta, gamma, alpha, xi, u, L1 * alpha^2, beta * xi]
+        { Fr::copy(0) }
+        { Fr::copy(6) }
+        { Fr::add(1, 0) }
+        { fr_push(proof.eval_a) }
+        { Fr::add(1, 0) }
+        { Fr::copy(1) }
+        { Fr::mul_by_constant(&vk.k1) }
+        { Fr::copy(7) }
+        { Fr::add(1, 0) }
+        { fr_push(proof.eval_b) }
+        { Fr::add(1, 0) }
+        { Fr::mul() }
+        { Fr::roll(1) }
+        { Fr::mul_by_constant(&vk.k2) }
+        { Fr::copy(6) }
+        { Fr::add(1, 0) }
+        { fr_push(proof.eval_c) }
+        { Fr::add(1, 0) }
+        { Fr::mul() }
+        // [beta, gamma, alpha, xi, u, L1 * alpha^2, (a + beta * xi + gamma) * ... * (c + beta * k2 * xi + gamma)]
+        { Fr::copy(4) }
+        { Fr::mul() }
+        { Fr::add(1, 0) }
+        { Fr::copy(1) }
+        { Fr::add(1, 0) }
+        { Fr::toaltstack() }
+        // [beta, gamma, alpha, xi, u]
+
+        // u * xi * w, xi
+        { Fr::copy(1) }
+        { Fr::mul() }
+        { Fr::mul_by_constant(&vk.w) }
+        { Fr::toaltstack() }
+        { Fr::toaltstack() }
+        { Fr::drop() }
+        { Fr::drop() }
+        { Fr::drop() }
+    }
+}
+
+// Input Stack: [ | -e, v^5, v^4, v^3, v^2, v, -zh * xin^2, -zh * xin, -zh, -s3_scalar, z_scalar, u * xi * w, xi]
+// Output Stack: [P2]
+//
+// P2 = xi * Wxi + u * xi * w * Wxiw + F - E with
+//   F - E = D + v * A + v^2 * B + v^3 * C + v^4 * S1 + v^5 * S2 - e * G1
+//   D = a * b * Qm + a * Ql + b * Qr + c * Qo + Qc + z_scalar * Z - s3_scalar * S3
+//       - zh * (T1 + xin * T2 + xin^2 * T3)
+fn compute_p2(vk: &PlonkVerifyingKey, proof: &PlonkProof) -> Script {
+    script! {
+        { G1Projective::push(proof.wxi.into_group()) }
+        { Fr::fromaltstack() }
+        { G1Projective::push(proof.wxiw.into_group()) }
+        { Fr::fromaltstack() }
+        { G1Projective::push(vk.qm.into_group()) }
+        { fr_push(proof.eval_a) }
+        { Fr::mul_by_constant(&proof.eval_b) }
+        { G1Projective::batched_scalar_mul::<3>() }
+
+        { G1Projective::push(vk.ql.into_group()) }
+        { fr_push(proof.eval_a) }
+        { G1Projective::push(vk.qr.into_group()) }
+        { fr_push(proof.eval_b) }
+        { G1Projective::push(vk.qo.into_group()) }
+        { fr_push(proof.eval_c) }
+        { G1Projective::batched_scalar_mul::<3>() }
+        { G1Projective::add() }
+
+        for p in [proof.z, vk.s3, proof.t1] {
+            { G1Projective::push(p.into_group()) }
+            { Fr::fromaltstack() }
+        }
+        { G1Projective::batched_scalar_mul::<3>() }
+        { G1Projective::add() }
+
+        for p in [proof.t2, proof.t3, proof.a] {
+            { G1Projective::push(p.into_group()) }
+            { Fr::fromaltstack() }
+        }
+        { G1Projective::batched_scalar_mul::<3>() }
+        { G1Projective::add() }
+
+        for p in [proof.b, proof.c, vk.s1] {
+            { G1Projective::push(p.into_group()) }
+            { Fr::fromaltstack() }
+        }
+        { G1Projective::batched_scalar_mul::<3>() }
+        { G1Projective::add() }
+
+        { G1Projective::push(vk.s2.into_group()) }
+        { Fr::fromaltstack() }
+        { G1Projective::push_generator() }
+        { Fr::fromaltstack() }
+        { G1Projective::batched_scalar_mul::<2>() }
+        { G1Projective::add() }
+
+        { G1Projective::push(vk.qc.into_group()) }
+        { G1Projective::add() }
+    }
+}
+
+// Input Stack: [ | u]
+// Output Stack: [P1] = [-(Wxi + u * Wxiw)]
+fn compute_p1(proof: &PlonkProof) -> Script {
+    script! {
+        { G1Projective::push(proof.wxiw.into_group()) }
+        { Fr::fromaltstack() }
+        { G1Projective::scalar_mul() }
+        { G1Projective::push(proof.wxi.into_group()) }
+        { G1Projective::add() }
+        { G1Projective::neg() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execute_script_as_chunks;
+    use ark_ec::CurveGroup;
+    use ark_ff::{One, Zero};
+    use ark_std::UniformRand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    type Poly = Vec<ark_bn254::Fr>;
+
+    fn poly_eval(p: &[ark_bn254::Fr], z: ark_bn254::Fr) -> ark_bn254::Fr {
+        p.iter().rev().fold(ark_bn254::Fr::zero(), |acc, c| acc * z + c)
+    }
+
+    fn poly_add(a: &[ark_bn254::Fr], b: &[ark_bn254::Fr]) -> Poly {
+        (0..a.len().max(b.len()))
+            .map(|i| a.get(i).copied().unwrap_or_default() + b.get(i).copied().unwrap_or_default())
+            .collect()
+    }
+
+    fn poly_scale(a: &[ark_bn254::Fr], s: ark_bn254::Fr) -> Poly {
+        a.iter().map(|c| *c * s).collect()
+    }
+
+    fn poly_mul(a: &[ark_bn254::Fr], b: &[ark_bn254::Fr]) -> Poly {
+        let mut product = vec![ark_bn254::Fr::zero(); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] += *x * y;
+            }
+        }
+        product
+    }
+
+    // p / (X - z), z has to be a root of p
+    fn poly_div_linear(p: &[ark_bn254::Fr], z: ark_bn254::Fr) -> Poly {
+        let mut quotient = vec![ark_bn254::Fr::zero(); p.len() - 1];
+        let mut carry = ark_bn254::Fr::zero();
+        for i in (1..p.len()).rev() {
+            carry = p[i] + carry * z;
+            quotient[i - 1] = carry;
+        }
+        assert!((p[0] + carry * z).is_zero(), "z isn't a root");
+        quotient
+    }
+
+    // p / (X^n - 1), p has to vanish on the domain
+    fn poly_div_vanishing(p: &[ark_bn254::Fr], n: usize) -> Poly {
+        let mut remainder = p.to_vec();
+        let mut quotient = vec![ark_bn254::Fr::zero(); p.len().saturating_sub(n)];
+        for i in (n..p.len()).rev() {
+            let c = remainder[i];
+            quotient[i - n] = c;
+            remainder[i] -= c;
+            remainder[i - n] += c;
+        }
+        assert!(remainder.iter().all(|c| c.is_zero()), "p doesn't vanish on the domain");
+        quotient
+    }
+
+    // The coefficients of the polynomial with the evaluations over the domain
+    fn interpolate(domain: &Domain, evaluations: &[ark_bn254::Fr]) -> Poly {
+        let n_inv = ark_bn254::Fr::from(domain.size()).inverse().unwrap();
+        let w_inv = domain.generator().inverse().unwrap();
+        (0..domain.size())
+            .map(|k| {
+                evaluations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| *e * w_inv.pow([i as u64 * k]))
+                    .sum::<ark_bn254::Fr>()
+                    * n_inv
+            })
+            .collect()
+    }
+
+    fn commit(powers_of_tau: &[ark_bn254::G1Affine], p: &[ark_bn254::Fr]) -> ark_bn254::G1Affine {
+        assert!(p.len() <= powers_of_tau.len(), "not enough powers of tau");
+        p.iter()
+            .zip(powers_of_tau)
+            .map(|(c, g)| *g * c)
+            .sum::<ark_bn254::G1Projective>()
+            .into_affine()
+    }
+
+    // [beta, gamma, alpha, xi, v, u], each of them only depends on the proof up to its round
+    fn challenges(
+        vk: &PlonkVerifyingKey,
+        proof: &PlonkProof,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Vec<ark_bn254::Fr> {
+        let stack = try_final_stack(compute_challenges(vk, proof, public_inputs)).unwrap();
+        (0..6).rev().map(|depth| from_stack::<Fr, ark_bn254::Fr>(&stack, depth)).collect()
+    }
+
+    // A proof of a small circuit, made by the rounds of the snarkjs prover without blinding. The
+    // trapdoor tau only gives the powers of tau, the prover commits to polynomials and never
+    // uses the formulas of the verifier.
+    //
+    // The public inputs are x * y and x + y, the rows are
+    //   0, 1  ql = 1, a = public input, checked against PI = -Σ input_i * L(i+1)
+    //   2     qm = 1, qo = -1, a = x, b = y, c = x * y, c copied to a of row 0
+    //   3     ql = qr = 1, qo = -1, a = x, b = y, c = x + y, c copied to a of row 1, a and b
+    //         copied to row 2
+    //   4     ql = 1, qc = -5, a = 5
+    // and the remaining ones are empty.
+    fn plonk_proof() -> (PlonkVerifyingKey, PlonkProof, Vec<ark_bn254::Fr>) {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let domain = Domain::new(8);
+        let n = domain.size() as usize;
+        let (zero, one) = (ark_bn254::Fr::zero(), ark_bn254::Fr::one());
+        let (k1, k2) = (ark_bn254::Fr::from(2), ark_bn254::Fr::from(3));
+
+        let tau = ark_bn254::Fr::rand(&mut prng);
+        let g1 = ark_bn254::G1Affine::generator();
+        let powers_of_tau: Vec<_> =
+            (0..n).map(|i| (g1 * tau.pow([i as u64])).into_affine()).collect();
+
+        // the circuit
+        let (x, y) = (ark_bn254::Fr::rand(&mut prng), ark_bn254::Fr::rand(&mut prng));
+        let public_inputs = vec![x * y, x + y];
+        let five = ark_bn254::Fr::from(5);
+        let (mut qm, mut ql, mut qr, mut qo, mut qc) =
+            (vec![zero; n], vec![zero; n], vec![zero; n], vec![zero; n], vec![zero; n]);
+        let mut wires = vec![vec![zero; n]; 3];
+        (ql[0], wires[0][0]) = (one, x * y);
+        (ql[1], wires[0][1]) = (one, x + y);
+        (qm[2], qo[2]) = (one, -one);
+        (wires[0][2], wires[1][2], wires[2][2]) = (x, y, x * y);
+        (ql[3], qr[3], qo[3]) = (one, one, -one);
+        (wires[0][3], wires[1][3], wires[2][3]) = (x, y, x + y);
+        (ql[4], qc[4], wires[0][4]) = (one, -five, five);
+
+        // the copy constraints swap the labels k_j * w^i of (column j, row i)
+        let k = [one, k1, k2];
+        let label = |(j, i): (usize, usize)| k[j] * domain.element(i as u64);
+        let mut sigma: Vec<Vec<_>> =
+            (0..3).map(|j| (0..n).map(|i| label((j, i))).collect()).collect();
+        for (p, q) in [((0, 0), (2, 2)), ((0, 1), (2, 3)), ((0, 2), (0, 3)), ((1, 2), (1, 3))] {
+            assert_eq!(wires[p.0][p.1], wires[q.0][q.1]);
+            sigma[p.0][p.1] = label(q);
+            sigma[q.0][q.1] = label(p);
+        }
+
+        let [qm, ql, qr, qo, qc] = [qm, ql, qr, qo, qc].map(|q| interpolate(&domain, &q));
+        let [s1, s2, s3] = [0, 1, 2].map(|j| interpolate(&domain, &sigma[j]));
+        let vk = PlonkVerifyingKey {
+            n_public: public_inputs.len(),
+            power: domain.log_size,
+            k1,
+            k2,
+            qm: commit(&powers_of_tau, &qm),
+            ql: commit(&powers_of_tau, &ql),
+            qr: commit(&powers_of_tau, &qr),
+            qo: commit(&powers_of_tau, &qo),
+            qc: commit(&powers_of_tau, &qc),
+            s1: commit(&powers_of_tau, &s1),
+            s2: commit(&powers_of_tau, &s2),
+            s3: commit(&powers_of_tau, &s3),
+            x_2: (ark_bn254::G2Affine::generator() * tau).into_affine(),
+            w: domain.generator(),
+        };
+
+        // round 1: the wires
+        let [a, b, c] = [0, 1, 2].map(|j| interpolate(&domain, &wires[j]));
+        let mut proof = PlonkProof {
+            a: commit(&powers_of_tau, &a),
+            b: commit(&powers_of_tau, &b),
+            c: commit(&powers_of_tau, &c),
+            z: g1,
+            t1: g1,
+            t2: g1,
+            t3: g1,
+            wxi: g1,
+            wxiw: g1,
+            eval_a: zero,
+            eval_b: zero,
+            eval_c: zero,
+            eval_s1: zero,
+            eval_s2: zero,
+            eval_zw: zero,
+        };
+
+        // round 2: the grand product of the permutation
+        let (beta, gamma) = {
+            let challenges = challenges(&vk, &proof, &public_inputs);
+            (challenges[0], challenges[1])
+        };
+        let mut z_evaluations = vec![one];
+        for i in 0..n {
+            let ratio = (0..3)
+                .map(|j| {
+                    (wires[j][i] + beta * label((j, i)) + gamma)
+                        / (wires[j][i] + beta * sigma[j][i] + gamma)
+                })
+                .product::<ark_bn254::Fr>();
+            z_evaluations.push(z_evaluations[i] * ratio);
+        }
+        assert_eq!(z_evaluations.pop(), Some(one));
+        let z = interpolate(&domain, &z_evaluations);
+        proof.z = commit(&powers_of_tau, &z);
+
+        // round 3: the quotient T = T1 + X^n * T2 + X^2n * T3
+        let alpha = challenges(&vk, &proof, &public_inputs)[2];
+        let mut pi = vec![zero; n];
+        for (i, input) in public_inputs.iter().enumerate() {
+            pi[i] = -*input;
+        }
+        let pi = interpolate(&domain, &pi);
+        let mut l1 = vec![zero; n];
+        l1[0] = one;
+        let l1 = interpolate(&domain, &l1);
+        let zw: Poly = z.iter().enumerate().map(|(i, c)| *c * domain.element(i as u64)).collect();
+        let linear = |p: &[ark_bn254::Fr], s: ark_bn254::Fr| poly_add(p, &[gamma, beta * s]);
+        let plus_gamma = |p: &[ark_bn254::Fr], s: &[ark_bn254::Fr]| {
+            poly_add(p, &poly_add(&poly_scale(s, beta), &[gamma]))
+        };
+
+        let gates = [
+            poly_mul(&poly_mul(&a, &b), &qm),
+            poly_mul(&a, &ql),
+            poly_mul(&b, &qr),
+            poly_mul(&c, &qo),
+            qc.clone(),
+            pi.clone(),
+        ]
+        .iter()
+        .fold(vec![], |acc, p| poly_add(&acc, p));
+        let permutation = poly_add(
+            &poly_mul(
+                &poly_mul(&poly_mul(&linear(&a, one), &linear(&b, k1)), &linear(&c, k2)),
+                &z,
+            ),
+            &poly_scale(
+                &poly_mul(
+                    &poly_mul(
+                        &poly_mul(&plus_gamma(&a, &s1), &plus_gamma(&b, &s2)),
+                        &plus_gamma(&c, &s3),
+                    ),
+                    &zw,
+                ),
+                -one,
+            ),
+        );
+        let first = poly_mul(&poly_add(&z, &[-one]), &l1);
+        let t = poly_div_vanishing(
+            &poly_add(
+                &gates,
+                &poly_add(&poly_scale(&permutation, alpha), &poly_scale(&first, alpha * alpha)),
+            ),
+            n,
+        );
+        assert!(t.len() <= 3 * n);
+        let [t1, t2, t3] =
+            [0, 1, 2].map(|i| t.iter().skip(i * n).take(n).copied().collect::<Poly>());
+        proof.t1 = commit(&powers_of_tau, &t1);
+        proof.t2 = commit(&powers_of_tau, &t2);
+        proof.t3 = commit(&powers_of_tau, &t3);
+
+        // round 4: the evaluations
+        let xi = challenges(&vk, &proof, &public_inputs)[3];
+        let xiw = xi * domain.generator();
+        proof.eval_a = poly_eval(&a, xi);
+        proof.eval_b = poly_eval(&b, xi);
+        proof.eval_c = poly_eval(&c, xi);
+        proof.eval_s1 = poly_eval(&s1, xi);
+        proof.eval_s2 = poly_eval(&s2, xi);
+        proof.eval_zw = poly_eval(&z, xiw);
+
+        // round 5: the linearisation R, which vanishes at xi, and the opening proofs
+        let v = challenges(&vk, &proof, &public_inputs)[4];
+        let (ea, eb, ec) = (proof.eval_a, proof.eval_b, proof.eval_c);
+        let xin = xi.pow([n as u64]);
+        let k_eval = alpha
+            * (ea + beta * proof.eval_s1 + gamma)
+            * (eb + beta * proof.eval_s2 + gamma)
+            * proof.eval_zw;
+        let z_scalar = alpha
+            * (ea + beta * xi + gamma)
+            * (eb + beta * k1 * xi + gamma)
+            * (ec + beta * k2 * xi + gamma)
+            + alpha * alpha * poly_eval(&l1, xi);
+        let r = [
+            poly_scale(&qm, ea * eb),
+            poly_scale(&ql, ea),
+            poly_scale(&qr, eb),
+            poly_scale(&qo, ec),
+            qc,
+            vec![poly_eval(&pi, xi) - alpha * alpha * poly_eval(&l1, xi)],
+            poly_scale(&z, z_scalar),
+            poly_scale(&plus_gamma(&[ec], &s3), -k_eval),
+            poly_scale(
+                &poly_add(&t1, &poly_add(&poly_scale(&t2, xin), &poly_scale(&t3, xin * xin))),
+                -(xin - one),
+            ),
+        ]
+        .iter()
+        .fold(vec![], |acc, p| poly_add(&acc, p));
+        assert!(poly_eval(&r, xi).is_zero());
+
+        let mut w = r;
+        let mut v_pow = one;
+        for (p, e) in [(&a, ea), (&b, eb), (&c, ec), (&s1, proof.eval_s1), (&s2, proof.eval_s2)] {
+            v_pow *= v;
+            w = poly_add(&w, &poly_scale(&poly_add(p, &[-e]), v_pow));
+        }
+        proof.wxi = commit(&powers_of_tau, &poly_div_linear(&w, xi));
+        proof.wxiw =
+            commit(&powers_of_tau, &poly_div_linear(&poly_add(&z, &[-proof.eval_zw]), xiw));
+
+        (vk, proof, public_inputs)
+    }
+
+    #[test]
+    fn test_plonk_verifier() {
+        let (vk, proof, public_inputs) = plonk_proof();
+
+        let script = Verifier::verify_proof(&vk, &proof, &public_inputs).unwrap();
+        println!("plonk.verify_proof = {} bytes", script.len());
+        let exec_result = execute_script(script);
+        println!("{}", exec_result);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_plonk_verifier_as_chunks() {
+        let (vk, proof, public_inputs) = plonk_proof();
+
+        let script = Verifier::verify_proof(&vk, &proof, &public_inputs).unwrap();
+        println!("plonk.verify_proof = {} bytes", script.len());
+        let exec_result = execute_script_as_chunks(script, 3_000_000, 2_000_000);
+        println!("{}", exec_result);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_plonk_verifier_errors() {
+        let (mut vk, mut proof, public_inputs) = plonk_proof();
+        assert_eq!(
+            Verifier::verify_proof(&vk, &proof, &public_inputs[..1]),
+            Err(VerifierError::InvalidPublicInputCount { expected: 2, actual: 1 })
+        );
+
+        proof.eval_a += ark_bn254::Fr::from(1);
+        assert_eq!(
+            Verifier::verify_proof(&vk, &proof, &public_inputs),
+            Err(VerifierError::InvalidProof)
+        );
+
+        vk.n_public = MAX_PUBLIC + 1;
+        let public_inputs = vec![ark_bn254::Fr::from(1); MAX_PUBLIC + 1];
+        assert_eq!(
+            Verifier::verify_proof(&vk, &proof, &public_inputs),
+            Err(VerifierError::TooManyPublicInputs(MAX_PUBLIC + 1))
+        );
+    }
+
+    // The hints of a valid proof, with which the script has to reject a tampered proof
+    fn valid_hints(
+        vk: &PlonkVerifyingKey,
+        proof: &PlonkProof,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Hints {
+        let points_script = Verifier::points_script(vk, proof, public_inputs).unwrap();
+        let (p2, p1) = Verifier::compute_points(points_script).unwrap();
+        let g2 = ark_bn254::G2Affine::generator();
+        Hints::from_miller_loop(Bn254::multi_miller_loop_affine([p2, p1], [g2, vk.x_2]).0)
+    }
+
+    #[test]
+    fn test_plonk_verifier_rejects_tampered_proof() {
+        let (vk, proof, public_inputs) = plonk_proof();
+        let hints = valid_hints(&vk, &proof, &public_inputs);
+
+        let mut tampered = proof.clone();
+        tampered.eval_a += ark_bn254::Fr::from(1);
+        let script = script! {
+            { Verifier::points_script(&vk, &tampered, &public_inputs).unwrap() }
+            { Verifier::pairing_script(&vk, &hints) }
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+
+        let mut tampered = proof.clone();
+        tampered.wxi = (tampered.wxi + ark_bn254::G1Affine::generator()).into_affine();
+        let script = script! {
+            { Verifier::points_script(&vk, &tampered, &public_inputs).unwrap() }
+            { Verifier::pairing_script(&vk, &hints) }
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+
+    #[test]
+    fn test_plonk_verifier_rejects_tampered_hints() {
+        let (vk, proof, public_inputs) = plonk_proof();
+        let hints = valid_hints(&vk, &proof, &public_inputs);
+
+        // c and c_inv stay consistent, only c^lambda = f * wi fails
+        let c = hints.c.double();
+        let tampered = Hints {
+            c,
+            c_inv: c.inverse().unwrap(),
+            wi: hints.wi,
+        };
+        let script = script! {
+            { Verifier::points_script(&vk, &proof, &public_inputs).unwrap() }
+            { Verifier::pairing_script(&vk, &tampered) }
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::execution_env::ExecutionEnv;
+use crate::source_map::SourceMap;
+use crate::treepp::Script;
+
+/// Size and resource usage of a named sub-script. All numbers include nested sub-scripts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GadgetProfile {
+    pub name: String,
+    /// Compiled size in bytes.
+    pub byte_size: usize,
+    /// Number of opcodes in the compiled script, including pushes.
+    pub opcode_count: usize,
+    /// Number of opcodes that were actually executed, skipped branches are not counted.
+    pub executed_opcodes: usize,
+    /// Highest number of stack elements the sub-script kept on top of the stack it was called
+    /// with, i.e. the depth of the caller is not included.
+    pub max_stack_depth: usize,
+    /// Same as `max_stack_depth` for the altstack.
+    pub max_altstack_depth: usize,
+}
+
+impl GadgetProfile {
+    fn new(name: String) -> Self { Self { name, ..Default::default() } }
+
+    fn merge(&mut self, other: &RangeStats) {
+        self.byte_size += other.byte_size;
+        self.opcode_count += other.opcode_count;
+        self.executed_opcodes += other.executed_opcodes;
+    }
+
+    fn merge_depths(&mut self, (max_stack_depth, max_altstack_depth): (usize, usize)) {
+        self.max_stack_depth = self.max_stack_depth.max(max_stack_depth);
+        self.max_altstack_depth = self.max_altstack_depth.max(max_altstack_depth);
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScriptProfile {
+    pub total: GadgetProfile,
+    /// One entry per distinct sub-script name, largest first.
+    pub gadgets: Vec<GadgetProfile>,
+    /// Compiled bytes per call stack (outermost first), used for the folded-stack export.
+    #[serde(skip)]
+    folded: Vec<(Vec<String>, usize)>,
+}
+
+impl ScriptProfile {
+    pub fn gadget(&self, name: &str) -> Option<&GadgetProfile> {
+        self.gadgets.iter().find(|gadget| gadget.name == name)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profile is serializable")
+    }
+
+    /// Exports the compiled bytes per call stack in the folded format understood by
+    /// `flamegraph.pl` and `inferno`, e.g. `outer;inner 1234`.
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for (call_stack, bytes) in &self.folded {
+            let frames: Vec<String> =
+                call_stack.iter().map(|frame| frame.replace([';', ' '], "_")).collect();
+            let frames = if frames.is_empty() { vec!["<root>".to_string()] } else { frames };
+            folded.push_str(&format!("{} {}\n", frames.join(";"), bytes));
+        }
+        folded
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct RangeStats {
+    byte_size: usize,
+    opcode_count: usize,
+    executed_opcodes: usize,
+}
+
+// A sub-script that is currently being executed, with the stack sizes it was entered with.
+struct OpenFrame<'a> {
+    name: &'a str,
+    stack_base: usize,
+    altstack_base: usize,
+    max_stack_depth: usize,
+    max_altstack_depth: usize,
+}
+
+impl OpenFrame<'_> {
+    fn close(self, depths: &mut HashMap<&str, (usize, usize)>) {
+        let depth = depths.entry(self.name).or_default();
+        depth.0 = depth.0.max(self.max_stack_depth);
+        depth.1 = depth.1.max(self.max_altstack_depth);
+    }
+}
+
+/// Executes `script` and reports the size and stack usage of every named sub-script.
+/// The script has to push its own inputs. The stack limit is not enforced so that oversized
+/// gadgets can still be measured.
+pub fn profile(script: &Script) -> ScriptProfile {
+    let source_map = SourceMap::new(script);
+    let ranges = source_map.ranges();
+    let compiled_script = script.clone().compile();
+    let script_len = compiled_script.len();
+
+    let mut stats = vec![RangeStats::default(); ranges.len()];
+    let range_index = |offset: usize| ranges.partition_point(|range| range.end <= offset);
+    for (i, range) in ranges.iter().enumerate() {
+        stats[i].byte_size = range.end - range.start;
+    }
+    for item in compiled_script.instruction_indices() {
+        let Ok((offset, _)) = item else { break };
+        stats[range_index(offset)].opcode_count += 1;
+    }
+
+    let mut exec = ExecutionEnv::new().without_stack_limit().exec(compiled_script);
+
+    // The depths of a sub-script are measured relative to the stack it was entered with. Frames
+    // are opened and closed whenever the executed opcode comes from a different call stack.
+    let mut total_depths = (0, 0);
+    let mut depths: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut open_frames: Vec<OpenFrame> = vec![];
+    let mut current_range = None;
+    loop {
+        let offset = script_len - exec.remaining_script().len();
+        let index = Some(range_index(offset)).filter(|index| *index < ranges.len());
+        if let Some(entered) = index.filter(|index| Some(*index) != current_range) {
+            current_range = index;
+            // Outermost frame first
+            let call_stack = ranges[entered].call_stack.iter().rev();
+            let common = open_frames
+                .iter()
+                .zip(call_stack.clone())
+                .take_while(|(frame, name)| frame.name == name.as_str())
+                .count();
+            for frame in open_frames.drain(common..) {
+                frame.close(&mut depths);
+            }
+            for name in call_stack.skip(common) {
+                open_frames.push(OpenFrame {
+                    name,
+                    stack_base: exec.stack().len(),
+                    altstack_base: exec.altstack().len(),
+                    max_stack_depth: 0,
+                    max_altstack_depth: 0,
+                });
+            }
+        }
+        let result = exec.exec_next();
+        if let Some(index) = index {
+            stats[index].executed_opcodes += 1;
+            let (stack_len, altstack_len) = (exec.stack().len(), exec.altstack().len());
+            total_depths.0 = total_depths.0.max(stack_len);
+            total_depths.1 = total_depths.1.max(altstack_len);
+            for frame in open_frames.iter_mut() {
+                frame.max_stack_depth =
+                    frame.max_stack_depth.max(stack_len.saturating_sub(frame.stack_base));
+                frame.max_altstack_depth =
+                    frame.max_altstack_depth.max(altstack_len.saturating_sub(frame.altstack_base));
+            }
+        }
+        if result.is_err() {
+            break;
+        }
+    }
+    for frame in open_frames.drain(..) {
+        frame.close(&mut depths);
+    }
+
+    let mut total = GadgetProfile::new("<total>".to_string());
+    total.merge_depths(total_depths);
+    let mut gadgets: HashMap<&str, GadgetProfile> = HashMap::new();
+    let mut folded: Vec<(Vec<String>, usize)> = vec![];
+    let mut folded_index: HashMap<Vec<String>, usize> = HashMap::new();
+    for (range, range_stats) in ranges.iter().zip(stats.iter()) {
+        total.merge(range_stats);
+        let mut seen: Vec<&str> = vec![];
+        for frame in &range.call_stack {
+            // Recursive builders should only be counted once per opcode.
+            if seen.contains(&frame.as_str()) {
+                continue;
+            }
+            seen.push(frame.as_str());
+            gadgets
+                .entry(frame.as_str())
+                .or_insert_with(|| GadgetProfile::new(frame.clone()))
+                .merge(range_stats);
+        }
+
+        let call_stack: Vec<String> = range.call_stack.iter().rev().cloned().collect();
+        match folded_index.get(&call_stack) {
+            Some(index) => folded[*index].1 += range_stats.byte_size,
+            None => {
+                folded_index.insert(call_stack.clone(), folded.len());
+                folded.push((call_stack, range_stats.byte_size));
+            }
+        }
+    }
+    for (name, gadget) in gadgets.iter_mut() {
+        if let Some(depth) = depths.get(name) {
+            gadget.merge_depths(*depth);
+        }
+    }
+
+    let mut gadgets: Vec<GadgetProfile> = gadgets.into_values().collect();
+    gadgets.sort_by(|a, b| b.byte_size.cmp(&a.byte_size).then_with(|| a.name.cmp(&b.name)));
+
+    ScriptProfile { total, gadgets, folded }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::bn254::fq::Fq;
+    use crate::treepp::script;
+
+    #[test]
+    fn test_profile_totals() {
+        let script = script! {
+            { Fq::push_one() }
+            { Fq::push_one() }
+            { Fq::mul() }
+            { Fq::push_one() }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let compiled_script = script.clone().compile();
+        let profile = profile(&script);
+
+        assert_eq!(profile.total.byte_size, compiled_script.len());
+        assert_eq!(profile.total.opcode_count, compiled_script.instructions().count());
+        assert!(profile.total.executed_opcodes <= profile.total.opcode_count);
+        assert!(profile.total.max_stack_depth >= 2 * Fq::N_LIMBS as usize);
+        for gadget in &profile.gadgets {
+            assert!(gadget.byte_size <= profile.total.byte_size);
+            assert!(gadget.max_stack_depth <= profile.total.max_stack_depth);
+        }
+    }
+
+    #[test]
+    fn test_profile_folded() {
+        let script = script! {
+            { Fq::push_one() }
+            { Fq::square() }
+            { Fq::drop() }
+            OP_TRUE
+        };
+        let compiled_len = script.clone().compile().len();
+        let profile = profile(&script);
+        let folded = profile.to_folded();
+
+        let bytes: usize = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().1.parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(bytes, compiled_len);
+
+        let json: serde_json::Value = serde_json::from_str(&profile.to_json()).unwrap();
+        assert_eq!(json["total"]["byte_size"], compiled_len);
+    }
+
+    #[test]
+    fn test_profile_relative_depth() {
+        let gadget = script! {
+            { Fq::push_one() }
+            { Fq::square() }
+            { Fq::drop() }
+        };
+        let shallow = profile(&script! {
+            { gadget.clone() }
+            OP_TRUE
+        });
+        let deep = profile(&script! {
+            for _ in 0..20 {
+                OP_1
+            }
+            { gadget }
+            for _ in 0..20 {
+                OP_DROP
+            }
+            OP_TRUE
+        });
+        assert_eq!(deep.total.max_stack_depth, shallow.total.max_stack_depth + 20);
+
+        // Sub-scripts that don't contain the extra elements report the same depth
+        let mut compared = 0;
+        for gadget in &shallow.gadgets {
+            if let Some(other) = deep.gadget(&gadget.name) {
+                if other.byte_size == gadget.byte_size {
+                    assert_eq!(other.max_stack_depth, gadget.max_stack_depth, "{}", gadget.name);
+                    compared += 1;
+                }
+            }
+        }
+        assert!(compared > 0);
+    }
+}
@@ -0,0 +1,274 @@
+use bitcoin::secp256k1::PublicKey;
+use num_bigint::BigUint;
+
+use crate::bn254::curves::{projective_nonzero_add, projective_nonzero_double, ProjectiveCurve};
+use crate::bn254::fp254impl::Fp254Impl;
+use crate::secp256k1::fq::Fq;
+use crate::secp256k1::fr::Fr;
+use crate::treepp::{script, Script};
+use std::sync::OnceLock;
+
+static DOUBLE_PROJECTIVE: OnceLock<Script> = OnceLock::new();
+static NONZERO_ADD_PROJECTIVE: OnceLock<Script> = OnceLock::new();
+
+const GENERATOR_X: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const GENERATOR_Y: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+/// Affine coordinates of a public key.
+fn coordinates(point: &PublicKey) -> (BigUint, BigUint) {
+    let serialized = point.serialize_uncompressed();
+    (
+        BigUint::from_bytes_be(&serialized[1..33]),
+        BigUint::from_bytes_be(&serialized[33..65]),
+    )
+}
+
+/// A secp256k1 point in Jacobian coordinates [x, y, z] with x/z² and y/z³ the affine
+/// coordinates. The point at infinity has z = 0. The formulas are shared with BN254, see
+/// [`ProjectiveCurve`].
+pub struct ProjectivePoint;
+
+impl ProjectiveCurve for ProjectivePoint {
+    type Fq = Fq;
+
+    fn nonzero_double() -> Script {
+        DOUBLE_PROJECTIVE
+            .get_or_init(projective_nonzero_double::<Fq>)
+            .clone()
+    }
+
+    fn nonzero_add() -> Script {
+        NONZERO_ADD_PROJECTIVE
+            .get_or_init(projective_nonzero_add::<Fq>)
+            .clone()
+    }
+}
+
+impl ProjectivePoint {
+    pub fn push_generator() -> Script {
+        script! {
+            { Fq::push_hex(GENERATOR_X) }
+            { Fq::push_hex(GENERATOR_Y) }
+            { Fq::push_one() }
+        }
+    }
+
+    pub fn push(point: &PublicKey) -> Script {
+        let (x, y) = coordinates(point);
+        script! {
+            { Fq::push_u32_le(&x.to_u32_digits()) }
+            { Fq::push_u32_le(&y.to_u32_digits()) }
+            { Fq::push_one() }
+        }
+    }
+
+    // Input Stack: [x, y, z]
+    // Output Stack: [x/z^2, y/z^3]
+    // The point at infinity is mapped to [0, 0].
+    pub fn into_affine() -> Script {
+        script! {
+            // Fq::inv maps zero to zero, so the point at infinity needs no special case
+            { Fq::inv() }
+
+            // compute Z^-2
+            { Fq::copy(0) }
+            { Fq::square() }
+            // compute Z^-3 = Z^-2 * z^-1
+            { Fq::copy(0) }
+            { Fq::roll(2) }
+            { Fq::mul() }
+
+            // compute Y/Z^3 = Y * Z^-3
+            { Fq::roll(2) }
+            { Fq::mul() }
+
+            // compute X/Z^2 = X * Z^-2
+            { Fq::roll(1) }
+            { Fq::roll(2) }
+            { Fq::mul() }
+
+            { Fq::roll(1) }
+        }
+    }
+
+    /// Double-and-add over the bits of the scalar, most significant bit first.
+    // [point, scalar]
+    pub fn scalar_mul() -> Script {
+        script! {
+            { Fr::decode_montgomery() }
+            { Fr::convert_to_le_bits_toaltstack() }
+
+            { ProjectivePoint::push_zero() }
+            for _ in 0..Fr::N_BITS {
+                { ProjectivePoint::double() }
+                OP_FROMALTSTACK
+                OP_IF
+                    { ProjectivePoint::copy(1) }
+                    { ProjectivePoint::add() }
+                OP_ENDIF
+            }
+
+            { ProjectivePoint::roll(1) }
+            { ProjectivePoint::drop() }
+        }
+    }
+}
+
+/// A secp256k1 point in affine coordinates [x, y].
+pub struct AffinePoint;
+
+impl AffinePoint {
+    pub fn push(point: &PublicKey) -> Script {
+        let (x, y) = coordinates(point);
+        script! {
+            { Fq::push_u32_le(&x.to_u32_digits()) }
+            { Fq::push_u32_le(&y.to_u32_digits()) }
+        }
+    }
+
+    // y² = x³ + 7
+    pub fn is_on_curve() -> Script {
+        script! {
+            { Fq::copy(1) }
+            { Fq::square() }
+            { Fq::roll(2) }
+            { Fq::mul() }
+            { Fq::push_hex("7") }
+            { Fq::add(1, 0) }
+            { Fq::roll(1) }
+            { Fq::square() }
+            { Fq::equal(1, 0) }
+        }
+    }
+
+    // Init stack: [x1,y1,x2,y2)
+    pub fn equalverify() -> Script {
+        script! {
+            { Fq::roll(2) }
+            { Fq::equalverify(1, 0) }
+            { Fq::equalverify(1, 0) }
+        }
+    }
+
+    // Input Stack: [x,y]
+    // Output Stack: [x,y,z] (z=1)
+    pub fn into_projective() -> Script { script!({ Fq::push_one() }) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AffinePoint, ProjectivePoint};
+    use crate::bn254::curves::ProjectiveCurve;
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::secp256k1::fr::Fr;
+    use crate::treepp::*;
+    use bitcoin::secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+    use num_bigint::BigUint;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn random_point(prng: &mut ChaCha20Rng) -> PublicKey {
+        PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::new(prng))
+    }
+
+    #[test]
+    fn test_generator() {
+        let secp = Secp256k1::new();
+        let one = SecretKey::from_slice(&Scalar::ONE.to_be_bytes()).unwrap();
+        let script = script! {
+            { ProjectivePoint::push_generator() }
+            { ProjectivePoint::push(&PublicKey::from_secret_key(&secp, &one)) }
+            { ProjectivePoint::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_is_on_curve() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+        let script = script! {
+            { AffinePoint::push(&random_point(&mut prng)) }
+            { AffinePoint::is_on_curve() }
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_double_and_add() {
+        println!("secp256k1.double: {} bytes", ProjectivePoint::double().len());
+        println!("secp256k1.add: {} bytes", ProjectivePoint::add().len());
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+
+        for _ in 0..3 {
+            let p = random_point(&mut prng);
+            let q = random_point(&mut prng);
+
+            let script = script! {
+                { ProjectivePoint::push(&p) }
+                { ProjectivePoint::double() }
+                { ProjectivePoint::push(&p.combine(&p).unwrap()) }
+                { ProjectivePoint::equalverify() }
+
+                // add a projective point with z ≠ 1 to an affine one
+                { ProjectivePoint::push(&p) }
+                { ProjectivePoint::double() }
+                { ProjectivePoint::push(&q) }
+                { ProjectivePoint::add() }
+                { ProjectivePoint::push(&p.combine(&p).unwrap().combine(&q).unwrap()) }
+                { ProjectivePoint::equalverify() }
+
+                { ProjectivePoint::push_zero() }
+                { ProjectivePoint::push(&q) }
+                { ProjectivePoint::add() }
+                { ProjectivePoint::push(&q) }
+                { ProjectivePoint::equalverify() }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_into_affine() {
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+        let p = random_point(&mut prng);
+
+        let script = script! {
+            { ProjectivePoint::push(&p) }
+            { ProjectivePoint::double() }
+            { ProjectivePoint::into_affine() }
+            { AffinePoint::push(&p.combine(&p).unwrap()) }
+            { AffinePoint::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        let scalar_mul = ProjectivePoint::scalar_mul();
+        println!("secp256k1.scalar_mul: {} bytes", scalar_mul.len());
+        let secp = Secp256k1::new();
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+
+        let p = random_point(&mut prng);
+        let k = SecretKey::new(&mut prng);
+        let q = p.mul_tweak(&secp, &Scalar::from(k)).unwrap();
+
+        let script = script! {
+            { ProjectivePoint::push(&p) }
+            { Fr::push_u32_le(&BigUint::from_bytes_be(&k.secret_bytes()).to_u32_digits()) }
+            { scalar_mul.clone() }
+            { ProjectivePoint::push(&q) }
+            { ProjectivePoint::equalverify() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
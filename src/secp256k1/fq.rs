@@ -0,0 +1,246 @@
+use crate::bigint::U256;
+use crate::bn254::fp254impl::Fp254Impl;
+use ark_ff::fields::{Fp256, MontBackend, MontConfig};
+
+#[derive(MontConfig)]
+#[modulus = "115792089237316195423570985008687907853269984665640564039457584007908834671663"]
+#[generator = "3"]
+pub struct FqConfig;
+
+/// The base field of secp256k1 for constants computed off-chain.
+pub type FqConstant = Fp256<MontBackend<FqConfig, 4>>;
+
+/// The base field of secp256k1.
+pub struct Fq;
+
+impl Fp254Impl for Fq {
+    const MODULUS: &'static str =
+        "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+
+    // 2²⁶¹ mod p  <=>  0x2000007a20
+    const MONTGOMERY_ONE: &'static str = "2000007a20";
+
+    // p = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f
+    const MODULUS_LIMBS: &'static [u32] = &[
+        0x1ffffc2f, 0x1ffffff7, 0x1fffffff, 0x1fffffff, 0x1fffffff, 0x1fffffff, 0x1fffffff, 0x1fffffff, 0xffffff
+    ];
+
+    // inv₂₆₁ p  <=>  0x153642e6faeaac7c6663b93d3d6a0d489e434ddc0123db5fa627c7f6e22ddacacf
+    const MODULUS_INV: &'static [u32] = &[
+        0xddacacf, 0x1e3fb711, 0x16d7e989, 0x1bb80247, 0x1489e434, 0x1e9eb506, 0x11998ee4, 0x1f5d558f, 0x153642e6
+    ];
+
+    const P_PLUS_ONE_DIV2: &'static str =
+        "7fffffffffffffffffffffffffffffffffffffffffffffffffffffff7ffffe18";
+
+    const TWO_P_PLUS_ONE_DIV3: &'static str =
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa9fffffd75";
+
+    const P_PLUS_TWO_DIV3: &'static str =
+        "55555555555555555555555555555555555555555555555555555554fffffebb";
+
+    type BigInt = U256;
+    type ConstantType = FqConstant;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bigint::U256;
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::secp256k1::fq::Fq;
+    use crate::treepp::*;
+    use core::ops::{Add, Mul, Rem, Shl, Sub};
+    use num_bigint::{BigUint, RandomBits};
+    use num_traits::{Num, One, Zero};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    fn random_elements(prng: &mut ChaCha20Rng) -> (BigUint, BigUint) {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let a: BigUint = prng.sample(RandomBits::new(256));
+        let b: BigUint = prng.sample(RandomBits::new(256));
+        (a.rem(&m), b.rem(&m))
+    }
+
+    #[test]
+    fn test_montgomery_constants() {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let r = BigUint::one().shl(261u32);
+        assert_eq!(
+            r.clone().rem(&m),
+            BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap()
+        );
+
+        let limbs = |v: &BigUint| -> Vec<u32> {
+            (0..9)
+                .map(|i| {
+                    *(v >> (29 * i) as u32)
+                        .rem(BigUint::one().shl(29u32))
+                        .to_u32_digits()
+                        .first()
+                        .unwrap_or(&0)
+                })
+                .collect()
+        };
+        assert_eq!(limbs(&m), Fq::MODULUS_LIMBS.to_vec());
+        let inv = m.modpow(&(r.clone() / 2u32 - BigUint::one()), &r);
+        assert_eq!(m.clone().mul(&inv).rem(&r), BigUint::one());
+        assert_eq!(limbs(&inv), Fq::MODULUS_INV.to_vec());
+    }
+
+    #[test]
+    fn test_decode_montgomery() {
+        let script = script! {
+            { Fq::push_one() }
+            { Fq::push_u32_le(&BigUint::from_str_radix(Fq::MONTGOMERY_ONE, 16).unwrap().to_u32_digits()) }
+            { Fq::decode_montgomery() }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_add_sub_double() {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        // the largest elements overflow 256 bits when added
+        let mut cases = vec![
+            (m.clone().sub(1u32), m.clone().sub(1u32)),
+            (BigUint::zero(), m.clone().sub(1u32)),
+        ];
+        for _ in 0..20 {
+            cases.push(random_elements(&mut prng));
+        }
+
+        for (a, b) in cases {
+            let script = script! {
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::push_u32_le(&b.to_u32_digits()) }
+                { Fq::add(1, 0) }
+                { Fq::push_u32_le(&a.clone().add(&b).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::push_u32_le(&b.to_u32_digits()) }
+                { Fq::sub(1, 0) }
+                { Fq::push_u32_le(&a.clone().add(&m).sub(&b).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&b.to_u32_digits()) }
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::sub(0, 1) }
+                { Fq::push_u32_le(&a.clone().add(&m).sub(&b).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::double(0) }
+                { Fq::push_u32_le(&a.clone().add(&a).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::neg(0) }
+                { Fq::push_u32_le(&m.clone().sub(&a).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_mul_square() {
+        println!("Fq.mul: {} bytes", Fq::mul().len());
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+
+        for _ in 0..10 {
+            let (a, b) = random_elements(&mut prng);
+            let script = script! {
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::push_u32_le(&b.to_u32_digits()) }
+                { Fq::mul() }
+                { Fq::push_u32_le(&a.clone().mul(&b).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::square() }
+                { Fq::push_u32_le(&a.clone().mul(&a).rem(&m).to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_inv() {
+        println!("Fq.inv: {} bytes", Fq::inv().len());
+        let mut prng = ChaCha20Rng::seed_from_u64(2);
+
+        let (a, _) = random_elements(&mut prng);
+        let script = script! {
+            { Fq::push_u32_le(&a.to_u32_digits()) }
+            { Fq::copy(0) }
+            { Fq::inv() }
+            { Fq::mul() }
+            { Fq::push_one() }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+
+    #[test]
+    fn test_div2_div3() {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let mut prng = ChaCha20Rng::seed_from_u64(3);
+
+        // the top limb of the largest elements is wider than the one of BN254
+        let mut cases = vec![m.clone().sub(1u32), m.clone().sub(2u32)];
+        for _ in 0..10 {
+            cases.push(random_elements(&mut prng).0);
+        }
+
+        for a in cases {
+            let script = script! {
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::double(0) }
+                { Fq::div2() }
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+
+                { Fq::push_u32_le(&a.clone().mul(3u32).rem(&m).to_u32_digits()) }
+                { Fq::div3() }
+                { Fq::push_u32_le(&a.to_u32_digits()) }
+                { Fq::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_is_field() {
+        let m = BigUint::from_str_radix(Fq::MODULUS, 16).unwrap();
+        let script = script! {
+            { U256::push_u32_le(&m.clone().sub(1u32).to_u32_digits()) }
+            { Fq::is_field() }
+            OP_VERIFY
+            { Fq::drop() }
+            { U256::push_u32_le(&m.to_u32_digits()) }
+            { Fq::is_field() }
+            OP_NOT OP_VERIFY
+            { Fq::drop() }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::bigint::U256;
+use crate::bn254::fp254impl::Fp254Impl;
+use ark_ff::fields::{Fp256, MontBackend, MontConfig};
+
+#[derive(MontConfig)]
+#[modulus = "115792089237316195423570985008687907852837564279074904382605163141518161494337"]
+#[generator = "7"]
+pub struct FrConfig;
+
+/// The scalar field of secp256k1 for constants computed off-chain.
+pub type FrConstant = Fp256<MontBackend<FrConfig, 4>>;
+
+/// The scalar field of secp256k1, i.e. integers modulo the group order n.
+pub struct Fr;
+
+impl Fp254Impl for Fr {
+    const MODULUS: &'static str =
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+    // 2²⁶¹ mod n  <=>  0x28aa24632a16ebf88805b42e65f937d7e0
+    const MONTGOMERY_ONE: &'static str = "28aa24632a16ebf88805b42e65f937d7e0";
+
+    // n = 0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141
+    const MODULUS_LIMBS: &'static [u32] = &[
+        0x10364141, 0x1e92f466, 0x12280eef, 0x1db9cd5e, 0x1fffebaa, 0x1fffffff, 0x1fffffff, 0x1fffffff, 0xffffff
+    ];
+
+    // inv₂₆₁ n  <=>  0x15261776f29b6b106c7680cf3ed83054a1af5ae537cb4613dbb4f20099aa774ec1
+    const MODULUS_INV: &'static [u32] = &[
+        0xa774ec1, 0x79004cd, 0x1184f6ed, 0x15ca6f96, 0x54a1af5, 0x79f6c18, 0x1b1da03, 0x1e536d62, 0x15261776
+    ];
+
+    const P_PLUS_ONE_DIV2: &'static str =
+        "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a1";
+
+    const TWO_P_PLUS_ONE_DIV3: &'static str =
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa9d1c9e899ca306ad27fe1945de0242b81";
+
+    const P_PLUS_TWO_DIV3: &'static str =
+        "55555555555555555555555555555554e8e4f44ce51835693ff0ca2ef01215c1";
+
+    type BigInt = U256;
+    type ConstantType = FrConstant;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::secp256k1::fr::Fr;
+    use crate::treepp::*;
+    use bitcoin::secp256k1::{Scalar, SecretKey};
+    use num_bigint::BigUint;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn fr_push(key: &SecretKey) -> Script {
+        Fr::push_u32_le(&BigUint::from_bytes_be(&key.secret_bytes()).to_u32_digits())
+    }
+
+    #[test]
+    fn test_add_mul_against_secp256k1() {
+        let mut prng = ChaCha20Rng::seed_from_u64(0);
+
+        for _ in 0..10 {
+            let a = SecretKey::new(&mut prng);
+            let b = SecretKey::new(&mut prng);
+            let sum = a.add_tweak(&Scalar::from(b)).unwrap();
+            let product = a.mul_tweak(&Scalar::from(b)).unwrap();
+
+            let script = script! {
+                { fr_push(&a) }
+                { fr_push(&b) }
+                { Fr::add(1, 0) }
+                { fr_push(&sum) }
+                { Fr::equalverify(1, 0) }
+
+                { fr_push(&sum) }
+                { fr_push(&b) }
+                { Fr::sub(1, 0) }
+                { fr_push(&a) }
+                { Fr::equalverify(1, 0) }
+
+                { fr_push(&a) }
+                { fr_push(&b) }
+                { Fr::mul() }
+                { fr_push(&product) }
+                { Fr::equalverify(1, 0) }
+                OP_TRUE
+            };
+            let exec_result = execute_script(script);
+            assert!(exec_result.success);
+        }
+    }
+
+    #[test]
+    fn test_inv_against_secp256k1() {
+        let mut prng = ChaCha20Rng::seed_from_u64(1);
+        let a = SecretKey::new(&mut prng);
+        let b = SecretKey::new(&mut prng);
+        let product = a.mul_tweak(&Scalar::from(b)).unwrap();
+
+        // (a ⋅ b) ⋅ b⁻¹ = a
+        let script = script! {
+            { fr_push(&product) }
+            { fr_push(&b) }
+            { Fr::inv() }
+            { Fr::mul() }
+            { fr_push(&a) }
+            { Fr::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(exec_result.success);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod fq;
+pub mod fr;
+
+pub mod curves;
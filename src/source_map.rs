@@ -0,0 +1,201 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::treepp::Script;
+
+/// The chain of `script!` invocations that produced the opcode at a given byte offset of the
+/// compiled script, innermost first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub offset: usize,
+    pub call_stack: Vec<String>,
+}
+
+impl SourceLocation {
+    /// Looks up the builder call stack of the opcode at `offset` in the compiled `script`.
+    pub fn locate(script: &Script, offset: usize) -> Self {
+        Self {
+            offset,
+            call_stack: parse_call_stack(&script.debug_info(offset)),
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut frames = self.call_stack.iter();
+        match frames.next() {
+            Some(frame) => write!(f, "inside {}", frame)?,
+            None => write!(f, "at an unknown location")?,
+        }
+        for frame in frames {
+            write!(f, " called from {}", frame)?;
+        }
+        write!(f, " (byte {})", self.offset)
+    }
+}
+
+/// A contiguous byte range of the compiled script produced by the same call stack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceRange {
+    pub start: usize,
+    pub end: usize,
+    pub call_stack: Vec<String>,
+}
+
+/// Maps byte ranges of a compiled script back to the nested `script!` calls that produced them.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    ranges: Vec<SourceRange>,
+}
+
+impl SourceMap {
+    /// Builds the source map of `script`. This queries the structured script once per opcode,
+    /// so prefer [`SourceLocation::locate`] when only a single offset is of interest.
+    pub fn new(script: &Script) -> Self {
+        let compiled = script.clone().compile();
+        let script_len = compiled.len();
+        let mut ranges: Vec<SourceRange> = vec![];
+        for item in compiled.instruction_indices() {
+            let Ok((offset, _)) = item else { break };
+            let call_stack = parse_call_stack(&script.debug_info(offset));
+            match ranges.last_mut() {
+                Some(range) if range.call_stack == call_stack => range.end = offset,
+                _ => {
+                    if let Some(range) = ranges.last_mut() {
+                        range.end = offset;
+                    }
+                    ranges.push(SourceRange { start: offset, end: offset, call_stack });
+                }
+            }
+        }
+        if let Some(range) = ranges.last_mut() {
+            range.end = script_len;
+        }
+        Self { ranges }
+    }
+
+    pub fn ranges(&self) -> &[SourceRange] { &self.ranges }
+
+    /// Returns the range containing the byte at `offset`.
+    pub fn lookup(&self, offset: usize) -> Option<SourceLocation> {
+        let index = self.ranges.partition_point(|range| range.end <= offset);
+        self.ranges
+            .get(index)
+            .filter(|range| range.start <= offset)
+            .map(|range| SourceLocation { offset, call_stack: range.call_stack.clone() })
+    }
+
+    /// Sums up the compiled size of every range by its innermost frame.
+    pub fn size_by_frame(&self) -> Vec<(String, usize)> {
+        let mut sizes: Vec<(String, usize)> = vec![];
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for range in &self.ranges {
+            let frame = range.call_stack.first().map_or("", String::as_str);
+            match indices.get(frame) {
+                Some(index) => sizes[*index].1 += range.end - range.start,
+                None => {
+                    indices.insert(frame, sizes.len());
+                    sizes.push((frame.to_string(), range.end - range.start));
+                }
+            }
+        }
+        sizes
+    }
+}
+
+// `Script::debug_info` lists the identifiers of the nested scripts from the outermost to the
+// innermost one, one per line.
+fn parse_call_stack(debug_info: &str) -> Vec<String> {
+    debug_info
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .rev()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bn254::fp254impl::Fp254Impl;
+    use crate::bn254::fq::Fq;
+    use crate::bn254::fq2::Fq2;
+    use crate::treepp::script;
+    use crate::{execute_script, execute_script_as_chunks, execute_script_as_chunks_vs_normal};
+
+    #[test]
+    fn test_parse_call_stack() {
+        let call_stack = parse_call_stack("outer\n  middle\n\n  inner\n");
+        assert_eq!(call_stack, vec!["inner", "middle", "outer"]);
+        let location = SourceLocation { offset: 7, call_stack };
+        assert_eq!(
+            location.to_string(),
+            "inside inner called from middle called from outer (byte 7)"
+        );
+    }
+
+    #[test]
+    fn test_source_map_covers_script() {
+        let script = script! {
+            { Fq2::push_one() }
+            { Fq2::push_one() }
+            { Fq2::mul(2, 0) }
+            { Fq2::drop() }
+            OP_TRUE
+        };
+        let script_len = script.clone().compile().len();
+        let source_map = SourceMap::new(&script);
+
+        let ranges = source_map.ranges();
+        assert!(!ranges.is_empty());
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, script_len);
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        assert!(source_map.lookup(script_len).is_none());
+        assert_eq!(
+            source_map.size_by_frame().iter().map(|(_, size)| size).sum::<usize>(),
+            script_len
+        );
+    }
+
+    #[test]
+    fn test_failure_location() {
+        let script = script! {
+            { Fq::push_one() }
+            { Fq::push_zero() }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let exec_result = execute_script(script);
+        assert!(!exec_result.success);
+        let location = exec_result.source_location.expect("failure should be located");
+        assert!(!location.call_stack.is_empty());
+    }
+
+    #[test]
+    fn test_chunked_failure_location() {
+        let script = script! {
+            { Fq::push_one() }
+            { Fq::push_one() }
+            { Fq::add(1, 0) }
+            { Fq::push_zero() }
+            { Fq::equalverify(1, 0) }
+            OP_TRUE
+        };
+        let expected = execute_script(script.clone()).source_location.unwrap();
+
+        let chunked = [
+            execute_script_as_chunks(script.clone(), 100, 0),
+            execute_script_as_chunks_vs_normal(script, 100, 0),
+        ];
+        for exec_result in chunked {
+            assert!(!exec_result.success);
+            let location = exec_result.source_location.expect("failure should be located");
+            assert_eq!(location.call_stack, expected.call_stack);
+        }
+    }
+}
@@ -16,6 +16,7 @@ pub mod mock;
 pub mod peg_in;
 pub mod serialization;
 pub mod setup;
+pub mod simulator;
 pub mod start_time;
 pub mod start_time_timeout;
 pub mod take_1;
@@ -0,0 +1,347 @@
+use bitcoin::{hashes::Hash, Amount, OutPoint, ScriptBuf, Txid};
+
+use bitvm::bridge::{
+    client::chain::chain::PegOutEvent,
+    constants::SHA256_DIGEST_LENGTH_IN_BYTES,
+    graphs::{
+        base::{
+            DUST_AMOUNT, FEE_AMOUNT, INITIAL_AMOUNT, MESSAGE_COMMITMENT_FEE_AMOUNT, ONE_HUNDRED,
+        },
+        peg_out::CommitmentMessageId,
+    },
+    scripts::{generate_pay_to_pubkey_script, generate_pay_to_pubkey_script_address},
+    superblock::{get_superblock_message, Superblock, SuperblockHash},
+    transactions::{
+        assert::AssertTransaction,
+        base::{BaseTransaction, Input, InputWithScript},
+        challenge::ChallengeTransaction,
+        disprove::DisproveTransaction,
+        disprove_chain::DisproveChainTransaction,
+        kick_off_1::KickOff1Transaction,
+        kick_off_2::KickOff2Transaction,
+        kick_off_timeout::KickOffTimeoutTransaction,
+        peg_in_confirm::PegInConfirmTransaction,
+        peg_in_deposit::PegInDepositTransaction,
+        peg_in_refund::PegInRefundTransaction,
+        peg_out::PegOutTransaction,
+        peg_out_confirm::PegOutConfirmTransaction,
+        pre_signed::PreSignedTransaction,
+        simulator::simulate_spend,
+        start_time::StartTimeTransaction,
+        start_time_timeout::StartTimeTimeoutTransaction,
+        take_2::Take2Transaction,
+    },
+    utils::get_start_time_block,
+};
+
+use super::super::setup::{setup_test, SetupConfig};
+
+// The transactions spend stub outpoints, so they can be checked without a network.
+fn stub_input(vout: u32, sats: u64) -> Input {
+    Input {
+        outpoint: OutPoint {
+            txid: Txid::all_zeros(),
+            vout,
+        },
+        amount: Amount::from_sat(sats),
+    }
+}
+
+fn assert_spendable<T: PreSignedTransaction + BaseTransaction>(name: &str, tx: &T) {
+    let report = simulate_spend(tx);
+    assert!(report.is_valid(), "{}:\n{}", name, report);
+}
+
+fn reward_script(config: &SetupConfig) -> ScriptBuf {
+    generate_pay_to_pubkey_script_address(
+        config.withdrawer_context.network,
+        &config.withdrawer_context.withdrawer_public_key,
+    )
+    .script_pubkey()
+}
+
+#[tokio::test]
+async fn test_peg_in_graph_simulated() {
+    let config = setup_test().await;
+    let amount = INITIAL_AMOUNT + FEE_AMOUNT;
+
+    let peg_in_deposit_tx = PegInDepositTransaction::new(
+        &config.depositor_context,
+        &config.connector_z,
+        stub_input(0, amount),
+    );
+    assert_spendable("peg-in deposit", &peg_in_deposit_tx);
+
+    let mut peg_in_confirm_tx = PegInConfirmTransaction::new(
+        &config.depositor_context,
+        &config.connector_0,
+        &config.connector_z,
+        stub_input(0, amount),
+    );
+    let secret_nonces_0 = peg_in_confirm_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = peg_in_confirm_tx.push_nonces(&config.verifier_1_context);
+    peg_in_confirm_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_z,
+        &secret_nonces_0,
+    );
+    peg_in_confirm_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_z,
+        &secret_nonces_1,
+    );
+    assert_spendable("peg-in confirm", &peg_in_confirm_tx);
+
+    let peg_in_refund_tx = PegInRefundTransaction::new(
+        &config.depositor_context,
+        &config.connector_z,
+        stub_input(0, amount),
+    );
+    assert_spendable("peg-in refund", &peg_in_refund_tx);
+}
+
+#[tokio::test]
+async fn test_peg_out_graph_simulated() {
+    let config = setup_test().await;
+
+    let peg_out_event = PegOutEvent {
+        source_outpoint: stub_input(0, 0).outpoint,
+        amount: Amount::from_sat(INITIAL_AMOUNT),
+        timestamp: 1722328130u32,
+        withdrawer_chain_address: config.withdrawer_evm_address.clone(),
+        withdrawer_public_key_hash: config.withdrawer_context.withdrawer_public_key.pubkey_hash(),
+        operator_public_key: config.operator_context.operator_public_key,
+        tx_hash: vec![],
+    };
+    let peg_out_tx = PegOutTransaction::new(
+        &config.operator_context,
+        &peg_out_event,
+        stub_input(1, INITIAL_AMOUNT + FEE_AMOUNT),
+    );
+    assert_spendable("peg-out", &peg_out_tx);
+
+    let peg_out_confirm_tx = PegOutConfirmTransaction::new(
+        &config.operator_context,
+        &config.connector_6,
+        stub_input(0, INITIAL_AMOUNT + FEE_AMOUNT),
+    );
+    assert_spendable("peg-out confirm", &peg_out_confirm_tx);
+
+    let mut kick_off_1_tx = KickOff1Transaction::new(
+        &config.operator_context,
+        &config.connector_1,
+        &config.connector_2,
+        &config.connector_6,
+        stub_input(
+            0,
+            INITIAL_AMOUNT + MESSAGE_COMMITMENT_FEE_AMOUNT * 2 + FEE_AMOUNT,
+        ),
+    );
+    let ethereum_txid = "8b274fbb76c72f66c467c976c61d5ac212620e036818b5986a33f7b557cb2de8";
+    let bitcoin_txid = "8b4cce4a1a9522392c095df6416533d89e1e6ac7bdf8ab3c1685426b321ed182";
+    kick_off_1_tx.sign(
+        &config.operator_context,
+        &config.connector_6,
+        bitcoin_txid.as_bytes(),
+        ethereum_txid.as_bytes(),
+        &config.commitment_secrets,
+    );
+    assert_spendable("kick-off 1", &kick_off_1_tx);
+
+    let mut kick_off_2_tx = KickOff2Transaction::new(
+        &config.operator_context,
+        &config.connector_1,
+        stub_input(0, ONE_HUNDRED * 2 / 100),
+    );
+    let sb_hash: SuperblockHash = [0xf0u8; SHA256_DIGEST_LENGTH_IN_BYTES];
+    let sb = Superblock {
+        height: 123,
+        time: 45678,
+        weight: 9012345,
+    };
+    kick_off_2_tx.sign_input_0(
+        &config.operator_context,
+        &config.connector_1,
+        &config.commitment_secrets[&CommitmentMessageId::Superblock],
+        &get_superblock_message(&sb, &sb_hash),
+    );
+    assert_spendable("kick-off 2", &kick_off_2_tx);
+
+    let mut kick_off_timeout_tx = KickOffTimeoutTransaction::new(
+        &config.operator_context,
+        &config.connector_1,
+        stub_input(0, ONE_HUNDRED * 2 / 100),
+    );
+    let secret_nonces_0 = kick_off_timeout_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = kick_off_timeout_tx.push_nonces(&config.verifier_1_context);
+    kick_off_timeout_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_1,
+        &secret_nonces_0,
+    );
+    kick_off_timeout_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_1,
+        &secret_nonces_1,
+    );
+    kick_off_timeout_tx.add_output(reward_script(&config));
+    assert_spendable("kick-off timeout", &kick_off_timeout_tx);
+
+    let mut start_time_tx = StartTimeTransaction::new(
+        &config.operator_context,
+        &config.connector_2,
+        stub_input(0, DUST_AMOUNT),
+    );
+    start_time_tx.sign(
+        &config.operator_context,
+        &config.connector_2,
+        &config.commitment_secrets[&CommitmentMessageId::StartTime],
+        get_start_time_block(),
+    );
+    assert_spendable("start time", &start_time_tx);
+
+    let mut start_time_timeout_tx = StartTimeTimeoutTransaction::new(
+        &config.operator_context,
+        &config.connector_1,
+        &config.connector_2,
+        stub_input(0, ONE_HUNDRED * 2 / 100),
+        stub_input(1, DUST_AMOUNT),
+    );
+    let secret_nonces_0 = start_time_timeout_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = start_time_timeout_tx.push_nonces(&config.verifier_1_context);
+    start_time_timeout_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_1,
+        &config.connector_2,
+        &secret_nonces_0,
+    );
+    start_time_timeout_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_1,
+        &config.connector_2,
+        &secret_nonces_1,
+    );
+    start_time_timeout_tx.add_output(reward_script(&config));
+    assert_spendable("start time timeout", &start_time_timeout_tx);
+
+    // The depositor imitates a third party crowdfunding the challenge
+    let crowdfunding_public_key = &config.depositor_context.depositor_public_key;
+    let mut challenge_tx = ChallengeTransaction::new(
+        &config.operator_context,
+        &config.connector_a,
+        stub_input(0, DUST_AMOUNT),
+        Amount::from_sat(INITIAL_AMOUNT),
+    );
+    let crowdfunding_script = generate_pay_to_pubkey_script(crowdfunding_public_key);
+    let crowdfunding_input = |vout: u32| {
+        let input = stub_input(vout, INITIAL_AMOUNT * 2 / 3);
+        InputWithScript {
+            outpoint: input.outpoint,
+            amount: input.amount,
+            script: &crowdfunding_script,
+        }
+    };
+    challenge_tx.add_inputs_and_output(
+        &config.depositor_context,
+        &vec![crowdfunding_input(1), crowdfunding_input(2)],
+        &config.depositor_context.depositor_keypair,
+        generate_pay_to_pubkey_script_address(
+            config.depositor_context.network,
+            crowdfunding_public_key,
+        )
+        .script_pubkey(),
+    );
+    assert_spendable("challenge", &challenge_tx);
+
+    let mut assert_tx = AssertTransaction::new(
+        &config.connector_4,
+        &config.connector_5,
+        &config.connector_b,
+        &config.connector_c,
+        stub_input(0, ONE_HUNDRED * 2 / 100),
+    );
+    let secret_nonces_0 = assert_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = assert_tx.push_nonces(&config.verifier_1_context);
+    assert_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_b,
+        &secret_nonces_0,
+    );
+    assert_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_b,
+        &secret_nonces_1,
+    );
+    assert_spendable("assert", &assert_tx);
+
+    let script_index = 1;
+    let mut disprove_tx = DisproveTransaction::new(
+        &config.operator_context,
+        &config.connector_5,
+        &config.connector_c,
+        stub_input(0, DUST_AMOUNT),
+        stub_input(1, INITIAL_AMOUNT),
+        script_index,
+    );
+    let secret_nonces_0 = disprove_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = disprove_tx.push_nonces(&config.verifier_1_context);
+    disprove_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_5,
+        &secret_nonces_0,
+    );
+    disprove_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_5,
+        &secret_nonces_1,
+    );
+    disprove_tx.add_input_output(&config.connector_c, script_index, reward_script(&config));
+    assert_spendable("disprove", &disprove_tx);
+
+    let mut disprove_chain_tx = DisproveChainTransaction::new(
+        &config.operator_context,
+        &config.connector_b,
+        stub_input(0, INITIAL_AMOUNT),
+    );
+    let secret_nonces_0 = disprove_chain_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = disprove_chain_tx.push_nonces(&config.verifier_1_context);
+    disprove_chain_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_b,
+        &secret_nonces_0,
+    );
+    disprove_chain_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_b,
+        &secret_nonces_1,
+    );
+    disprove_chain_tx.add_output(reward_script(&config));
+    assert_spendable("disprove chain", &disprove_chain_tx);
+
+    let mut take_2_tx = Take2Transaction::new(
+        &config.operator_context,
+        &config.connector_0,
+        &config.connector_4,
+        &config.connector_5,
+        &config.connector_c,
+        stub_input(0, INITIAL_AMOUNT + FEE_AMOUNT),
+        stub_input(1, DUST_AMOUNT),
+        stub_input(2, ONE_HUNDRED * 2 / 100),
+        stub_input(3, DUST_AMOUNT),
+    );
+    let secret_nonces_0 = take_2_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = take_2_tx.push_nonces(&config.verifier_1_context);
+    take_2_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_0,
+        &config.connector_5,
+        &secret_nonces_0,
+    );
+    take_2_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_0,
+        &config.connector_5,
+        &secret_nonces_1,
+    );
+    assert_spendable("take 2", &take_2_tx);
+}
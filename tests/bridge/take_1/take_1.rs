@@ -1,15 +1,20 @@
-use bitcoin::{consensus::encode::serialize_hex, Amount};
+use bitcoin::{consensus::encode::serialize_hex, hashes::Hash, Amount, OutPoint, Txid};
 
 use bitvm::bridge::{
     connectors::base::{P2wshConnector, TaprootConnector},
     graphs::base::{DUST_AMOUNT, FEE_AMOUNT, INITIAL_AMOUNT, ONE_HUNDRED},
     transactions::{
         base::{BaseTransaction, Input},
+        pre_signed::{execute_taproot_input, PreSignedTransaction},
+        simulator::{simulate_spend, simulate_transaction, InputVerdict},
         take_1::Take1Transaction,
     },
 };
 
-use super::super::{helper::generate_stub_outpoint, setup::setup_test};
+use super::super::{
+    helper::generate_stub_outpoint,
+    setup::{setup_test, SetupConfig},
+};
 
 #[tokio::test]
 async fn test_take_1_tx() {
@@ -83,3 +88,76 @@ async fn test_take_1_tx() {
     println!("Transaction hex: \n{}", serialize_hex(&tx));
     assert!(result.is_ok());
 }
+
+// Signs a take 1 transaction spending stub outpoints, so it can be checked without a network.
+fn offline_take_1_tx(config: &SetupConfig) -> Take1Transaction {
+    let input = |vout: u32, sats: u64| Input {
+        outpoint: OutPoint {
+            txid: Txid::all_zeros(),
+            vout,
+        },
+        amount: Amount::from_sat(sats),
+    };
+
+    let mut take_1_tx = Take1Transaction::new(
+        &config.operator_context,
+        &config.connector_0,
+        &config.connector_3,
+        &config.connector_a,
+        &config.connector_b,
+        input(0, INITIAL_AMOUNT + FEE_AMOUNT),
+        input(1, DUST_AMOUNT),
+        input(2, DUST_AMOUNT),
+        input(3, ONE_HUNDRED * 2 / 100),
+    );
+
+    let secret_nonces_0 = take_1_tx.push_nonces(&config.verifier_0_context);
+    let secret_nonces_1 = take_1_tx.push_nonces(&config.verifier_1_context);
+
+    take_1_tx.pre_sign(
+        &config.verifier_0_context,
+        &config.connector_0,
+        &config.connector_b,
+        &secret_nonces_0,
+    );
+    take_1_tx.pre_sign(
+        &config.verifier_1_context,
+        &config.connector_0,
+        &config.connector_b,
+        &secret_nonces_1,
+    );
+
+    take_1_tx
+}
+
+#[tokio::test]
+async fn test_take_1_tx_offline() {
+    let config = setup_test().await;
+    let take_1_tx = offline_take_1_tx(&config);
+
+    // Input 2 is a P2WSH input, the others are taproot script path spends.
+    for input_index in [0, 1, 3] {
+        let result = execute_taproot_input(&take_1_tx, input_index);
+        assert!(result.success, "input {}: {}", input_index, result);
+    }
+}
+
+#[tokio::test]
+async fn test_take_1_tx_simulated() {
+    let config = setup_test().await;
+    let take_1_tx = offline_take_1_tx(&config);
+
+    let report = simulate_spend(&take_1_tx);
+    println!("{}", report);
+    assert_eq!(report.inputs.len(), 4);
+    assert!(report
+        .inputs
+        .iter()
+        .all(|input| input.verdict == InputVerdict::Pass));
+
+    // Changing the output invalidates the signatures of all inputs.
+    let mut tx = take_1_tx.finalize();
+    tx.output[0].value -= Amount::from_sat(1);
+    let report = simulate_transaction(&tx, take_1_tx.prev_outs(), take_1_tx.prev_scripts());
+    assert_eq!(report.failures().len(), 4, "{}", report);
+}